@@ -1,4 +1,9 @@
-use std::any::type_name;
+use std::{
+    any::{type_name, TypeId}, 
+    collections::HashSet,
+    sync::{atomic::{AtomicUsize, Ordering}, Arc}, 
+    time::{Duration, Instant}
+};
 
 ///
 /// see bevy bevy_app/../app.rs
@@ -6,23 +11,45 @@ use std::any::type_name;
 
 use essay_ecs_core::{
     error::Result,
-    schedule::ScheduleLabel, 
-    store::FromStore, 
+    schedule::{ScheduleLabel, SystemLabel, SystemMeta, SystemObserver}, 
+    store::{CommandTrace, CommandsSender, Despawning, FromStore, SnapshotComponents}, 
+    util::LabelId,
     IntoPhaseConfigs, IntoSystem, IntoSystemConfig, Schedule, Schedules, Store
 };
 
-use crate::{event::{Event, Events}, First};
+use crate::{diagnostics::{event_queue, EventQueue}, event::{Event, Events, InEvent, OutEvent}, pipeline::{PipeBack, PipeFront}, request::Requests, First, PreUpdate};
 
-use super::{plugin::{Plugins, Plugin}, main_schedule::MainSchedulePlugin, Main};
+use super::{
+    plugin::{Plugins, Plugin}, 
+    main_schedule::{MainSchedule, MainSchedulePlugin}, 
+    resource_init::{reinit_resource, ResourceDeps, ResourceInits},
+    Main, PreStartup, Startup, PostStartup, Exit, AppExit,
+};
 
 #[cfg(test)]
 use essay_ecs_core::entity::{Bundle, EntityId};
 
+// resource type name and whether a store contains it
+type RequiredResource = (&'static str, fn(&Store) -> bool);
+
+// resource type and how reset_world restores it
+type ResourceReset = (TypeId, fn(&mut Store));
+
 pub struct App {
     store: Store,
     plugins: Plugins,
     main_schedule: Box<dyn ScheduleLabel>,
     runner: Box<dyn FnOnce(App) -> Result<()> + Send>,
+    event_counts: Vec<fn(&Store) -> usize>,
+    event_queues: Vec<EventQueue>,
+    systems_run: Option<Arc<AtomicUsize>>,
+    resource_inits: ResourceInits,
+    app_resources: HashSet<TypeId>,
+    resource_resets: Vec<ResourceReset>,
+    schedule_labels: HashSet<LabelId>,
+    required_resources: Vec<RequiredResource>,
+    is_strict: bool,
+    is_transactional: bool,
 }
 
 impl App {
@@ -43,6 +70,16 @@ impl App {
             plugins: Plugins::default(),
             main_schedule: Box::new(Main),
             runner: Box::new(run_once),
+            event_counts: Vec::new(),
+            event_queues: Vec::new(),
+            systems_run: None,
+            resource_inits: ResourceInits::default(),
+            app_resources: HashSet::from([TypeId::of::<Schedules>()]),
+            resource_resets: Vec::new(),
+            schedule_labels: HashSet::from([Main.label_id()]),
+            required_resources: Vec::new(),
+            is_strict: false,
+            is_transactional: false,
         }
     }
 
@@ -64,6 +101,34 @@ impl App {
         self
     }
 
+    ///
+    /// Adds a system to the Exit schedule, run once by App::exit, and
+    /// registers AppExit for it.
+    ///
+    pub fn on_exit<M>(&mut self, into_system: impl IntoSystemConfig<M>) -> &mut Self {
+        self.event::<AppExit>()
+            .system(Exit, into_system)
+    }
+
+    ///
+    /// Adds a group of systems, e.g. `(a, b).label(Feature)`.
+    ///
+    pub fn systems<M>(
+        &mut self, 
+        label: impl AsRef<dyn ScheduleLabel>,
+        into_systems: impl IntoSystemConfig<M>
+    ) -> &mut Self {
+        self.system(label, into_systems)
+    }
+
+    ///
+    /// Removes systems with the label from all schedules, e.g. when
+    /// disabling a plugin. Returns the number of systems removed.
+    ///
+    pub fn remove_systems(&mut self, label: impl SystemLabel) -> usize {
+        self.resource_mut::<Schedules>().remove_systems(label)
+    }
+
     pub fn phase(
         &mut self, 
         label: impl AsRef<dyn ScheduleLabel>,
@@ -87,11 +152,44 @@ impl App {
     //
 
     pub fn insert_resource<T: Send + 'static>(&mut self, value: T) {
+        self.app_resources.insert(TypeId::of::<T>());
         self.store.insert_resource(value);
     }
 
     pub fn init_resource<T: FromStore + Send + 'static>(&mut self) -> &mut Self {
-        self.store.init_resource::<T>();
+        self.app_resources.insert(TypeId::of::<T>());
+
+        if ! self.store.contains_resource::<T>() {
+            self.store.init_resource::<T>();
+            self.resource_resets.push((TypeId::of::<T>(), reinit_resource::<T>));
+        }
+
+        self
+    }
+
+    ///
+    /// Initializes T during finish, after the resources in D, e.g.
+    /// `init_resource_with_deps::<Atlas, (Config,)>()`. Panics in finish
+    /// if the dependencies are circular.
+    ///
+    pub fn init_resource_with_deps<T, D>(&mut self) -> &mut Self
+    where
+        T: FromStore + Send + 'static,
+        D: ResourceDeps
+    {
+        self.app_resources.insert(TypeId::of::<T>());
+        self.resource_inits.add::<T, D>();
+
+        self
+    }
+
+    ///
+    /// Declares a resource that must be inserted by the end of finish,
+    /// e.g. a plugin's config, so a missing resource panics at startup
+    /// naming the type instead of failing in the first system that uses it.
+    ///
+    pub fn require_resource<T: Send + 'static>(&mut self) -> &mut Self {
+        self.required_resources.push((type_name::<T>(), Store::contains_resource::<T>));
 
         self
     }
@@ -144,15 +242,101 @@ impl App {
     // events
     //
 
+    pub(crate) fn event_queues(&self) -> &[EventQueue] {
+        &self.event_queues
+    }
+
     pub fn event<E: Event>(&mut self) -> &mut Self {
         if ! self.store.contains_resource::<Events<E>>() {
             self.init_resource::<Events<E>>()
                 .system(First, Events::<E>::update);
+
+            Events::<E>::share_stamps(&mut self.store);
+
+            // event buffers aren't restored by a failed transactional tick,
+            // so its events are still read on the next tick
+            self.store.untracked_resource::<Events<E>>();
+
+            // readers keep their cursors, so a reset clears the buffers
+            // instead of replacing the resource
+            for (id, reset) in &mut self.resource_resets {
+                if *id == TypeId::of::<Events<E>>() {
+                    *reset = Events::<E>::store_clear;
+                }
+            }
+
+            self.event_counts.push(Events::<E>::store_n_sent);
+            self.event_queues.push(event_queue::<E>());
+        }
+
+        self
+    }
+
+    ///
+    /// Converts every A event into a B event in PreUpdate, after the
+    /// First event update, so B readers in Update see the converted events
+    /// in the same tick.
+    ///
+    pub fn map_event<A: Event, B: Event>(
+        &mut self, 
+        map: impl Fn(&A) -> B + Send + Sync + 'static
+    ) -> &mut Self {
+        self.event::<A>();
+        self.event::<B>();
+
+        self.system(PreUpdate, move |mut input: InEvent<A>, mut output: OutEvent<B>| {
+            for event in input.iter() {
+                output.send(map(event));
+            }
+        })
+    }
+
+    ///
+    /// Registers a request/response channel between OutRequest<Req, Res>
+    /// and InRequest<Req, Res> systems.
+    ///
+    pub fn request<Req, Res>(&mut self) -> &mut Self
+    where
+        Req: Send + Sync + 'static,
+        Res: Send + Sync + 'static
+    {
+        self.init_resource::<Requests<Req, Res>>()
+    }
+
+    ///
+    /// Registers a pipeline between PipeOut<T> and PipeIn<T> systems.
+    /// Consumers read the producer's output from the previous tick, so the
+    /// producer and its consumers can run in parallel.
+    ///
+    pub fn pipeline<T: Default + Send + Sync + 'static>(&mut self) -> &mut Self {
+        self.pipelined::<T>(2)
+    }
+
+    ///
+    /// Registers a pipeline whose consumers read the producer's output
+    /// from depth - 1 ticks ago. A depth below 2 is treated as 2.
+    ///
+    pub fn pipelined<T: Default + Send + Sync + 'static>(&mut self, depth: usize) -> &mut Self {
+        if ! self.store.contains_resource::<PipeFront<T>>() {
+            self.init_resource::<PipeFront<T>>()
+                .init_resource::<PipeBack<T>>()
+                .system(First, PipeFront::<T>::swap);
         }
 
+        self.resource_mut::<PipeFront<T>>().set_depth(depth);
+
         self
     }
 
+    ///
+    /// Handle for other threads to queue spawn, insert and resource
+    /// commands. The Main schedule applies them at the start of each tick,
+    /// before the First schedule runs.
+    ///
+    pub fn commands_sender(&mut self) -> CommandsSender {
+        self.store.commands_sender()
+    }
+
     //
     // plugins
     //
@@ -188,15 +372,78 @@ impl App {
     }
 
     pub fn finish(&mut self) -> &mut Self {
+        if let Err(err) = self.resource_inits.init(&mut self.store) {
+            panic!("{}", err.message());
+        }
+
         let plugins = std::mem::take(&mut self.plugins);
 
         plugins.finish(self);
 
         self.plugins = plugins;
 
+        let missing: Vec<&str> = self.required_resources.iter()
+            .filter(|(_, contains)| ! contains(&self.store))
+            .map(|(name, _)| *name)
+            .collect();
+
+        if ! missing.is_empty() {
+            panic!("required resources were never inserted: {}\n\tadd them with App::insert_resource or App::init_resource",
+                missing.join(", ")
+            );
+        }
+
+        if self.is_strict {
+            if let Err(err) = self.validate() {
+                panic!("{}", err.message());
+            }
+        }
+
+        self
+    }
+
+    ///
+    /// Runs validate in finish, panicking with its report.
+    ///
+    pub fn strict(&mut self) -> &mut Self {
+        self.is_strict = true;
+
         self
     }
 
+    ///
+    /// Checks every schedule before the first tick and reports all the
+    /// problems at once: systems whose params can't initialize, e.g.
+    /// resources never inserted, conditions on marker systems, phases
+    /// without systems, and schedule labels that were never declared with
+    /// App::schedule or by the main schedule, so nothing runs them.
+    ///
+    pub fn validate(&mut self) -> Result<()> {
+        let mut problems = Vec::new();
+
+        for label in self.resource::<Schedules>().labels() {
+            if ! self.schedule_labels.contains(&label.label_id()) {
+                problems.push(format!("unknown schedule label {:?}", label));
+            }
+
+            let schedule_problems = self.store.try_eval_schedule(&label, |store, schedule| {
+                Ok(schedule.validate(store))
+            })?;
+
+            for problem in schedule_problems {
+                problems.push(format!("{:?}: {}", label, problem));
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            problems.sort();
+
+            Err(format!("schedule validation failed:\n\t{}", problems.join("\n\t")).into())
+        }
+    }
+
     pub fn cleanup(&mut self) -> &mut Self {
         let plugins = std::mem::take(&mut self.plugins);
 
@@ -217,13 +464,177 @@ impl App {
         label: impl AsRef<dyn ScheduleLabel>, 
         schedule: Schedule
     ) -> &mut Self {
+        self.schedule_labels.insert(label.as_ref().label_id());
         self.store.add_schedule(label, schedule);
 
         self
     }
 
+    ///
+    /// Declares a schedule that systems are added to lazily, and that is
+    /// run by the main schedule or a plugin, for App::validate.
+    ///
+    pub fn declare_schedule(&mut self, label: impl AsRef<dyn ScheduleLabel>) -> &mut Self {
+        self.schedule_labels.insert(label.as_ref().label_id());
+
+        self
+    }
+
+    ///
+    /// Runs the schedule concurrently with a main schedule like Update
+    /// each tick, e.g. audio processing beside the simulation. The tick
+    /// fails if their systems' access overlaps with a writer, including
+    /// any exclusive system in either schedule. See
+    /// Store::run_schedules_parallel for how grouped schedules run.
+    ///
+    pub fn parallel_schedule(
+        &mut self,
+        with: impl ScheduleLabel,
+        label: impl ScheduleLabel
+    ) -> &mut Self {
+        self.declare_schedule(label.box_clone());
+        self.resource_mut::<MainSchedule>().add_parallel(with, label);
+
+        self
+    }
+
     pub fn tick(&mut self) -> Result<()> {
-        self.store.run_schedule(&self.main_schedule)
+        if let Some(trace) = self.store.get_resource_mut::<CommandTrace>() {
+            trace.clear();
+        }
+
+        if self.is_transactional {
+            let label = self.main_schedule.box_clone();
+
+            self.store.transaction(|store| store.run_schedule(label))
+        } else {
+            self.store.run_schedule(&self.main_schedule)
+        }
+    }
+
+    ///
+    /// Runs each tick as a Store::transaction: commands are applied at the
+    /// end of a successful tick, and a failed tick drops them and restores
+    /// tracked values instead of closing the store. Systems writing an
+    /// untracked component or resource fail, see Store::transaction.
+    ///
+    pub fn transactional(&mut self) -> &mut Self {
+        self.is_transactional = true;
+        self.store.track_components::<Despawning>();
+
+        self
+    }
+
+    ///
+    /// Records each tick's applied commands in the CommandTrace resource,
+    /// for tests asserting on a tick's side effects.
+    ///
+    pub fn trace_commands(&mut self) -> &mut Self {
+        self.store.trace_commands(true);
+
+        self
+    }
+
+    ///
+    /// Restores the S components when a transactional tick fails.
+    ///
+    pub fn track_components<S: SnapshotComponents>(&mut self) -> &mut Self {
+        self.store.track_components::<S>();
+
+        self
+    }
+
+    pub fn track_resource<T: Clone + Send + 'static>(&mut self) -> &mut Self {
+        self.store.track_resource::<T>();
+
+        self
+    }
+
+    ///
+    /// Despawns every entity and drops the resources added while running,
+    /// then reruns the startup schedules. Schedules and resources added
+    /// through the App are kept, so nothing is rebuilt or replanned.
+    /// Resources from init_resource are initialized again and event
+    /// buffers are cleared, while values from insert_resource keep any
+    /// changes made while running. Call after the first tick, which runs
+    /// startup itself.
+    ///
+    pub fn reset_world(&mut self) -> Result<()> {
+        self.store.despawn_all();
+
+        let app_resources = &self.app_resources;
+        self.store.retain_resources(|id| app_resources.contains(&id));
+
+        for (_, reset) in &self.resource_resets {
+            reset(&mut self.store);
+        }
+
+        self.resource_inits.reset(&mut self.store);
+
+        self.store.run_schedule_optional(PreStartup)?;
+        self.store.run_schedule_optional(Startup)?;
+        self.store.run_schedule_optional(PostStartup)?;
+
+        Ok(())
+    }
+
+    ///
+    /// Initializes and plans every schedule ahead of the first tick,
+    /// including params, views and executor thread pools.
+    ///
+    pub fn finish_schedules(&mut self) -> Result<()> {
+        self.store.finish_schedules()
+    }
+
+    ///
+    /// Ticks the main schedule and reports the activity of the tick.
+    ///
+    pub fn update(&mut self) -> Result<TickReport> {
+        let systems_run = match &self.systems_run {
+            Some(systems_run) => systems_run.clone(),
+            None => {
+                let systems_run = Arc::new(AtomicUsize::new(0));
+
+                self.resource_mut::<Schedules>()
+                    .add_observer(CountObserver(systems_run.clone()));
+                self.systems_run = Some(systems_run.clone());
+
+                systems_run
+            }
+        };
+
+        let systems_start = systems_run.load(Ordering::Relaxed);
+        let commands_start = self.store.commands_applied();
+        let events_start = self.events_sent();
+        let start = Instant::now();
+
+        self.tick()?;
+
+        Ok(TickReport {
+            systems_run: systems_run.load(Ordering::Relaxed) - systems_start,
+            commands_applied: self.store.commands_applied() - commands_start,
+            events_sent: self.events_sent() - events_start,
+            duration: start.elapsed(),
+        })
+    }
+
+    fn events_sent(&self) -> usize {
+        self.event_counts.iter().map(|count| count(&self.store)).sum()
+    }
+
+    ///
+    /// Sends AppExit, unless a system already sent one, and runs the Exit
+    /// schedule, for teardown after the last tick. Runners call exit when
+    /// they stop ticking.
+    ///
+    pub fn exit(&mut self) -> Result<()> {
+        if let Some(events) = self.store.get_resource_mut::<Events<AppExit>>() {
+            if events.is_empty() {
+                events.send(AppExit);
+            }
+        }
+
+        self.store.run_schedule_optional(Exit)
     }
 
     pub fn runner(&mut self, runner: impl FnOnce(App) -> Result<()> + 'static + Send) -> &mut Self {
@@ -262,21 +673,61 @@ impl Default for App {
     }
 }
 
+///
+/// Activity of a single App::update tick. Systems run includes the
+/// main schedule's own system.
+///
+#[derive(Clone, Debug, Default)]
+pub struct TickReport {
+    systems_run: usize,
+    commands_applied: usize,
+    events_sent: usize,
+    duration: Duration,
+}
+
+impl TickReport {
+    pub fn systems_run(&self) -> usize {
+        self.systems_run
+    }
+
+    pub fn commands_applied(&self) -> usize {
+        self.commands_applied
+    }
+
+    pub fn events_sent(&self) -> usize {
+        self.events_sent
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+}
+
+struct CountObserver(Arc<AtomicUsize>);
+
+impl SystemObserver for CountObserver {
+    fn before_run(&self, _meta: &SystemMeta) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
 fn run_once(mut app: App) -> Result<()> {
     app.finish();
     app.cleanup();
 
-    app.tick()
+    app.tick()?;
+
+    app.exit()
 }
 
 
 #[cfg(test)]
 mod tests {
-    use std::sync::{Mutex, Arc};
+    use std::sync::{atomic::{AtomicUsize, Ordering}, Mutex, Arc};
 
-    use essay_ecs_core::{Commands, Component, Res, Store};
+    use essay_ecs_core::{error::Result, param::HeapSize, store::FromStore, Commands, Component, IntoSystemConfig, Local, Phase, Query, Res, ResMut, ScheduleLabel, Store, SystemLabel};
 
-    use crate::{app::{app::App, Update, Startup}, event::{Event, OutEvent, InEvent}, PreUpdate};
+    use crate::{app::{app::App, Update, Startup}, event::{Event, Events, OutEvent, InEvent}, First, PostUpdate, PreUpdate};
 
     mod ecs { pub mod core { pub use essay_ecs_core::*; }}
     use ecs as essay_ecs;
@@ -390,6 +841,73 @@ mod tests {
         assert_eq!(take(&value), "spawn, update");
     }
 
+    #[test]
+    fn reset_world() {
+        let mut app = App::new();
+
+        app.insert_resource(TestA(3));
+
+        app.system(Startup, |mut cmd: Commands, n: Res<TestA>| {
+            for i in 0..n.0 {
+                cmd.spawn(CompB(i));
+            }
+        });
+
+        app.system(Update, |mut cmd: Commands| {
+            cmd.spawn(CompB(100));
+            cmd.insert_resource(TestB(1));
+        });
+
+        app.tick().unwrap();
+        app.tick().unwrap();
+        assert_eq!(app.eval(|q: Query<&CompB>| q.iter().count()).unwrap(), 5);
+        assert!(app.contains_resource::<TestB>());
+
+        app.reset_world().unwrap();
+
+        assert_eq!(app.eval(|q: Query<&CompB>| q.iter().count()).unwrap(), 3);
+        assert_eq!(app.resource::<TestA>(), &TestA(3));
+        assert!(! app.contains_resource::<TestB>());
+
+        app.tick().unwrap();
+        assert_eq!(app.eval(|q: Query<&CompB>| q.iter().count()).unwrap(), 4);
+    }
+
+    #[test]
+    fn reset_world_resources() {
+        let mut app = App::new();
+
+        app.event::<TestEvent>();
+        app.init_resource::<DepA>();
+        app.init_resource_with_deps::<DepB, (DepA,)>();
+        app.insert_resource(TestA(3));
+
+        app.system(Update, |mut a: ResMut<DepA>, mut b: ResMut<DepB>, mut t: ResMut<TestA>, mut out: OutEvent<TestEvent>| {
+            a.0 += 10;
+            b.0 += 10;
+            t.0 += 1;
+            out.send(TestEvent(a.0));
+        });
+
+        app.finish();
+        app.tick().unwrap();
+        assert_eq!(app.resource::<DepA>().0, 11);
+        assert_eq!(app.resource::<DepB>().0, 12);
+        assert_eq!(app.resource::<Events<TestEvent>>().len(), 1);
+
+        app.reset_world().unwrap();
+
+        assert_eq!(app.resource::<DepA>().0, 1);
+        assert_eq!(app.resource::<DepB>().0, 2);
+        assert!(app.resource::<Events<TestEvent>>().is_empty());
+        // inserted values can't be rebuilt, so they keep their changes
+        assert_eq!(app.resource::<TestA>(), &TestA(4));
+
+        app.tick().unwrap();
+        assert_eq!(app.resource::<DepA>().0, 11);
+        assert_eq!(app.resource::<Events<TestEvent>>().len(), 1);
+    }
+
     #[test]
     fn events() {
         let mut app = App::new();
@@ -419,6 +937,288 @@ mod tests {
         assert_eq!(take(&value), "TestEvent(3)");
     }
 
+    #[test]
+    fn map_event() {
+        let mut app = App::new();
+        let value = Vec::<String>::new();
+        let value = Arc::new(Mutex::new(value));
+
+        app.map_event(|event: &TestEvent| OtherEvent(event.0 * 10));
+
+        let mut counter = 1;
+        app.system(First, move |mut writer: OutEvent<TestEvent>| {
+            writer.send(TestEvent(counter));
+            counter += 1;
+        });
+
+        let ptr = Arc::clone(&value);
+        app.system(Update, move |mut reader: InEvent<OtherEvent>| {
+            for event in reader.iter() {
+                push(&ptr, &format!("{:?}", event));
+            }
+        });
+
+        app.tick().unwrap();
+        assert_eq!(take(&value), "OtherEvent(10)");
+        app.tick().unwrap();
+        assert_eq!(take(&value), "OtherEvent(20)");
+    }
+
+    #[test]
+    fn finish_schedules() {
+        static N_INIT: AtomicUsize = AtomicUsize::new(0);
+
+        #[derive(Debug)]
+        struct Probe;
+
+        impl Default for Probe {
+            fn default() -> Self {
+                N_INIT.fetch_add(1, Ordering::Relaxed);
+                Probe
+            }
+        }
+
+        impl HeapSize for Probe {}
+
+        let mut app = App::new();
+        app.system(Update, |_probe: Local<Probe>| {});
+
+        app.finish_schedules().unwrap();
+        assert_eq!(N_INIT.load(Ordering::Relaxed), 1);
+
+        app.tick().unwrap();
+        assert_eq!(N_INIT.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn remove_systems() {
+        let mut app = App::new();
+        let values = Arc::new(Mutex::new(Vec::<String>::new()));
+
+        let ptr_a = Arc::clone(&values);
+        let ptr_b = Arc::clone(&values);
+        app.systems(Update, (
+            move || push(&ptr_a, "a"),
+            move || push(&ptr_b, "b"),
+        ).label(Feature));
+
+        let ptr = Arc::clone(&values);
+        app.system(Update, move || push(&ptr, "c"));
+
+        app.tick().unwrap();
+        let mut result: Vec<String> = values.lock().unwrap().drain(..).collect();
+        result.sort();
+        assert_eq!(result.join(", "), "a, b, c");
+
+        assert_eq!(app.remove_systems(Feature), 2);
+        assert_eq!(app.remove_systems(Feature), 0);
+
+        app.tick().unwrap();
+        assert_eq!(take(&values), "c");
+    }
+
+    #[derive(SystemLabel, Clone, Debug, PartialEq, Eq, Hash)]
+    struct Feature;
+
+    #[test]
+    fn validate() {
+        let mut app = App::new();
+
+        app.phase(Update, (TestPhase,));
+        app.system(Update, |_a: Res<TestA>| {});
+        app.system(Orphan, || {});
+
+        let err = app.validate().unwrap_err();
+        let message = err.message();
+        assert!(message.contains("unknown schedule label Orphan"));
+        assert!(message.contains("Update: Res<essay_ecs_app::app::app::tests::TestA> is an unknown resource"));
+        assert!(message.contains("tests::TestPhase has no systems"));
+
+        app.insert_resource(TestA(1));
+        app.system(Update, (|| {}).phase(TestPhase));
+        app.declare_schedule(Orphan);
+
+        app.strict().finish();
+        app.tick().unwrap();
+    }
+
+    #[derive(ScheduleLabel, Clone, Debug, PartialEq, Eq, Hash)]
+    struct Orphan;
+
+    #[test]
+    #[should_panic(expected = "required resources were never inserted: essay_ecs_app::app::app::tests::TestB")]
+    fn require_resource() {
+        let mut app = App::new();
+
+        app.require_resource::<TestA>().require_resource::<TestB>();
+        app.insert_resource(TestA(1));
+
+        app.finish();
+    }
+
+    #[test]
+    fn missing_resource_error() {
+        let mut app = App::new();
+
+        app.system(Update, |_b: Res<TestB>| {});
+
+        let err = app.tick().unwrap_err();
+        let message = err.message();
+
+        assert!(message.starts_with("Res<essay_ecs_app::app::app::tests::TestB> is an unknown resource"));
+        assert!(message.contains("\n\tin essay_ecs_app::app::app::tests::missing_resource_error::{{closure}}"));
+        assert!(message.contains("\n\tin schedule Update"));
+    }
+
+    #[test]
+    fn transactional_tick() {
+        let mut app = App::new();
+
+        app.transactional().track_resource::<TestA>();
+        app.insert_resource(TestA(0));
+
+        app.system(Update, |mut a: ResMut<TestA>, mut c: Commands| {
+            a.0 += 1;
+            c.spawn(CompB(a.0));
+        });
+
+        app.system(PostUpdate, |_store: &mut Store, mut n: Local<u32>| -> Result<()> {
+            *n += 1;
+
+            if *n == 2 { Err("second tick failed".into()) } else { Ok(()) }
+        });
+
+        app.tick().unwrap();
+        assert_eq!(app.resource::<TestA>(), &TestA(1));
+        assert_eq!(app.eval(|q: Query<&CompB>| q.iter().count()).unwrap(), 1);
+
+        assert_eq!(app.tick().unwrap_err().message(), "second tick failed");
+        assert_eq!(app.resource::<TestA>(), &TestA(1));
+        assert_eq!(app.eval(|q: Query<&CompB>| q.iter().count()).unwrap(), 1);
+
+        app.tick().unwrap();
+        assert_eq!(app.resource::<TestA>(), &TestA(2));
+        assert_eq!(app.eval(|q: Query<&CompB>| q.iter().count()).unwrap(), 2);
+    }
+
+    #[derive(Phase, Clone, Debug, PartialEq, Eq, Hash)]
+    struct TestPhase;
+
+    #[test]
+    fn commands_sender() {
+        let mut app = App::new();
+
+        let sender = app.commands_sender();
+
+        let values = Arc::new(Mutex::new(Vec::<u32>::new()));
+
+        let ptr = values.clone();
+        app.system(Update, move |q: Query<&CompB>| {
+            for value in q.iter() {
+                ptr.lock().unwrap().push(value.0);
+            }
+        });
+
+        app.tick().unwrap();
+        assert!(values.lock().unwrap().is_empty());
+
+        std::thread::spawn(move || {
+            sender.spawn(CompB(7));
+        }).join().unwrap();
+
+        app.tick().unwrap();
+        assert_eq!(*values.lock().unwrap(), vec![7]);
+    }
+
+    #[test]
+    fn commands_sender_before_first() {
+        let mut app = App::new();
+
+        let sender = app.commands_sender();
+
+        let values = Arc::new(Mutex::new(Vec::<usize>::new()));
+
+        let ptr = values.clone();
+        app.system(First, move |q: Query<&CompB>| {
+            ptr.lock().unwrap().push(q.iter().count());
+        });
+
+        sender.spawn(CompB(7));
+
+        app.tick().unwrap();
+        assert_eq!(*values.lock().unwrap(), vec![1]);
+    }
+
+    #[test]
+    fn update_report() {
+        let mut app = App::new();
+
+        app.event::<TestEvent>();
+
+        app.system(Update, |mut cmd: Commands, mut writer: OutEvent<TestEvent>| {
+            cmd.add(|_store: &mut Store| {});
+            cmd.add(|_store: &mut Store| {});
+            writer.send(TestEvent(1));
+        });
+
+        app.update().unwrap();
+
+        let report = app.update().unwrap();
+        assert_eq!(report.systems_run(), 3);
+        assert_eq!(report.commands_applied(), 2);
+        assert_eq!(report.events_sent(), 1);
+    }
+
+    #[test]
+    fn init_resource_with_deps() {
+        let mut app = App::new();
+
+        app.init_resource_with_deps::<DepC, (DepB, DepA)>();
+        app.init_resource_with_deps::<DepB, (DepA,)>();
+        app.init_resource_with_deps::<DepA, ()>();
+
+        assert!(! app.contains_resource::<DepC>());
+
+        app.finish();
+
+        assert_eq!(app.resource::<DepC>().0, 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "circular resource initialization")]
+    fn init_resource_circular() {
+        let mut app = App::new();
+
+        app.init_resource_with_deps::<DepA, (DepB,)>();
+        app.init_resource_with_deps::<DepB, (DepA,)>();
+
+        app.finish();
+    }
+
+    struct DepA(u32);
+
+    impl FromStore for DepA {
+        fn init(_store: &mut Store) -> Self {
+            DepA(1)
+        }
+    }
+
+    struct DepB(u32);
+
+    impl FromStore for DepB {
+        fn init(store: &mut Store) -> Self {
+            DepB(store.resource::<DepA>().0 + 1)
+        }
+    }
+
+    struct DepC(u32);
+
+    impl FromStore for DepC {
+        fn init(store: &mut Store) -> Self {
+            DepC(store.resource::<DepB>().0 + 1)
+        }
+    }
+
     #[test]
     fn eval() {
         let mut app = App::new();
@@ -446,6 +1246,9 @@ mod tests {
     #[derive(Component)]
     struct CompA;
 
+    #[derive(Component)]
+    struct CompB(u32);
+
     #[derive(Debug, Clone, PartialEq)]
     struct TestA(u32);
 
@@ -458,6 +1261,12 @@ mod tests {
 
     impl Event for TestEvent {}
 
+    #[derive(Debug)]
+    #[allow(unused)]    
+    struct OtherEvent(u32);
+
+    impl Event for OtherEvent {}
+
     fn take(ptr: &Arc<Mutex<Vec<String>>>) -> String {
         ptr.lock().unwrap().drain(..).collect::<Vec<String>>().join(", ")
     }
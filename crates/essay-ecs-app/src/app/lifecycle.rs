@@ -0,0 +1,40 @@
+use crate::event::Event;
+
+//
+// Lifecycle events are only sent once registered with App::event, so
+// apps that don't read them don't pay for the event updates.
+//
+
+///
+/// Sent once by the main schedule after the startup schedules, so
+/// systems see it on the first tick.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AppStart;
+
+impl Event for AppStart {}
+
+///
+/// Sent by Ticker::pause. The pause takes effect after the next tick,
+/// so systems can react before the app stops ticking.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PrePause;
+
+impl Event for PrePause {}
+
+///
+/// Sent by Ticker::resume, read on the first tick after the pause.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Resume;
+
+impl Event for Resume {}
+
+///
+/// Sent by App::exit before it runs the Exit schedule.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AppExit;
+
+impl Event for AppExit {}
@@ -1,10 +1,14 @@
 use essay_ecs_core::{ScheduleLabel, 
     error::Result, 
     schedule::{ScheduleLabel, Executors}, 
-    Store, Local, Schedule
+    store::Despawning,
+    system::any_with_component,
+    IntoSystemConfig, Store, Local, Schedule
 };
 
-use super::{plugin::Plugin, App};
+use crate::{arena::FrameArena, event::Events};
+
+use super::{plugin::Plugin, App, AppStart};
 
 mod ecs { pub mod core { pub use essay_ecs_core::*; } }
 use ecs as essay_ecs;
@@ -19,15 +23,32 @@ impl Main {
             store.run_schedule_optional(PreStartup)?;
             store.run_schedule_optional(Startup)?;
             store.run_schedule_optional(PostStartup)?;
+
+            if let Some(events) = store.get_resource_mut::<Events<AppStart>>() {
+                events.send(AppStart);
+            }
+        }
+
+        // commands from CommandsSender handles, before First
+        store.apply_sent_commands();
+
+        let groups = store.resource::<MainSchedule>().groups();
+
+        for group in groups {
+            let labels: Vec<&dyn ScheduleLabel> = group.iter()
+                .map(|label| label.as_ref())
+                .filter(|label| store.contains_schedule(label))
+                .collect();
+
+            match labels.as_slice() {
+                [] => {},
+                [label] => store.run_schedule(label)?,
+                labels => store.run_schedules_parallel(labels)?,
+            }
         }
 
-        let labels : Vec<Box<dyn ScheduleLabel>> = store
-            .resource::<MainSchedule>().schedules
-            .iter()
-            .map(|x| x.box_clone())
-            .collect();
-        for label in labels {
-            store.run_schedule_optional(label)?;
+        if let Some(arena) = store.get_resource_mut::<FrameArena>() {
+            arena.reset();
         }
 
         Ok(())
@@ -36,6 +57,32 @@ impl Main {
 
 pub struct MainSchedule {
     schedules: Vec<Box<dyn ScheduleLabel>>,
+    parallel: Vec<(Box<dyn ScheduleLabel>, Box<dyn ScheduleLabel>)>,
+}
+
+impl MainSchedule {
+    pub(crate) fn add_parallel(
+        &mut self,
+        with: impl ScheduleLabel,
+        label: impl ScheduleLabel
+    ) {
+        self.parallel.push((Box::new(with), Box::new(label)));
+    }
+
+    // each main schedule with the schedules running in parallel with it
+    fn groups(&self) -> Vec<Vec<Box<dyn ScheduleLabel>>> {
+        self.schedules.iter().map(|label| {
+            let mut group = vec![label.box_clone()];
+
+            for (with, parallel) in &self.parallel {
+                if with.label_id() == label.label_id() {
+                    group.push(parallel.box_clone());
+                }
+            }
+
+            group
+        }).collect()
+    }
 }
 
 impl Default for MainSchedule {
@@ -48,6 +95,7 @@ impl Default for MainSchedule {
                 Box::new(PostUpdate),
                 Box::new(Last),
                 ],
+            parallel: Vec::new(),
         }
     }
 }
@@ -76,6 +124,12 @@ pub struct PostUpdate;
 #[derive(ScheduleLabel, Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct Last;
 
+///
+/// Schedule run once by App::exit, after the last tick.
+///
+#[derive(ScheduleLabel, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Exit;
+
 pub struct MainSchedulePlugin;
 
 impl Plugin for MainSchedulePlugin {
@@ -84,8 +138,19 @@ impl Plugin for MainSchedulePlugin {
         main_schedule.set_executor(Executors::Single);
 
         app.schedule(Main, main_schedule)
+            .declare_schedule(PreStartup)
+            .declare_schedule(Startup)
+            .declare_schedule(PostStartup)
+            .declare_schedule(First)
+            .declare_schedule(PreUpdate)
+            .declare_schedule(Update)
+            .declare_schedule(PostUpdate)
+            .declare_schedule(Last)
+            .declare_schedule(Exit)
             .init_resource::<MainSchedule>()
-            .system(Main, Main::main_system);
+            .init_resource::<FrameArena>()
+            .system(Main, Main::main_system)
+            .system(Last, Despawning::update.run_if(any_with_component::<Despawning>));
     }
 }
 
@@ -203,6 +268,29 @@ mod tests {
         assert_eq!(take(&value), "first, pre-update, update, post-update, last");
     }
 
+    #[test]
+    fn parallel_schedule() {
+        let mut app = App::new();
+        let value = Arc::new(Mutex::new(Vec::<String>::new()));
+
+        app.parallel_schedule(Update, Bogus);
+
+        let ptr = Arc::clone(&value);
+        app.system(Update, move || push(&ptr, "update"));
+
+        let ptr = Arc::clone(&value);
+        app.system(Bogus, move || push(&ptr, "bogus"));
+
+        let ptr = Arc::clone(&value);
+        app.system(Last, move || push(&ptr, "last"));
+
+        app.tick().unwrap();
+        let mut values = value.lock().unwrap().drain(..).collect::<Vec<String>>();
+        assert_eq!(values.pop().unwrap(), "last");
+        values.sort();
+        assert_eq!(values, vec!["bogus", "update"]);
+    }
+
     #[derive(ScheduleLabel, Clone, Copy, Debug, PartialEq, Eq, Hash)]
     pub struct Bogus;
 
@@ -1,13 +1,26 @@
 mod main_schedule;
 mod plugin;
 mod app;
+mod ticker;
+mod resource_init;
+mod lifecycle;
+mod runner;
 
-pub use app::App;
+pub use app::{App, TickReport};
+
+pub use ticker::Ticker;
+
+pub use runner::{ScheduleRunner, TickRate};
+
+pub use lifecycle::{AppStart, PrePause, Resume, AppExit};
+
+pub use resource_init::ResourceDeps;
 
 pub use main_schedule::{
     Main, 
     PreStartup, Startup, PostStartup,
     First, PreUpdate, Update, PostUpdate, Last,
+    Exit,
     MainSchedulePlugin,
 };
 
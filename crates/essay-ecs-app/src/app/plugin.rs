@@ -206,7 +206,7 @@ mod tests {
     impl Plugin for TestSpawn {
         fn build(&self, app: &mut App) {
             let value = self.value.clone();
-            app.system(Startup, move |mut c: Commands| c.spawn(value.clone()));
+            app.system(Startup, move |mut c: Commands| { c.spawn(value.clone()); });
         }
     }
 }
\ No newline at end of file
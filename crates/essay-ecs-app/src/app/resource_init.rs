@@ -0,0 +1,136 @@
+use std::any::{type_name, TypeId};
+
+use essay_ecs_core::{
+    error::{Error, Result},
+    store::FromStore,
+    Store,
+};
+
+///
+/// Tuple of resource types a resource needs before its FromStore init,
+/// e.g. `(Config,)` or `(Config, Assets)`.
+///
+pub trait ResourceDeps: 'static {
+    fn type_ids() -> Vec<TypeId>;
+}
+
+///
+/// Resource constructors deferred until App::finish, run in dependency
+/// order.
+///
+#[derive(Default)]
+pub(crate) struct ResourceInits {
+    pending: Vec<ResourceInit>,
+    // initialized resources in init order, rerun by App::reset_world
+    done: Vec<fn(&mut Store)>,
+}
+
+struct ResourceInit {
+    id: TypeId,
+    name: &'static str,
+    deps: Vec<TypeId>,
+    init: fn(&mut Store),
+    reinit: fn(&mut Store),
+    contains: fn(&Store) -> bool,
+}
+
+impl ResourceInits {
+    pub(crate) fn add<T, D>(&mut self)
+    where
+        T: FromStore + Send + 'static,
+        D: ResourceDeps
+    {
+        let id = TypeId::of::<T>();
+
+        if self.pending.iter().any(|item| item.id == id) {
+            return;
+        }
+
+        self.pending.push(ResourceInit {
+            id,
+            name: type_name::<T>(),
+            deps: D::type_ids(),
+            init: Store::init_resource::<T>,
+            reinit: reinit_resource::<T>,
+            contains: Store::contains_resource::<T>,
+        });
+    }
+
+    ///
+    /// Initializes the pending resources once their pending dependencies
+    /// are initialized. Circular dependencies are an error.
+    ///
+    pub(crate) fn init(&mut self, store: &mut Store) -> Result<()> {
+        while ! self.pending.is_empty() {
+            let ready = self.pending.iter().position(|item| {
+                item.deps.iter().all(|dep| {
+                    ! self.pending.iter().any(|other| other.id == *dep)
+                })
+            });
+
+            match ready {
+                Some(index) => {
+                    let item = self.pending.remove(index);
+
+                    // an inserted value isn't ours to reinitialize
+                    if ! (item.contains)(store) {
+                        (item.init)(store);
+                        self.done.push(item.reinit);
+                    }
+                }
+                None => {
+                    let names: Vec<&str> = self.pending.iter()
+                        .map(|item| item.name)
+                        .collect();
+
+                    self.pending.clear();
+
+                    return Err(Error::new(&format!(
+                        "circular resource initialization: {}",
+                        names.join(", ")
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// Reruns the initializers of the resources already initialized.
+    ///
+    pub(crate) fn reset(&self, store: &mut Store) {
+        for init in &self.done {
+            init(store);
+        }
+    }
+}
+
+///
+/// Replaces T with a newly initialized value.
+///
+pub(crate) fn reinit_resource<T: FromStore + Send + 'static>(store: &mut Store) {
+    let value = T::init(store);
+
+    store.insert_resource(value);
+}
+
+macro_rules! impl_resource_deps_tuple {
+    ($($part:ident),*) => {
+        impl<$($part: 'static),*> ResourceDeps for ($($part,)*) {
+            fn type_ids() -> Vec<TypeId> {
+                vec![$(TypeId::of::<$part>()),*]
+            }
+        }
+    }
+}
+
+impl_resource_deps_tuple!();
+impl_resource_deps_tuple!(R1);
+impl_resource_deps_tuple!(R1, R2);
+impl_resource_deps_tuple!(R1, R2, R3);
+impl_resource_deps_tuple!(R1, R2, R3, R4);
+impl_resource_deps_tuple!(R1, R2, R3, R4, R5);
+impl_resource_deps_tuple!(R1, R2, R3, R4, R5, R6);
+impl_resource_deps_tuple!(R1, R2, R3, R4, R5, R6, R7);
+impl_resource_deps_tuple!(R1, R2, R3, R4, R5, R6, R7, R8);
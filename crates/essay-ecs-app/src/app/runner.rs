@@ -0,0 +1,285 @@
+use std::{thread, time::{Duration, Instant}};
+
+use essay_ecs_core::error::Result;
+
+use crate::event::Events;
+
+use super::{App, AppExit, Plugin};
+
+///
+/// Runner plugin that ticks the app once, or in a loop until a system
+/// sends AppExit, optionally limited to a target tick rate.
+///
+#[derive(Clone, Debug)]
+pub struct ScheduleRunner {
+    is_loop: bool,
+    period: Option<Duration>,
+    max_ticks: Option<usize>,
+}
+
+impl ScheduleRunner {
+    pub fn once() -> Self {
+        Self {
+            is_loop: false,
+            period: None,
+            max_ticks: None,
+        }
+    }
+
+    pub fn run_loop() -> Self {
+        Self {
+            is_loop: true,
+            period: None,
+            max_ticks: None,
+        }
+    }
+
+    ///
+    /// Limits the loop to hz ticks per second by sleeping between ticks.
+    ///
+    pub fn target_hz(mut self, hz: f64) -> Self {
+        assert!(hz > 0., "target_hz must be positive");
+
+        self.period = Some(Duration::from_secs_f64(1. / hz));
+
+        self
+    }
+
+    ///
+    /// Stops the loop after n ticks even without an AppExit.
+    ///
+    pub fn max_ticks(mut self, n: usize) -> Self {
+        self.max_ticks = Some(n);
+
+        self
+    }
+
+    fn run(&self, mut app: App) -> Result<()> {
+        app.finish();
+        app.cleanup();
+
+        if ! self.is_loop {
+            app.tick()?;
+
+            return app.exit();
+        }
+
+        let mut ticks = 0;
+        let mut deadline = Instant::now();
+        let mut last = None;
+
+        loop {
+            let start = Instant::now();
+
+            if let Some(last) = last {
+                app.resource_mut::<TickRate>().update(start - last);
+            }
+            last = Some(start);
+
+            app.tick()?;
+            ticks += 1;
+
+            if self.max_ticks.is_some_and(|max| ticks >= max)
+                || app.get_resource::<Events<AppExit>>().is_some_and(|e| ! e.is_empty()) {
+                break;
+            }
+
+            if let Some(period) = self.period {
+                // the next deadline follows the last one, so sleep
+                // overshoot is recovered instead of accumulating
+                deadline += period;
+
+                let now = Instant::now();
+
+                if deadline > now {
+                    thread::sleep(deadline - now);
+                } else if now - deadline > period {
+                    // too far behind to catch up, e.g. after a stall
+                    deadline = now;
+                }
+            }
+        }
+
+        app.exit()
+    }
+}
+
+impl Plugin for ScheduleRunner {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(TickRate::new(self.period));
+        app.event::<AppExit>();
+
+        let runner = self.clone();
+        app.runner(move |app| runner.run(app));
+    }
+}
+
+///
+/// Tick rate of a looping ScheduleRunner: the target and the measured
+/// rate and jitter, averaged over recent ticks.
+///
+#[derive(Clone, Debug)]
+pub struct TickRate {
+    target: Option<Duration>,
+    period: Duration,
+    jitter: Duration,
+    ticks: usize,
+}
+
+impl TickRate {
+    // weight of the latest tick in the running averages
+    const ALPHA: f64 = 0.1;
+
+    fn new(target: Option<Duration>) -> Self {
+        Self {
+            target,
+            period: Duration::ZERO,
+            jitter: Duration::ZERO,
+            ticks: 0,
+        }
+    }
+
+    fn update(&mut self, period: Duration) {
+        if self.ticks == 0 {
+            self.period = period;
+        } else {
+            let mean = self.period.as_secs_f64();
+            let value = period.as_secs_f64();
+
+            let jitter = (value - mean).abs();
+
+            self.period = Duration::from_secs_f64(mean + Self::ALPHA * (value - mean));
+            self.jitter = Duration::from_secs_f64(
+                self.jitter.as_secs_f64() + Self::ALPHA * (jitter - self.jitter.as_secs_f64())
+            );
+        }
+
+        self.ticks += 1;
+    }
+
+    pub fn target_hz(&self) -> Option<f64> {
+        self.target.map(|period| 1. / period.as_secs_f64())
+    }
+
+    pub fn actual_hz(&self) -> f64 {
+        if self.period > Duration::ZERO {
+            1. / self.period.as_secs_f64()
+        } else {
+            0.
+        }
+    }
+
+    ///
+    /// Average tick period.
+    ///
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    ///
+    /// Average deviation of a tick's period from the average period.
+    ///
+    pub fn jitter(&self) -> Duration {
+        self.jitter
+    }
+
+    ///
+    /// Ticks measured, one less than the ticks run.
+    ///
+    pub fn ticks(&self) -> usize {
+        self.ticks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::{Arc, Mutex}, time::{Duration, Instant}};
+
+    use essay_ecs_core::{Local, Res};
+
+    use crate::{app::{App, AppExit, Update}, event::{Events, OutEvent}};
+
+    use super::{ScheduleRunner, TickRate};
+
+    #[test]
+    fn loop_until_exit() {
+        let mut app = App::new();
+        app.plugin(ScheduleRunner::run_loop());
+
+        let count = Arc::new(Mutex::new(0));
+
+        let ptr = count.clone();
+        app.system(Update, move |mut exit: OutEvent<AppExit>, mut n: Local<usize>| {
+            *n += 1;
+            *ptr.lock().unwrap() = *n;
+
+            if *n == 5 {
+                exit.send(AppExit);
+            }
+        });
+
+        app.run().unwrap();
+
+        assert_eq!(*count.lock().unwrap(), 5);
+    }
+
+    #[test]
+    fn frame_limiter() {
+        let mut app = App::new();
+        app.plugin(ScheduleRunner::run_loop().target_hz(100.).max_ticks(11));
+
+        let rate = Arc::new(Mutex::new(None));
+
+        let ptr = rate.clone();
+        app.on_exit(move |tick_rate: Res<TickRate>| {
+            *ptr.lock().unwrap() = Some(tick_rate.clone());
+        });
+
+        let start = Instant::now();
+        app.run().unwrap();
+
+        // sleeps only lengthen ticks, so only the lower bound is reliable
+        assert!(start.elapsed() >= Duration::from_millis(100));
+
+        let rate = rate.lock().unwrap().take().unwrap();
+        assert_eq!(rate.ticks(), 10);
+        assert_eq!(rate.target_hz(), Some(100.));
+        assert!(rate.actual_hz() > 0.);
+    }
+
+    #[test]
+    fn tick_rate_average() {
+        let mut rate = TickRate::new(None);
+        assert_eq!(rate.actual_hz(), 0.);
+
+        rate.update(Duration::from_millis(10));
+        assert_eq!(rate.period(), Duration::from_millis(10));
+        assert_eq!(rate.jitter(), Duration::ZERO);
+
+        rate.update(Duration::from_millis(20));
+        assert!((rate.period().as_secs_f64() - 0.011).abs() < 1e-9);
+        assert!((rate.jitter().as_secs_f64() - 0.001).abs() < 1e-9);
+        assert_eq!(rate.ticks(), 2);
+    }
+
+    #[test]
+    fn exit_sent_once() {
+        let mut app = App::new();
+        app.plugin(ScheduleRunner::run_loop());
+
+        let count = Arc::new(Mutex::new(0));
+
+        app.system(Update, |mut exit: OutEvent<AppExit>| {
+            exit.send(AppExit);
+        });
+
+        let ptr = count.clone();
+        app.on_exit(move |events: Res<Events<AppExit>>| {
+            *ptr.lock().unwrap() = events.len();
+        });
+
+        app.run().unwrap();
+
+        assert_eq!(*count.lock().unwrap(), 1);
+    }
+}
@@ -0,0 +1,302 @@
+use std::{sync::mpsc, thread::{self, JoinHandle}};
+
+use essay_ecs_core::error::Result;
+
+use crate::event::{Event, Events};
+
+use super::{App, PrePause, Resume};
+
+type Request = Box<dyn FnOnce(&mut App) + Send>;
+
+///
+/// Handle for driving an App from an external loop, e.g. a GUI or
+/// server event loop, instead of the App's runner.
+///
+/// The app may hold non-send resources, so it is built and ticked on
+/// its own thread. The Ticker only holds a channel to that thread, so
+/// it can be moved to whichever thread runs the external loop.
+///
+pub struct Ticker {
+    sender: Option<mpsc::Sender<Request>>,
+    handle: Option<JoinHandle<()>>,
+    is_paused: bool,
+    is_pausing: bool,
+}
+
+impl Ticker {
+    ///
+    /// Builds the app on a new thread, finishes its plugins, and returns
+    /// a Ticker for driving it.
+    ///
+    pub fn spawn(build: impl FnOnce() -> App + Send + 'static) -> Self {
+        let (sender, receiver) = mpsc::channel::<Request>();
+
+        let handle = thread::spawn(move || {
+            let mut app = build();
+            app.finish();
+            app.cleanup();
+
+            while let Ok(request) = receiver.recv() {
+                request(&mut app);
+            }
+        });
+
+        Self {
+            sender: Some(sender),
+            handle: Some(handle),
+            is_paused: false,
+            is_pausing: false,
+        }
+    }
+
+    ///
+    /// Ticks the main schedule, unless the ticker is paused.
+    ///
+    pub fn tick(&mut self) -> Result<()> {
+        if self.is_pausing {
+            // last tick, delivering PrePause
+            self.is_pausing = false;
+            self.with_app(|app| app.tick())?
+        } else if self.is_paused {
+            Ok(())
+        } else {
+            self.with_app(|app| app.tick())?
+        }
+    }
+
+    ///
+    /// Sends PrePause and pauses after the next tick.
+    ///
+    pub fn pause(&mut self) -> Result<()> {
+        if ! self.is_paused {
+            self.with_app(|app| send_registered(app, PrePause))?;
+            self.is_paused = true;
+            self.is_pausing = true;
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// Sends Resume, read on the next tick.
+    ///
+    pub fn resume(&mut self) -> Result<()> {
+        if self.is_paused {
+            self.with_app(|app| send_registered(app, Resume))?;
+            self.is_paused = false;
+            self.is_pausing = false;
+        }
+
+        Ok(())
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.is_paused
+    }
+
+    ///
+    /// Sends an event, which systems read on the next tick.
+    ///
+    pub fn inject_event<E: Event + Send>(&mut self, event: E) -> Result<()> {
+        self.with_app(move |app| {
+            app.event::<E>();
+
+            app.resource_mut::<Events<E>>().send(event);
+        })
+    }
+
+    ///
+    /// Sends AppExit and runs the Exit schedule.
+    ///
+    pub fn exit(&mut self) -> Result<()> {
+        self.with_app(|app| app.exit())?
+    }
+
+    ///
+    /// Runs a closure on the app's thread and waits for its result.
+    ///
+    pub fn with_app<R>(
+        &self,
+        fun: impl FnOnce(&mut App) -> R + Send + 'static
+    ) -> Result<R>
+    where
+        R: Send + 'static
+    {
+        let (sender, receiver) = mpsc::sync_channel(1);
+
+        let request: Request = Box::new(move |app| {
+            let _ = sender.send(fun(app));
+        });
+
+        match &self.sender {
+            Some(tx) if tx.send(request).is_ok() => {},
+            _ => return Err("ticker's app thread has stopped".into()),
+        }
+
+        receiver.recv()
+            .map_err(|_| "ticker's app thread panicked".into())
+    }
+}
+
+// lifecycle events are only sent when the app reads them
+fn send_registered<E: Event>(app: &mut App, event: E) {
+    if let Some(events) = app.get_mut_resource::<Events<E>>() {
+        events.send(event);
+    }
+}
+
+impl Drop for Ticker {
+    fn drop(&mut self) {
+        // closing the channel ends the app thread's loop
+        self.sender.take();
+
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use essay_ecs_core::{util::test::TestValues, Store};
+
+    use crate::{app::{App, AppExit, AppStart, PrePause, Resume, Update}, event::{Event, Events, InEvent}};
+
+    use super::Ticker;
+
+    #[test]
+    fn ticker_sender_thread() {
+        let values = TestValues::new();
+        let ptr = values.clone();
+
+        let mut ticker = Ticker::spawn(move || {
+            let mut app = App::new();
+
+            let mut ptr = ptr;
+            app.event::<TestEvent>();
+            app.system(Update, move |mut reader: InEvent<TestEvent>| {
+                for event in reader.iter() {
+                    ptr.push(&format!("{:?}", event));
+                }
+            });
+
+            app
+        });
+
+        let sender = ticker.with_app(|app| app.commands_sender()).unwrap();
+
+        thread::spawn(move || {
+            sender.add(|store: &mut Store| {
+                store.resource_mut::<Events<TestEvent>>().send(TestEvent(1));
+            });
+        }).join().unwrap();
+
+        ticker.tick().unwrap();
+
+        ticker.pause().unwrap();
+        ticker.inject_event(TestEvent(2)).unwrap();
+        ticker.tick().unwrap();
+        assert!(ticker.is_paused());
+
+        ticker.resume().unwrap();
+        ticker.tick().unwrap();
+
+        let mut values = values;
+        assert_eq!(values.take(), "TestEvent(1), TestEvent(2)");
+    }
+
+    #[test]
+    fn ticker_moved_to_thread() {
+        let values = TestValues::new();
+        let ptr = values.clone();
+
+        let ticker = Ticker::spawn(move || {
+            let mut app = App::new();
+
+            let mut ptr = ptr;
+            app.system(Update, move || ptr.push("tick"));
+
+            app
+        });
+
+        let ticker = thread::spawn(move || {
+            let mut ticker = ticker;
+
+            ticker.tick().unwrap();
+            ticker.tick().unwrap();
+
+            ticker
+        }).join().unwrap();
+
+        let mut values = values;
+        assert_eq!(values.take(), "tick, tick");
+
+        drop(ticker);
+    }
+
+    #[test]
+    fn lifecycle_events() {
+        let values = TestValues::new();
+        let ptr = values.clone();
+
+        let mut ticker = Ticker::spawn(move || {
+            let mut app = App::new();
+            app.event::<AppStart>()
+                .event::<PrePause>()
+                .event::<Resume>();
+
+            let mut ptr_update = ptr.clone();
+            app.system(Update, move |
+                mut start: InEvent<AppStart>,
+                mut pause: InEvent<PrePause>,
+                mut resume: InEvent<Resume>
+            | {
+                for event in start.iter() {
+                    ptr_update.push(&format!("{:?}", event));
+                }
+                for event in pause.iter() {
+                    ptr_update.push(&format!("{:?}", event));
+                }
+                for event in resume.iter() {
+                    ptr_update.push(&format!("{:?}", event));
+                }
+            });
+
+            let mut ptr = ptr;
+            app.on_exit(move |mut exit: InEvent<AppExit>| {
+                for event in exit.iter() {
+                    ptr.push(&format!("{:?}", event));
+                }
+            });
+
+            app
+        });
+
+        let mut values = values;
+
+        ticker.tick().unwrap();
+        assert_eq!(values.take(), "AppStart");
+
+        ticker.pause().unwrap();
+        ticker.tick().unwrap();
+        assert_eq!(values.take(), "PrePause");
+
+        ticker.tick().unwrap();
+        ticker.tick().unwrap();
+        assert_eq!(values.take(), "");
+
+        ticker.resume().unwrap();
+        ticker.tick().unwrap();
+        assert_eq!(values.take(), "Resume");
+
+        ticker.exit().unwrap();
+        assert_eq!(values.take(), "AppExit");
+    }
+
+    #[derive(Debug)]
+    struct TestEvent(usize);
+
+    impl Event for TestEvent {}
+}
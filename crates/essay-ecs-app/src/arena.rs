@@ -0,0 +1,383 @@
+use std::{
+    alloc::{self, Layout},
+    marker::PhantomData,
+    mem,
+    ops::{Deref, DerefMut},
+    ptr::{self, NonNull},
+    slice,
+    sync::Mutex,
+};
+
+///
+/// Bump allocator for transient per-tick data, reset by the main schedule
+/// after Last. Chunks are kept across resets, so steady-state ticks don't
+/// touch the global allocator.
+///
+pub struct FrameArena {
+    inner: Mutex<ArenaInner>,
+}
+
+struct ArenaInner {
+    chunks: Vec<Chunk>,
+    current: usize,
+}
+
+struct Chunk {
+    data: NonNull<u8>,
+    layout: Layout,
+    used: usize,
+}
+
+// chunks are only accessed through the mutex
+unsafe impl Send for FrameArena {}
+unsafe impl Sync for FrameArena {}
+
+impl FrameArena {
+    const CHUNK_SIZE: usize = 64 * 1024;
+
+    pub fn new() -> Self {
+        Self {
+            inner: Mutex::new(ArenaInner {
+                chunks: Vec::new(),
+                current: 0,
+            }),
+        }
+    }
+
+    pub fn alloc<T>(&self, value: T) -> FrameBox<'_, T> {
+        let ptr = self.alloc_layout(Layout::new::<T>()).cast::<T>();
+
+        unsafe {
+            ptr.as_ptr().write(value);
+
+            FrameBox { value: &mut *ptr.as_ptr() }
+        }
+    }
+
+    pub fn vec<T>(&self) -> FrameVec<'_, T> {
+        self.vec_with_capacity(0)
+    }
+
+    pub fn vec_with_capacity<T>(&self, capacity: usize) -> FrameVec<'_, T> {
+        let ptr = if capacity > 0 {
+            self.alloc_array::<T>(capacity)
+        } else {
+            NonNull::dangling()
+        };
+
+        FrameVec {
+            arena: self,
+            ptr,
+            len: 0,
+            capacity,
+            marker: PhantomData,
+        }
+    }
+
+    ///
+    /// Bytes allocated since the last reset.
+    ///
+    pub fn bytes_used(&self) -> usize {
+        let inner = self.inner.lock().unwrap();
+
+        inner.chunks.iter().map(|c| c.used).sum()
+    }
+
+    ///
+    /// Bytes reserved from the global allocator.
+    ///
+    pub fn capacity(&self) -> usize {
+        let inner = self.inner.lock().unwrap();
+
+        inner.chunks.iter().map(|c| c.layout.size()).sum()
+    }
+
+    ///
+    /// Frees every allocation while keeping the chunks. Taking &mut self
+    /// ensures no FrameBox or FrameVec is still alive.
+    ///
+    pub fn reset(&mut self) {
+        let inner = self.inner.get_mut().unwrap();
+
+        for chunk in &mut inner.chunks {
+            chunk.used = 0;
+        }
+
+        inner.current = 0;
+    }
+
+    fn alloc_array<T>(&self, n: usize) -> NonNull<T> {
+        let layout = Layout::array::<T>(n).expect("frame allocation overflow");
+
+        self.alloc_layout(layout).cast::<T>()
+    }
+
+    fn alloc_layout(&self, layout: Layout) -> NonNull<u8> {
+        if layout.size() == 0 {
+            return unsafe { NonNull::new_unchecked(layout.align() as *mut u8) };
+        }
+
+        let mut inner = self.inner.lock().unwrap();
+
+        loop {
+            let current = inner.current;
+
+            if let Some(chunk) = inner.chunks.get_mut(current) {
+                if let Some(ptr) = chunk.alloc(layout) {
+                    return ptr;
+                }
+
+                inner.current += 1;
+            } else {
+                let size = Self::CHUNK_SIZE.max(layout.size() + layout.align());
+                inner.chunks.push(Chunk::new(size));
+            }
+        }
+    }
+}
+
+impl Default for FrameArena {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Chunk {
+    fn new(size: usize) -> Self {
+        let layout = Layout::from_size_align(size, 16).unwrap();
+
+        let data = unsafe { alloc::alloc(layout) };
+
+        Self {
+            data: NonNull::new(data).unwrap_or_else(|| alloc::handle_alloc_error(layout)),
+            layout,
+            used: 0,
+        }
+    }
+
+    fn alloc(&mut self, layout: Layout) -> Option<NonNull<u8>> {
+        let base = self.data.as_ptr() as usize;
+        let start = (base + self.used).next_multiple_of(layout.align());
+        let end = start + layout.size();
+
+        if end <= base + self.layout.size() {
+            self.used = end - base;
+
+            Some(unsafe { NonNull::new_unchecked(start as *mut u8) })
+        } else {
+            None
+        }
+    }
+}
+
+impl Drop for Chunk {
+    fn drop(&mut self) {
+        unsafe { alloc::dealloc(self.data.as_ptr(), self.layout) }
+    }
+}
+
+///
+/// Value allocated in the FrameArena. Drop runs when the box is dropped,
+/// and the memory is reclaimed at the next reset.
+///
+pub struct FrameBox<'a, T> {
+    value: &'a mut T,
+}
+
+impl<T> Deref for FrameBox<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        self.value
+    }
+}
+
+impl<T> DerefMut for FrameBox<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.value
+    }
+}
+
+impl<T> Drop for FrameBox<'_, T> {
+    fn drop(&mut self) {
+        unsafe { ptr::drop_in_place(self.value as *mut T) }
+    }
+}
+
+///
+/// Growable vector in the FrameArena. Growing copies into a new block,
+/// and the old block is reclaimed at the next reset.
+///
+pub struct FrameVec<'a, T> {
+    arena: &'a FrameArena,
+    ptr: NonNull<T>,
+    len: usize,
+    capacity: usize,
+    marker: PhantomData<T>,
+}
+
+unsafe impl<T: Send> Send for FrameVec<'_, T> {}
+unsafe impl<T: Sync> Sync for FrameVec<'_, T> {}
+
+impl<'a, T> FrameVec<'a, T> {
+    pub fn push(&mut self, value: T) {
+        if self.len == self.capacity {
+            self.grow();
+        }
+
+        unsafe { self.ptr.as_ptr().add(self.len).write(value); }
+
+        self.len += 1;
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            None
+        } else {
+            self.len -= 1;
+
+            unsafe { Some(self.ptr.as_ptr().add(self.len).read()) }
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn clear(&mut self) {
+        let len = mem::replace(&mut self.len, 0);
+
+        unsafe {
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(self.ptr.as_ptr(), len));
+        }
+    }
+
+    fn grow(&mut self) {
+        if mem::size_of::<T>() == 0 {
+            self.capacity = usize::MAX;
+            return;
+        }
+
+        let capacity = (2 * self.capacity).max(4);
+        let ptr = self.arena.alloc_array::<T>(capacity);
+
+        unsafe {
+            ptr::copy_nonoverlapping(self.ptr.as_ptr(), ptr.as_ptr(), self.len);
+        }
+
+        self.ptr = ptr;
+        self.capacity = capacity;
+    }
+}
+
+impl<T> Deref for FrameVec<'_, T> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<T> DerefMut for FrameVec<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<T> Extend<T> for FrameVec<'_, T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<T> Drop for FrameVec<'_, T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{rc::Rc, cell::Cell};
+
+    use essay_ecs_core::Res;
+
+    use crate::app::{App, Update};
+
+    use super::FrameArena;
+
+    #[test]
+    fn alloc_reset() {
+        let mut arena = FrameArena::new();
+
+        let mut a = arena.alloc(10u32);
+        *a += 1;
+        assert_eq!(*a, 11);
+
+        let mut vec = arena.vec::<u64>();
+        vec.extend(0..100);
+        assert_eq!(vec.iter().sum::<u64>(), 4950);
+        assert_eq!(vec.pop(), Some(99));
+
+        drop(a);
+        drop(vec);
+
+        let capacity = arena.capacity();
+        assert!(arena.bytes_used() > 0);
+
+        arena.reset();
+        assert_eq!(arena.bytes_used(), 0);
+
+        let vec = arena.vec_with_capacity::<u8>(1000);
+        assert_eq!(vec.capacity(), 1000);
+        drop(vec);
+
+        assert_eq!(arena.capacity(), capacity);
+    }
+
+    #[test]
+    fn drop_values() {
+        let arena = FrameArena::new();
+        let count = Rc::new(Cell::new(0));
+
+        let mut vec = arena.vec();
+        for _ in 0..10 {
+            vec.push(DropCount(count.clone()));
+        }
+
+        let value = arena.alloc(DropCount(count.clone()));
+
+        drop(vec);
+        assert_eq!(count.get(), 10);
+
+        drop(value);
+        assert_eq!(count.get(), 11);
+    }
+
+    #[test]
+    fn reset_each_tick() {
+        let mut app = App::new();
+
+        app.system(Update, |arena: Res<FrameArena>| {
+            let mut vec = arena.vec_with_capacity(16);
+            vec.extend(0..16u32);
+
+            assert_eq!(arena.bytes_used(), 16 * 4);
+        });
+
+        app.tick().unwrap();
+        app.tick().unwrap();
+
+        assert_eq!(app.resource::<FrameArena>().bytes_used(), 0);
+    }
+
+    struct DropCount(Rc<Cell<usize>>);
+
+    impl Drop for DropCount {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+}
@@ -0,0 +1,218 @@
+use std::{collections::HashMap, marker::PhantomData, sync::Mutex};
+
+use essay_ecs_core::{
+    entity::{Component, EntityId},
+    schedule::ScheduleLabel,
+    Query, ResMut,
+};
+
+use crate::app::{App, Plugin, PostUpdate, PreUpdate};
+
+///
+/// Device side of a mirrored column, e.g. a wgpu storage buffer and the
+/// compute pipeline that updates it. The ECS stays the source of truth:
+/// values are uploaded each tick and the results read back into T,
+/// except for values systems changed after the upload.
+///
+pub trait ComputeBackend<T>: Send + 'static {
+    ///
+    /// Copies the column to the device, resizing buffers to data.len().
+    ///
+    fn upload(&mut self, data: &[T]);
+
+    ///
+    /// Dispatches the compute work after the upload.
+    ///
+    fn dispatch(&mut self) {
+    }
+
+    ///
+    /// Reads the results back, with data in the order of the last upload.
+    ///
+    fn download(&mut self, data: &mut [T]);
+}
+
+///
+/// Resource with the backend and the staging copy of the column for T.
+///
+pub struct ComputeMirror<T, B> {
+    backend: B,
+    staging: Vec<T>,
+    results: Vec<T>,
+    rows: HashMap<EntityId, usize>,
+}
+
+impl<T: Component + Copy + PartialEq, B: ComputeBackend<T>> ComputeMirror<T, B> {
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    pub fn backend_mut(&mut self) -> &mut B {
+        &mut self.backend
+    }
+
+    ///
+    /// Rows in the last upload.
+    ///
+    pub fn len(&self) -> usize {
+        self.staging.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.staging.is_empty()
+    }
+
+    fn upload(mut mirror: ResMut<Self>, query: Query<(EntityId, &T)>) {
+        let mirror = &mut *mirror;
+
+        mirror.staging.clear();
+        mirror.rows.clear();
+
+        for (id, value) in query.iter() {
+            mirror.rows.insert(id, mirror.staging.len());
+            mirror.staging.push(*value);
+        }
+
+        mirror.backend.upload(&mirror.staging);
+        mirror.backend.dispatch();
+    }
+
+    fn download(mut mirror: ResMut<Self>, query: Query<(EntityId, &mut T)>) {
+        let mirror = &mut *mirror;
+
+        mirror.results.clone_from(&mirror.staging);
+        mirror.backend.download(&mut mirror.results);
+
+        // entities spawned after the upload keep their values, and so do
+        // values written since the upload, which are newer than the device's
+        for (id, value) in query.iter() {
+            if let Some(row) = mirror.rows.get(&id) {
+                if *value == mirror.staging[*row] {
+                    *value = mirror.results[*row];
+                }
+            }
+        }
+    }
+}
+
+///
+/// Mirrors the Copy column T into a compute backend, uploading in
+/// PreUpdate and reading back in PostUpdate by default, so Update systems
+/// run while the device computes. A value an Update system changes keeps
+/// the change instead of the device's result.
+///
+pub struct ComputePlugin<T, B> {
+    backend: Mutex<Option<B>>,
+    upload: Box<dyn ScheduleLabel>,
+    download: Box<dyn ScheduleLabel>,
+    marker: PhantomData<fn(T)>,
+}
+
+impl<T: Component + Copy + PartialEq, B: ComputeBackend<T>> ComputePlugin<T, B> {
+    pub fn new(backend: B) -> Self {
+        Self {
+            backend: Mutex::new(Some(backend)),
+            upload: Box::new(PreUpdate),
+            download: Box::new(PostUpdate),
+            marker: PhantomData,
+        }
+    }
+
+    pub fn upload(mut self, label: impl ScheduleLabel) -> Self {
+        self.upload = Box::new(label);
+
+        self
+    }
+
+    ///
+    /// Schedule that reads back the results. Readback into the same
+    /// schedule as the upload runs after it.
+    ///
+    pub fn download(mut self, label: impl ScheduleLabel) -> Self {
+        self.download = Box::new(label);
+
+        self
+    }
+}
+
+impl<T: Component + Copy + PartialEq, B: ComputeBackend<T>> Plugin for ComputePlugin<T, B> {
+    fn build(&self, app: &mut App) {
+        let backend = self.backend.lock().unwrap().take()
+            .expect("ComputePlugin backend was already used");
+
+        app.insert_resource(ComputeMirror::<T, B> {
+            backend,
+            staging: Vec::new(),
+            results: Vec::new(),
+            rows: HashMap::new(),
+        });
+
+        app.system(self.upload.box_clone(), ComputeMirror::<T, B>::upload);
+        app.system(self.download.box_clone(), ComputeMirror::<T, B>::download);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use essay_ecs_core::{entity::Component, Query, Store};
+
+    use crate::app::{App, Update};
+
+    use super::{ComputeBackend, ComputeMirror, ComputePlugin};
+
+    #[test]
+    fn compute_mirror() {
+        let mut app = App::new();
+
+        app.plugin(ComputePlugin::<Field, Doubler>::new(Doubler(Vec::new())));
+
+        // writes one field while the device computes
+        app.system(Update, |q: Query<&mut Field>| {
+            for field in q.iter() {
+                if field.0 < 2. {
+                    field.0 += 10.;
+                }
+            }
+        });
+
+        app.eval(|store: &mut Store| {
+            store.spawn(Field(1.));
+            store.spawn(Field(3.));
+            Ok(())
+        }).unwrap();
+
+        app.tick().unwrap();
+
+        let mut values = app.eval(|q: Query<&Field>| {
+            q.iter().map(|f| f.0).collect::<Vec<_>>()
+        }).unwrap();
+        values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        // the Update write is kept, the untouched field takes the result
+        assert_eq!(values, vec![6., 11.]);
+        assert_eq!(app.resource::<ComputeMirror<Field, Doubler>>().len(), 2);
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Field(f32);
+
+    impl Component for Field {}
+
+    struct Doubler(Vec<Field>);
+
+    impl ComputeBackend<Field> for Doubler {
+        fn upload(&mut self, data: &[Field]) {
+            self.0 = data.to_vec();
+        }
+
+        fn dispatch(&mut self) {
+            for field in self.0.iter_mut() {
+                field.0 *= 2.;
+            }
+        }
+
+        fn download(&mut self, data: &mut [Field]) {
+            data.copy_from_slice(&self.0);
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use std::{any::type_name, env, fs, io, path::{Path, PathBuf}};
+
+use essay_ecs_core::error::Result;
+use serde::{de::DeserializeOwned, Serialize};
+use toml::{Table, Value};
+
+use crate::app::App;
+
+///
+/// Plugin tunables loaded by App::init_config. Values are layered: the
+/// Default impl, then the SECTION table of the config file, then
+/// environment variables named SECTION_FIELD in upper case, e.g.
+/// `RENDER_WIDTH=800` for the width field of the "render" section.
+///
+pub trait Config: Serialize + DeserializeOwned + Default + Send + 'static {
+    const SECTION: &'static str;
+}
+
+///
+/// Path of the TOML file read by App::init_config, "config.toml" if the
+/// resource is missing. A missing file is not an error.
+///
+pub struct ConfigFile(pub PathBuf);
+
+impl App {
+    pub fn config_file(&mut self, path: impl AsRef<Path>) -> &mut Self {
+        self.insert_resource(ConfigFile(path.as_ref().to_path_buf()));
+
+        self
+    }
+
+    ///
+    /// Loads T into a resource. Errors with the file, section and field
+    /// on a malformed file or a value that doesn't match T.
+    ///
+    pub fn init_config<T: Config>(&mut self) -> Result<&mut Self> {
+        let path = match self.get_resource::<ConfigFile>() {
+            Some(file) => file.0.clone(),
+            None => PathBuf::from("config.toml"),
+        };
+
+        let config = load_config::<T>(&path, |key| env::var(key).ok())?;
+        self.insert_resource(config);
+
+        Ok(self)
+    }
+}
+
+///
+/// Loads T from the layered sources, reading environment variables
+/// through `var`.
+///
+pub fn load_config<T: Config>(
+    path: &Path,
+    var: impl Fn(&str) -> Option<String>
+) -> Result<T> {
+    let mut table = match Value::try_from(T::default()) {
+        Ok(Value::Table(table)) => table,
+        Ok(_) => return Err(format!("config {} must be a struct", type_name::<T>()).into()),
+        Err(err) => return Err(format!("config {}: {}", type_name::<T>(), err).into()),
+    };
+
+    if let Some(section) = file_section(path, T::SECTION)? {
+        for (key, value) in section {
+            table.insert(key, value);
+        }
+    }
+
+    let prefix = env_name(T::SECTION);
+    let keys: Vec<String> = table.keys().cloned().collect();
+
+    for key in keys {
+        if let Some(text) = var(&format!("{}_{}", prefix, env_name(&key))) {
+            let value = match table.get(&key) {
+                Some(Value::String(_)) => Value::String(text),
+                _ => env_value(&text),
+            };
+
+            table.insert(key, value);
+        }
+    }
+
+    match Value::Table(table).try_into() {
+        Ok(config) => Ok(config),
+        Err(err) => Err(format!("config [{}] in {} or environment: {}",
+            T::SECTION, path.display(), err
+        ).into()),
+    }
+}
+
+fn file_section(path: &Path, section: &str) -> Result<Option<Table>> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(format!("config file {}: {}", path.display(), err).into()),
+    };
+
+    let mut file: Table = match text.parse() {
+        Ok(file) => file,
+        Err(err) => return Err(format!("config file {}: {}", path.display(), err).into()),
+    };
+
+    match file.remove(section) {
+        Some(Value::Table(table)) => Ok(Some(table)),
+        Some(_) => Err(format!("config file {}: [{}] is not a table",
+            path.display(), section
+        ).into()),
+        None => Ok(None),
+    }
+}
+
+fn env_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect()
+}
+
+// Environment values for non-string fields are TOML values, e.g. 800,
+// true or [1, 2], falling back to a plain string. String fields take the
+// text as is, so RENDER_TITLE=123 is the string "123".
+fn env_value(text: &str) -> Value {
+    match format!("value = {}", text).parse::<Table>() {
+        Ok(mut table) => table.remove("value").unwrap(),
+        Err(_) => Value::String(text.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, env, fs, path::Path};
+
+    use serde::{Deserialize, Serialize};
+
+    use crate::app::App;
+
+    use super::{load_config, Config};
+
+    #[test]
+    fn config_layers() {
+        let path = env::temp_dir().join(format!("essay-config-{}.toml", std::process::id()));
+        fs::write(&path, "[render]\nwidth = 800\ntitle = \"file\"\n\n[other]\nwidth = 1\n").unwrap();
+
+        let vars = HashMap::from([("RENDER_TITLE", "env"), ("RENDER_VSYNC", "false")]);
+        let var = |key: &str| vars.get(key).map(|v| v.to_string());
+
+        let config = load_config::<TestRender>(&path, var).unwrap();
+        assert_eq!(config, TestRender { width: 800, height: 480, title: "env".into(), vsync: false });
+
+        let config = load_config::<TestRender>(&path, |key| {
+            (key == "RENDER_TITLE").then(|| "123".to_string())
+        }).unwrap();
+        assert_eq!(config.title, "123");
+
+        let config = load_config::<TestRender>(Path::new("missing.toml"), |_| None).unwrap();
+        assert_eq!(config, TestRender::default());
+
+        let err = load_config::<TestRender>(&path, |key| {
+            (key == "RENDER_HEIGHT").then(|| "tall".to_string())
+        }).unwrap_err();
+        assert!(err.message().contains("[render]"));
+
+        fs::write(&path, "[render\nwidth = 800\n").unwrap();
+        let err = load_config::<TestRender>(&path, |_| None).unwrap_err();
+        assert!(err.message().contains("config file"));
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn init_config() {
+        let mut app = App::new();
+
+        app.config_file("missing.toml").init_config::<TestRender>().unwrap();
+
+        assert_eq!(app.resource::<TestRender>(), &TestRender::default());
+    }
+
+    #[test]
+    fn init_config_error() {
+        let path = env::temp_dir().join(format!("essay-config-err-{}.toml", std::process::id()));
+        fs::write(&path, "[render]\nwidth = \"wide\"\n").unwrap();
+
+        let mut app = App::new();
+
+        let err = app.config_file(&path).init_config::<TestRender>().err().unwrap();
+        assert!(err.message().contains("[render]"));
+        assert!(app.get_resource::<TestRender>().is_none());
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct TestRender {
+        width: u32,
+        height: u32,
+        title: String,
+        vsync: bool,
+    }
+
+    impl Default for TestRender {
+        fn default() -> Self {
+            Self { width: 640, height: 480, title: "default".into(), vsync: true }
+        }
+    }
+
+    impl Config for TestRender {
+        const SECTION: &'static str = "render";
+    }
+}
@@ -0,0 +1,371 @@
+use std::{
+    any::type_name,
+    collections::BTreeMap,
+    mem,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use essay_ecs_core::{
+    error::Result,
+    schedule::{SystemMeta, SystemObserver},
+    Res, ResMut, Schedules, ScheduleLabel, Store,
+};
+use log::info;
+
+use crate::{
+    app::{App, Plugin, Last},
+    event::{Event, Events},
+};
+
+mod ecs { pub mod core { pub use essay_ecs_core::*; } }
+use ecs as essay_ecs;
+
+///
+/// Schedule run every DiagnosticsPlugin interval ticks, after Last.
+/// Reporter systems fill the DiagnosticsReport resource, which is then
+/// published to the sinks.
+///
+#[derive(ScheduleLabel, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Diagnostics;
+
+pub trait DiagnosticSink: Send + 'static {
+    fn report(&mut self, report: &DiagnosticsReport);
+}
+
+///
+/// Default sink, writing the report to the log at info level.
+///
+pub struct LogSink;
+
+impl DiagnosticSink for LogSink {
+    fn report(&mut self, report: &DiagnosticsReport) {
+        info!("diagnostics tick {}: {} entities", report.tick(), report.entity_count());
+
+        for system in report.systems() {
+            info!("  system {} runs={} mean={:?}", system.name(), system.runs(), system.mean());
+        }
+
+        for (name, len) in report.events() {
+            info!("  events {} queued={}", name, len);
+        }
+
+        for (name, bytes) in report.memory() {
+            info!("  memory {} bytes={}", name, bytes);
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct DiagnosticsReport {
+    tick: usize,
+    entity_count: usize,
+    systems: Vec<SystemTiming>,
+    events: Vec<(String, usize)>,
+    memory: Vec<(String, usize)>,
+}
+
+impl DiagnosticsReport {
+    pub fn tick(&self) -> usize {
+        self.tick
+    }
+
+    pub fn entity_count(&self) -> usize {
+        self.entity_count
+    }
+
+    ///
+    /// Timings since the previous report, sorted by system name.
+    ///
+    pub fn systems(&self) -> &[SystemTiming] {
+        &self.systems
+    }
+
+    pub fn events(&self) -> &[(String, usize)] {
+        &self.events
+    }
+
+    ///
+    /// Approximate bytes held by each system's locals and command queue,
+    /// largest first.
+    ///
+    pub fn memory(&self) -> &[(String, usize)] {
+        &self.memory
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SystemTiming {
+    name: String,
+    runs: usize,
+    total: Duration,
+}
+
+impl SystemTiming {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn runs(&self) -> usize {
+        self.runs
+    }
+
+    pub fn total(&self) -> Duration {
+        self.total
+    }
+
+    pub fn mean(&self) -> Duration {
+        if self.runs > 0 {
+            self.total / self.runs as u32
+        } else {
+            Duration::ZERO
+        }
+    }
+}
+
+///
+/// Runs the Diagnostics schedule every interval ticks with the built-in
+/// entity count, system timing, event queue and system memory reporters. Reports go to
+/// the registered sinks, or to LogSink when none are registered.
+///
+pub struct DiagnosticsPlugin {
+    interval: usize,
+    sinks: Mutex<Vec<Box<dyn DiagnosticSink>>>,
+}
+
+impl DiagnosticsPlugin {
+    pub fn new() -> Self {
+        Self {
+            interval: 60,
+            sinks: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn interval(mut self, ticks: usize) -> Self {
+        assert!(ticks > 0);
+
+        self.interval = ticks;
+
+        self
+    }
+
+    pub fn sink(self, sink: impl DiagnosticSink) -> Self {
+        self.sinks.lock().unwrap().push(Box::new(sink));
+
+        self
+    }
+}
+
+impl Default for DiagnosticsPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Plugin for DiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        let mut sinks: Vec<Box<dyn DiagnosticSink>> = self.sinks.lock().unwrap()
+            .drain(..)
+            .collect();
+
+        if sinks.is_empty() {
+            sinks.push(Box::new(LogSink));
+        }
+
+        let timings = TimingObserver::default();
+
+        app.insert_resource(DiagnosticsState {
+            interval: self.interval,
+            ticks: 0,
+            sinks,
+            events: Vec::new(),
+        });
+        app.insert_resource(SystemTimings(timings.0.clone()));
+        app.init_resource::<DiagnosticsReport>();
+
+        app.resource_mut::<Schedules>().add_observer(timings);
+
+        app.declare_schedule(Diagnostics);
+        app.system(Diagnostics, DiagnosticsState::entity_count_system);
+        app.system(Diagnostics, DiagnosticsState::system_timings_system);
+        app.system(Diagnostics, DiagnosticsState::event_queues_system);
+        app.system(Diagnostics, DiagnosticsState::memory_system);
+
+        app.system(Last, DiagnosticsState::run_system);
+    }
+
+    fn finish(&self, app: &mut App) {
+        let events = app.event_queues().to_vec();
+
+        app.resource_mut::<DiagnosticsState>().events = events;
+    }
+}
+
+struct DiagnosticsState {
+    interval: usize,
+    ticks: usize,
+    sinks: Vec<Box<dyn DiagnosticSink>>,
+    events: Vec<EventQueue>,
+}
+
+impl DiagnosticsState {
+    fn run_system(store: &mut Store) -> Result<()> {
+        let state = store.resource_mut::<DiagnosticsState>();
+        state.ticks += 1;
+
+        if ! state.ticks.is_multiple_of(state.interval) {
+            return Ok(());
+        }
+
+        let tick = state.ticks;
+
+        store.run_schedule_optional(Diagnostics)?;
+
+        let mut report = mem::take(store.resource_mut::<DiagnosticsReport>());
+        report.tick = tick;
+
+        for sink in &mut store.resource_mut::<DiagnosticsState>().sinks {
+            sink.report(&report);
+        }
+
+        Ok(())
+    }
+
+    fn entity_count_system(store: &mut Store) -> Result<()> {
+        let count = store.entity_count();
+
+        store.resource_mut::<DiagnosticsReport>().entity_count = count;
+
+        Ok(())
+    }
+
+    fn system_timings_system(
+        timings: Res<SystemTimings>,
+        mut report: ResMut<DiagnosticsReport>
+    ) {
+        let timings = mem::take(&mut *timings.0.lock().unwrap());
+
+        report.systems = timings.into_iter()
+            .map(|(name, (runs, total))| SystemTiming { name, runs, total })
+            .collect();
+    }
+
+    fn event_queues_system(store: &mut Store) -> Result<()> {
+        let events: Vec<(String, usize)> = store.resource::<DiagnosticsState>().events
+            .iter()
+            .map(|(name, len)| (name.to_string(), len(store)))
+            .collect();
+
+        store.resource_mut::<DiagnosticsReport>().events = events;
+
+        Ok(())
+    }
+
+    fn memory_system(store: &mut Store) -> Result<()> {
+        let mut memory = store.resource::<Schedules>().system_memory();
+        memory.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        store.resource_mut::<DiagnosticsReport>().memory = memory;
+
+        Ok(())
+    }
+}
+
+type TimingMap = Arc<Mutex<BTreeMap<String, (usize, Duration)>>>;
+
+struct SystemTimings(TimingMap);
+
+#[derive(Default)]
+struct TimingObserver(TimingMap);
+
+impl SystemObserver for TimingObserver {
+    fn after_run(&self, meta: &SystemMeta, elapsed: Duration) {
+        let mut timings = self.0.lock().unwrap();
+
+        let entry = timings.entry(meta.name().to_string()).or_default();
+        entry.0 += 1;
+        entry.1 += elapsed;
+    }
+}
+
+///
+/// Event type name and its buffered length in a store.
+///
+pub(crate) type EventQueue = (&'static str, fn(&Store) -> usize);
+
+pub(crate) fn event_queue<E: Event>() -> EventQueue {
+    (type_name::<E>(), Events::<E>::store_len)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use essay_ecs_core::Commands;
+
+    use crate::{app::{App, Update}, event::{Event, OutEvent}};
+
+    use super::{DiagnosticSink, DiagnosticsPlugin, DiagnosticsReport};
+
+    #[test]
+    fn diagnostics_interval() {
+        let reports = Arc::new(Mutex::new(Vec::<DiagnosticsReport>::new()));
+
+        let mut app = App::new();
+
+        app.event::<TestEvent>();
+        app.plugin(DiagnosticsPlugin::new()
+            .interval(3)
+            .sink(TestSink(reports.clone()))
+        );
+
+        app.system(Update, |mut c: Commands, mut out: OutEvent<TestEvent>| {
+            c.spawn(TestA(1));
+            out.send(TestEvent);
+        });
+
+        app.finish();
+
+        for _ in 0..7 {
+            app.tick().unwrap();
+        }
+
+        let reports = reports.lock().unwrap();
+        assert_eq!(reports.len(), 2);
+
+        assert_eq!(reports[0].tick(), 3);
+        assert_eq!(reports[0].entity_count(), 3);
+        assert_eq!(reports[1].tick(), 6);
+        assert_eq!(reports[1].entity_count(), 6);
+
+        let update = reports[1].systems().iter()
+            .find(|s| s.name().contains("diagnostics::tests"))
+            .unwrap();
+        assert_eq!(update.runs(), 3);
+
+        assert_eq!(reports[1].events().len(), 1);
+        assert!(reports[1].events()[0].0.contains("TestEvent"));
+        assert_eq!(reports[1].events()[0].1, 2);
+
+        let memory = reports[1].memory().iter()
+            .find(|(name, _)| name.contains("diagnostics::tests"))
+            .unwrap();
+        assert!(memory.1 > 0);
+    }
+
+    struct TestSink(Arc<Mutex<Vec<DiagnosticsReport>>>);
+
+    impl DiagnosticSink for TestSink {
+        fn report(&mut self, report: &DiagnosticsReport) {
+            self.0.lock().unwrap().push(report.clone());
+        }
+    }
+
+    struct TestA(#[allow(unused)] u32);
+
+    impl essay_ecs_core::entity::Component for TestA {}
+
+    struct TestEvent;
+
+    impl Event for TestEvent {}
+}
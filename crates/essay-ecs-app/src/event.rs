@@ -1,14 +1,16 @@
-use std::{marker::PhantomData, mem, ops::{DerefMut, Deref}};
+use std::{any::type_name, marker::PhantomData, mem, ops::{DerefMut, Deref}, sync::{atomic::{AtomicU64, Ordering}, Arc}};
 
 use essay_ecs_core::{
     error::Result,
     Local, 
     ResMut, 
     Store, 
+    param::HeapSize,
     prelude::Param, 
     schedule::{SystemMeta, UnsafeStore}, 
     Res
 };
+use log::warn;
 
 // see bevy_ecs/src/event.rs
 //
@@ -16,16 +18,161 @@ use essay_ecs_core::{
 // Where events are resources, channels are components.
 //
 
+const WARN_LEN: usize = 4096;
+const WARN_LAG: usize = 4096;
+
+///
+/// Send order counter shared by a store's event types, for merged
+/// InEvents readers.
+///
+#[derive(Clone, Default)]
+pub(crate) struct EventStamps(Arc<AtomicU64>);
+
 pub struct Events<E: Event> {
     events_next: Vec<E>,
     events_prev: Vec<E>,
 
+    stamps_next: Vec<u64>,
+    stamps_prev: Vec<u64>,
+
     ticks: usize,
+    n_sent: usize,
+
+    peak_len: usize,
+    warn_len: usize,
+    warn_lag: usize,
+
+    // systems with an OutEvent, named in the buffer warning
+    writers: Vec<String>,
+
+    // send order counter, shared with the store's other event types
+    // once a reader or writer is initialized
+    stamps: Arc<AtomicU64>,
 }
 
 impl<E: Event> Events<E> {
     pub fn send(&mut self, event: E) {
         self.events_next.push(event);
+        self.stamps_next.push(self.stamps.fetch_add(1, Ordering::Relaxed));
+        self.n_sent += 1;
+
+        let len = self.len();
+
+        if self.peak_len < len {
+            self.peak_len = len;
+
+            if self.warn_len <= len {
+                if self.writers.is_empty() {
+                    warn!("Events<{}> holds {} events, is Events::update scheduled?",
+                        type_name::<E>(), len);
+                } else {
+                    warn!("Events<{}> holds {} events sent by system {}, is Events::update scheduled?",
+                        type_name::<E>(), len, self.writers.join(", "));
+                }
+
+                self.warn_len = 2 * len;
+            }
+        }
+    }
+
+    ///
+    /// Total number of events sent since the resource was created.
+    ///
+    pub fn n_sent(&self) -> usize {
+        self.n_sent
+    }
+
+    ///
+    /// Reader that isn't tied to a system, for the runner or exclusive
+    /// systems. Like InEvent, it only sees events from the current and
+    /// previous update.
+    ///
+    pub fn get_reader(&self) -> ManualEventReader<E> {
+        ManualEventReader {
+            cursor: InEventCursor::default(),
+        }
+    }
+
+    ///
+    /// Events readable this update, from this and the previous update.
+    ///
+    pub fn len(&self) -> usize {
+        self.events_prev.len() + self.events_next.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    ///
+    /// Largest number of buffered events since the resource was created.
+    ///
+    pub fn peak_len(&self) -> usize {
+        self.peak_len
+    }
+
+    ///
+    /// Buffer size that logs a warning, doubling after each warning.
+    /// Defaults to 4096.
+    ///
+    pub fn set_warn_len(&mut self, warn_len: usize) {
+        self.warn_len = warn_len;
+    }
+
+    ///
+    /// Unread events in a reader that log a warning, doubling after each
+    /// warning for that reader. Defaults to 4096.
+    ///
+    pub fn set_warn_lag(&mut self, warn_lag: usize) {
+        self.warn_lag = warn_lag;
+    }
+
+    ///
+    /// Switches the resource to the store's shared send order counter.
+    ///
+    pub(crate) fn share_stamps(store: &mut Store) {
+        store.init_resource::<EventStamps>();
+        let stamps = store.resource::<EventStamps>().0.clone();
+
+        if let Some(events) = store.get_resource_mut::<Events<E>>() {
+            if ! Arc::ptr_eq(&events.stamps, &stamps) {
+                events.stamps = stamps;
+            }
+        }
+    }
+
+    fn add_writer(&mut self, name: &str) {
+        if ! self.writers.iter().any(|writer| writer == name) {
+            self.writers.push(name.to_string());
+        }
+    }
+
+    // sequence number of the oldest readable event
+    fn first_seq(&self) -> usize {
+        self.n_sent - self.len()
+    }
+
+    pub(crate) fn store_len(store: &Store) -> usize {
+        store.get_resource::<Events<E>>().map_or(0, |events| events.len())
+    }
+
+    pub(crate) fn store_n_sent(store: &Store) -> usize {
+        store.get_resource::<Events<E>>().map_or(0, |events| events.n_sent)
+    }
+
+    ///
+    /// Drops the buffered events. Readers skip past them like events
+    /// dropped by update.
+    ///
+    pub fn clear(&mut self) {
+        self.update_inner();
+        self.update_inner();
+    }
+
+    pub(crate) fn store_clear(store: &mut Store) {
+        if let Some(events) = store.get_resource_mut::<Events<E>>() {
+            events.clear();
+        }
     }
 
     pub fn update(mut event: ResMut<Events<E>>) {
@@ -34,7 +181,9 @@ impl<E: Event> Events<E> {
 
     fn update_inner(&mut self) {
         mem::swap(&mut self.events_next, &mut self.events_prev);
+        mem::swap(&mut self.stamps_next, &mut self.stamps_prev);
         self.events_next.drain(..);
+        self.stamps_next.clear();
         self.ticks += 1;
     }
 }
@@ -44,7 +193,15 @@ impl<E: Event> Default for Events<E> {
         Self { 
             events_next: Default::default(), 
             events_prev: Default::default(),
+            stamps_next: Default::default(),
+            stamps_prev: Default::default(),
             ticks: 1,
+            n_sent: 0,
+            peak_len: 0,
+            warn_len: WARN_LEN,
+            warn_lag: WARN_LAG,
+            writers: Vec::new(),
+            stamps: Default::default(),
         }
     }
 }
@@ -55,6 +212,14 @@ pub struct InEvent<'w, 's, E: Event> {
 }
 
 impl<E: Event> InEvent<'_, '_, E> {
+    ///
+    /// Events sent that this reader hasn't read, including events dropped
+    /// by Events::update before the reader saw them.
+    ///
+    pub fn lag(&self) -> usize {
+        self.cursor.lag(&self.events)
+    }
+
     pub fn iter(&mut self) -> InEventIter<E> {
         InEventIter {
             events: self.events.deref(),
@@ -81,11 +246,30 @@ impl<'w, E: Event> Iterator for InEventIter<'w, '_, E> {
 pub struct InEventCursor<E: Event> {
     ticks: usize,
     i_events: usize,
+    // sequence number after the last read event
+    seq: Option<usize>,
     marker: PhantomData<E>,
 }
 
 impl<E: Event> InEventCursor<E> {
     fn next<'a>(&mut self, events: &'a Events<E>) -> Option<&'a E> {
+        let (_, event) = self.peek(events)?;
+
+        self.i_events += 1;
+
+        if self.ticks + 1 == events.ticks {
+            self.seq = Some(events.first_seq() + self.i_events);
+        } else {
+            self.seq = Some(events.n_sent - events.events_next.len() + self.i_events);
+        }
+
+        Some(event)
+    }
+
+    ///
+    /// Next unread event and its send stamp, without consuming it.
+    ///
+    fn peek<'a>(&mut self, events: &'a Events<E>) -> Option<(u64, &'a E)> {
         if self.ticks + 1 < events.ticks {
             self.ticks = events.ticks - 1;
             self.i_events = 0;
@@ -93,9 +277,10 @@ impl<E: Event> InEventCursor<E> {
 
         if self.ticks + 1 == events.ticks {
             if self.i_events < events.events_prev.len() {
-                let event = &events.events_prev[self.i_events];
-                self.i_events += 1;
-                return Some(event);
+                return Some((
+                    events.stamps_prev[self.i_events], 
+                    &events.events_prev[self.i_events]
+                ));
             } else {
                 self.ticks += 1;
                 self.i_events = 0;
@@ -103,13 +288,15 @@ impl<E: Event> InEventCursor<E> {
         }
 
         if self.i_events < events.events_next.len() {
-            let event = &events.events_next[self.i_events];
-            self.i_events += 1;
-            Some(event)
+            Some((events.stamps_next[self.i_events], &events.events_next[self.i_events]))
         } else {
             None
         }
     }
+
+    fn lag(&self, events: &Events<E>) -> usize {
+        events.n_sent - self.seq.unwrap_or(events.n_sent)
+    }
 }
 
 impl<E: Event> Default for InEventCursor<E> {
@@ -117,11 +304,63 @@ impl<E: Event> Default for InEventCursor<E> {
         Self {
             ticks: 0,
             i_events: 0,
+            seq: None,
             marker: PhantomData,
         }
     }
 }
 
+impl<E: Event> HeapSize for InEventCursor<E> {}
+
+///
+/// Reader lag warning state, named for the reading system.
+///
+pub struct InEventLag {
+    name: String,
+    warn_lag: Option<usize>,
+}
+
+impl InEventLag {
+    fn check<E: Event>(&mut self, events: &Events<E>, cursor: &mut InEventCursor<E>) {
+        // a new reader starts with the events it can still read
+        if cursor.seq.is_none() {
+            cursor.seq = Some(events.first_seq());
+        }
+
+        let lag = cursor.lag(events);
+        let warn_lag = *self.warn_lag.get_or_insert(events.warn_lag);
+
+        if lag == 0 {
+            self.warn_lag = Some(events.warn_lag);
+        } else if warn_lag <= lag {
+            warn!("system {} has {} unread Events<{}>, is it reading them?",
+                self.name, lag, type_name::<E>());
+
+            self.warn_lag = Some(2 * lag);
+        }
+    }
+}
+
+pub struct ManualEventReader<E: Event> {
+    cursor: InEventCursor<E>,
+}
+
+impl<E: Event> ManualEventReader<E> {
+    pub fn iter<'a>(&'a mut self, events: &'a Events<E>) -> InEventIter<'a, 'a, E> {
+        InEventIter {
+            events,
+            cursor: &mut self.cursor,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<E: Event> Default for ManualEventReader<E> {
+    fn default() -> Self {
+        Self { cursor: Default::default() }
+    }
+}
+
 pub struct OutEvent<'w, E: Event> {
     events: ResMut<'w, Events<E>>,
 }
@@ -134,6 +373,123 @@ impl<'a, E: Event> OutEvent<'a, E> {
 
 pub trait Event : Send + Sync + 'static {}
 
+///
+/// Reader for several event types, merged in send order. For example,
+/// InEvents<(A, B)> yields EventOf2::A(&A) and EventOf2::B(&B) items.
+///
+pub struct InEvents<'w, 's, T: EventSet> {
+    readers: T::Readers<'w, 's>,
+}
+
+impl<T: EventSet> InEvents<'_, '_, T> {
+    pub fn iter(&mut self) -> InEventsIter<'_, T> {
+        InEventsIter {
+            iters: T::iters(&mut self.readers),
+        }
+    }
+}
+
+pub struct InEventsIter<'a, T: EventSet> {
+    iters: T::Iters<'a>,
+}
+
+impl<'a, T: EventSet> Iterator for InEventsIter<'a, T> {
+    type Item = T::Item<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        T::next(&mut self.iters)
+    }
+}
+
+///
+/// Tuple of event types for InEvents.
+///
+pub trait EventSet: 'static {
+    type Item<'a>;
+    type Readers<'w, 's>;
+    type Iters<'a>;
+    type Local: Send + Sync + 'static;
+
+    fn init(meta: &mut SystemMeta, store: &mut Store) -> Result<Self::Local>;
+
+    fn readers<'w, 's>(
+        store: &'w UnsafeStore, 
+        local: &'s mut Self::Local
+    ) -> Result<Self::Readers<'w, 's>>;
+
+    fn iters<'a>(readers: &'a mut Self::Readers<'_, '_>) -> Self::Iters<'a>;
+
+    fn next<'a>(iters: &mut Self::Iters<'a>) -> Option<Self::Item<'a>>;
+}
+
+impl<E: Event> InEventIter<'_, '_, E> {
+    fn peek_stamp(&mut self) -> Option<u64> {
+        self.cursor.peek(self.events).map(|(stamp, _)| stamp)
+    }
+}
+
+macro_rules! impl_event_set {
+    ($item:ident, $($event:ident $var:ident),*) => {
+        ///
+        /// Item of a merged InEvents reader, tagged by event type.
+        ///
+        #[derive(Debug)]
+        pub enum $item<'a, $($event),*> {
+            $($event(&'a $event),)*
+        }
+
+        impl<$($event: Event),*> EventSet for ($($event,)*) {
+            type Item<'a> = $item<'a, $($event),*>;
+            type Readers<'w, 's> = ($(InEvent<'w, 's, $event>,)*);
+            type Iters<'a> = ($(InEventIter<'a, 'a, $event>,)*);
+            type Local = ($(<InEvent<'static, 'static, $event> as Param>::Local,)*);
+
+            fn init(meta: &mut SystemMeta, store: &mut Store) -> Result<Self::Local> {
+                Ok(($(InEvent::<$event>::init(meta, store)?,)*))
+            }
+
+            fn readers<'w, 's>(
+                store: &'w UnsafeStore, 
+                local: &'s mut Self::Local
+            ) -> Result<Self::Readers<'w, 's>> {
+                let ($($var,)*) = local;
+
+                Ok(($(InEvent::<$event>::arg(store, $var)?,)*))
+            }
+
+            fn iters<'a>(readers: &'a mut Self::Readers<'_, '_>) -> Self::Iters<'a> {
+                let ($($var,)*) = readers;
+
+                ($($var.iter(),)*)
+            }
+
+            fn next<'a>(iters: &mut Self::Iters<'a>) -> Option<Self::Item<'a>> {
+                let ($($var,)*) = iters;
+
+                let mut min: Option<u64> = None;
+                $(
+                    if let Some(stamp) = $var.peek_stamp() {
+                        min = Some(min.map_or(stamp, |min| min.min(stamp)));
+                    }
+                )*
+                let min = min?;
+
+                $(
+                    if $var.peek_stamp() == Some(min) {
+                        return $var.next().map($item::$event);
+                    }
+                )*
+
+                None
+            }
+        }
+    }
+}
+
+impl_event_set!(EventOf2, A a, B b);
+impl_event_set!(EventOf3, A a, B b, C c);
+impl_event_set!(EventOf4, A a, B b, C c, D d);
+
 
 // TODO: create #[derive(Param)]
 
@@ -142,13 +498,17 @@ impl<'w, 's, E: Event> Param for InEvent<'w, 's, E> {
 
     type Local = (
         <Res<'w, Events<E>> as Param>::Local, 
-        <Local<'s, InEventCursor<E>> as Param>::Local
+        <Local<'s, InEventCursor<E>> as Param>::Local,
+        InEventLag,
     );
 
     fn init(meta: &mut SystemMeta, world: &mut Store) -> Result<Self::Local> {
+        Events::<E>::share_stamps(world);
+
         Ok((
             Res::<Events<E>>::init(meta, world)?,
-            Local::<InEventCursor<E>>::init(meta, world)?
+            Local::<InEventCursor<E>>::init(meta, world)?,
+            InEventLag { name: meta.name().to_string(), warn_lag: None },
         ))
     }
 
@@ -156,11 +516,36 @@ impl<'w, 's, E: Event> Param for InEvent<'w, 's, E> {
         world: &'w1 UnsafeStore,
         state: &'s1 mut Self::Local, 
     ) -> Result<Self::Arg<'w1, 's1>> {
-        let (e_st, c_st) = state;
+        let (e_st, c_st, lag) = state;
+
+        let events = Res::<Events<E>>::arg(world, e_st)?;
+        let mut cursor = Local::<InEventCursor<E>>::arg(world, c_st)?;
+
+        lag.check(&events, &mut cursor);
+
+        Ok(InEvent { events, cursor })
+    }
+
+    fn memory_size(state: &Self::Local) -> usize {
+        mem::size_of::<Self::Local>() + state.2.name.capacity()
+    }
+}
+
+impl<'w, 's, T: EventSet> Param for InEvents<'w, 's, T> {
+    type Arg<'w1, 's1> = InEvents<'w1, 's1, T>;
+
+    type Local = T::Local;
 
-        Ok(InEvent {
-            events: Res::<Events<E>>::arg(world, e_st)?,
-            cursor: Local::<InEventCursor<E>>::arg(world, c_st)?,
+    fn init(meta: &mut SystemMeta, world: &mut Store) -> Result<Self::Local> {
+        T::init(meta, world)
+    }
+
+    fn arg<'w1, 's1>(
+        world: &'w1 UnsafeStore,
+        state: &'s1 mut Self::Local, 
+    ) -> Result<Self::Arg<'w1, 's1>> {
+        Ok(InEvents {
+            readers: T::readers(world, state)?,
         })
     }
 }
@@ -173,7 +558,10 @@ impl<'w, E: Event> Param for OutEvent<'w, E> {
     type Local = <ResMut<'w, Events<E>> as Param>::Local;
 
     fn init(meta: &mut SystemMeta, world: &mut Store) -> Result<Self::Local> {
-        ResMut::<Events<E>>::init(meta, world)
+        ResMut::<Events<E>>::init(meta, world).inspect(|_| {
+            world.resource_mut::<Events<E>>().add_writer(meta.name());
+            Events::<E>::share_stamps(world);
+        })
     }
 
     fn arg<'w1, 's1>(
@@ -189,13 +577,15 @@ impl<'w, E: Event> Param for OutEvent<'w, E> {
 
 #[cfg(test)]
 mod test {
-    use essay_ecs_core::core_app::{CoreApp, Core};
+    use essay_ecs_core::{core_app::{CoreApp, Core}, Schedule, Store};
+
+    use std::sync::Mutex;
 
     use essay_ecs_core::util::test::TestValues;
 
     use crate::event::OutEvent;
 
-    use super::{Event, Events, InEvent};
+    use super::{Event, EventOf2, Events, InEvent, InEvents};
 
     #[test]
     fn test_read_no_update() {
@@ -231,6 +621,28 @@ mod test {
         assert_eq!(values.take(), "");
     }
 
+    #[test]
+    fn manual_reader() {
+        let mut app = CoreApp::new();
+        app.init_resource::<Events<TestEvent>>();
+
+        let mut reader = app.resource::<Events<TestEvent>>().get_reader();
+
+        let events = app.resource_mut::<Events<TestEvent>>();
+        events.send(TestEvent(1));
+        events.send(TestEvent(2));
+
+        let values: Vec<usize> = reader.iter(events).map(|e| e.0).collect();
+        assert_eq!(values, vec![1, 2]);
+        assert_eq!(reader.iter(events).count(), 0);
+
+        events.update_inner();
+        events.send(TestEvent(3));
+
+        let values: Vec<usize> = reader.iter(events).map(|e| e.0).collect();
+        assert_eq!(values, vec![3]);
+    }
+
     #[test]
     fn test_read_update() {
         let mut app = CoreApp::new();
@@ -314,9 +726,190 @@ mod test {
         assert_eq!(values.take(), "");
     }
 
+    #[test]
+    fn reader_lag() {
+        let mut app = CoreApp::new();
+        app.init_resource::<Events<TestEvent>>();
+
+        let mut values = TestValues::new();
+        let mut ptr = values.clone();
+
+        app.system(Core, move |mut writer: OutEvent<TestEvent>| {
+            writer.send(TestEvent(1));
+        });
+
+        app.system(Core, move |mut reader: InEvent<TestEvent>| {
+            reader.iter().count();
+            ptr.push(&format!("read {}", reader.lag()));
+        });
+
+        let mut ptr = values.clone();
+        app.system(Core, move |reader: InEvent<TestEvent>| {
+            ptr.push(&format!("skip {}", reader.lag()));
+        });
+
+        // without Events::update the buffer grows and the skipping reader lags
+        for _ in 0..3 {
+            app.tick().unwrap();
+        }
+
+        let text = values.take();
+        assert!(! text.contains("read 2") && ! text.contains("read 3"));
+        assert!(text.contains("skip 2"));
+
+        assert_eq!(app.resource::<Events<TestEvent>>().len(), 3);
+        assert_eq!(app.resource::<Events<TestEvent>>().peak_len(), 3);
+    }
+
+    #[test]
+    fn in_event_memory_size() {
+        let mut store = Store::new();
+        store.init_resource::<Events<WarnEvent>>();
+
+        let mut schedule = Schedule::new();
+        schedule.add_system(warn_skipper);
+        schedule.tick(&mut store).unwrap();
+
+        let memory = schedule.system_memory();
+        let (name, size) = memory.iter()
+            .find(|(name, _)| name.contains("warn_skipper"))
+            .unwrap();
+
+        // the reader's state includes the system name used by lag warnings
+        assert!(*size >= name.len(), "{} < {}", size, name.len());
+    }
+
+    #[test]
+    fn warnings_name_systems() {
+        log::set_logger(&CAPTURE).ok();
+        log::set_max_level(log::LevelFilter::Warn);
+
+        let mut app = CoreApp::new();
+        app.init_resource::<Events<WarnEvent>>();
+
+        {
+            let events = app.resource_mut::<Events<WarnEvent>>();
+            events.set_warn_len(3);
+            events.set_warn_lag(2);
+        }
+
+        app.system(Core, warn_writer);
+        app.system(Core, warn_skipper);
+
+        for _ in 0..4 {
+            app.tick().unwrap();
+        }
+
+        let logs: Vec<String> = CAPTURE.0.lock().unwrap().iter()
+            .filter(|msg| msg.contains("WarnEvent"))
+            .cloned()
+            .collect();
+
+        assert!(logs.iter().any(|msg| msg.contains("holds 3 events") && msg.contains("warn_writer")),
+            "{:?}", logs);
+        assert!(logs.iter().any(|msg| msg.contains("warn_skipper") && msg.contains("has 2 unread")), "{:?}", logs);
+    }
+
+    fn warn_writer(mut writer: OutEvent<WarnEvent>) {
+        writer.send(WarnEvent);
+    }
+
+    fn warn_skipper(_reader: InEvent<WarnEvent>) {
+    }
+
+    struct Capture(Mutex<Vec<String>>);
+
+    static CAPTURE: Capture = Capture(Mutex::new(Vec::new()));
+
+    impl log::Log for Capture {
+        fn enabled(&self, metadata: &log::Metadata) -> bool {
+            metadata.level() <= log::Level::Warn
+        }
+
+        fn log(&self, record: &log::Record) {
+            if self.enabled(record.metadata()) {
+                self.0.lock().unwrap().push(record.args().to_string());
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[derive(Debug)]
+    struct WarnEvent;
+
+    impl Event for WarnEvent {}
+
+    #[test]
+    fn merged_readers() {
+        let mut app = CoreApp::new();
+        app.init_resource::<Events<TestEvent>>();
+        app.init_resource::<Events<TestEventB>>();
+
+        let mut values = TestValues::new();
+        let mut ptr = values.clone();
+
+        app.system(Core, move |mut reader: InEvents<(TestEvent, TestEventB)>| {
+            for event in reader.iter() {
+                match event {
+                    EventOf2::A(event) => ptr.push(&format!("{:?}", event)),
+                    EventOf2::B(event) => ptr.push(&format!("{:?}", event)),
+                }
+            }
+        });
+
+        app.tick().unwrap();
+        assert_eq!(values.take(), "");
+
+        app.resource_mut::<Events<TestEventB>>().send(TestEventB(1));
+        app.resource_mut::<Events<TestEvent>>().send(TestEvent(2));
+        app.resource_mut::<Events<TestEventB>>().send(TestEventB(3));
+        app.tick().unwrap();
+        assert_eq!(values.take(), "TestEventB(1), TestEvent(2), TestEventB(3)");
+
+        // events from the previous update come first
+        app.resource_mut::<Events<TestEvent>>().send(TestEvent(4));
+        app.resource_mut::<Events<TestEvent>>().update_inner();
+        app.resource_mut::<Events<TestEventB>>().update_inner();
+        app.resource_mut::<Events<TestEventB>>().send(TestEventB(5));
+        app.tick().unwrap();
+        assert_eq!(values.take(), "TestEvent(4), TestEventB(5)");
+
+        app.tick().unwrap();
+        assert_eq!(values.take(), "");
+    }
+
+    #[test]
+    fn stamps_per_store() {
+        let mut app_a = CoreApp::new();
+        let mut app_b = CoreApp::new();
+
+        for app in [&mut app_a, &mut app_b] {
+            app.init_resource::<Events<TestEvent>>();
+            app.init_resource::<Events<TestEventB>>();
+            app.system(Core, |_reader: InEvents<(TestEvent, TestEventB)>| {});
+            app.tick().unwrap();
+        }
+
+        app_a.resource_mut::<Events<TestEvent>>().send(TestEvent(1));
+        app_b.resource_mut::<Events<TestEvent>>().send(TestEvent(1));
+        app_a.resource_mut::<Events<TestEventB>>().send(TestEventB(2));
+
+        // each store numbers its own sends
+        assert_eq!(app_a.resource::<Events<TestEvent>>().stamps_next, vec![0]);
+        assert_eq!(app_a.resource::<Events<TestEventB>>().stamps_next, vec![1]);
+        assert_eq!(app_b.resource::<Events<TestEvent>>().stamps_next, vec![0]);
+    }
+
     #[derive(Debug)]
     #[allow(unused)]
     pub struct TestEvent(usize);
 
+    #[derive(Debug)]
+    #[allow(unused)]
+    pub struct TestEventB(usize);
+
+    impl Event for TestEventB {}
+
     impl Event for TestEvent {}
 }
@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+
+use essay_ecs_core::{IntoSystemConfig, Res, schedule::ScheduleLabel};
+
+use crate::{app::App, config::Config};
+
+///
+/// Runtime feature flags, loaded as the "features" config section, e.g.
+/// `enabled = ["telemetry"]` in the config file or
+/// `FEATURES_ENABLED='["telemetry"]'` in the environment.
+///
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Features {
+    enabled: Vec<String>,
+}
+
+impl Features {
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.enabled.iter().any(|f| f == name)
+    }
+
+    pub fn enable(&mut self, name: &str) {
+        if ! self.is_enabled(name) {
+            self.enabled.push(name.to_string());
+        }
+    }
+
+    pub fn disable(&mut self, name: &str) {
+        self.enabled.retain(|f| f != name);
+    }
+
+    pub fn enabled(&self) -> &[String] {
+        &self.enabled
+    }
+}
+
+impl Config for Features {
+    const SECTION: &'static str = "features";
+}
+
+///
+/// Flag in the Features registry, for App::system_if.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FeatureFlag(String);
+
+impl FeatureFlag {
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+}
+
+pub fn cfg_flag(name: &str) -> FeatureFlag {
+    FeatureFlag(name.to_string())
+}
+
+impl App {
+    ///
+    /// Adds a system that only runs while the flag is enabled in the
+    /// Features resource, loading Features from the config if needed.
+    /// A config that fails to load logs a warning and uses the defaults.
+    /// Flags are checked each tick, so they can be toggled at runtime.
+    ///
+    pub fn system_if<M>(
+        &mut self,
+        flag: FeatureFlag,
+        label: impl AsRef<dyn ScheduleLabel>,
+        into_system: impl IntoSystemConfig<M>
+    ) -> &mut Self {
+        if ! self.contains_resource::<Features>() {
+            if let Err(err) = self.init_config::<Features>() {
+                log::warn!("{}, using default features", err.message());
+                self.insert_resource(Features::default());
+            }
+        }
+
+        self.system(label, into_system.run_if(move |features: Res<Features>| {
+            features.is_enabled(flag.name())
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use essay_ecs_core::util::test::TestValues;
+
+    use crate::app::{App, Update};
+
+    use super::{cfg_flag, Features};
+
+    #[test]
+    fn system_if_flag() {
+        let mut app = App::new();
+
+        let mut features = Features::default();
+        features.enable("telemetry");
+        app.insert_resource(features);
+
+        let mut values = TestValues::new();
+
+        let mut ptr = values.clone();
+        app.system_if(cfg_flag("telemetry"), Update, move || ptr.push("telemetry"));
+
+        let mut ptr = values.clone();
+        app.system_if(cfg_flag("debug"), Update, move || ptr.push("debug"));
+
+        app.tick().unwrap();
+        assert_eq!(values.take(), "telemetry");
+
+        app.resource_mut::<Features>().disable("telemetry");
+        app.resource_mut::<Features>().enable("debug");
+
+        app.tick().unwrap();
+        assert_eq!(values.take(), "debug");
+    }
+}
@@ -0,0 +1,122 @@
+use essay_ecs_core::{entity::Component, Query};
+
+///
+/// Previous and current values of a component T, updated by the fixed
+/// step, so rendering between steps can sample with the accumulator alpha.
+///
+pub struct Interpolated<T> {
+    previous: T,
+    current: T,
+}
+
+impl<T: Component + Clone> Interpolated<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            previous: value.clone(),
+            current: value,
+        }
+    }
+
+    pub fn previous(&self) -> &T {
+        &self.previous
+    }
+
+    pub fn current(&self) -> &T {
+        &self.current
+    }
+
+    ///
+    /// Records a new value from the fixed step.
+    ///
+    pub fn push(&mut self, value: T) {
+        self.previous = std::mem::replace(&mut self.current, value);
+    }
+
+    ///
+    /// Copies each entity's T into its Interpolated<T>. Add this system
+    /// at the end of the schedule that runs the fixed step.
+    ///
+    pub fn update(query: Query<(&T, &mut Interpolated<T>)>) {
+        for (value, interpolated) in query.iter() {
+            interpolated.push(value.clone());
+        }
+    }
+}
+
+impl<T: Component + Clone + Lerp> Interpolated<T> {
+    ///
+    /// Value between previous (alpha = 0) and current (alpha = 1).
+    ///
+    pub fn sample(&self, alpha: f32) -> T {
+        self.previous.lerp(&self.current, alpha.clamp(0., 1.))
+    }
+}
+
+impl<T: Component> Component for Interpolated<T> {}
+
+pub trait Lerp {
+    fn lerp(&self, other: &Self, alpha: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(&self, other: &Self, alpha: f32) -> Self {
+        self + (other - self) * alpha
+    }
+}
+
+impl Lerp for f64 {
+    fn lerp(&self, other: &Self, alpha: f32) -> Self {
+        self + (other - self) * alpha as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use essay_ecs_core::{entity::Component, Store};
+
+    use crate::app::{App, Update};
+
+    use super::{Interpolated, Lerp};
+
+    #[test]
+    fn update_and_sample() {
+        let mut app = App::new();
+
+        app.system(Update, Interpolated::<Pos>::update);
+
+        app.eval(|store: &mut Store| {
+            store.spawn((Pos(0.), Interpolated::new(Pos(0.))));
+            Ok(())
+        }).unwrap();
+
+        app.tick().unwrap();
+
+        app.eval(|store: &mut Store| {
+            for pos in store.query::<&mut Pos>() {
+                pos.0 = 10.;
+            }
+            Ok(())
+        }).unwrap();
+
+        app.tick().unwrap();
+
+        let samples = app.eval(|store: &mut Store| {
+            Ok(store.query::<&Interpolated<Pos>>()
+                .map(|i| (i.sample(0.).0, i.sample(0.25).0, i.sample(1.).0))
+                .collect::<Vec<_>>())
+        }).unwrap();
+
+        assert_eq!(samples, vec![(0., 2.5, 10.)]);
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Pos(f32);
+
+    impl Component for Pos {}
+
+    impl Lerp for Pos {
+        fn lerp(&self, other: &Self, alpha: f32) -> Self {
+            Pos(self.0.lerp(&other.0, alpha))
+        }
+    }
+}
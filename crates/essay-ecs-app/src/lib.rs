@@ -1,4 +1,15 @@
+pub mod arena;
+pub mod compute;
+pub mod config;
+pub mod diagnostics;
 pub mod event;
+pub mod features;
+pub mod interpolate;
+pub mod math;
+pub mod persist;
+pub mod pipeline;
+pub mod prev;
+pub mod request;
 mod channel;
 mod app;
 
@@ -10,11 +21,23 @@ pub mod prelude {
             App,
             PreStartup, Startup, PostStartup,
             Main, 
-            First, PreUpdate, Update, PostUpdate, Last,
+            First, PreUpdate, Update, PostUpdate, Last, Exit,
+            AppStart, PrePause, Resume, AppExit,
 
-            Plugin, 
+            Plugin, Ticker, ScheduleRunner, TickRate,
         },
-        event::{Events, InEvent, OutEvent},
+        arena::{FrameArena, FrameBox, FrameVec},
+        compute::{ComputeBackend, ComputeMirror, ComputePlugin},
+        config::Config,
+        diagnostics::{Diagnostics, DiagnosticsPlugin, DiagnosticSink},
+        event::{Events, InEvent, InEvents, OutEvent, ManualEventReader},
+        features::{cfg_flag, FeatureFlag, Features},
+        interpolate::{Interpolated, Lerp},
+        math::{Fixed, Scalar, Vec2},
+        persist::{Persist, PersistPlugin},
+        pipeline::{PipeIn, PipeOut},
+        prev::{Prev, PrevPlugin},
+        request::{InRequest, OutRequest, RequestId, Requests},
     };
 
     pub use essay_ecs_app_macros::Event;
@@ -0,0 +1,284 @@
+use std::{fmt, ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign}};
+
+use crate::interpolate::Lerp;
+
+///
+/// Scalar for the math helpers: f32 by default, or Fixed with the
+/// fixed-point feature for simulations that must be bit-identical across
+/// platforms and compilers.
+///
+#[cfg(not(feature = "fixed-point"))]
+pub type Scalar = f32;
+
+#[cfg(feature = "fixed-point")]
+pub type Scalar = Fixed;
+
+///
+/// Signed 32.32 fixed-point number. Arithmetic is integer-only, so
+/// results don't depend on float modes, fused multiply-add or libm.
+///
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Fixed(i64);
+
+impl Fixed {
+    pub const FRAC_BITS: u32 = 32;
+    pub const ZERO: Fixed = Fixed(0);
+    pub const ONE: Fixed = Fixed(1 << Self::FRAC_BITS);
+
+    pub const fn from_bits(bits: i64) -> Self {
+        Self(bits)
+    }
+
+    pub const fn to_bits(self) -> i64 {
+        self.0
+    }
+
+    pub const fn from_int(value: i32) -> Self {
+        Self((value as i64) << Self::FRAC_BITS)
+    }
+
+    pub fn from_f32(value: f32) -> Self {
+        Self((value as f64 * Self::ONE.0 as f64) as i64)
+    }
+
+    pub fn to_f32(self) -> f32 {
+        (self.0 as f64 / Self::ONE.0 as f64) as f32
+    }
+
+    pub fn abs(self) -> Self {
+        Self(self.0.abs())
+    }
+}
+
+impl fmt::Debug for Fixed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Fixed({})", self.to_f32())
+    }
+}
+
+impl From<i32> for Fixed {
+    fn from(value: i32) -> Self {
+        Self::from_int(value)
+    }
+}
+
+impl From<i16> for Fixed {
+    fn from(value: i16) -> Self {
+        Self::from_int(value as i32)
+    }
+}
+
+impl Add for Fixed {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0.wrapping_add(rhs.0))
+    }
+}
+
+impl Sub for Fixed {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self(self.0.wrapping_sub(rhs.0))
+    }
+}
+
+impl Mul for Fixed {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self(((self.0 as i128 * rhs.0 as i128) >> Self::FRAC_BITS) as i64)
+    }
+}
+
+impl Div for Fixed {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self {
+        Self((((self.0 as i128) << Self::FRAC_BITS) / rhs.0 as i128) as i64)
+    }
+}
+
+impl Neg for Fixed {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self(self.0.wrapping_neg())
+    }
+}
+
+impl AddAssign for Fixed {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for Fixed {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl Lerp for Fixed {
+    fn lerp(&self, other: &Self, alpha: f32) -> Self {
+        *self + (*other - *self) * Fixed::from_f32(alpha)
+    }
+}
+
+///
+/// 2d vector of Scalar. Components wrap it, like Position(Vec2).
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Vec2 {
+    pub x: Scalar,
+    pub y: Scalar,
+}
+
+impl Vec2 {
+    pub fn new(x: Scalar, y: Scalar) -> Self {
+        Self { x, y }
+    }
+
+    pub fn dot(&self, other: Vec2) -> Scalar {
+        self.x * other.x + self.y * other.y
+    }
+
+    pub fn scale(&self, factor: Scalar) -> Vec2 {
+        Vec2::new(self.x * factor, self.y * factor)
+    }
+}
+
+impl Add for Vec2 {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Vec2::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl Sub for Vec2 {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Vec2::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl AddAssign for Vec2 {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Lerp for Vec2 {
+    fn lerp(&self, other: &Self, alpha: f32) -> Self {
+        Vec2::new(self.x.lerp(&other.x, alpha), self.y.lerp(&other.y, alpha))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use essay_ecs_core::{entity::Component, Query, Store};
+
+    use crate::app::{App, Update};
+
+    use super::{Fixed, Scalar, Vec2};
+
+    #[test]
+    fn fixed_ops() {
+        let a = Fixed::from_int(3);
+        let b = Fixed::from_f32(0.5);
+
+        assert_eq!((a + b).to_f32(), 3.5);
+        assert_eq!((a - b).to_f32(), 2.5);
+        assert_eq!((a * b).to_f32(), 1.5);
+        assert_eq!((a / b).to_f32(), 6.);
+        assert_eq!((-a).to_f32(), -3.);
+        assert_eq!(Fixed::ONE.to_bits(), 1 << 32);
+    }
+
+    ///
+    /// Golden bits of a fixed-point spring simulation. The value only
+    /// depends on integer arithmetic, so it must match on every platform.
+    ///
+    #[test]
+    fn fixed_simulation_bits() {
+        let mut app = App::new();
+
+        app.system(Update, |q: Query<&mut Spring>| {
+            let k = Fixed::from_int(1) / Fixed::from_int(10);
+            let dt = Fixed::from_int(1) / Fixed::from_int(20);
+
+            for spring in q.iter() {
+                spring.v -= spring.x * k;
+                spring.x += spring.v * dt;
+            }
+        });
+
+        app.eval(|store: &mut Store| {
+            store.spawn(Spring { x: Fixed::from_int(1), v: Fixed::ZERO });
+            Ok(())
+        }).unwrap();
+
+        for _ in 0..1000 {
+            app.tick().unwrap();
+        }
+
+        let bits = app.eval(|q: Query<&Spring>| {
+            q.iter().map(|s| (s.x.to_bits(), s.v.to_bits())).next().unwrap()
+        }).unwrap();
+
+        assert_eq!(bits, (-321788932, -6073040080));
+    }
+
+    #[test]
+    fn scalar_repeatable() {
+        assert_eq!(run_particles(500), run_particles(500));
+    }
+
+    fn run_particles(n: usize) -> Vec<Vec2> {
+        let mut app = App::new();
+
+        app.system(Update, |q: Query<(&mut Position, &Velocity)>| {
+            for (pos, vel) in q.iter() {
+                pos.0 += vel.0.scale(scalar(1) / scalar(60));
+            }
+        });
+
+        app.eval(|store: &mut Store| {
+            for i in 0..8 {
+                store.spawn((
+                    Position(Vec2::new(scalar(i), scalar(0))),
+                    Velocity(Vec2::new(scalar(1), scalar(i % 3))),
+                ));
+            }
+            Ok(())
+        }).unwrap();
+
+        for _ in 0..n {
+            app.tick().unwrap();
+        }
+
+        app.eval(|q: Query<&Position>| q.iter().map(|p| p.0).collect()).unwrap()
+    }
+
+    fn scalar(value: i16) -> Scalar {
+        Scalar::from(value)
+    }
+
+    struct Spring {
+        x: Fixed,
+        v: Fixed,
+    }
+
+    impl Component for Spring {}
+
+    struct Position(Vec2);
+
+    impl Component for Position {}
+
+    struct Velocity(Vec2);
+
+    impl Component for Velocity {}
+}
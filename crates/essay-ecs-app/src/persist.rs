@@ -0,0 +1,340 @@
+use std::{any::type_name, fs, io, path::{Path, PathBuf}};
+
+use essay_ecs_core::{
+    error::{Error, Result},
+    Store
+};
+use serde::{de::DeserializeOwned, Serialize};
+use toml::{Table, Value};
+
+use crate::app::{App, Plugin, PreStartup, Last};
+
+///
+/// A serde resource that PersistPlugin can save to disk and restore at
+/// startup.
+///
+/// Resources are saved as TOML with VERSION beside the data. Data saved
+/// by an older version is passed through migrate before it's loaded.
+///
+pub trait Persist: Serialize + DeserializeOwned + Send + 'static {
+    const VERSION: u32 = 0;
+
+    fn migrate(version: u32, _data: Value) -> Result<Value> {
+        Err(format!("{} can't migrate persisted version {} to {}",
+            type_name::<Self>(), version, Self::VERSION
+        ).into())
+    }
+}
+
+///
+/// Saves registered resources to a directory every interval ticks and
+/// restores them in PreStartup.
+///
+pub struct PersistPlugin {
+    path: PathBuf,
+    interval: usize,
+    entries: Vec<PersistEntry>,
+}
+
+impl PersistPlugin {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            interval: 100,
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn interval(mut self, ticks: usize) -> Self {
+        assert!(ticks > 0);
+
+        self.interval = ticks;
+
+        self
+    }
+
+    pub fn resource<T: Persist>(mut self, name: &str) -> Self {
+        assert!(
+            ! self.entries.iter().any(|e| e.name == name),
+            "duplicate persist name {:?}", name
+        );
+
+        self.entries.push(PersistEntry {
+            name: name.to_string(),
+            save: save_resource::<T>,
+            load: load_resource::<T>,
+        });
+
+        self
+    }
+
+    ///
+    /// Saves all registered resources immediately, e.g. before exit.
+    ///
+    pub fn save(store: &mut Store) -> Result<()> {
+        let (path, entries) = match store.get_resource::<PersistState>() {
+            Some(state) => (state.path.clone(), state.entries.clone()),
+            None => return Err("PersistPlugin is not installed".into()),
+        };
+
+        fs::create_dir_all(&path).map_err(Error::other)?;
+
+        for entry in &entries {
+            (entry.save)(store, &entry.path(&path))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Plugin for PersistPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(PersistState {
+            path: self.path.clone(),
+            interval: self.interval,
+            ticks: 0,
+            entries: self.entries.clone(),
+        });
+
+        app.system(PreStartup, PersistState::load_system);
+        app.system(Last, PersistState::save_system);
+    }
+}
+
+struct PersistState {
+    path: PathBuf,
+    interval: usize,
+    ticks: usize,
+    entries: Vec<PersistEntry>,
+}
+
+impl PersistState {
+    fn load_system(store: &mut Store) -> Result<()> {
+        let state = store.resource::<PersistState>();
+        let path = state.path.clone();
+        let entries = state.entries.clone();
+
+        for entry in &entries {
+            (entry.load)(store, &entry.path(&path))?;
+        }
+
+        Ok(())
+    }
+
+    fn save_system(store: &mut Store) -> Result<()> {
+        let state = store.resource_mut::<PersistState>();
+        state.ticks += 1;
+
+        if state.ticks.is_multiple_of(state.interval) {
+            PersistPlugin::save(store)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[derive(Clone)]
+struct PersistEntry {
+    name: String,
+    save: fn(&Store, &Path) -> Result<()>,
+    load: fn(&mut Store, &Path) -> Result<()>,
+}
+
+impl PersistEntry {
+    fn path(&self, dir: &Path) -> PathBuf {
+        dir.join(format!("{}.toml", self.name))
+    }
+}
+
+fn save_resource<T: Persist>(store: &Store, path: &Path) -> Result<()> {
+    if let Some(value) = store.get_resource::<T>() {
+        let value = match Value::try_from(value) {
+            Ok(value) => value,
+            Err(err) => return Err(format!("persist {}: {}", type_name::<T>(), err).into()),
+        };
+
+        let mut table = Table::new();
+        table.insert("version".to_string(), Value::Integer(T::VERSION.into()));
+        table.insert("data".to_string(), value);
+
+        let data = table.to_string();
+
+        // write then rename so a crash mid-save keeps the previous file
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, data).map_err(Error::other)?;
+        fs::rename(&tmp, path).map_err(Error::other)?;
+    }
+
+    Ok(())
+}
+
+fn load_resource<T: Persist>(store: &mut Store, path: &Path) -> Result<()> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(Error::other(err)),
+    };
+
+    let mut table: Table = match text.parse() {
+        Ok(table) => table,
+        Err(err) => return Err(format!("persist file {}: {}", path.display(), err).into()),
+    };
+
+    let version = table.get("version")
+        .and_then(Value::as_integer)
+        .and_then(|version| u32::try_from(version).ok());
+
+    let version = match version {
+        Some(version) => version,
+        None => {
+            return Err(format!("invalid persist version in {}", path.display()).into());
+        }
+    };
+
+    let data = match table.remove("data") {
+        Some(data) => data,
+        None => return Err(format!("missing persist data in {}", path.display()).into()),
+    };
+
+    let data = if version == T::VERSION {
+        data
+    } else {
+        T::migrate(version, data)?
+    };
+
+    match data.try_into::<T>() {
+        Ok(value) => store.insert_resource(value),
+        Err(err) => {
+            return Err(format!("persist file {}: {}", path.display(), err).into());
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use essay_ecs_core::error::Result;
+    use serde::{Deserialize, Serialize};
+    use toml::Value;
+
+    use crate::app::App;
+
+    use super::{Persist, PersistPlugin};
+
+    #[test]
+    fn save_interval_and_restore() {
+        let dir = test_dir("save_interval_and_restore");
+
+        let mut app = App::new();
+        app.insert_resource(TestA(1));
+        app.plugin(PersistPlugin::new(&dir).interval(2).resource::<TestA>("a"));
+
+        app.tick().unwrap();
+        assert!(! dir.join("a.toml").exists());
+
+        app.resource_mut::<TestA>().0 = 7;
+        app.tick().unwrap();
+        assert_eq!(fs::read_to_string(dir.join("a.toml")).unwrap(), "data = 7\nversion = 0\n");
+
+        let mut app = App::new();
+        app.insert_resource(TestA(1));
+        app.plugin(PersistPlugin::new(&dir).resource::<TestA>("a"));
+
+        app.tick().unwrap();
+        assert_eq!(app.resource::<TestA>(), &TestA(7));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn save_struct() {
+        let dir = test_dir("save_struct");
+
+        let mut app = App::new();
+        app.insert_resource(TestC { name: "c".to_string(), values: vec![1, 2] });
+        app.plugin(PersistPlugin::new(&dir).interval(1).resource::<TestC>("c"));
+
+        app.tick().unwrap();
+
+        let mut app = App::new();
+        app.plugin(PersistPlugin::new(&dir).resource::<TestC>("c"));
+
+        app.tick().unwrap();
+        assert_eq!(app.resource::<TestC>(), &TestC { name: "c".to_string(), values: vec![1, 2] });
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn missing_file_keeps_default() {
+        let dir = test_dir("missing_file_keeps_default");
+
+        let mut app = App::new();
+        app.insert_resource(TestA(3));
+        app.plugin(PersistPlugin::new(&dir).resource::<TestA>("a"));
+
+        app.tick().unwrap();
+        assert_eq!(app.resource::<TestA>(), &TestA(3));
+    }
+
+    #[test]
+    fn migrate() {
+        let dir = test_dir("migrate");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("b.toml"), "version = 1\ndata = 5\n").unwrap();
+
+        let mut app = App::new();
+        app.plugin(PersistPlugin::new(&dir).resource::<TestB>("b"));
+
+        app.tick().unwrap();
+        assert_eq!(app.resource::<TestB>(), &TestB(5, 0));
+
+        fs::write(dir.join("b.toml"), "version = 0\ndata = 5\n").unwrap();
+
+        let mut app = App::new();
+        app.plugin(PersistPlugin::new(&dir).resource::<TestB>("b"));
+
+        assert!(app.tick().is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join(format!("essay-ecs-persist-{}-{}", std::process::id(), name));
+
+        let _ = fs::remove_dir_all(&dir);
+
+        dir
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct TestA(u32);
+
+    impl Persist for TestA {}
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct TestB(u32, u32);
+
+    impl Persist for TestB {
+        const VERSION: u32 = 2;
+
+        fn migrate(version: u32, data: Value) -> Result<Value> {
+            match version {
+                1 => Ok(Value::Array(vec![data, Value::Integer(0)])),
+                _ => Err(format!("unknown TestB version {}", version).into()),
+            }
+        }
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct TestC {
+        name: String,
+        values: Vec<u32>,
+    }
+
+    impl Persist for TestC {}
+}
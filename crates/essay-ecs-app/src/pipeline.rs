@@ -0,0 +1,260 @@
+use std::{collections::VecDeque, mem, ops::{Deref, DerefMut}};
+
+use essay_ecs_core::{
+    error::Result,
+    Res,
+    ResMut,
+    Store,
+    prelude::Param,
+    schedule::{SystemMeta, UnsafeStore},
+};
+
+//
+// Double-buffered pipeline between systems. The producer writes the back
+// buffer while consumers read the front buffer, the producer's output
+// from the previous tick. The buffers are separate resources, so the
+// planner runs the producer and consumers in parallel. Deeper pipelines
+// hold published values for more ticks before consumers see them.
+//
+
+///
+/// Front buffer of a pipeline, the value published depth - 1 ticks ago.
+///
+pub struct PipeFront<T> {
+    value: T,
+    // values published in recent ticks, None if the producer skipped one
+    pending: VecDeque<Option<T>>,
+    depth: usize,
+}
+
+///
+/// Back buffer of a pipeline, written this tick.
+///
+pub struct PipeBack<T> {
+    value: T,
+    is_dirty: bool,
+}
+
+impl<T: Default> Default for PipeFront<T> {
+    fn default() -> Self {
+        Self {
+            value: T::default(),
+            pending: VecDeque::new(),
+            depth: 2,
+        }
+    }
+}
+
+impl<T: Default> Default for PipeBack<T> {
+    fn default() -> Self {
+        Self {
+            value: T::default(),
+            is_dirty: false,
+        }
+    }
+}
+
+impl<T: Default + Send + Sync + 'static> PipeFront<T> {
+    ///
+    /// Ticks between the producer's write and its consumers' read, plus
+    /// one, so the default double buffer has depth 2.
+    ///
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub(crate) fn set_depth(&mut self, depth: usize) {
+        self.depth = depth.max(2);
+    }
+
+    ///
+    /// Publishes the back buffer if the producer wrote it this tick, run
+    /// in First. The back buffer then holds an older value until the
+    /// producer writes it.
+    ///
+    pub fn swap(mut front: ResMut<PipeFront<T>>, mut back: ResMut<PipeBack<T>>) {
+        let front = &mut *front;
+
+        let published = if back.is_dirty {
+            back.is_dirty = false;
+            Some(mem::take(&mut back.value))
+        } else {
+            None
+        };
+
+        front.pending.push_back(published);
+
+        while front.pending.len() > front.depth - 2 {
+            if let Some(value) = front.pending.pop_front().flatten() {
+                // recycles the old front's allocation for the producer
+                back.value = mem::replace(&mut front.value, value);
+            }
+        }
+    }
+}
+
+///
+/// Consumer side of a pipeline: the producer's output from the previous
+/// tick.
+///
+pub struct PipeIn<'w, T: Send + Sync + 'static> {
+    front: Res<'w, PipeFront<T>>,
+}
+
+impl<T: Send + Sync + 'static> Deref for PipeIn<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.front.value
+    }
+}
+
+///
+/// Producer side of a pipeline, published to PipeIn on the next tick.
+///
+pub struct PipeOut<'w, T: Send + Sync + 'static> {
+    back: ResMut<'w, PipeBack<T>>,
+}
+
+impl<T: Send + Sync + 'static> Deref for PipeOut<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.back.value
+    }
+}
+
+impl<T: Send + Sync + 'static> DerefMut for PipeOut<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        // only written buffers are published
+        self.back.is_dirty = true;
+
+        &mut self.back.value
+    }
+}
+
+impl<T: Send + Sync + 'static> Param for PipeIn<'_, T> {
+    type Arg<'w, 's> = PipeIn<'w, T>;
+
+    type Local = <Res<'static, PipeFront<T>> as Param>::Local;
+
+    fn init(meta: &mut SystemMeta, world: &mut Store) -> Result<Self::Local> {
+        Res::<PipeFront<T>>::init(meta, world)
+    }
+
+    fn arg<'w, 's>(
+        world: &'w UnsafeStore,
+        state: &'s mut Self::Local,
+    ) -> Result<Self::Arg<'w, 's>> {
+        Ok(PipeIn {
+            front: Res::<PipeFront<T>>::arg(world, state)?,
+        })
+    }
+}
+
+impl<T: Send + Sync + 'static> Param for PipeOut<'_, T> {
+    type Arg<'w, 's> = PipeOut<'w, T>;
+
+    type Local = <ResMut<'static, PipeBack<T>> as Param>::Local;
+
+    fn init(meta: &mut SystemMeta, world: &mut Store) -> Result<Self::Local> {
+        ResMut::<PipeBack<T>>::init(meta, world)
+    }
+
+    fn arg<'w, 's>(
+        world: &'w UnsafeStore,
+        state: &'s mut Self::Local,
+    ) -> Result<Self::Arg<'w, 's>> {
+        Ok(PipeOut {
+            back: ResMut::<PipeBack<T>>::arg(world, state)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use essay_ecs_core::{util::test::TestValues, Local};
+
+    use crate::app::{App, Update};
+
+    use super::{PipeIn, PipeOut};
+
+    #[test]
+    fn pipeline_one_tick_latency() {
+        let mut app = App::new();
+        app.pipeline::<u32>();
+
+        app.system(Update, |mut out: PipeOut<u32>, mut n: Local<u32>| {
+            *n += 1;
+            *out = *n;
+        });
+
+        let mut values = TestValues::new();
+
+        let mut ptr = values.clone();
+        app.system(Update, move |input: PipeIn<u32>| {
+            ptr.push(&format!("{}", *input));
+        });
+
+        app.tick().unwrap();
+        assert_eq!(values.take(), "0");
+
+        app.tick().unwrap();
+        assert_eq!(values.take(), "1");
+
+        app.tick().unwrap();
+        assert_eq!(values.take(), "2");
+    }
+
+    #[test]
+    fn pipeline_skipped_producer_tick() {
+        let mut app = App::new();
+        app.pipeline::<u32>();
+
+        app.system(Update, |mut out: PipeOut<u32>, mut n: Local<u32>| {
+            *n += 1;
+
+            // skips the second tick
+            if *n != 2 {
+                *out = *n;
+            }
+        });
+
+        let mut values = TestValues::new();
+
+        let mut ptr = values.clone();
+        app.system(Update, move |input: PipeIn<u32>| {
+            ptr.push(&format!("{}", *input));
+        });
+
+        for _ in 0..4 {
+            app.tick().unwrap();
+        }
+
+        assert_eq!(values.take(), "0, 1, 1, 3");
+    }
+
+    #[test]
+    fn pipelined_depth() {
+        let mut app = App::new();
+        app.pipelined::<u32>(3);
+
+        app.system(Update, |mut out: PipeOut<u32>, mut n: Local<u32>| {
+            *n += 1;
+            *out = *n;
+        });
+
+        let mut values = TestValues::new();
+
+        let mut ptr = values.clone();
+        app.system(Update, move |input: PipeIn<u32>| {
+            ptr.push(&format!("{}", *input));
+        });
+
+        for _ in 0..5 {
+            app.tick().unwrap();
+        }
+
+        assert_eq!(values.take(), "0, 0, 1, 2, 3");
+    }
+}
@@ -0,0 +1,138 @@
+use std::{collections::HashSet, marker::PhantomData, ops::Deref};
+
+use essay_ecs_core::{
+    entity::{Component, EntityId},
+    schedule::ScheduleLabel,
+    Commands, Local, Query,
+};
+
+use crate::app::{App, First, Plugin};
+
+///
+/// Value of T from the previous copy, maintained by PrevPlugin<T> for
+/// every entity with a T, e.g. to estimate velocity from a position.
+///
+pub struct Prev<T>(T);
+
+impl<T> Prev<T> {
+    pub fn get(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> Deref for Prev<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T: Component> Component for Prev<T> {}
+
+impl<T: Component + Clone> Prev<T> {
+    ///
+    /// Copies T into Prev<T>, adding Prev<T> to new entities when
+    /// commands are applied.
+    ///
+    pub fn update(
+        prev: Query<(EntityId, &T, &mut Prev<T>)>,
+        values: Query<(EntityId, &T)>,
+        mut commands: Commands,
+        mut seen: Local<HashSet<EntityId>>,
+    ) {
+        seen.clear();
+
+        for (id, value, prev) in prev.iter() {
+            prev.0 = value.clone();
+            seen.insert(id);
+        }
+
+        for (id, value) in values.iter() {
+            if ! seen.contains(&id) {
+                commands.entity(id).insert(Prev(value.clone()));
+            }
+        }
+    }
+}
+
+///
+/// Opt-in Prev<T> for a component type, copied in the First schedule by
+/// default, so Prev<T> holds the value at the end of the previous tick.
+///
+pub struct PrevPlugin<T> {
+    schedule: Box<dyn ScheduleLabel>,
+    marker: PhantomData<fn(T)>,
+}
+
+impl<T: Component + Clone> PrevPlugin<T> {
+    pub fn new() -> Self {
+        Self {
+            schedule: Box::new(First),
+            marker: PhantomData,
+        }
+    }
+
+    ///
+    /// Schedule that runs the copy, e.g. Last to hold this tick's values.
+    ///
+    pub fn schedule(mut self, label: impl ScheduleLabel) -> Self {
+        self.schedule = Box::new(label);
+
+        self
+    }
+}
+
+impl<T: Component + Clone> Default for PrevPlugin<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Component + Clone> Plugin for PrevPlugin<T> {
+    fn build(&self, app: &mut App) {
+        app.system(self.schedule.box_clone(), Prev::<T>::update);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use essay_ecs_core::{entity::Component, Query, Store};
+
+    use crate::app::{App, Update};
+
+    use super::{Prev, PrevPlugin};
+
+    #[test]
+    fn prev_values() {
+        let mut app = App::new();
+
+        app.plugin(PrevPlugin::<Pos>::new());
+
+        app.system(Update, |q: Query<&mut Pos>| {
+            for pos in q.iter() {
+                pos.0 += 1.;
+            }
+        });
+
+        app.eval(|store: &mut Store| {
+            store.spawn(Pos(0.));
+            Ok(())
+        }).unwrap();
+
+        app.tick().unwrap();
+        app.tick().unwrap();
+        app.tick().unwrap();
+
+        let values = app.eval(|q: Query<(&Pos, &Prev<Pos>)>| {
+            q.iter().map(|(pos, prev)| (pos.0, prev.get().0)).collect::<Vec<_>>()
+        }).unwrap();
+
+        assert_eq!(values, vec![(3., 2.)]);
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Pos(f32);
+
+    impl Component for Pos {}
+}
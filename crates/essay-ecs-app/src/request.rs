@@ -0,0 +1,270 @@
+use std::{collections::HashMap, mem};
+
+use essay_ecs_core::{
+    error::Result,
+    Local,
+    ResMut,
+    Store,
+    param::HeapSize,
+    prelude::Param,
+    schedule::{SystemMeta, UnsafeStore},
+};
+
+//
+// Request/response channel between systems. A requester sends a Req
+// with a RequestId, a responder answers it, and the Res is routed back
+// to the requesting system by the ids it sent.
+//
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RequestId(u64);
+
+impl RequestId {
+    pub fn index(&self) -> u64 {
+        self.0
+    }
+}
+
+impl HeapSize for RequestId {}
+
+pub struct Requests<Req, Res> {
+    next_id: u64,
+    requests: Vec<(RequestId, Req)>,
+    responses: HashMap<RequestId, Res>,
+}
+
+impl<Req: Send + Sync + 'static, Res: Send + Sync + 'static> Requests<Req, Res> {
+    pub fn send(&mut self, request: Req) -> RequestId {
+        let id = RequestId(self.next_id);
+        self.next_id += 1;
+
+        self.requests.push((id, request));
+
+        id
+    }
+
+    ///
+    /// Takes the unanswered requests. Each must be answered with respond
+    /// or its requester never sees a response.
+    ///
+    pub fn take_requests(&mut self) -> Vec<(RequestId, Req)> {
+        mem::take(&mut self.requests)
+    }
+
+    pub fn respond(&mut self, id: RequestId, response: Res) {
+        self.responses.insert(id, response);
+    }
+
+    pub fn take_response(&mut self, id: RequestId) -> Option<Res> {
+        self.responses.remove(&id)
+    }
+
+    ///
+    /// Requests waiting for a responder.
+    ///
+    pub fn n_requests(&self) -> usize {
+        self.requests.len()
+    }
+
+    ///
+    /// Responses waiting for their requester.
+    ///
+    pub fn n_responses(&self) -> usize {
+        self.responses.len()
+    }
+}
+
+impl<Req, Res> Default for Requests<Req, Res> {
+    fn default() -> Self {
+        Self {
+            next_id: 0,
+            requests: Vec::new(),
+            responses: HashMap::new(),
+        }
+    }
+}
+
+///
+/// Sends requests and reads the responses to this system's requests.
+/// Responses stay queued until read, so a response answered later in the
+/// tick is read on the next run.
+///
+pub struct OutRequest<'w, 's, Req, Res> {
+    requests: ResMut<'w, Requests<Req, Res>>,
+    pending: Local<'s, Vec<RequestId>>,
+}
+
+impl<Req: Send + Sync + 'static, Res: Send + Sync + 'static> OutRequest<'_, '_, Req, Res> {
+    pub fn send(&mut self, request: Req) -> RequestId {
+        let id = self.requests.send(request);
+
+        self.pending.push(id);
+
+        id
+    }
+
+    ///
+    /// Takes the responses that have arrived, in request order.
+    ///
+    pub fn responses(&mut self) -> Vec<(RequestId, Res)> {
+        let mut responses = Vec::new();
+
+        let requests = &mut self.requests;
+
+        self.pending.retain(|id| {
+            match requests.take_response(*id) {
+                Some(response) => {
+                    responses.push((*id, response));
+                    false
+                }
+                None => true,
+            }
+        });
+
+        responses
+    }
+
+    ///
+    /// Requests sent by this system without a response yet.
+    ///
+    pub fn n_pending(&self) -> usize {
+        self.pending.len()
+    }
+}
+
+///
+/// Answers requests sent by OutRequest systems.
+///
+pub struct InRequest<'w, Req, Res> {
+    requests: ResMut<'w, Requests<Req, Res>>,
+}
+
+impl<Req: Send + Sync + 'static, Res: Send + Sync + 'static> InRequest<'_, Req, Res> {
+    pub fn take(&mut self) -> Vec<(RequestId, Req)> {
+        self.requests.take_requests()
+    }
+
+    pub fn respond(&mut self, id: RequestId, response: Res) {
+        self.requests.respond(id, response);
+    }
+
+    ///
+    /// Answers every queued request with fun.
+    ///
+    pub fn answer(&mut self, mut fun: impl FnMut(Req) -> Res) {
+        for (id, request) in self.requests.take_requests() {
+            let response = fun(request);
+
+            self.requests.respond(id, response);
+        }
+    }
+}
+
+// TODO: create #[derive(Param)]
+
+impl<Req, Res> Param for OutRequest<'_, '_, Req, Res>
+where
+    Req: Send + Sync + 'static,
+    Res: Send + Sync + 'static
+{
+    type Arg<'w, 's> = OutRequest<'w, 's, Req, Res>;
+
+    type Local = (
+        <ResMut<'static, Requests<Req, Res>> as Param>::Local,
+        <Local<'static, Vec<RequestId>> as Param>::Local,
+    );
+
+    fn init(meta: &mut SystemMeta, world: &mut Store) -> Result<Self::Local> {
+        Ok((
+            ResMut::<Requests<Req, Res>>::init(meta, world)?,
+            Local::<Vec<RequestId>>::init(meta, world)?
+        ))
+    }
+
+    fn arg<'w, 's>(
+        world: &'w UnsafeStore,
+        state: &'s mut Self::Local,
+    ) -> Result<Self::Arg<'w, 's>> {
+        let (r_st, p_st) = state;
+
+        Ok(OutRequest {
+            requests: ResMut::<Requests<Req, Res>>::arg(world, r_st)?,
+            pending: Local::<Vec<RequestId>>::arg(world, p_st)?,
+        })
+    }
+}
+
+impl<Req, Res> Param for InRequest<'_, Req, Res>
+where
+    Req: Send + Sync + 'static,
+    Res: Send + Sync + 'static
+{
+    type Arg<'w, 's> = InRequest<'w, Req, Res>;
+
+    type Local = <ResMut<'static, Requests<Req, Res>> as Param>::Local;
+
+    fn init(meta: &mut SystemMeta, world: &mut Store) -> Result<Self::Local> {
+        ResMut::<Requests<Req, Res>>::init(meta, world)
+    }
+
+    fn arg<'w, 's>(
+        world: &'w UnsafeStore,
+        state: &'s mut Self::Local,
+    ) -> Result<Self::Arg<'w, 's>> {
+        Ok(InRequest {
+            requests: ResMut::<Requests<Req, Res>>::arg(world, state)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use essay_ecs_core::core_app::{CoreApp, Core};
+
+    use essay_ecs_core::util::test::TestValues;
+
+    use super::{InRequest, OutRequest, Requests};
+
+    #[test]
+    fn request_response() {
+        let mut app = CoreApp::new();
+        app.init_resource::<Requests<u32, String>>();
+
+        let mut values = TestValues::new();
+
+        let mut ptr = values.clone();
+        app.system(Core, move |mut req: OutRequest<u32, String>| {
+            for (_, response) in req.responses() {
+                ptr.push(&format!("a:{}", response));
+            }
+
+            req.send(1);
+        });
+
+        let mut ptr = values.clone();
+        app.system(Core, move |mut req: OutRequest<u32, String>| {
+            for (_, response) in req.responses() {
+                ptr.push(&format!("b:{}", response));
+            }
+
+            req.send(10);
+            req.send(20);
+        });
+
+        app.system(Core, |mut req: InRequest<u32, String>| {
+            req.answer(|value| format!("{}", value + 1));
+        });
+
+        app.tick().unwrap();
+        assert_eq!(values.take(), "");
+
+        app.tick().unwrap();
+        assert_eq!(values.take(), "a:2, b:11, b:21");
+
+        app.tick().unwrap();
+        assert_eq!(values.take(), "a:2, b:11, b:21");
+
+        assert_eq!(app.resource::<Requests<u32, String>>().n_responses(), 3);
+        assert_eq!(app.resource::<Requests<u32, String>>().n_requests(), 0);
+    }
+}
@@ -0,0 +1,65 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use essay_ecs_core::{entity::Component, Store};
+
+struct Small(f32);
+impl Component for Small {}
+
+// 128 bytes, spanning two cache lines
+struct Large([f32; 32]);
+impl Component for Large {}
+
+struct TagA(u8);
+impl Component for TagA {}
+
+struct TagB(u8);
+impl Component for TagB {}
+
+const N: usize = 10_000;
+
+// one large and two small tables, for table ordering
+fn spawn(store: &mut Store) {
+    for i in 0..N {
+        let large = Large([i as f32; 32]);
+
+        match i % 8 {
+            0 => { store.spawn((Small(i as f32), large, TagA(0))); }
+            1 => { store.spawn((Small(i as f32), large, TagB(0))); }
+            _ => { store.spawn((Small(i as f32), large)); }
+        }
+    }
+}
+
+fn iterate(c: &mut Criterion) {
+    c.bench_function("iter_small", |b| {
+        let mut store = Store::new();
+        spawn(&mut store);
+
+        b.iter(|| {
+            let sum: f32 = store.query::<&Small>().map(|v| v.0).sum();
+            black_box(sum);
+        });
+    });
+
+    c.bench_function("iter_large_stride", |b| {
+        let mut store = Store::new();
+        spawn(&mut store);
+
+        b.iter(|| {
+            let sum: f32 = store.query::<&Large>().map(|v| v.0[0]).sum();
+            black_box(sum);
+        });
+    });
+
+    c.bench_function("iter_large_stride_largest_first", |b| {
+        let mut store = Store::new();
+        spawn(&mut store);
+
+        b.iter(|| {
+            let sum: f32 = store.query::<&Large>().largest_first().map(|v| v.0[0]).sum();
+            black_box(sum);
+        });
+    });
+}
+
+criterion_group!(benches, iterate);
+criterion_main!(benches);
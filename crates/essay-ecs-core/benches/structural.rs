@@ -0,0 +1,53 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use essay_ecs_core::{entity::Component, Store};
+
+struct Position(f32);
+impl Component for Position {}
+
+struct Velocity(f32);
+impl Component for Velocity {}
+
+struct Marker;
+impl Component for Marker {}
+
+const N: usize = 10_000;
+
+fn spawn(store: &mut Store) -> Vec<essay_ecs_core::entity::EntityId> {
+    (0..N).map(|i| store.spawn((Position(i as f32), Velocity(1.)))).collect()
+}
+
+fn add_remove(c: &mut Criterion) {
+    c.bench_function("add_remove_marker", |b| {
+        let mut store = Store::new();
+        let ids = spawn(&mut store);
+
+        b.iter(|| {
+            for id in &ids {
+                store.entity_mut(*id).insert(Marker);
+            }
+
+            for id in &ids {
+                store.entity_mut(*id).remove_bundle::<Marker>();
+            }
+
+            black_box(&store);
+        });
+    });
+
+    c.bench_function("toggle_bundle", |b| {
+        let mut store = Store::new();
+        let ids = spawn(&mut store);
+
+        b.iter(|| {
+            for id in &ids {
+                store.entity_mut(*id).remove_bundle::<(Position, Velocity)>();
+                store.entity_mut(*id).insert_bundle((Position(0.), Velocity(0.)));
+            }
+
+            black_box(&store);
+        });
+    });
+}
+
+criterion_group!(benches, add_remove);
+criterion_main!(benches);
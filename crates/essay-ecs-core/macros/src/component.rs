@@ -1,5 +1,5 @@
 use proc_macro::TokenStream;
-use syn::{parse_macro_input, DeriveInput};
+use syn::{parse_macro_input, Data, DeriveInput, Ident, LitInt};
 use quote::quote;
 
 pub fn derive_component(input: TokenStream) -> TokenStream {
@@ -7,9 +7,92 @@ pub fn derive_component(input: TokenStream) -> TokenStream {
 
     let name = &ast.ident;
 
+    let mut align: Option<LitInt> = None;
+
+    for attr in &ast.attrs {
+        if ! attr.path().is_ident("component") {
+            continue;
+        }
+
+        let result = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("align") {
+                let value: LitInt = meta.value()?.parse()?;
+                let n: usize = value.base10_parse()?;
+
+                if ! n.is_power_of_two() {
+                    return Err(meta.error("component align must be a power of two"));
+                }
+
+                align = Some(value);
+
+                Ok(())
+            } else {
+                Err(meta.error("unsupported component attribute"))
+            }
+        });
+
+        if let Err(err) = result {
+            return err.to_compile_error().into();
+        }
+    }
+
+    let align = align.map(|align| quote! { const ALIGN: usize = #align; });
+
+    // variant indexes for VariantIs filters, e.g. State::ACTIVE for State::Active
+    let variant = match &ast.data {
+        Data::Enum(data) => {
+            let arms = data.variants.iter().enumerate().map(|(i, variant)| {
+                let ident = &variant.ident;
+
+                quote! { Self::#ident { .. } => #i, }
+            });
+
+            let consts = data.variants.iter().enumerate().map(|(i, variant)| {
+                let ident = Ident::new(
+                    &screaming_snake(&variant.ident.to_string()),
+                    variant.ident.span()
+                );
+
+                quote! { pub const #ident: usize = #i; }
+            });
+
+            quote! {
+                impl essay_ecs::core::entity::Variant for #name {
+                    fn variant(&self) -> usize {
+                        match self {
+                            #(#arms)*
+                        }
+                    }
+                }
+
+                #[allow(unused)]
+                impl #name {
+                    #(#consts)*
+                }
+            }
+        }
+        _ => quote! {},
+    };
+
     TokenStream::from(quote! {
         impl essay_ecs::core::entity::Component for #name {
-
+            #align
         }
+
+        #variant
     })
-}
\ No newline at end of file
+}
+
+fn screaming_snake(name: &str) -> String {
+    let mut value = String::new();
+
+    for (i, ch) in name.chars().enumerate() {
+        if ch.is_uppercase() && i > 0 {
+            value.push('_');
+        }
+
+        value.extend(ch.to_uppercase());
+    }
+
+    value
+}
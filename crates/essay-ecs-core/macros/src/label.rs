@@ -21,3 +21,17 @@ pub fn derive_schedule_label(input: TokenStream) -> TokenStream {
         }
     })
 }
+
+pub fn derive_system_label(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+
+    let name = &ast.ident;
+
+    TokenStream::from(quote! {
+        impl essay_ecs::core::schedule::SystemLabel for #name {
+            fn box_clone(&self) -> Box<dyn essay_ecs::core::schedule::SystemLabel> {
+                Box::new(Clone::clone(self))
+            }
+        }
+    })
+}
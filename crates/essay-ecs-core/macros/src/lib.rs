@@ -18,7 +18,7 @@ pub fn derive_component(input: TokenStream) -> TokenStream {
     component::derive_component(input)
 }
 
-#[proc_macro_derive(Param)]
+#[proc_macro_derive(Param, attributes(param))]
 pub fn derive_param(input: TokenStream) -> TokenStream {
     param::derive_param(input)
 }
@@ -28,6 +28,11 @@ pub fn derive_schedule_label(input: TokenStream) -> TokenStream {
     label::derive_schedule_label(input)
 }
 
+#[proc_macro_derive(SystemLabel)]
+pub fn derive_system_label(input: TokenStream) -> TokenStream {
+    label::derive_system_label(input)
+}
+
 #[proc_macro_derive(Phase, attributes(component))]
 pub fn derive_task_set(input: TokenStream) -> TokenStream {
     phase::derive_phase(input)
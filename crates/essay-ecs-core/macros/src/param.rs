@@ -25,13 +25,19 @@ pub fn derive_param(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
 
     let fields: Vec<ParamField> = match fields {
         Fields::Named(ref fields) => {
-            fields.named.iter().enumerate().map(|(index, field)| {
-                ParamField {
+            let fields: syn::Result<Vec<ParamField>> = fields.named.iter().enumerate().map(|(index, field)| {
+                Ok(ParamField {
                     ident: field.ident.clone(),
                     var: format_ident!("f_{index}"),
                     ty: field.ty.clone(),
-                }
-            }).collect()
+                    kind: field_kind(field)?,
+                })
+            }).collect();
+
+            match fields {
+                Ok(fields) => fields,
+                Err(err) => return err.to_compile_error().into(),
+            }
         }
         Fields::Unnamed(_) => {
             return syn::Error::new(span, "tuples currently unsupported").into_compile_error().into();
@@ -53,6 +59,7 @@ pub fn derive_param(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
 
     // let arg_types = arg_types(&fields);
     let arg_fields = arg_fields(&fields);
+    let memory_sizes = memory_sizes(&fields);
     
     //return syn::Error::new(span, format!("Test {:#?}", state_init)).into_compile_error().into();
 
@@ -73,6 +80,10 @@ pub fn derive_param(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
                 })
             }
 
+            fn __memory_size<'w, 's>(state: &__Local<'w, 's>) -> usize {
+                0 #(#memory_sizes)*
+            }
+
             impl <#(#ty_impl_w1)*> essay_ecs::core::param::Param for #ident <#(#ty_gen_w1)*> {
                 type Local = __Local<'static, 'static>;
                 type Arg<'w, 's> = #ident #ty_gen;
@@ -92,6 +103,10 @@ pub fn derive_param(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
                         #(#arg_fields)*
                     })
                 }
+
+                fn memory_size(state: &Self::Local) -> usize {
+                    __memory_size(state)
+                }
             }
         };
     }.into()
@@ -121,29 +136,94 @@ struct ParamField {
     ident: Option<Ident>,
     var: Ident,
     ty: Type,
+    kind: FieldKind,
+}
+
+enum FieldKind {
+    // including Option<Res<T>> and Option<ResMut<T>>, which are None
+    // when the resource is missing
+    Param,
+    // #[param(default)], Default::default() without store access
+    Default,
+}
+
+fn field_kind(field: &syn::Field) -> syn::Result<FieldKind> {
+    let mut is_default = false;
+
+    for attr in &field.attrs {
+        if ! attr.path().is_ident("param") {
+            continue;
+        }
+
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("default") {
+                is_default = true;
+
+                Ok(())
+            } else {
+                Err(meta.error("unsupported param attribute"))
+            }
+        })?;
+    }
+
+    if is_default {
+        Ok(FieldKind::Default)
+    } else {
+        Ok(FieldKind::Param)
+    }
 }
 
 fn state_types(fields: &Vec<ParamField>) -> Vec<TokenStream> {
     fields.iter().map(|field| {
-        let ParamField{var, ty, ..} = field;
+        let ParamField{var, ty, kind, ..} = field;
 
-        quote! { #var: <#ty as essay_ecs::core::param::Param>::Local, }
+        match kind {
+            FieldKind::Param => {
+                quote! { #var: <#ty as essay_ecs::core::param::Param>::Local, }
+            }
+            FieldKind::Default => quote! {},
+        }
     }).collect()
 }
 
 fn state_init(fields: &Vec<ParamField>) -> Vec<TokenStream> {
     fields.iter().map(|field| {
-        let ParamField{var, ty, ..} = field;
+        let ParamField{var, ty, kind, ..} = field;
 
-        quote! { #var: <#ty as essay_ecs::core::param::Param>::Local::init(meta, store)?, }
+        match kind {
+            FieldKind::Param => {
+                quote! { #var: <#ty as essay_ecs::core::param::Param>::init(meta, store)?, }
+            }
+            FieldKind::Default => quote! {},
+        }
     }).collect()
 }
 
 fn arg_fields(fields: &Vec<ParamField>) -> Vec<TokenStream> {
     fields.iter().map(|field| {
-        let ParamField { ident, var, ty } = field;
+        let ParamField { ident, var, ty, kind } = field;
         
-        quote! { #ident: <#ty as essay_ecs::core::param::Param>::Arg::<'w, 's>::arg(store, &mut state.#var)?, }
+        match kind {
+            FieldKind::Param => {
+                quote! { #ident: <#ty as essay_ecs::core::param::Param>::Arg::<'w, 's>::arg(store, &mut state.#var)?, }
+            }
+            FieldKind::Default => {
+                quote! { #ident: ::std::default::Default::default(), }
+            }
+        }
     }).collect()
 }
 
+
+fn memory_sizes(fields: &[ParamField]) -> Vec<TokenStream> {
+    fields.iter().map(|field| {
+        let ParamField { var, ty, kind, .. } = field;
+
+        match kind {
+            FieldKind::Param => {
+                quote! { + <#ty as essay_ecs::core::param::Param>::memory_size(&state.#var) }
+            }
+            FieldKind::Default => quote! {},
+        }
+    }).collect()
+}
@@ -15,21 +15,38 @@ use ecs as essay_ecs;
 ///
 /// ECS application only using the essay_ecs_core crate.
 /// 
-/// Primarily for testing, but it also serves as a focus to visualize 
-/// the core API.
+/// A minimal runtime without plugins: one main schedule, Core by default,
+/// run once per tick. It also serves as a focus to visualize the core API.
 /// 
-/// Applications should generally use essay_ecs::App instead. 
+/// Applications that need plugins or the main schedule phases should use
+/// essay_ecs::App instead. 
 /// 
 pub struct CoreApp {
     store: Store,
     main_schedule: Box<dyn ScheduleLabel>,
 }
 
+///
+/// Fluent configuration for CoreApp, e.g.
+/// CoreApp::builder().executor(Executors::Single).resource(value).build()
+/// 
+pub struct CoreAppBuilder {
+    app: CoreApp,
+    executor: Option<Box<dyn ExecutorFactory>>,
+}
+
 impl CoreApp {
     pub fn new() -> Self {
         CoreApp::default()
     }
 
+    pub fn builder() -> CoreAppBuilder {
+        CoreAppBuilder {
+            app: CoreApp::new(),
+            executor: None,
+        }
+    }
+
     pub fn empty() -> Self {
         let mut store = Store::new();
 
@@ -128,9 +145,20 @@ impl CoreApp {
     }
 
     pub fn tick(&mut self) -> Result<()> {
+        self.store.clear_command_trace();
+
         self.store.run_schedule(&self.main_schedule)
     }
 
+    ///
+    /// Records each tick's applied commands in the CommandTrace resource.
+    ///
+    pub fn trace_commands(&mut self) -> &mut Self {
+        self.store.trace_commands(true);
+
+        self
+    }
+
     pub fn set_executor(&mut self, executor: impl ExecutorFactory + 'static) -> &mut Self {
         self.resource_mut::<Schedules>().set_executor(executor);
         
@@ -151,13 +179,93 @@ impl Default for CoreApp {
     }
 }
 
+impl CoreAppBuilder {
+    pub fn executor(mut self, executor: impl ExecutorFactory + 'static) -> Self {
+        self.executor = Some(Box::new(executor));
+
+        self
+    }
+
+    pub fn resource<T: Send + 'static>(mut self, value: T) -> Self {
+        self.app.insert_resource(value);
+
+        self
+    }
+
+    pub fn init_resource<T: FromStore + Send + 'static>(mut self) -> Self {
+        self.app.init_resource::<T>();
+
+        self
+    }
+
+    ///
+    /// Adds or replaces the schedule for label.
+    /// 
+    pub fn schedule(
+        mut self, 
+        label: impl AsRef<dyn ScheduleLabel>, 
+        schedule: Schedule
+    ) -> Self {
+        self.app.resource_mut::<Schedules>().insert(label, schedule);
+
+        self
+    }
+
+    pub fn system<M>(
+        mut self, 
+        label: impl AsRef<dyn ScheduleLabel>,
+        system: impl IntoSystemConfig<M>
+    ) -> Self {
+        self.app.system(label, system);
+
+        self
+    }
+
+    pub fn phase(
+        mut self, 
+        label: impl AsRef<dyn ScheduleLabel>,
+        into_phases: impl IntoPhaseConfigs
+    ) -> Self {
+        self.app.phase(label, into_phases);
+
+        self
+    }
+
+    ///
+    /// Schedule run by tick, Core by default.
+    /// 
+    pub fn main_schedule(mut self, label: impl AsRef<dyn ScheduleLabel>) -> Self {
+        self.app.main_schedule = label.as_ref().box_clone();
+
+        self
+    }
+
+    pub fn build(self) -> CoreApp {
+        let mut app = self.app;
+
+        // applied last so it covers schedules added by the builder
+        if let Some(executor) = self.executor {
+            app.resource_mut::<Schedules>().set_executor_box(executor);
+        }
+
+        app
+    }
+}
+
 #[derive(ScheduleLabel, Clone, Debug, PartialEq, Hash, Eq)]
 pub struct Core;
 
 #[cfg(test)]
 mod test {
+    use std::{sync::{mpsc, Arc, Mutex}, thread, time::Duration};
+
+    use crate::{error::Result, schedule::Executors, Res, ResMut, Schedule, ScheduleLabel, Store};
+
     use super::{Core, CoreApp};
 
+    mod ecs { pub mod core { pub use crate::*; } }
+    use ecs as essay_ecs;
+
     #[test]
     fn test_schedule() {
         let mut app = CoreApp::new();
@@ -167,5 +275,190 @@ mod test {
         app.tick().unwrap();
 
     }
+
+    #[test]
+    fn component_align() {
+        let mut app = CoreApp::new();
+
+        assert_eq!(<Aligned as crate::entity::Component>::ALIGN, 64);
+
+        app.eval(|mut c: crate::Commands| {
+            for i in 0..10 {
+                c.spawn(Aligned([i as f32; 3]));
+            }
+        }).unwrap();
+
+        let addrs: Vec<usize> = app.query::<&Aligned>()
+            .map(|a| a as *const Aligned as usize)
+            .collect();
+
+        assert_eq!(addrs.len(), 10);
+        assert!(addrs.iter().all(|addr| addr % 64 == 0));
+        assert_eq!(addrs[1] - addrs[0], 64);
+    }
+
+    #[derive(crate::Component)]
+    #[component(align = 64)]
+    struct Aligned(#[allow(unused)] [f32; 3]);
+
+    #[test]
+    fn builder() {
+        let mut app = CoreApp::builder()
+            .executor(Executors::Single)
+            .resource(TestA(1))
+            .system(Core, |mut a: ResMut<TestA>| a.0 += 1)
+            .schedule(TestLabel, Schedule::new())
+            .system(TestLabel, |mut a: ResMut<TestA>| a.0 += 10)
+            .build();
+
+        app.tick().unwrap();
+        assert_eq!(app.resource::<TestA>(), &TestA(2));
+
+        let mut app = CoreApp::builder()
+            .resource(TestA(1))
+            .schedule(TestLabel, Schedule::new())
+            .system(TestLabel, |mut a: ResMut<TestA>| a.0 += 10)
+            .main_schedule(TestLabel)
+            .build();
+
+        app.tick().unwrap();
+        assert_eq!(app.resource::<TestA>(), &TestA(11));
+    }
+
+    #[test]
+    fn run_schedules_parallel() {
+        let (tx_a, rx_a) = mpsc::channel::<()>();
+        let (tx_b, rx_b) = mpsc::channel::<()>();
+        let rx_a = Mutex::new(rx_a);
+        let rx_b = Mutex::new(rx_b);
+
+        let mut app = CoreApp::builder()
+            .resource(TestA(0))
+            .resource(TestB(0))
+            .schedule(TestLabel, Schedule::new())
+            .schedule(TestLabelB, Schedule::new())
+            // each system waits for the other, so they must run concurrently
+            .system(TestLabel, move |mut a: ResMut<TestA>| {
+                tx_a.send(()).unwrap();
+                rx_b.lock().unwrap().recv_timeout(Duration::from_secs(5)).unwrap();
+                a.0 += 1;
+            })
+            .system(TestLabelB, move |mut b: ResMut<TestB>| {
+                tx_b.send(()).unwrap();
+                rx_a.lock().unwrap().recv_timeout(Duration::from_secs(5)).unwrap();
+                b.0 += 1;
+            })
+            .system(Core, |store: &mut Store| {
+                store.run_schedules_parallel(&[&TestLabel, &TestLabelB])
+            })
+            .build();
+
+        app.tick().unwrap();
+        app.tick().unwrap();
+
+        assert_eq!(app.resource::<TestA>(), &TestA(2));
+        assert_eq!(app.resource::<TestB>(), &TestB(2));
+    }
+
+    #[test]
+    fn run_schedules_parallel_executor_threads() {
+        let ids = Arc::new(Mutex::new(Vec::new()));
+        let ptr = ids.clone();
+
+        let mut app = CoreApp::builder()
+            .resource(TestA(0))
+            .resource(TestB(0))
+            .schedule(TestLabel, Schedule::new())
+            .schedule(TestLabelB, Schedule::new())
+            .system(TestLabel, |mut a: ResMut<TestA>| a.0 += 1)
+            .system(TestLabelB, move |mut b: ResMut<TestB>| {
+                ptr.lock().unwrap().push(thread::current().id());
+                b.0 += 1;
+            })
+            .system(Core, |store: &mut Store| {
+                store.run_schedules_parallel(&[&TestLabel, &TestLabelB])
+            })
+            .build();
+
+        app.tick().unwrap();
+        app.tick().unwrap();
+        app.tick().unwrap();
+
+        assert_eq!(app.resource::<TestA>(), &TestA(3));
+        assert_eq!(app.resource::<TestB>(), &TestB(3));
+
+        // the schedule's executor thread runs it each tick
+        let ids = ids.lock().unwrap();
+        assert_eq!(ids.len(), 3);
+        assert!(ids.iter().all(|id| *id == ids[0]));
+        assert_ne!(ids[0], thread::current().id());
+    }
+
+    #[test]
+    fn run_schedules_parallel_conflict() {
+        let mut app = CoreApp::builder()
+            .resource(TestA(0))
+            .schedule(TestLabel, Schedule::new())
+            .schedule(TestLabelB, Schedule::new())
+            .system(TestLabel, |mut a: ResMut<TestA>| a.0 += 1)
+            .system(TestLabelB, |a: Res<TestA>| assert!(a.0 < 100))
+            .build();
+
+        let err = app.eval(|store: &mut Store| {
+            store.run_schedules_parallel(&[&TestLabel, &TestLabelB])
+        }).unwrap_err();
+
+        assert!(err.message().contains("can't run in parallel"));
+
+        assert_eq!(app.resource::<TestA>(), &TestA(0));
+        app.eval(|store: &mut Store| store.run_schedule(TestLabel)).unwrap();
+        assert_eq!(app.resource::<TestA>(), &TestA(1));
+    }
+
+    #[test]
+    fn run_schedules_parallel_exclusive() {
+        let mut app = CoreApp::builder()
+            .resource(TestA(0))
+            .resource(TestB(0))
+            .schedule(TestLabel, Schedule::new())
+            .schedule(TestLabelB, Schedule::new())
+            .system(TestLabel, |mut a: ResMut<TestA>| a.0 += 1)
+            .system(TestLabelB, |store: &mut Store| -> Result<()> {
+                store.resource_mut::<TestB>().0 += 1;
+                Ok(())
+            })
+            .build();
+
+        let err = app.eval(|store: &mut Store| {
+            store.run_schedules_parallel(&[&TestLabel, &TestLabelB])
+        }).unwrap_err();
+
+        assert!(err.message().contains("can't run in parallel"), "{}", err.message());
+        assert!(err.message().contains("the whole store"), "{}", err.message());
+
+        // neither schedule ran, and both still run on their own
+        assert_eq!(app.resource::<TestA>(), &TestA(0));
+        assert_eq!(app.resource::<TestB>(), &TestB(0));
+
+        app.eval(|store: &mut Store| {
+            store.run_schedule(TestLabel)?;
+            store.run_schedule(TestLabelB)
+        }).unwrap();
+
+        assert_eq!(app.resource::<TestA>(), &TestA(1));
+        assert_eq!(app.resource::<TestB>(), &TestB(1));
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct TestA(u32);
+
+    #[derive(Debug, PartialEq)]
+    struct TestB(u32);
+
+    #[derive(ScheduleLabel, Clone, Debug, PartialEq, Hash, Eq)]
+    struct TestLabel;
+
+    #[derive(ScheduleLabel, Clone, Debug, PartialEq, Hash, Eq)]
+    struct TestLabelB;
 }
 
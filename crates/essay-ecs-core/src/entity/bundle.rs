@@ -9,6 +9,13 @@ pub trait Bundle:'static {
     fn build(builder: &mut InsertBuilder);
 
     unsafe fn insert(cursor: &mut InsertCursor, value: Self);
+
+    ///
+    /// Type names of the bundle's components, for command tracing.
+    ///
+    fn type_names(names: &mut Vec<&'static str>) {
+        names.push(std::any::type_name::<Self>());
+    }
 }
 
 pub struct InsertBuilder<'a> {
@@ -20,6 +27,8 @@ pub struct InsertPlan {
     table_id: TableId,
     columns: Vec<ColumnId>,
     index_map: Vec<usize>,
+    // rows of entity columns the bundle replaces, dropped on complete
+    replaced: Vec<usize>,
 }
 
 pub struct InsertCursor<'a> {
@@ -50,8 +59,8 @@ impl<'a,'t> InsertBuilder<'a> {
         }
     }
 
-    pub(crate) fn add_column<T:'static>(&mut self) {
-        let id = self.store.add_column::<T>();
+    pub(crate) fn add_column_aligned<T:'static>(&mut self, align: usize) {
+        let id = self.store.add_column_aligned::<T>(align);
         
         self.columns.push(id);
     }
@@ -62,22 +71,36 @@ impl<'a,'t> InsertBuilder<'a> {
 
         let mut index_map = Vec::<usize>::new();
 
+        // a column the entity already has takes the bundle's new value
         for table_column in table.columns() {
             index_map.push(self.columns.iter()
-                .position(|c| c == table_column)
+                .rposition(|c| c == table_column)
                 .unwrap()
             );
         }
 
+        let replaced = (0..self.columns.len())
+            .filter(|index| ! index_map.contains(index))
+            .collect();
+
         InsertPlan {
             table_id,
             columns: self.columns.clone(),
             index_map: index_map,
+            replaced,
         }
     }
 }
 
 impl InsertPlan {
+    pub(crate) fn table_id(&self) -> TableId {
+        self.table_id
+    }
+
+    pub(crate) fn columns(&self) -> &Vec<ColumnId> {
+        &self.columns
+    }
+
     pub(crate) fn insert<T:'static>(
         &self,
         store: &mut EntityStore, 
@@ -146,6 +169,11 @@ impl<'a> InsertCursor<'a> {
             columns.push(self.rows[*index]);
         }
 
+        // drops the entity's values the bundle replaced
+        for index in &self.plan.replaced {
+            self.store.column_mut(self.plan.columns[*index]).remove(self.rows[*index]);
+        }
+
         self.store.insert_or_spawn(self.id, self.plan.table_id, columns)
     }
 }
@@ -156,7 +184,7 @@ impl<'a> InsertCursor<'a> {
 
 impl<T:Component> Bundle for T {
     fn build(builder: &mut InsertBuilder) {
-        builder.add_column::<T>();
+        builder.add_column_aligned::<T>(T::ALIGN);
     }
 
     unsafe fn insert(cursor: &mut InsertCursor, this: Self) {
@@ -186,6 +214,12 @@ macro_rules! impl_insert_tuple {
                     $part::insert(cursor, $part);
                 )*
             }
+
+            fn type_names(names: &mut Vec<&'static str>) {
+                $(
+                    $part::type_names(names);
+                )*
+            }
         }
     }
 }
@@ -267,6 +267,16 @@ impl Column {
         }
     }
 
+    ///
+    /// Grows the column to hold at least `capacity` rows. Zero-sized
+    /// columns are skipped since they never allocate.
+    ///
+    pub(crate) fn reserve_total(&mut self, capacity: usize) {
+        if self.pad_size > 0 && self.capacity < capacity {
+            self.extend(capacity);
+        }
+    }
+
     fn extend(&mut self, new_capacity: usize) {
         assert!(self.pad_size > 0, "zero sized column items can't be pushed");
         assert!(self.capacity < new_capacity);
@@ -4,8 +4,21 @@ use std::{
     any::{TypeId, type_name}, 
     borrow::Cow, 
     alloc::Layout, 
+    sync::Arc,
 };
 
+use super::{bundle::InsertPlan, ComponentId};
+
+///
+/// Listener for new tables, with the table's components.
+///
+pub type TableListener = Box<dyn FnMut(TableId, &[ComponentId]) + Send + Sync>;
+
+///
+/// Listener for new view tables, a table newly matching a view.
+///
+pub type ViewTableListener = Box<dyn FnMut(ViewId, TableId) + Send + Sync>;
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct ColumnId(usize);
 
@@ -67,6 +80,43 @@ pub(crate) struct StoreMeta {
 
     view_table_map: HashMap<(ViewId,TableId), ViewTableId>,
     view_tables: Vec<ViewTableType>,
+
+    // spawn plans keyed by bundle type
+    spawn_plans: HashMap<TypeId, Arc<InsertPlan>>,
+
+    // table transitions keyed by source table and bundle type
+    add_edges: HashMap<(TableId, TypeId), Arc<InsertPlan>>,
+    remove_edges: HashMap<(TableId, TypeId), Arc<RemoveEdge>>,
+
+    table_listeners: Vec<TableListener>,
+    view_table_listeners: Vec<ViewTableListener>,
+}
+
+///
+/// Destination table for removing a bundle, and whether each source
+/// column is kept.
+///
+pub(crate) struct RemoveEdge {
+    table_id: TableId,
+    keep: Vec<bool>,
+}
+
+impl RemoveEdge {
+    pub(crate) fn new(table_id: TableId, keep: Vec<bool>) -> Self {
+        Self { table_id, keep }
+    }
+
+    pub(crate) fn table_id(&self) -> TableId {
+        self.table_id
+    }
+
+    pub(crate) fn keep(&self) -> &Vec<bool> {
+        &self.keep
+    }
+
+    pub(crate) fn is_noop(&self) -> bool {
+        self.keep.iter().all(|keep| *keep)
+    }
 }
 
 //
@@ -197,6 +247,10 @@ impl ViewType {
         self.cols.iter().position(|col| *col == col_id)
     }
 
+    pub(crate) fn columns(&self) -> &Vec<ColumnId> {
+        &self.cols
+    }
+
     pub(crate) fn view_tables(&self) -> &Vec<ViewTableId> {
         &self.view_tables
     }
@@ -289,6 +343,13 @@ impl StoreMeta {
 
             view_table_map: HashMap::new(),
             view_tables: Vec::new(),
+
+            spawn_plans: HashMap::new(),
+            add_edges: HashMap::new(),
+            remove_edges: HashMap::new(),
+
+            table_listeners: Vec::new(),
+            view_table_listeners: Vec::new(),
         }
     }
 
@@ -314,6 +375,14 @@ impl StoreMeta {
     }
 
     pub fn add_column<T:'static>(&mut self) -> ColumnId {
+        self.add_column_aligned::<T>(0)
+    }
+
+    ///
+    /// Adds a column whose items are aligned to at least `align`. The
+    /// first registration of a type fixes its layout.
+    ///
+    pub fn add_column_aligned<T:'static>(&mut self, align: usize) -> ColumnId {
         let type_id = TypeId::of::<T>();
 
         let id = *self.column_map.entry(type_id)
@@ -327,7 +396,10 @@ impl StoreMeta {
                 name: Cow::Borrowed(type_name::<T>()),
 
                 _layout: Layout::new::<T>(),
-                layout_padded: Layout::new::<T>().pad_to_align(),
+                layout_padded: Layout::new::<T>()
+                    .align_to(align.max(1))
+                    .expect("component alignment must be a power of two")
+                    .pad_to_align(),
 
                 tables: Vec::new(),
                 views: Vec::new(),
@@ -370,11 +442,101 @@ impl StoreMeta {
         });
 
         self.fill_table_columns(table_id);
+
+        let components = self.table_components(table_id);
+        for listener in &mut self.table_listeners {
+            listener(table_id, &components);
+        }
+
         self.add_view_tables_from_table(table_id);
 
         table_id
     }
 
+    fn table_components(&self, table_id: TableId) -> Vec<ComponentId> {
+        self.table(table_id).columns().iter()
+            .map(|id| ComponentId::from(*id))
+            .collect()
+    }
+
+    ///
+    /// Calls listener for every table, existing tables immediately and
+    /// new tables as they're created, so extensions can track tables
+    /// incrementally instead of rescanning.
+    ///
+    pub(crate) fn on_new_table(&mut self, mut listener: TableListener) {
+        for table in &self.tables {
+            listener(table.id(), &self.table_components(table.id()));
+        }
+
+        self.table_listeners.push(listener);
+    }
+
+    ///
+    /// Calls listener for every view table, existing ones immediately.
+    ///
+    pub(crate) fn on_new_view_table(&mut self, mut listener: ViewTableListener) {
+        for view_table in &self.view_tables {
+            listener(view_table.view_id, view_table.table_id);
+        }
+
+        self.view_table_listeners.push(listener);
+    }
+
+    //
+    // Table edges
+    //
+
+    pub(crate) fn spawn_plan(&self, bundle: TypeId) -> Option<Arc<InsertPlan>> {
+        self.spawn_plans.get(&bundle).cloned()
+    }
+
+    pub(crate) fn insert_spawn_plan(
+        &mut self,
+        bundle: TypeId,
+        plan: InsertPlan
+    ) -> Arc<InsertPlan> {
+        let plan = Arc::new(plan);
+
+        self.spawn_plans.insert(bundle, plan.clone());
+
+        plan
+    }
+
+    pub(crate) fn add_edge(&self, table_id: TableId, bundle: TypeId) -> Option<Arc<InsertPlan>> {
+        self.add_edges.get(&(table_id, bundle)).cloned()
+    }
+
+    pub(crate) fn insert_add_edge(
+        &mut self,
+        table_id: TableId,
+        bundle: TypeId,
+        plan: InsertPlan
+    ) -> Arc<InsertPlan> {
+        let plan = Arc::new(plan);
+
+        self.add_edges.insert((table_id, bundle), plan.clone());
+
+        plan
+    }
+
+    pub(crate) fn remove_edge(&self, table_id: TableId, bundle: TypeId) -> Option<Arc<RemoveEdge>> {
+        self.remove_edges.get(&(table_id, bundle)).cloned()
+    }
+
+    pub(crate) fn insert_remove_edge(
+        &mut self,
+        table_id: TableId,
+        bundle: TypeId,
+        edge: RemoveEdge
+    ) -> Arc<RemoveEdge> {
+        let edge = Arc::new(edge);
+
+        self.remove_edges.insert((table_id, bundle), edge.clone());
+
+        edge
+    }
+
     fn fill_table_columns(&mut self, table_id: TableId) {
         let columns = self.table(table_id).columns().clone();
 
@@ -434,6 +596,15 @@ impl StoreMeta {
         self.view_tables.get(id.index()).unwrap()
     }
 
+    pub(crate) fn get_view_table(
+        &self,
+        view_id: ViewId,
+        table_id: TableId
+    ) -> Option<&ViewTableType> {
+        self.view_table_map.get(&(view_id, table_id))
+            .map(|id| self.view_table(*id))
+    }
+
     pub(crate) fn add_view_table(
         &mut self,
         table_id: TableId, 
@@ -467,8 +638,14 @@ impl StoreMeta {
 
         self.view_tables.push(ViewTableType::new(view_table_id, table, view_type));
 
+        // tables are added in id order, so views iterate them in table id
+        // order, for locality
         let view_type = self.view_mut(view_id);
         view_type.view_tables.push(view_table_id);
+
+        for listener in &mut self.view_table_listeners {
+            listener(view_id, table_id);
+        }
     }
 
     fn add_view_tables_from_table(
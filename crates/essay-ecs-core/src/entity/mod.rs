@@ -7,7 +7,7 @@ pub(crate) mod meta;
 
 pub use store::{
     EntityStore, ComponentId, Component,
-    EntityId,
+    EntityId, WorldId, EntityAllocator,
 };
 
 pub use bundle::{
@@ -16,6 +16,8 @@ pub use bundle::{
 
 pub use view::{
     View, ViewBuilder, ViewPlan, ViewCursor, ViewIterator,
+    Mapped, MappedView, ViewExplain, TableExplain,
+    ViewFilter, Variant, VariantIs,
 };
 
-pub use meta::ViewId;
+pub use meta::{TableId, ViewId, TableListener, ViewTableListener};
@@ -1,17 +1,31 @@
+use std::any::{type_name, TypeId};
+use std::borrow::Cow;
+use std::mem;
 use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use crate::error::Result;
+use crate::param::HeapSize;
 
 use super::column::{Column, RowId};
 use super::bundle::{InsertBuilder, Bundle, InsertPlan};
 use super::ViewId;
 use super::table::{Table, TableRow};
-use super::view::{View, ViewIterator, ViewBuilder, ViewPlan};
-use super::meta::{StoreMeta, ColumnId, TableId, ViewType};
+use super::view::{View, ViewFilter, ViewIterator, ViewBuilder, ViewPlan};
+use super::meta::{StoreMeta, ColumnId, TableId, ViewType, ViewTableType, RemoveEdge};
 
 #[derive (Debug, Copy, Clone, PartialEq, Hash, Eq, PartialOrd, Ord)]
 pub struct ComponentId(usize);
 
+///
+/// EntityId carries its WorldId in debug builds so ids used with the wrong
+/// store are caught.
+///
 #[derive(Debug,Clone,Copy,PartialEq,Hash,PartialOrd,Eq)]
-pub struct EntityId(u32, u32);
+pub struct EntityId(u32, u32, #[cfg(debug_assertions)] WorldId);
+
+#[derive(Debug, Clone, Copy, PartialEq, Hash, PartialOrd, Eq, Ord)]
+pub struct WorldId(u32);
 
 #[derive(Debug)]
 pub struct Entity {
@@ -21,9 +35,9 @@ pub struct Entity {
 }
 
 impl Entity {
-    fn empty(len: usize) -> Entity {
+    fn empty(len: usize, world: WorldId) -> Entity {
         Self {
-            id: EntityId(len as u32, EntityId::FREE_MASK),
+            id: EntityId::from_parts(len as u32, EntityId::FREE_MASK, world),
             table: TableId::UNSET,
             row: RowId::UNSET,
         }
@@ -35,18 +49,50 @@ impl Entity {
 }
 
 struct EntityAlloc {
+    world: WorldId,
+
     capacity: usize,
 
     free_list: Vec<EntityId>, 
+
+    // ids reserved while a transaction runs, freed if it fails
+    reserved: Vec<EntityId>,
+    is_logging: bool,
 }
 
-pub trait Component: Send + Sync + 'static {}
+///
+/// Shared handle to the entity id allocator, so ids can be reserved
+/// without borrowing the store.
+///
+#[derive(Clone)]
+pub struct EntityAllocator(Arc<Mutex<EntityAlloc>>);
+
+impl EntityAllocator {
+    pub(crate) fn alloc(&self) -> EntityId {
+        self.0.lock().unwrap().alloc()
+    }
+}
+
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is not a Component",
+    label = "not a Component",
+    note = "add #[derive(Component)] to `{Self}`",
+)]
+pub trait Component: Send + Sync + 'static {
+    ///
+    /// Minimum column alignment, e.g. 32 or 64 for SIMD loads. Zero uses
+    /// the type's own alignment. Set with `#[component(align = 64)]`.
+    ///
+    const ALIGN: usize = 0;
+}
 
 //
 // implementation
 //
 
 pub struct EntityStore {
+    world: WorldId,
+
     meta: StoreMeta,
 
     columns: Vec<Column>,
@@ -56,11 +102,24 @@ pub struct EntityStore {
     entities: Vec<Entity>,
 
     free_list: Arc<Mutex<EntityAlloc>>,
+
+    // entities whose table changed, read by cached queries and only
+    // recorded once one is registered
+    changes: Vec<EntityId>,
+    change_base: usize,
+    is_change_log: bool,
 }
 
+// changes kept before the log is dropped, forcing cached queries to rebuild
+const MAX_CHANGES: usize = 16 * 1024;
+
 impl EntityStore {
     pub fn new() -> Self {
+        let world = WorldId::next();
+
         let mut store = Self {
+            world,
+
             meta: StoreMeta::new(),
 
             columns: Vec::new(),
@@ -69,7 +128,11 @@ impl EntityStore {
 
             entities: Vec::new(),
 
-            free_list: Arc::new(Mutex::new(EntityAlloc::new())),
+            free_list: Arc::new(Mutex::new(EntityAlloc::new(world))),
+
+            changes: Vec::new(),
+            change_base: 0,
+            is_change_log: false,
         };
 
         store.add_table(Vec::new());
@@ -81,10 +144,61 @@ impl EntityStore {
         &self.meta
     }
 
+    pub fn on_new_table(
+        &mut self,
+        listener: impl FnMut(TableId, &[ComponentId]) + Send + Sync + 'static
+    ) {
+        self.meta.on_new_table(Box::new(listener))
+    }
+
+    pub fn on_new_view_table(
+        &mut self,
+        listener: impl FnMut(ViewId, TableId) + Send + Sync + 'static
+    ) {
+        self.meta.on_new_view_table(Box::new(listener))
+    }
+
+    pub fn component_id<T:'static>(&self) -> Option<ComponentId> {
+        self.meta.get_column::<T>().map(ComponentId::from)
+    }
+
     pub fn len(&self) -> usize {
         self.entities.len()
     }
 
+    pub fn entity_count(&self) -> usize {
+        self.tables.iter().map(|table| table.len()).sum()
+    }
+
+    pub(crate) fn count_view(&self, plan: &ViewPlan) -> usize {
+        if plan.is_filtered() {
+            return unsafe { self.iter_view_with_plan::<()>(Cow::Borrowed(plan)) }.count();
+        }
+
+        let view = self.meta().view(plan.view());
+
+        view.view_tables().iter()
+            .map(|id| self.meta().view_table(*id).table_id())
+            .map(|id| self.tables[id.index()].len())
+            .sum()
+    }
+
+    pub(crate) fn table_len(&self, table_id: TableId) -> usize {
+        self.tables[table_id.index()].len()
+    }
+
+    pub fn world_id(&self) -> WorldId {
+        self.world
+    }
+
+    #[inline]
+    fn check_world(&self, id: EntityId) {
+        if let Some(world) = id.world() {
+            assert!(world == self.world,
+                "{:?} belongs to {:?} but was used in {:?}", id, world, self.world);
+        }
+    }
+
     //
     // Column
     //
@@ -93,8 +207,8 @@ impl EntityStore {
         &mut self.columns[column_id.index()]
     }
     
-    pub(crate) fn add_column<T:'static>(&mut self) -> ColumnId {
-        let column_id = self.meta.add_column::<T>();
+    pub(crate) fn add_column_aligned<T:'static>(&mut self, align: usize) -> ColumnId {
+        let column_id = self.meta.add_column_aligned::<T>(align);
 
         if column_id.index() < self.columns.len() {
             return column_id;
@@ -112,9 +226,16 @@ impl EntityStore {
     //
 
     pub fn get<T:'static>(&self, id: EntityId) -> Option<&T> {
+        self.check_world(id);
+
         match self.meta().get_column::<T>() {
             Some(column_id) => {
                 let entity = self.entities.get(id.index())?;
+
+                if entity.id != id || ! entity.is_alloc() {
+                    return None;
+                }
+
                 let table = &self.tables[entity.table.index()];
                 let row = table.get(entity.row)?;
 
@@ -129,8 +250,44 @@ impl EntityStore {
     }
 
     pub fn get_mut<T:'static>(&mut self, id: EntityId) -> Option<&mut T> {
+        unsafe { self.get_mut_unchecked(id) }
+    }
+
+    ///
+    /// Mutable T components of distinct entities, None if any entity
+    /// lacks a T. Panics if an id is repeated.
+    ///
+    pub fn get_many_mut<T:'static, const N: usize>(
+        &mut self, 
+        ids: [EntityId; N]
+    ) -> Option<[&mut T; N]> {
+        for (i, id) in ids.iter().enumerate() {
+            assert!(! ids[..i].contains(id), "get_many_mut with duplicate entity {:?}", id);
+        }
+
+        let store: &Self = self;
+
+        // ids are distinct, so the references are disjoint
+        let values = ids.map(|id| unsafe { store.get_mut_unchecked::<T>(id) });
+
+        if values.iter().any(|value| value.is_none()) {
+            return None;
+        }
+
+        Some(values.map(|value| value.unwrap()))
+    }
+
+    #[allow(clippy::mut_from_ref)]
+    unsafe fn get_mut_unchecked<T:'static>(&self, id: EntityId) -> Option<&mut T> {
+        self.check_world(id);
+
         let column_id = self.meta().get_column::<T>()?;
         let entity = self.entities.get(id.index())?;
+
+        if entity.id != id || ! entity.is_alloc() {
+            return None;
+        }
+
         let table = &self.tables[entity.table.index()];
         let row = table.get(entity.row)?;
 
@@ -145,6 +302,48 @@ impl EntityStore {
         self.free_list.lock().unwrap().alloc()
     }
 
+    pub(crate) fn allocator(&self) -> EntityAllocator {
+        EntityAllocator(self.free_list.clone())
+    }
+
+    ///
+    /// Starts recording reserved ids, for free_unspawned.
+    ///
+    pub(crate) fn log_reserved(&mut self) {
+        let mut alloc = self.free_list.lock().unwrap();
+
+        alloc.reserved.clear();
+        alloc.is_logging = true;
+    }
+
+    ///
+    /// Stops recording reserved ids, and frees the ones that were never
+    /// spawned if is_free, e.g. ids of dropped spawn commands.
+    ///
+    pub(crate) fn end_reserved(&mut self, is_free: bool) {
+        let mut alloc = self.free_list.lock().unwrap();
+        alloc.is_logging = false;
+
+        let mut reserved = mem::take(&mut alloc.reserved);
+
+        if is_free {
+            for id in reserved.drain(..) {
+                let is_unspawned = match self.entities.get(id.index()) {
+                    // a spawned id is alive, or freed by its despawn
+                    Some(entity) => entity.id != id && entity.id != id.free(),
+                    None => true,
+                };
+
+                if is_unspawned {
+                    alloc.free(id.free());
+                }
+            }
+        }
+
+        reserved.clear();
+        alloc.reserved = reserved;
+    }
+
     pub fn spawn_empty(&mut self) -> EntityId {
         let id = self.alloc_entity_id();
 
@@ -158,26 +357,60 @@ impl EntityStore {
 
         let id = self.alloc_entity_id();
 
-        self.spawn_with_plan(plan, id, value)
+        self.spawn_with_plan(&plan, id, value)
     }
 
     pub(crate) fn spawn_id<T:Bundle>(&mut self, id: EntityId, value: T) -> EntityId {
         let plan = self.insert_plan::<T>();
 
-        self.spawn_with_plan(plan, id, value)
+        self.spawn_with_plan(&plan, id, value)
     }
 
-    pub(crate) fn insert_plan<T:Bundle>(&mut self) -> InsertPlan {
+    pub(crate) fn spawn_batch_id<T:Bundle>(&mut self, values: Vec<(EntityId, T)>) {
+        let plan = self.insert_plan::<T>();
+
+        for (id, value) in values {
+            self.spawn_with_plan(&plan, id, value);
+        }
+    }
+
+    ///
+    /// Pre-allocates table and column storage for `capacity` entities of
+    /// bundle T. Despawned rows go on the free lists and are recycled by
+    /// later spawns, so churn within the pool doesn't reallocate.
+    ///
+    pub fn pool<T:Bundle>(&mut self, capacity: usize) {
+        let plan = self.insert_plan::<T>();
+
+        for column_id in plan.columns() {
+            self.columns[column_id.index()].reserve_total(capacity);
+        }
+
+        self.tables[plan.table_id().index()].reserve_total(capacity);
+
+        self.entities.reserve(capacity.saturating_sub(self.entities.len()));
+    }
+
+    ///
+    /// Plan for spawning bundle T, built once per bundle type.
+    ///
+    pub(crate) fn insert_plan<T:Bundle>(&mut self) -> Arc<InsertPlan> {
+        if let Some(plan) = self.meta.spawn_plan(TypeId::of::<T>()) {
+            return plan;
+        }
+
         let mut builder = InsertBuilder::new(self);
 
         T::build(&mut builder);
 
-        builder.build()
+        let plan = builder.build();
+
+        self.meta.insert_spawn_plan(TypeId::of::<T>(), plan)
     }
 
     pub(crate) fn spawn_with_plan<T:Bundle>(
         &mut self, 
-        plan: InsertPlan, 
+        plan: &InsertPlan, 
         id: EntityId,
         value: T
     ) -> EntityId {
@@ -190,14 +423,29 @@ impl EntityStore {
         cursor.complete()
     }
 
+    ///
+    /// Adds the bundle to the entity, moving it along the cached table
+    /// edge for its table and the bundle type.
+    ///
     pub(crate) fn extend<T:Bundle>(&mut self, id: EntityId, value: T) -> EntityId {
-        let mut builder = InsertBuilder::new(self);
+        self.check_world(id);
 
-        builder.add_entity(id);
+        let table_id = self.entities[id.index()].table;
 
-        T::build(&mut builder);
+        let plan = match self.meta.add_edge(table_id, TypeId::of::<T>()) {
+            Some(plan) => plan,
+            None => {
+                let mut builder = InsertBuilder::new(self);
 
-        let plan = builder.build();
+                builder.add_entity(id);
+
+                T::build(&mut builder);
+
+                let plan = builder.build();
+
+                self.meta.insert_add_edge(table_id, TypeId::of::<T>(), plan)
+            }
+        };
 
         let mut cursor = plan.cursor(self, id);
 
@@ -208,6 +456,64 @@ impl EntityStore {
         cursor.complete()
     }
 
+    ///
+    /// Removes and drops the bundle's components the entity has, moving it
+    /// to the table of its remaining components.
+    ///
+    pub(crate) fn remove_bundle<T:Bundle>(&mut self, id: EntityId) -> bool {
+        self.check_world(id);
+
+        let entity = &self.entities[id.index()];
+        assert_eq!(entity.id, id);
+
+        let table_id = entity.table;
+        let row_id = entity.row;
+
+        let edge = match self.meta.remove_edge(table_id, TypeId::of::<T>()) {
+            Some(edge) => edge,
+            None => {
+                let plan = self.insert_plan::<T>();
+
+                let (keep, keep_cols): (Vec<bool>, Vec<ColumnId>) = self.meta.table(table_id)
+                    .columns().iter()
+                    .map(|col_id| (! plan.columns().contains(col_id), *col_id))
+                    .unzip();
+
+                let keep_cols = keep_cols.into_iter()
+                    .zip(&keep)
+                    .filter(|(_, keep)| **keep)
+                    .map(|(col_id, _)| col_id)
+                    .collect();
+
+                let dest_id = self.add_table(keep_cols);
+
+                self.meta.insert_remove_edge(table_id, TypeId::of::<T>(), RemoveEdge::new(dest_id, keep))
+            }
+        };
+
+        if edge.is_noop() {
+            return false;
+        }
+
+        let table = &self.tables[table_id.index()];
+        let table_row = table.get(row_id).unwrap();
+
+        let mut keep_rows = Vec::new();
+
+        for ((col_id, col_row), keep) in 
+            table.meta().columns().iter().zip(table_row.columns()).zip(edge.keep()) {
+            if *keep {
+                keep_rows.push(*col_row);
+            } else {
+                self.columns[col_id.index()].remove(*col_row);
+            }
+        }
+
+        self.insert(id, edge.table_id(), keep_rows);
+
+        true
+    }
+
     pub(crate) fn add_table(&mut self, cols: Vec<ColumnId>) -> TableId {
         let table_id = self.meta.add_table(cols);
 
@@ -228,6 +534,8 @@ impl EntityStore {
         table_id: TableId, 
         columns: Vec<RowId>
     ) -> EntityId {
+        self.check_world(id);
+
         if id.index() < self.entities.len() && self.entities[id.index()].is_alloc()  {
             self.insert(id, table_id, columns)
         } else {
@@ -253,6 +561,7 @@ impl EntityStore {
         };
 
         self.entities[id.index()] = entity;
+        self.record_change(id);
         
         id // TODO: next()
     }
@@ -269,6 +578,8 @@ impl EntityStore {
     }
 
     pub(crate) fn despawn(&mut self, id: EntityId) {
+        self.check_world(id);
+
         self.remove_table_row_and_columns(id);
 
         let entity = &mut self.entities[id.index()];
@@ -280,6 +591,19 @@ impl EntityStore {
         entity.row = RowId::UNSET;
 
         self.free_list.lock().unwrap().free(entity.id);
+
+        self.record_change(id);
+    }
+
+    pub(crate) fn despawn_all(&mut self) {
+        let ids: Vec<EntityId> = self.entities.iter()
+            .filter(|entity| entity.is_alloc())
+            .map(|entity| entity.id)
+            .collect();
+
+        for id in ids {
+            self.despawn(id);
+        }
     }
 
     fn remove_table_row_and_columns(&mut self, id: EntityId) {
@@ -343,44 +667,236 @@ impl EntityStore {
         let id = entity.id;
         assert!(id.is_alloc());
 
+        self.record_change(id);
+
         if id.index() < self.entities.len() {
             // TODO:
             // assert_eq!(self.entities[id.index()].id.alloc(), id);
             self.entities[id.index()] = entity;
         } else {
             while self.entities.len() < id.index() {
-                self.entities.push(Entity::empty(self.entities.len()));
+                self.entities.push(Entity::empty(self.entities.len(), self.world));
             }
 
             self.entities.push(entity);
         }
     }
 
+    fn record_change(&mut self, id: EntityId) {
+        if ! self.is_change_log {
+            return;
+        }
+
+        if self.changes.len() >= MAX_CHANGES {
+            self.change_base += self.changes.len();
+            self.changes.clear();
+        }
+
+        self.changes.push(id);
+    }
+
+    ///
+    /// Starts recording table changes for changes_since.
+    ///
+    pub(crate) fn enable_change_log(&mut self) {
+        self.is_change_log = true;
+    }
+
+    ///
+    /// Sequence number of the next table change, for changes_since.
+    ///
+    pub(crate) fn change_seq(&self) -> usize {
+        self.change_base + self.changes.len()
+    }
+
+    ///
+    /// Entities spawned, despawned or moved to a new table since seq, or
+    /// None if the log no longer reaches back to seq.
+    ///
+    pub(crate) fn changes_since(&self, seq: usize) -> Option<&[EntityId]> {
+        if seq < self.change_base {
+            None
+        } else {
+            self.changes.get(seq - self.change_base..)
+        }
+    }
+
     //
     // View
     //
 
     pub fn iter_view<'a,T:View>(&mut self) -> ViewIterator<'_,T> {
-        let plan = self.view_plan::<T>();
+        let plan = match self.view_plan::<T>() {
+            Ok(plan) => plan,
+            Err(err) => panic!("{}", err),
+        };
         
-        unsafe { self.iter_view_with_plan(plan) }
+        unsafe { self.iter_view_with_plan(Cow::Owned(plan)) }
+    }
+
+    pub(crate) fn view_plan<T:View>(&mut self) -> Result<ViewPlan> {
+        self.view_plan_filtered::<T, ()>()
     }
 
-    pub(crate) fn view_plan<T:View>(&mut self) -> ViewPlan {
+    pub(crate) fn view_plan_filtered<T:View, F:ViewFilter>(&mut self) -> Result<ViewPlan> {
         let mut builder = ViewBuilder::new(self);
 
         T::build(&mut builder);
 
+        // filter columns follow the view's, so item derefs are unchanged
+        F::build(&mut builder);
+
         builder.build()
     }
 
-    pub(crate) unsafe fn iter_view_with_plan<T:View>(
-        &self, 
-        plan: ViewPlan
-    ) -> ViewIterator<'_,T> {
+    pub(crate) fn lens_plan<T:View>(&self, base: &ViewPlan) -> Result<ViewPlan> {
+        let mut builder = ViewBuilder::new_lens(self);
+
+        T::build(&mut builder);
+
+        builder.build_lens(base)
+    }
+
+    pub(crate) unsafe fn iter_view_with_plan<'a, T:View>(
+        &'a self, 
+        plan: Cow<'a, ViewPlan>
+    ) -> ViewIterator<'a,T> {
         ViewIterator::new(self, plan)
     }
 
+    ///
+    /// The view's item for one entity, None if the entity is dead or
+    /// doesn't match the view.
+    ///
+    pub fn view_one<T:View>(&mut self, id: EntityId) -> Option<T::Item<'_>> {
+        let plan = match self.view_plan::<T>() {
+            Ok(plan) => plan,
+            Err(err) => panic!("{}", err),
+        };
+
+        unsafe { self.view_one_with_plan::<T>(&plan, id) }
+    }
+
+    pub(crate) unsafe fn view_one_with_plan<'a, T:View>(
+        &'a self, 
+        plan: &ViewPlan,
+        id: EntityId
+    ) -> Option<T::Item<'a>> {
+        self.check_world(id);
+
+        let entity = self.entities.get(id.index())?;
+
+        if entity.id != id || ! entity.is_alloc() {
+            return None;
+        }
+
+        let table = self.meta().table(entity.table);
+        let row = self.tables[entity.table.index()].get(entity.row)?;
+
+        let view_table = self.meta().view(plan.view()).view_tables().iter()
+            .map(|view_table_id| self.meta().view_table(*view_table_id))
+            .find(|view_table| view_table.table_id() == entity.table)?;
+
+        if ! plan.is_match(self, table, row) {
+            return None;
+        }
+
+        let mut cursor = plan.new_cursor(self, table, view_table, row);
+
+        Some(T::deref(&mut cursor))
+    }
+
+    //
+    // Column export/import
+    //
+
+    ///
+    /// Copies every T component into a flat vector in view order, so bulk
+    /// data can be handed to analysis code without per-entity iteration.
+    ///
+    pub fn export_column<T:Component + Copy>(&mut self) -> Vec<T> {
+        self.iter_view::<&T>().copied().collect()
+    }
+
+    ///
+    /// Overwrites every T component from values, in the same order as
+    /// export_column. Fails without changes if the number of values doesn't
+    /// match the number of T components.
+    ///
+    pub fn import_column<T:Component>(
+        &mut self,
+        values: impl IntoIterator<Item=T>
+    ) -> Result<usize> {
+        let values: Vec<T> = values.into_iter().collect();
+        let count = self.iter_view::<&T>().count();
+
+        if values.len() != count {
+            return Err(format!(
+                "import_column<{}> expected {} values but received {}",
+                type_name::<T>(), count, values.len()
+            ).into());
+        }
+
+        for (item, value) in self.iter_view::<&mut T>().zip(values) {
+            *item = value;
+        }
+
+        Ok(count)
+    }
+
+    ///
+    /// True if the live entity's table belongs to the view.
+    ///
+    pub(crate) fn view_contains(&self, plan: &ViewPlan, id: EntityId) -> bool {
+        self.view_table_of(plan, id).is_some()
+    }
+
+    pub(crate) fn view_entities(&self, plan: &ViewPlan) -> Vec<EntityId> {
+        let view = self.meta().view(plan.view());
+
+        view.view_tables().iter()
+            .map(|id| self.meta().view_table(*id).table_id())
+            .flat_map(|id| self.tables[id.index()].iter_ids())
+            .filter(|id| ! plan.is_filtered() || self.view_table_of(plan, *id).is_some())
+            .collect()
+    }
+
+    pub(crate) unsafe fn view_get_with_plan<T:View>(
+        &self,
+        plan: &ViewPlan,
+        id: EntityId
+    ) -> Option<T::Item<'_>> {
+        let view_table = self.view_table_of(plan, id)?;
+        let entity = &self.entities[id.index()];
+        let table = self.meta().table(entity.table);
+        let row = self.tables[entity.table.index()].get(entity.row)?;
+
+        let mut cursor = plan.new_cursor(self, table, view_table, row);
+
+        Some(T::deref(&mut cursor))
+    }
+
+    fn view_table_of(&self, plan: &ViewPlan, id: EntityId) -> Option<&ViewTableType> {
+        let entity = self.entities.get(id.index())?;
+
+        if entity.id != id || ! entity.is_alloc() {
+            return None;
+        }
+
+        let view_table = self.meta().get_view_table(plan.view(), entity.table)?;
+
+        if plan.is_filtered() {
+            let table = self.meta().table(entity.table);
+            let row = self.tables[entity.table.index()].get(entity.row)?;
+
+            if ! plan.is_match(self, table, row) {
+                return None;
+            }
+        }
+
+        Some(view_table)
+    }
+
     pub(crate) fn view(&self, view_id: ViewId) -> &ViewType {
         self.meta.view(view_id)
     }
@@ -440,8 +956,33 @@ impl EntityStore {
         }
     }
 
+    ///
+    /// Type names of the components attached to an entity.
+    ///
+    pub fn component_names(&self, id: EntityId) -> impl Iterator<Item=&str> {
+        self.check_world(id);
+
+        let columns = self.entities.get(id.index())
+            .and_then(|entity| self.get_table(entity.table))
+            .map_or(&[][..], |table| table.meta().columns().as_slice());
+
+        columns.iter().map(|col| self.meta().column(*col).name().as_ref())
+    }
+
+    ///
+    /// True if the id refers to a live entity of the current generation.
+    ///
+    pub fn contains_entity(&self, id: EntityId) -> bool {
+        self.check_world(id);
+
+        match self.entities.get(id.index()) {
+            Some(entity) => entity.id == id && entity.is_alloc(),
+            None => false,
+        }
+    }
+
     pub(crate) fn get_entity(&self, id: EntityId) -> Option<EntityId> {
-        if id.index() < self.entities.len() {
+        if self.contains_entity(id) {
             Some(id)
         } else {
             None
@@ -450,22 +991,31 @@ impl EntityStore {
 }
 
 impl EntityAlloc {
-    fn new() -> Self {
+    fn new(world: WorldId) -> Self {
         Self {
+            world,
             capacity: 0,
             free_list: Default::default(),
+            reserved: Default::default(),
+            is_logging: false,
         }
     }
 
     pub fn alloc(&mut self) -> EntityId {
-        if let Some(entity) = self.free_list.pop() {
+        let id = if let Some(entity) = self.free_list.pop() {
             entity.alloc()
         } else {
             let index = self.capacity;
             self.capacity = index + 1;
 
-            EntityId::new(index)
+            EntityId::new(index, self.world)
+        };
+
+        if self.is_logging {
+            self.reserved.push(id);
         }
+
+        id
     }
 
     fn free(&mut self, id: EntityId) {
@@ -475,19 +1025,48 @@ impl EntityAlloc {
     } 
 }
 
+impl HeapSize for EntityId {}
+
 impl EntityId {
     const FREE_MASK : u32 = 0x8000_0000;
 
-    pub(crate) fn new(index: usize) -> Self {
-        Self(index as u32, 0)
+    pub(crate) fn new(index: usize, world: WorldId) -> Self {
+        Self::from_parts(index as u32, 0, world)
+    }
+
+    #[cfg(debug_assertions)]
+    fn from_parts(index: u32, gen: u32, world: WorldId) -> Self {
+        Self(index, gen, world)
+    }
+
+    #[cfg(not(debug_assertions))]
+    fn from_parts(index: u32, gen: u32, _world: WorldId) -> Self {
+        Self(index, gen)
+    }
+
+    ///
+    /// The owning world, available only in debug builds.
+    ///
+    #[cfg(debug_assertions)]
+    pub fn world(&self) -> Option<WorldId> {
+        Some(self.2)
+    }
+
+    #[cfg(not(debug_assertions))]
+    pub fn world(&self) -> Option<WorldId> {
+        None
     }
 
     pub(crate) fn index(&self) -> usize {
         self.0 as usize
     }
 
-    pub(crate) fn _gen(&self) -> u32 {
-        self.1
+    ///
+    /// Generation of the index slot, incremented when the entity is
+    /// despawned, so a reused slot gets a new id.
+    ///
+    pub fn generation(&self) -> u32 {
+        self.1 & !Self::FREE_MASK
     }
 
     pub(crate) fn is_alloc(&self) -> bool {
@@ -497,13 +1076,13 @@ impl EntityId {
     pub(crate) fn free(&self) -> EntityId {
         assert!(self.is_alloc());
 
-        EntityId(self.0, (self.1 + 1) | Self::FREE_MASK)
+        EntityId { 1: (self.1 + 1) | Self::FREE_MASK, ..*self }
     }
 
     pub(crate) fn alloc(&self) -> EntityId {
         assert!(! self.is_alloc());
 
-        EntityId(self.0, self.1 & !Self::FREE_MASK)
+        EntityId { 1: self.1 & !Self::FREE_MASK, ..*self }
     }
 
     pub(crate) fn _is_next_alloc(&self, id: EntityId) -> bool {
@@ -512,6 +1091,14 @@ impl EntityId {
     }
 }
 
+impl WorldId {
+    fn next() -> Self {
+        static NEXT: AtomicU32 = AtomicU32::new(0);
+
+        WorldId(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
 impl From<ColumnId> for ComponentId {
     fn from(id: ColumnId) -> Self {
         ComponentId(id.index())
@@ -520,9 +1107,11 @@ impl From<ColumnId> for ComponentId {
 
 #[cfg(test)]
 mod tests {
+    use std::{any::TypeId, sync::Arc};
+
     use crate::entity::{bundle::InsertCursor, Component};
 
-    use super::{EntityStore, InsertBuilder, Bundle};
+    use super::{EntityStore, EntityId, InsertBuilder, Bundle};
 
     #[test]
     fn spawn() {
@@ -656,6 +1245,58 @@ mod tests {
         assert_eq!(values.join(","), "(TestA(1), TestB(10))");
     }
 
+    #[test]
+    fn spawn_plan_cache() {
+        let mut store = EntityStore::new();
+
+        let id_a = store.spawn((TestA(1), TestB(2)));
+        let plan = store.meta().spawn_plan(TypeId::of::<(TestA, TestB)>()).unwrap();
+
+        let id_b = store.spawn((TestA(3), TestB(4)));
+        assert!(Arc::ptr_eq(&plan, &store.insert_plan::<(TestA, TestB)>()));
+        assert_eq!(store.entities[id_a.index()].table, plan.table_id());
+        assert_eq!(store.entities[id_b.index()].table, plan.table_id());
+
+        assert!(store.meta().spawn_plan(TypeId::of::<TestA>()).is_none());
+        store.spawn(TestA(5));
+        assert!(store.meta().spawn_plan(TypeId::of::<TestA>()).is_some());
+
+        assert_eq!(store.iter_view::<&TestA>().count(), 3);
+    }
+
+    #[test]
+    fn table_edges() {
+        let mut store = EntityStore::new();
+
+        let ids: Vec<EntityId> = (0..4).map(|i| store.spawn(TestA(i))).collect();
+        let table_a = store.entities[ids[0].index()].table;
+
+        for id in &ids {
+            store.extend(*id, TestB(id.index() as u16 + 10));
+        }
+
+        let edge = store.meta().add_edge(table_a, TypeId::of::<TestB>()).unwrap();
+        let table_ab = edge.table_id();
+        assert!(ids.iter().all(|id| store.entities[id.index()].table == table_ab));
+
+        assert!(store.remove_bundle::<TestA>(ids[1]));
+        assert!(store.remove_bundle::<TestA>(ids[2]));
+        assert!(! store.remove_bundle::<TestA>(ids[2]));
+
+        let edge = store.meta().remove_edge(table_ab, TypeId::of::<TestA>()).unwrap();
+        assert_eq!(edge.keep(), &vec![false, true]);
+
+        let values: Vec<String> = store.iter_view::<(&TestA, &TestB)>()
+            .map(|v| format!("{:?}", v))
+            .collect();
+        assert_eq!(values.join(","), "(TestA(0), TestB(10)),(TestA(3), TestB(13))");
+
+        let values: Vec<String> = store.iter_view::<&TestB>()
+            .map(|v| format!("{:?}", v))
+            .collect();
+        assert_eq!(values.len(), 4);
+    }
+
     #[test]
     fn despawn() {
         let mut store = EntityStore::new();
@@ -681,6 +1322,47 @@ mod tests {
         assert_eq!(values.join(","), "TestA(4),TestA(2),TestA(3),TestA(5)");
     }
 
+    #[test]
+    fn pool() {
+        let mut store = EntityStore::new();
+
+        store.pool::<(TestA, TestB)>(16);
+
+        let plan = store.insert_plan::<(TestA, TestB)>();
+        let column_id = plan.columns()[0];
+        assert_eq!(store.column_mut(column_id)._capacity(), 16);
+
+        for i in 0..100 {
+            let ids: Vec<EntityId> = (0..16)
+                .map(|j| store.spawn((TestA(i * 16 + j), TestB(j as u16))))
+                .collect();
+
+            for id in ids {
+                store.despawn(id);
+            }
+        }
+
+        assert_eq!(store.column_mut(column_id)._capacity(), 16);
+        assert_eq!(store.column_mut(column_id).len(), 16);
+        assert_eq!(store.entity_count(), 0);
+    }
+
+    #[test]
+    fn change_log() {
+        let mut store = EntityStore::new();
+
+        let id_0 = store.spawn(TestA(1));
+        assert_eq!(store.change_seq(), 0);
+
+        store.enable_change_log();
+
+        let id_1 = store.spawn(TestA(2));
+        store.despawn(id_0);
+
+        assert_eq!(store.changes_since(0), Some(&[id_1, id_0][..]));
+        assert_eq!(store.change_seq(), 2);
+    }
+
     #[derive(Debug, PartialEq)]
     struct TestA(u32);
 
@@ -697,7 +1379,7 @@ mod tests {
     
     impl Bundle for TestC {
         fn build(builder: &mut InsertBuilder) {
-            builder.add_column::<TestC>()
+            builder.add_column_aligned::<TestC>(0)
         }
 
         unsafe fn insert(cursor: &mut InsertCursor, value: Self) {
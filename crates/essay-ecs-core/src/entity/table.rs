@@ -36,6 +36,14 @@ impl Table {
         &self.meta
     }
 
+    pub(crate) fn len(&self) -> usize {
+        self.rows.len() - self.free_list.len()
+    }
+
+    pub(crate) fn reserve_total(&mut self, capacity: usize) {
+        self.rows.reserve(capacity.saturating_sub(self.rows.len()));
+    }
+
     pub(crate) fn position(&self, column_id: ColumnId) -> Option<usize> {
         self.meta.position(column_id)
     }
@@ -54,6 +62,15 @@ impl Table {
         self.rows.get(row_index)
     }
 
+    ///
+    /// Entities of the table's allocated rows.
+    ///
+    pub(crate) fn iter_ids(&self) -> impl Iterator<Item=EntityId> + '_ {
+        self.rows.iter()
+            .filter(|row| row.is_alloc())
+            .map(|row| row.entity_id())
+    }
+
     pub(crate) fn push(
         &mut self, 
         entity_id: EntityId, 
@@ -1,13 +1,22 @@
 
-use std::{marker::PhantomData, collections::HashSet};
+use std::{any::type_name, borrow::Cow, cmp::Reverse, fmt, marker::PhantomData, collections::HashSet, sync::Arc};
+
+use crate::error::Result;
 
 use super::{
     {EntityStore, ViewId}, 
     meta::{TableMeta, ViewTableType, ColumnId}, 
-    store::Component, table::TableRow, EntityId,
+    store::Component, table::TableRow, column::RowId, EntityId,
 };
 
-pub trait View : Send + Sync {
+use crate::store::{EntityRef, StableId};
+
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is not a query view",
+    label = "not a View",
+    note = "views are &T or &mut T for a Component T, EntityId, EntityRef, Mapped<M> for a MappedView M, (), or tuples of views",
+)]
+pub trait View {
     type Item<'a>;
 
     fn build(builder: &mut ViewBuilder);
@@ -25,11 +34,23 @@ pub struct ViewCursor<'a, 't> {
 }
 
 pub struct ViewBuilder<'a> {
-    store: &'a mut EntityStore, 
+    store: BuilderStore<'a>, 
     columns: Vec<ColumnId>,
 
     components: HashSet<ColumnId>,
     mut_components: HashSet<ColumnId>,
+    is_exclusive: bool,
+
+    filters: Vec<RowFilter>,
+
+    missing: Vec<&'static str>,
+}
+
+// lens builders only look up existing columns, because they run while
+// the store is shared with other systems
+enum BuilderStore<'a> {
+    Mut(&'a mut EntityStore),
+    Ref(&'a EntityStore),
 }
 
 #[derive(Clone)]
@@ -39,6 +60,31 @@ pub struct ViewPlan {
 
     components: HashSet<ColumnId>,
     mut_components: HashSet<ColumnId>,
+    is_exclusive: bool,
+
+    filters: Vec<RowFilter>,
+}
+
+type RowTest = Arc<dyn Fn(&EntityStore, RowId) -> bool + Send + Sync>;
+
+// per-row test on a component value, e.g. an enum variant
+#[derive(Clone)]
+pub(crate) struct RowFilter {
+    column: ColumnId,
+    name: &'static str,
+    test: RowTest,
+}
+
+impl RowFilter {
+    fn new<T:'static>(column: ColumnId, name: &'static str, test: fn(&T) -> bool) -> Self {
+        Self {
+            column,
+            name,
+            test: Arc::new(move |store, row_id| {
+                unsafe { store.get_by_id::<T>(column, row_id) }.is_some_and(test)
+            }),
+        }
+    }
 }
 
 impl ViewPlan {
@@ -70,6 +116,127 @@ impl ViewPlan {
     pub(crate) fn mut_components(&self) -> &HashSet<ColumnId> {
         &self.mut_components
     }
+
+    ///
+    /// True if the view reads whole entities, so its system must run
+    /// exclusively.
+    ///
+    pub(crate) fn is_exclusive(&self) -> bool {
+        self.is_exclusive
+    }
+
+    pub(crate) fn is_filtered(&self) -> bool {
+        ! self.filters.is_empty()
+    }
+
+    ///
+    /// True if the row passes the plan's filters.
+    ///
+    pub(crate) fn is_match(&self, store: &EntityStore, table: &TableMeta, row: &TableRow) -> bool {
+        self.filters.iter().all(|filter| {
+            match table.position(filter.column) {
+                Some(index) => (filter.test)(store, row.column_row(index)),
+                None => false,
+            }
+        })
+    }
+}
+
+///
+/// Tables matched by a view plan, their row counts, and the plan's
+/// component access, for debugging queries that match nothing.
+///
+#[derive(Clone, Debug)]
+pub struct ViewExplain {
+    pub columns: Vec<String>,
+    pub reads: Vec<String>,
+    pub writes: Vec<String>,
+    pub is_exclusive: bool,
+    pub filters: Vec<String>,
+    pub tables: Vec<TableExplain>,
+}
+
+#[derive(Clone, Debug)]
+pub struct TableExplain {
+    pub table: usize,
+    pub columns: Vec<String>,
+    pub rows: usize,
+}
+
+impl ViewExplain {
+    pub(crate) fn new(store: &EntityStore, plan: &ViewPlan) -> Self {
+        let meta = store.meta();
+        let name = |id: &ColumnId| meta.column(*id).name().to_string();
+
+        let view = meta.view(plan.view());
+
+        let mut reads: Vec<String> = plan.components().difference(plan.mut_components())
+            .map(name)
+            .collect();
+        reads.sort();
+
+        let mut writes: Vec<String> = plan.mut_components().iter()
+            .map(name)
+            .collect();
+        writes.sort();
+
+        let tables = view.view_tables().iter()
+            .map(|id| meta.view_table(*id).table_id())
+            .map(|table_id| TableExplain {
+                table: table_id.index(),
+                columns: meta.table(table_id).columns().iter().map(name).collect(),
+                rows: store.table_len(table_id),
+            })
+            .collect();
+
+        Self {
+            columns: view.columns().iter().map(name).collect(),
+            reads,
+            writes,
+            is_exclusive: plan.is_exclusive(),
+            filters: plan.filters.iter().map(|f| f.name.to_string()).collect(),
+            tables,
+        }
+    }
+
+    ///
+    /// Total rows in the matched tables.
+    ///
+    pub fn rows(&self) -> usize {
+        self.tables.iter().map(|t| t.rows).sum()
+    }
+}
+
+impl fmt::Display for ViewExplain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "view [{}] reads [{}] writes [{}]",
+            self.columns.join(", "),
+            self.reads.join(", "),
+            self.writes.join(", "),
+        )?;
+
+        if self.is_exclusive {
+            write!(f, " exclusive")?;
+        }
+
+        if ! self.filters.is_empty() {
+            write!(f, " filters [{}]", self.filters.join(", "))?;
+        }
+
+        if self.tables.is_empty() {
+            write!(f, "\n  no matching tables")?;
+        }
+
+        for table in &self.tables {
+            write!(f, "\n  table {} [{}]: {} rows",
+                table.table,
+                table.columns.join(", "),
+                table.rows
+            )?;
+        }
+
+        Ok(())
+    }
 }
 
 impl<'a, 't> ViewCursor<'a, 't> {
@@ -96,49 +263,182 @@ impl<'a, 't> ViewCursor<'a, 't> {
     fn entity_id(&self) -> EntityId {
         self.row.entity_id()
     }
+
+    fn entity_ref(&self) -> EntityRef<'t> {
+        EntityRef::new(self.row.entity_id(), self.store)
+    }
 }
 
 impl<'a, 't> ViewBuilder<'a> {
     pub(crate) fn new(store: &'a mut EntityStore) -> Self {
         Self {
-            store,
+            store: BuilderStore::Mut(store),
+            columns: Vec::new(),
+            components: Default::default(),
+            mut_components: Default::default(),
+            is_exclusive: false,
+            filters: Vec::new(),
+            missing: Vec::new(),
+        }
+    }
+
+    pub(crate) fn new_lens(store: &'a EntityStore) -> Self {
+        Self {
+            store: BuilderStore::Ref(store),
             columns: Vec::new(),
             components: Default::default(),
             mut_components: Default::default(),
+            is_exclusive: false,
+            filters: Vec::new(),
+            missing: Vec::new(),
         }
     }
 
     pub fn add_ref<T:'static>(&mut self) {
-        let col_id = self.store.add_column::<T>();
+        self.add_ref_aligned::<T>(0);
+    }
 
-        self.columns.push(col_id);
+    pub(crate) fn add_ref_aligned<T:'static>(&mut self, align: usize) {
+        if let Some(col_id) = self.column::<T>(align) {
+            self.columns.push(col_id);
 
-        self.components.insert(col_id);
+            self.components.insert(col_id);
+        }
     }
 
     pub fn add_mut<T:'static>(&mut self) {
-        let col_id = self.store.add_column::<T>();
+        self.add_mut_aligned::<T>(0);
+    }
 
-        self.columns.push(col_id);
+    pub(crate) fn add_mut_aligned<T:'static>(&mut self, align: usize) {
+        if let Some(col_id) = self.column::<T>(align) {
+            self.columns.push(col_id);
 
-        self.mut_components.insert(col_id);
+            self.mut_components.insert(col_id);
+        }
     }
 
-    pub(crate) fn build(self) -> ViewPlan {
-        let view_id = self.store.add_view(&self.columns);
-        let view = self.store.view(view_id);
+    ///
+    /// Matches only entities whose T passes test. T is read, but isn't
+    /// part of the view's items.
+    ///
+    pub fn add_filter<T:Component>(&mut self, name: &'static str, test: fn(&T) -> bool) {
+        if let Some(col_id) = self.column::<T>(T::ALIGN) {
+            if ! self.columns.contains(&col_id) {
+                self.columns.push(col_id);
+                self.components.insert(col_id);
+            }
+
+            self.filters.push(RowFilter::new(col_id, name, test));
+        }
+    }
+
+    ///
+    /// Adds access to every component of the entity.
+    ///
+    pub fn add_entity(&mut self) {
+        self.is_exclusive = true;
+    }
+
+    fn column<T:'static>(&mut self, align: usize) -> Option<ColumnId> {
+        match &mut self.store {
+            BuilderStore::Mut(store) => Some(store.add_column_aligned::<T>(align)),
+            BuilderStore::Ref(store) => {
+                let col_id = store.meta().get_column::<T>();
+
+                if col_id.is_none() {
+                    self.missing.push(type_name::<T>());
+                }
+
+                col_id
+            }
+        }
+    }
+
+    pub(crate) fn build(self) -> Result<ViewPlan> {
+        let store = match self.store {
+            BuilderStore::Mut(store) => store,
+            BuilderStore::Ref(_) => panic!("lens builder can't build a new view"),
+        };
+
+        // entity access would alias the view's mutable columns
+        if self.is_exclusive && ! self.mut_components.is_empty() {
+            return Err("EntityRef can't be in a view with mutable components".into());
+        }
+
+        // the store's stable id map would miss in-place changes
+        if store.meta().get_column::<StableId>().is_some_and(|id| self.mut_components.contains(&id)) {
+            return Err("StableId is read-only, insert a new StableId to change it".into());
+        }
+
+        let view_id = store.add_view(&self.columns);
+        let view = store.view(view_id);
 
         let cols = self.columns.iter()
             .map(|col_id| view.column_position(*col_id).unwrap())
             .collect();
 
-        ViewPlan {
+        Ok(ViewPlan {
             view: view_id,
             cols: cols,
 
             components: self.components,
             mut_components: self.mut_components,
+            is_exclusive: self.is_exclusive,
+
+            filters: self.filters,
+        })
+    }
+
+    ///
+    /// Builds a plan that reuses base's view, so it iterates over the same
+    /// tables. Every lens column must be in base, and mutable columns must
+    /// be mutable in base.
+    ///
+    pub(crate) fn build_lens(self, base: &ViewPlan) -> Result<ViewPlan> {
+        if let Some(name) = self.missing.first() {
+            return Err(format!("lens component {} is not in the query", name).into());
+        }
+
+        let store = match self.store {
+            BuilderStore::Mut(store) => &*store,
+            BuilderStore::Ref(store) => store,
+        };
+
+        let view = store.view(base.view);
+
+        let mut cols = Vec::new();
+
+        for col_id in &self.columns {
+            match view.column_position(*col_id) {
+                Some(position) => cols.push(position),
+                None => {
+                    return Err(format!("lens column {:?} is not in the query", col_id).into());
+                }
+            }
         }
+
+        if self.is_exclusive && ! base.is_exclusive {
+            return Err("lens entity access is not in the query".into());
+        }
+
+        for col_id in &self.mut_components {
+            if ! base.mut_components.contains(col_id) {
+                return Err(format!("lens column {:?} is read-only in the query", col_id).into());
+            }
+        }
+
+        Ok(ViewPlan {
+            view: base.view,
+            cols,
+
+            components: self.components,
+            mut_components: self.mut_components,
+            is_exclusive: self.is_exclusive,
+
+            // the lens sees the same entities as its base
+            filters: base.filters.clone(),
+        })
     }
 }
 
@@ -146,19 +446,22 @@ pub struct ViewIterator<'a, T:View> {
     store: &'a EntityStore,
 
     view_id: ViewId,
-    plan: ViewPlan,
+    plan: Cow<'a, ViewPlan>,
 
     view_type_index: usize,
 
     row_index: usize,
 
+    // view table visit order, when not in table id order
+    order: Option<Vec<usize>>,
+
     marker: PhantomData<T>,
 }
 
 impl<'a, T:View> ViewIterator<'a, T> {
     pub(crate) fn new(
         table: &'a EntityStore, 
-        plan: ViewPlan,
+        plan: Cow<'a, ViewPlan>,
     ) -> Self {
         Self {
             store: table,
@@ -169,9 +472,41 @@ impl<'a, T:View> ViewIterator<'a, T> {
             view_type_index: 0,
             row_index: 0,
 
+            order: None,
+
             marker: PhantomData,
         }
     }
+
+    ///
+    /// Iterates the largest tables first instead of in table id order,
+    /// e.g. so the bulk of the work starts early.
+    ///
+    pub fn largest_first(mut self) -> Self {
+        let meta = self.store.meta();
+        let view_tables = meta.view(self.view_id).view_tables();
+
+        let mut order: Vec<usize> = (0..view_tables.len()).collect();
+
+        // stable, so equal tables stay in table id order
+        order.sort_by_key(|i| {
+            Reverse(self.store.table_len(meta.view_table(view_tables[*i]).table_id()))
+        });
+
+        self.order = Some(order);
+        self
+    }
+
+    ///
+    /// Position of the next item, as view table and row indexes.
+    ///
+    pub(crate) fn position(&self) -> (usize, usize) {
+        (self.view_type_index, self.row_index)
+    }
+
+    pub(crate) fn seek(&mut self, position: (usize, usize)) {
+        (self.view_type_index, self.row_index) = position;
+    }
 }
 
 impl<'a, T:View> Iterator for ViewIterator<'a, T>
@@ -182,7 +517,12 @@ impl<'a, T:View> Iterator for ViewIterator<'a, T>
         let view = self.store.meta().view(self.view_id);
 
         while self.view_type_index < view.view_tables().len() {
-            let view_table_id = view.view_tables()[self.view_type_index];
+            let index = match &self.order {
+                Some(order) => order[self.view_type_index],
+                None => self.view_type_index,
+            };
+
+            let view_table_id = view.view_tables()[index];
             let view_table = self.store.meta().view_table(view_table_id);
             let table_id = view_table.table_id();
             let table = self.store.meta().table(table_id);
@@ -190,7 +530,7 @@ impl<'a, T:View> Iterator for ViewIterator<'a, T>
             self.row_index += 1;
 
             while let Some(row) = self.store.get_row_by_type_index(table_id, row_index) {
-                if row.is_alloc() {
+                if row.is_alloc() && self.plan.is_match(self.store, table, row) {
                     return unsafe { 
                         let mut cursor = self.plan.new_cursor(
                             self.store,
@@ -223,7 +563,7 @@ impl<T:Component> View for &T {
     type Item<'t> = &'t T;
 
     fn build(builder: &mut ViewBuilder) {
-        builder.add_ref::<T>();
+        builder.add_ref_aligned::<T>(T::ALIGN);
     }
 
     unsafe fn deref<'a, 't>(cursor: &mut ViewCursor<'a, 't>) -> Self::Item<'t> { // Self::Item { // <'a> {
@@ -235,7 +575,7 @@ impl<T:Component> View for &mut T {
     type Item<'t> = &'t mut T;
 
     fn build(builder: &mut ViewBuilder) {
-        builder.add_mut::<T>();
+        builder.add_mut_aligned::<T>(T::ALIGN);
     }
 
     unsafe fn deref<'a, 't>(cursor: &mut ViewCursor<'a, 't>) -> Self::Item<'t> { //<'a> {
@@ -243,6 +583,16 @@ impl<T:Component> View for &mut T {
     }
 }
 
+impl View for () {
+    type Item<'t> = ();
+
+    fn build(_builder: &mut ViewBuilder) {
+    }
+
+    unsafe fn deref<'a, 't>(_cursor: &mut ViewCursor<'a, 't>) -> Self::Item<'t> {
+    }
+}
+
 impl View for EntityId {
     type Item<'t> = EntityId;
 
@@ -254,6 +604,114 @@ impl View for EntityId {
     }
 }
 
+impl View for EntityRef<'_> {
+    type Item<'t> = EntityRef<'t>;
+
+    fn build(builder: &mut ViewBuilder) {
+        builder.add_entity();
+    }
+
+    unsafe fn deref<'a, 't>(cursor: &mut ViewCursor<'a, 't>) -> Self::Item<'t> {
+        cursor.entity_ref()
+    }
+}
+
+//
+// Mapped views
+//
+
+///
+/// Virtual view item computed from the Source components, registered by
+/// implementing the trait and used in a query as Mapped<M>, e.g.
+/// `Query<Mapped<Position>>` with Position mapped from `&Transform`.
+///
+pub trait MappedView: Send + Sync + 'static {
+    type Source: View;
+    type Item<'t>;
+
+    fn map<'t>(source: <Self::Source as View>::Item<'t>) -> Self::Item<'t>;
+}
+
+pub struct Mapped<M>(PhantomData<M>);
+
+impl<M: MappedView> View for Mapped<M> {
+    type Item<'t> = M::Item<'t>;
+
+    fn build(builder: &mut ViewBuilder) {
+        M::Source::build(builder);
+    }
+
+    unsafe fn deref<'a, 't>(cursor: &mut ViewCursor<'a, 't>) -> Self::Item<'t> {
+        M::map(M::Source::deref(cursor))
+    }
+}
+
+//
+// Filters
+//
+
+///
+/// Query filter narrowing the entities without adding items, used as
+/// the second Query parameter, e.g. `Query<&Pos, VariantIs<State, {State::ACTIVE}>>`.
+/// Filters test component values per row, so filtered counts iterate.
+///
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is not a query filter",
+    label = "not a ViewFilter",
+    note = "the second Query parameter is a filter, e.g. VariantIs; views go in the first",
+)]
+pub trait ViewFilter: Send + Sync + 'static {
+    fn build(builder: &mut ViewBuilder);
+}
+
+impl ViewFilter for () {
+    fn build(_builder: &mut ViewBuilder) {
+    }
+}
+
+///
+/// Enum component with variant indexes, implemented by
+/// #[derive(Component)] on enums along with an index constant per
+/// variant, e.g. State::ACTIVE for State::Active.
+///
+pub trait Variant: Component {
+    fn variant(&self) -> usize;
+}
+
+///
+/// Filter on the variant of an enum component T.
+///
+pub struct VariantIs<T: Variant, const V: usize>(PhantomData<T>);
+
+impl<T: Variant, const V: usize> VariantIs<T, V> {
+    fn test(value: &T) -> bool {
+        value.variant() == V
+    }
+}
+
+impl<T: Variant, const V: usize> ViewFilter for VariantIs<T, V> {
+    fn build(builder: &mut ViewBuilder) {
+        builder.add_filter::<T>(type_name::<Self>(), Self::test);
+    }
+}
+
+macro_rules! impl_filter_tuple {
+    ($($part:ident),*) => {
+        impl<$($part:ViewFilter,)*> ViewFilter for ($($part,)*)
+        {
+            fn build(builder: &mut ViewBuilder) {
+                $(
+                    $part::build(builder);
+                )*
+            }
+        }
+    }
+}
+
+impl_filter_tuple!(P1,P2);
+impl_filter_tuple!(P1,P2,P3);
+impl_filter_tuple!(P1,P2,P3,P4);
+
 //
 // View composed of tuples
 //
@@ -287,3 +745,129 @@ impl_query_tuple!(P1,P2);
 impl_query_tuple!(P1,P2,P3);
 impl_query_tuple!(P1,P2,P3,P4);
 impl_query_tuple!(P1,P2,P3,P4,P5);
+
+#[cfg(test)]
+mod tests {
+    use crate::{core_app::CoreApp, entity::{Component, EntityId}, Commands, Query, Store};
+
+    use super::{Mapped, MappedView, Variant, VariantIs, View};
+
+    mod ecs { pub mod core { pub use crate::*; }}
+    use ecs as essay_ecs;
+
+    #[test]
+    fn mapped_view() {
+        let mut app = CoreApp::new();
+
+        app.run_system(|s: &mut crate::Store| {
+            s.spawn(Transform([1., 2., 0.5]));
+            s.spawn((Transform([3., 4., 2.]), TestA(1)));
+            Ok(())
+        }).unwrap();
+
+        let mut values = app.eval(|q: Query<Mapped<Position>>| {
+            q.iter().map(|p| p.0).collect::<Vec<[f32; 2]>>()
+        }).unwrap();
+        values.sort_by(|a, b| a[0].total_cmp(&b[0]));
+
+        assert_eq!(values, vec![[0.5, 1.], [6., 8.]]);
+
+        let values = app.eval(|q: Query<(EntityId, Mapped<Position>, &TestA)>| {
+            q.iter().map(|(_, p, a)| (p.0, a.0)).collect::<Vec<_>>()
+        }).unwrap();
+
+        assert_eq!(values, vec![([6., 8.], 1)]);
+    }
+
+    #[test]
+    fn variant_filter() {
+        let mut app = CoreApp::new();
+
+        let ids = app.eval(|mut c: Commands| {
+            (0..6).map(|i| {
+                let state = match i % 3 {
+                    0 => State::Idle,
+                    1 => State::Active(i),
+                    _ => State::Done { count: i },
+                };
+
+                c.spawn((TestA(i), state)).id()
+            }).collect::<Vec<EntityId>>()
+        }).unwrap();
+
+        let values = app.eval(|q: Query<&TestA, VariantIs<State, {State::ACTIVE}>>| {
+            assert_eq!(q.count(), 2);
+            q.iter().map(|a| a.0).collect::<Vec<u32>>()
+        }).unwrap();
+        assert_eq!(values, vec![1, 4]);
+
+        app.eval(move |q: Query<&mut State>| {
+            for state in q.iter() {
+                if matches!(state, State::Idle) {
+                    *state = State::Done { count: 0 };
+                }
+            }
+        }).unwrap();
+
+        let values = app.eval(|q: Query<(EntityId, &State), VariantIs<State, {State::DONE}>>| {
+            q.iter().map(|(id, _)| id).collect::<Vec<EntityId>>()
+        }).unwrap();
+        assert_eq!(values, vec![ids[0], ids[2], ids[3], ids[5]]);
+
+        assert_eq!(State::Idle.variant(), State::IDLE);
+    }
+
+    #[test]
+    fn largest_first() {
+        let mut store = Store::new();
+
+        store.spawn(TestA(1));
+
+        for i in 0..3 {
+            store.spawn((TestA(10 + i), Transform([0.; 3])));
+        }
+
+        let values: Vec<u32> = store.query::<&TestA>().map(|a| a.0).collect();
+        assert_eq!(values, vec![1, 10, 11, 12]);
+
+        let values: Vec<u32> = store.query::<&TestA>().largest_first().map(|a| a.0).collect();
+        assert_eq!(values, vec![10, 11, 12, 1]);
+
+        // the order follows the current table sizes
+        for i in 2..5 {
+            store.spawn(TestA(i));
+        }
+
+        let values: Vec<u32> = store.query::<&TestA>().largest_first().map(|a| a.0).collect();
+        assert_eq!(values, vec![1, 2, 3, 4, 10, 11, 12]);
+    }
+
+    #[derive(crate::Component)]
+    #[allow(unused)]
+    enum State {
+        Idle,
+        Active(u32),
+        Done { count: u32 },
+    }
+
+    struct Transform([f32; 3]);
+
+    impl Component for Transform {}
+
+    struct TestA(u32);
+
+    impl Component for TestA {}
+
+    struct Position([f32; 2]);
+
+    impl MappedView for Position {
+        type Source = &'static Transform;
+        type Item<'t> = Position;
+
+        fn map<'t>(source: <Self::Source as View>::Item<'t>) -> Self::Item<'t> {
+            let [x, y, scale] = source.0;
+
+            Position([x * scale, y * scale])
+        }
+    }
+}
@@ -9,7 +9,7 @@ pub mod store;
 pub mod resource;
 
 pub use essay_ecs_core_macros::{
-    Component, ScheduleLabel, Phase
+    Component, ScheduleLabel, SystemLabel, Phase
 };
 
 pub use schedule::{
@@ -21,15 +21,20 @@ pub use system::IntoSystem;
 
 pub use param::{
     Local,
-    Res, ResMut, Query, 
+    Res, ResMut, Query, CachedQuery, PersistentCursor,
+    Rng, RngSeed, TaskScope,
 };
 
 pub use store::{
     Store, Commands
 };
 
+pub use core_app::{
+    CoreApp, CoreAppBuilder, Core,
+};
+
 pub mod prelude {
-    pub use essay_ecs_core_macros::{Component, ScheduleLabel, Phase};
+    pub use essay_ecs_core_macros::{Component, ScheduleLabel, SystemLabel, Phase};
 
     pub use crate::{
         param::{Param, Res, ResMut},
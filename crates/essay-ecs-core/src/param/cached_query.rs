@@ -0,0 +1,205 @@
+use std::{collections::HashMap, marker::PhantomData, mem};
+
+use crate::{
+    entity::{ComponentId, EntityId, View, ViewPlan},
+    error::Result,
+    schedule::{SystemMeta, UnsafeStore},
+    Store
+};
+
+use super::Param;
+
+///
+/// Query keeping an explicit list of matching entities, updated from the
+/// store's spawn, despawn and insert/remove changes before each run
+/// instead of walking every view table. Suited to rarely changing
+/// memberships, where tables are large but matches are few.
+///
+pub struct CachedQuery<'w, 's, Q:View> {
+    world: &'w UnsafeStore,
+    state: &'s CachedState,
+    marker: PhantomData<Q>,
+}
+
+impl<'w, 's, Q:View> CachedQuery<'w, 's, Q> {
+    pub fn iter(&self) -> impl Iterator<Item=Q::Item<'_>> {
+        let world = self.world;
+        let plan = &self.state.plan;
+
+        self.state.ids.iter().filter_map(move |id| unsafe {
+            world.as_mut().view_get_from_plan::<Q>(plan, *id)
+        })
+    }
+
+    ///
+    /// Cached entities, in an unspecified order.
+    ///
+    pub fn ids(&self) -> &[EntityId] {
+        &self.state.ids
+    }
+
+    pub fn count(&self) -> usize {
+        self.state.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.state.ids.is_empty()
+    }
+}
+
+pub struct CachedState {
+    plan: ViewPlan,
+    ids: Vec<EntityId>,
+    index: HashMap<EntityId, usize>,
+    seq: Option<usize>,
+}
+
+impl CachedState {
+    fn update(&mut self, world: &Store) {
+        let changes = self.seq.and_then(|seq| world.entity_changes_since(seq));
+
+        match changes {
+            Some(changes) => {
+                for id in changes {
+                    if world.view_contains(&self.plan, *id) {
+                        self.insert(*id);
+                    } else {
+                        self.remove(*id);
+                    }
+                }
+            }
+            None => {
+                self.ids = world.view_entities(&self.plan);
+                self.index = self.ids.iter()
+                    .enumerate()
+                    .map(|(i, id)| (*id, i))
+                    .collect();
+            }
+        }
+
+        self.seq = Some(world.entity_change_seq());
+    }
+
+    fn insert(&mut self, id: EntityId) {
+        if ! self.index.contains_key(&id) {
+            self.index.insert(id, self.ids.len());
+            self.ids.push(id);
+        }
+    }
+
+    fn remove(&mut self, id: EntityId) {
+        if let Some(i) = self.index.remove(&id) {
+            self.ids.swap_remove(i);
+
+            if let Some(moved) = self.ids.get(i) {
+                self.index.insert(*moved, i);
+            }
+        }
+    }
+}
+
+impl<Q:View> Param for CachedQuery<'_, '_, Q>
+{
+    type Arg<'w, 's> = CachedQuery<'w, 's, Q>;
+    type Local = CachedState;
+
+    fn init(meta: &mut SystemMeta, world: &mut Store) -> Result<Self::Local> {
+        let plan = world.view_build::<Q>()?;
+
+        for id in plan.components() {
+            meta.insert_component(ComponentId::from(*id));
+        }
+
+        for id in plan.mut_components() {
+            meta.insert_component_mut(ComponentId::from(*id));
+        }
+
+        if plan.is_exclusive() {
+            meta.set_exclusive();
+        }
+
+        world.enable_entity_changes();
+
+        Ok(CachedState {
+            plan,
+            ids: Vec::new(),
+            index: HashMap::new(),
+            seq: None,
+        })
+    }
+
+    fn arg<'w, 's>(
+        world: &'w UnsafeStore,
+        state: &'s mut Self::Local,
+    ) -> Result<Self::Arg<'w, 's>> {
+        state.update(world);
+
+        Ok(CachedQuery {
+            world,
+            state,
+            marker: PhantomData,
+        })
+    }
+
+    fn memory_size(state: &Self::Local) -> usize {
+        mem::size_of::<CachedState>()
+            + state.ids.capacity() * mem::size_of::<EntityId>()
+            + state.index.capacity() * mem::size_of::<(EntityId, usize)>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::{core_app::{Core, CoreApp}, entity::Component, Commands};
+
+    use super::CachedQuery;
+
+    #[test]
+    fn cached_query() {
+        let mut app = CoreApp::new();
+
+        let ids = app.eval(|mut c: Commands| {
+            for i in 0..100 {
+                c.spawn(TestA(i));
+            }
+
+            let a = c.spawn(TestA(100)).id();
+            let b = c.spawn((TestA(101), TestB(1))).id();
+            c.spawn((TestA(102), TestB(2)));
+
+            (a, b)
+        }).unwrap();
+
+        let values = Arc::new(Mutex::new(Vec::<u32>::new()));
+        let ptr = values.clone();
+
+        app.system(Core, move |q: CachedQuery<&mut TestB>| {
+            let mut v: Vec<u32> = q.iter().map(|b| { b.0 += 10; b.0 }).collect();
+            v.sort();
+            *ptr.lock().unwrap() = v;
+        });
+
+        app.tick().unwrap();
+        assert_eq!(*values.lock().unwrap(), vec![11, 12]);
+
+        app.eval(move |mut c: Commands| {
+            c.entity(ids.0).insert(TestB(4));
+            c.entity(ids.1).despawn();
+            c.spawn(TestB(3));
+        }).unwrap();
+
+        app.tick().unwrap();
+        assert_eq!(*values.lock().unwrap(), vec![13, 14, 22]);
+    }
+
+    #[allow(unused)]
+    struct TestA(u32);
+
+    impl Component for TestA {}
+
+    struct TestB(u32);
+
+    impl Component for TestB {}
+}
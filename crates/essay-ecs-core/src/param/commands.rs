@@ -5,7 +5,7 @@ use crate::{
     Store
 };
 
-use super::Param;
+use super::{BufferKind, Param};
 
 impl Param for Commands<'_, '_> {
     type Arg<'w, 's> = Commands<'w, 's>;
@@ -25,4 +25,12 @@ impl Param for Commands<'_, '_> {
     fn flush(world: &mut Store, queue: &mut Self::Local) {
         queue.flush(world);
     }
+
+    fn buffer_kind() -> Option<BufferKind> {
+        Some(BufferKind::commands())
+    }
+
+    fn memory_size(queue: &Self::Local) -> usize {
+        queue.memory_size()
+    }
 }
@@ -0,0 +1,200 @@
+use std::{any::TypeId, ops::{Deref, DerefMut}};
+
+use crate::{
+    error::Result,
+    store::CommandQueue,
+    schedule::{SystemMeta, UnsafeStore},
+    Store
+};
+
+use super::Param;
+
+///
+/// System-local buffer for Deferred, filled while the system runs in
+/// parallel and applied to the store when the system's commands flush.
+///
+pub trait SystemBuffer: Default + Send + Sync + 'static {
+    fn apply(&mut self, store: &mut Store);
+}
+
+///
+/// Kind of a system's deferred buffer, for ordering flush stages with
+/// Schedule::set_flush_order.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BufferKind(TypeId);
+
+impl BufferKind {
+    ///
+    /// The Deferred<T> buffer kind.
+    ///
+    pub fn of<T: SystemBuffer>() -> Self {
+        BufferKind(TypeId::of::<T>())
+    }
+
+    ///
+    /// The Commands buffer kind.
+    ///
+    pub fn commands() -> Self {
+        BufferKind(TypeId::of::<CommandQueue>())
+    }
+}
+
+///
+/// Batched mutations in a user-defined buffer, a generalization of
+/// Commands, e.g. index updates or stats accumulated during a tick.
+///
+pub struct Deferred<'s, T: SystemBuffer>(&'s mut T);
+
+impl<T: SystemBuffer> Deref for Deferred<'_, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.0
+    }
+}
+
+impl<T: SystemBuffer> DerefMut for Deferred<'_, T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.0
+    }
+}
+
+impl<T: SystemBuffer> Param for Deferred<'_, T> {
+    type Arg<'w, 's> = Deferred<'s, T>;
+    type Local = T;
+
+    fn init(_meta: &mut SystemMeta, _world: &mut Store) -> Result<Self::Local> {
+        Ok(T::default())
+    }
+
+    #[inline]
+    fn arg<'w, 's>(
+        _world: &'w UnsafeStore,
+        buffer: &'s mut Self::Local,
+    ) -> Result<Self::Arg<'w, 's>> {
+        Ok(Deferred(buffer))
+    }
+
+    fn flush(world: &mut Store, buffer: &mut Self::Local) {
+        buffer.apply(world);
+    }
+
+    fn buffer_kind() -> Option<BufferKind> {
+        Some(BufferKind::of::<T>())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use crate::{store::Store, schedule::{Schedule, SystemMeta}, system::{IntoSystem, System}, Commands};
+
+    use super::{BufferKind, Deferred, SystemBuffer};
+
+    #[test]
+    fn deferred_apply() {
+        let mut world = Store::new();
+        world.insert_resource(0u32);
+
+        let mut schedule = Schedule::new();
+        schedule.add_system(|mut stats: Deferred<Stats>| {
+            stats.add(1);
+            stats.add(2);
+        });
+        schedule.add_system(|mut stats: Deferred<Stats>| {
+            stats.add(10);
+        });
+
+        schedule.tick(&mut world).unwrap();
+        assert_eq!(*world.resource::<u32>(), 13);
+
+        schedule.tick(&mut world).unwrap();
+        assert_eq!(*world.resource::<u32>(), 26);
+    }
+
+    #[test]
+    fn flush_order() {
+        let mut world = Store::new();
+        world.insert_resource(Vec::<String>::new());
+
+        let mut schedule = Schedule::new();
+        schedule.add_system(|mut log: Deferred<Log>| log.add("deferred"));
+        schedule.add_system(|mut c: Commands| c.add(|store: &mut Store| {
+            store.resource_mut::<Vec<String>>().push("commands".to_string());
+        }));
+
+        schedule.tick(&mut world).unwrap();
+        assert_eq!(world.resource::<Vec<String>>().join(", "), "deferred, commands");
+
+        world.resource_mut::<Vec<String>>().clear();
+        schedule.set_flush_order([BufferKind::commands(), BufferKind::of::<Log>()]);
+
+        schedule.tick(&mut world).unwrap();
+        assert_eq!(world.resource::<Vec<String>>().join(", "), "commands, deferred");
+    }
+
+    #[test]
+    fn flush_order_exclusive() {
+        let mut world = Store::new();
+        world.insert_resource(Vec::<String>::new());
+
+        let mut schedule = Schedule::new();
+        schedule.add_system(|mut log: Deferred<Log>| log.add("deferred"));
+        schedule.add_system(|_store: &mut Store, mut c: Commands| {
+            c.add(|store: &mut Store| {
+                store.resource_mut::<Vec<String>>().push("exclusive".to_string());
+            });
+            Ok(())
+        });
+        schedule.set_flush_order([BufferKind::of::<Log>(), BufferKind::commands()]);
+
+        // exclusive systems apply their commands when they return
+        schedule.tick(&mut world).unwrap();
+        assert_eq!(world.resource::<Vec<String>>().join(", "), "exclusive, deferred");
+
+        // the exclusive system's Commands belong to the commands stage
+        let mut system = IntoSystem::into_system(|_: &mut Store, _: Commands| Ok(()));
+        system.init(&mut SystemMeta::empty(), &mut world).unwrap();
+
+        let kinds = RefCell::new(Vec::new());
+        system.flush_filtered(&mut world, &|kind| {
+            kinds.borrow_mut().push(kind);
+            false
+        });
+        assert_eq!(kinds.into_inner(), vec![Some(BufferKind::commands())]);
+    }
+
+    #[derive(Default)]
+    struct Stats(Vec<u32>);
+
+    impl Stats {
+        fn add(&mut self, value: u32) {
+            self.0.push(value);
+        }
+    }
+
+    impl SystemBuffer for Stats {
+        fn apply(&mut self, store: &mut Store) {
+            *store.resource_mut::<u32>() += self.0.drain(..).sum::<u32>();
+        }
+    }
+
+    #[derive(Default)]
+    struct Log(Vec<String>);
+
+    impl Log {
+        fn add(&mut self, msg: &str) {
+            self.0.push(msg.to_string());
+        }
+    }
+
+    impl SystemBuffer for Log {
+        fn apply(&mut self, store: &mut Store) {
+            store.resource_mut::<Vec<String>>().append(&mut self.0);
+        }
+    }
+}
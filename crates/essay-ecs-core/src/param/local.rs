@@ -3,7 +3,10 @@
 // Local param
 //
 
-use std::ops::{Deref, DerefMut};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    mem, ops::{Deref, DerefMut},
+};
 
 use crate::{
     error::Result,
@@ -32,7 +35,109 @@ impl<'s, T:FromStore> DerefMut for Local<'s, T> {
     }
 }
 
-impl<'a, T: FromStore + Send + Sync + 'static> Param for Local<'a, T> {
+///
+/// Heap memory owned by a Local's state, reported by Local::memory_size.
+/// Types without heap buffers can use the default.
+///
+pub trait HeapSize {
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+macro_rules! impl_heap_size_none {
+    ($($ty:ty),*) => {
+        $(impl HeapSize for $ty {})*
+    }
+}
+
+impl_heap_size_none!(
+    (), bool, char,
+    u8, u16, u32, u64, u128, usize,
+    i8, i16, i32, i64, i128, isize,
+    f32, f64
+);
+
+impl HeapSize for String {
+    fn heap_size(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl<T: HeapSize> HeapSize for Option<T> {
+    fn heap_size(&self) -> usize {
+        self.as_ref().map_or(0, T::heap_size)
+    }
+}
+
+impl<T: HeapSize> HeapSize for Box<T> {
+    fn heap_size(&self) -> usize {
+        mem::size_of::<T>() + self.as_ref().heap_size()
+    }
+}
+
+impl<T: HeapSize> HeapSize for Vec<T> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * mem::size_of::<T>()
+            + self.iter().map(T::heap_size).sum::<usize>()
+    }
+}
+
+impl<T: HeapSize> HeapSize for VecDeque<T> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * mem::size_of::<T>()
+            + self.iter().map(T::heap_size).sum::<usize>()
+    }
+}
+
+impl<T: HeapSize, S> HeapSize for HashSet<T, S> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * mem::size_of::<T>()
+            + self.iter().map(T::heap_size).sum::<usize>()
+    }
+}
+
+impl<K: HeapSize, V: HeapSize, S> HeapSize for HashMap<K, V, S> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * mem::size_of::<(K, V)>()
+            + self.iter().map(|(k, v)| k.heap_size() + v.heap_size()).sum::<usize>()
+    }
+}
+
+// btrees don't expose a capacity, so only their entries are counted
+impl<T: HeapSize> HeapSize for BTreeSet<T> {
+    fn heap_size(&self) -> usize {
+        self.len() * mem::size_of::<T>()
+            + self.iter().map(T::heap_size).sum::<usize>()
+    }
+}
+
+impl<K: HeapSize, V: HeapSize> HeapSize for BTreeMap<K, V> {
+    fn heap_size(&self) -> usize {
+        self.len() * mem::size_of::<(K, V)>()
+            + self.iter().map(|(k, v)| k.heap_size() + v.heap_size()).sum::<usize>()
+    }
+}
+
+macro_rules! impl_heap_size_tuple {
+    ($($param:ident),*) => {
+        #[allow(non_snake_case)]
+        impl<$($param: HeapSize,)*> HeapSize for ($($param,)*) {
+            fn heap_size(&self) -> usize {
+                let ($($param,)*) = self;
+
+                0 $(+ $param.heap_size())*
+            }
+        }
+    }
+}
+
+impl_heap_size_tuple!(P1);
+impl_heap_size_tuple!(P1, P2);
+impl_heap_size_tuple!(P1, P2, P3);
+impl_heap_size_tuple!(P1, P2, P3, P4);
+
+impl<'a, T: FromStore + HeapSize + Send + Sync + 'static> Param for Local<'a, T> {
     type Local = T;
     type Arg<'w, 's> = Local<'s, T>;
 
@@ -51,14 +156,24 @@ impl<'a, T: FromStore + Send + Sync + 'static> Param for Local<'a, T> {
 
     fn flush(_world: &mut Store, _state: &mut Self::Local) {
     }
+
+    ///
+    /// The inline size of T plus the heap memory it reports through
+    /// HeapSize, like a Vec's buffer.
+    ///
+    fn memory_size(state: &Self::Local) -> usize {
+        mem::size_of::<T>() + state.heap_size()
+    }
 }
 
 
 #[cfg(test)]
 mod tests {
-    use crate::{store::Store, schedule::Schedule, param::ResMut};
+    use std::mem;
+
+    use crate::{store::Store, schedule::Schedule, param::{Param, ResMut}};
 
-    use super::Local;
+    use super::{HeapSize, Local};
 
     #[test]
     fn test_local() {
@@ -80,4 +195,23 @@ mod tests {
         *value = format!("local({})", local.0);
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn local_memory_size() {
+        let mut vec = Vec::<u64>::with_capacity(16);
+        vec.push(1);
+
+        assert_eq!(
+            <Local<Vec<u64>> as Param>::memory_size(&vec),
+            mem::size_of::<Vec<u64>>() + 16 * mem::size_of::<u64>()
+        );
+
+        let mut name = String::with_capacity(32);
+        name.push_str("name");
+        assert_eq!(name.heap_size(), 32);
+
+        assert_eq!(
+            <Local<u32> as Param>::memory_size(&3),
+            mem::size_of::<u32>()
+        );
+    }
+}
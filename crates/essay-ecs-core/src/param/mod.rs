@@ -1,12 +1,23 @@
 pub mod commands;
 mod world;
 mod query;
+mod cached_query;
+mod deferred;
 mod local;
 mod param;
 mod res;
+mod res_lock;
+mod rng;
+mod task_scope;
 
 pub use param::{Arg, Param};
-pub use local::Local;
+pub use local::{HeapSize, Local};
+pub use world::ReadStore;
+pub use deferred::{BufferKind, Deferred, SystemBuffer};
 pub use res::{Res, ResMut};
-pub use query::Query;
+pub use res_lock::{ResArc, ResLock, Shared};
+pub use rng::{Rng, RngSeed, RngState, RngStream};
+pub use task_scope::{Scope, Task, TaskScope};
+pub use cached_query::{CachedQuery, CachedState};
+pub use query::{Query, QueryArrays, QueryLens, QueryPart, PersistentCursor, CursorIter};
 
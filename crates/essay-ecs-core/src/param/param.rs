@@ -1,13 +1,23 @@
+use std::mem;
+
 use crate::{
     error::Result,
     store::Store, 
     schedule::{SystemMeta, UnsafeStore}
 };
 
+use super::BufferKind;
+
 //
 // Param
 //
  
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is not a system parameter",
+    label = "not a Param",
+    note = "system parameters include Res<T>, ResMut<T>, Query<V>, Commands and Local<T>",
+    note = "components are read through a Query, e.g. Query<&T>",
+)]
 pub trait Param {
     type Arg<'s, 'l>: Param<Local = Self::Local>;
     type Local: Send + Sync + 'static;
@@ -22,6 +32,36 @@ pub trait Param {
     #[allow(unused)]
     fn flush(store: &mut Store, local: &mut Self::Local) {
     }
+
+    ///
+    /// Kind of deferred buffer flushed by the param, None if it has none.
+    ///
+    fn buffer_kind() -> Option<BufferKind> {
+        None
+    }
+
+    ///
+    /// Flushes the buffers whose kind the filter accepts, for ordered
+    /// flush stages.
+    ///
+    fn flush_filtered(
+        store: &mut Store,
+        local: &mut Self::Local,
+        filter: &dyn Fn(Option<BufferKind>) -> bool
+    ) {
+        if filter(Self::buffer_kind()) {
+            Self::flush(store, local);
+        }
+    }
+
+    ///
+    /// Approximate bytes held by the local state, for diagnostics.
+    /// Defaults to the inline size. Params whose state owns heap memory
+    /// override it to add their allocations, or the heap isn't counted.
+    ///
+    fn memory_size(_local: &Self::Local) -> usize {
+        mem::size_of::<Self::Local>()
+    }
 }
 
 pub type Arg<'w, 's, P> = <P as Param>::Arg<'w, 's>;
@@ -64,6 +104,24 @@ macro_rules! impl_param_tuple {
                     $param::flush(world, $param);
                 )*
             }
+
+            fn flush_filtered(
+                world: &mut Store, 
+                state: &mut Self::Local,
+                filter: &dyn Fn(Option<BufferKind>) -> bool
+            ) {
+                let ($($param,)*) = state;
+
+                $(
+                    $param::flush_filtered(world, $param, filter);
+                )*
+            }
+
+            fn memory_size(state: &Self::Local) -> usize {
+                let ($($param,)*) = state;
+
+                0 $(+ $param::memory_size($param))*
+            }
         }
     }
 }
@@ -106,8 +164,9 @@ mod test {
 
     use crate::{
         error::Result,
-        param::{Param, Res}, 
-        schedule::{SystemMeta, UnsafeStore}, 
+        param::{Param, Res, ResMut}, 
+        schedule::{Schedule, SystemMeta, UnsafeStore}, 
+        util::test::TestValues,
         Store
     };
 
@@ -163,6 +222,46 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_derive_param_optional() -> Result<()> {
+        let mut app = CoreApp::new();
+
+        assert_eq!(
+            "None None",
+            app.eval(|param: OptionalParam| param.value())?
+        );
+
+        app.insert_resource(1u32);
+        app.insert_resource("string".to_string());
+
+        assert_eq!(
+            "Some(1) Some(\"string\")",
+            app.eval(|param: OptionalParam| param.value())?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_derive_param_optional_inserted_later() -> Result<()> {
+        let mut store = Store::new();
+        let mut schedule = Schedule::new();
+
+        let mut values = TestValues::new();
+        let mut ptr = values.clone();
+        schedule.add_system(move |param: OptionalParam| ptr.push(&param.value()));
+
+        schedule.tick(&mut store)?;
+        assert_eq!(values.take(), "None None");
+
+        store.insert_resource(1u32);
+
+        schedule.tick(&mut store)?;
+        assert_eq!(values.take(), "Some(1) None");
+
+        Ok(())
+    }
+
     #[derive(Debug)]
     struct TestArg<V> {
         name: String,
@@ -212,6 +311,26 @@ mod test {
         }
     }
 
+    #[derive(Param)]
+    struct OptionalParam<'w> {
+        count: Option<Res<'w, u32>>,
+        string: Option<ResMut<'w, String>>,
+        #[param(default)]
+        marker: PhantomData<u8>,
+    }
+
+    impl OptionalParam<'_> {
+        fn value(&self) -> String {
+            let _ = self.marker;
+
+            format!(
+                "{:?} {:?}",
+                self.count.as_ref().map(|c| **c),
+                self.string.as_ref().map(|s| s.get())
+            )
+        }
+    }
+
     /*
     const _: () = {
     struct __PState<'w, 's> {
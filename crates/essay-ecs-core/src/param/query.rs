@@ -1,23 +1,27 @@
-use std::marker::PhantomData;
+use std::{iter::Take, marker::PhantomData};
 
 use crate::{
-    entity::{View, ViewPlan, ComponentId, ViewIterator}, 
+    entity::{View, ViewFilter, ViewPlan, ComponentId, ViewIterator, ViewExplain}, 
     error::Result,
     schedule::{SystemMeta, UnsafeStore}, 
     Store
 };
 
-use super::Param;
+use super::{HeapSize, Param};
 
 
-pub struct Query<'w, 's, Q:View> {
+///
+/// Query over the entities matching view Q and filter F, e.g.
+/// `Query<&Pos, VariantIs<State, {State::ACTIVE}>>`.
+///
+pub struct Query<'w, 's, Q:View, F:ViewFilter=()> {
     world: &'w UnsafeStore,
     plan: &'s ViewPlan,
-    marker: PhantomData<Q>,
+    marker: PhantomData<(Q, F)>,
 }
 
 //impl<'w, 's, Q: View> Query<'w, 's, Q> {
-impl<'w, 's, Q:View> Query<'w, 's, Q> {
+impl<'w, 's, Q:View, F:ViewFilter> Query<'w, 's, Q, F> {
     fn new(world: &'w UnsafeStore, plan: &'s ViewPlan) -> Self {
         Self {
             world,
@@ -29,15 +33,296 @@ impl<'w, 's, Q:View> Query<'w, 's, Q> {
     pub fn iter(&self) -> ViewIterator<Q> {
         unsafe { self.world.as_mut().view_iter_from_plan(&self.plan) }
     }
+
+    ///
+    /// Number of matching entities, from table lengths without iterating
+    /// unless the query is filtered.
+    ///
+    pub fn count(&self) -> usize {
+        self.world.count_from_plan(self.plan)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count() == 0
+    }
+
+    ///
+    /// Matched tables, row counts and component access of the query's
+    /// plan, printable with {}.
+    ///
+    pub fn explain(&self) -> ViewExplain {
+        self.world.explain_from_plan(self.plan)
+    }
+
+    ///
+    /// Iterates over `[Item; N]` chunks for unrolled inner loops. Items
+    /// must be Copy, so only read-only views qualify. Trailing items that
+    /// don't fill a chunk are available from `remainder()`.
+    ///
+    pub fn iter_arrays<const N: usize>(&self) -> QueryArrays<'_, Q, N>
+    where
+        for<'a> Q::Item<'a>: Copy
+    {
+        QueryArrays::new(self.iter())
+    }
+
+    ///
+    /// Narrows the query to a sub-view L, e.g. Query<(&A, &mut B)> to
+    /// Query<&A>, for passing to helper functions. L must only use
+    /// components in Q, and only mutably if Q is mutable.
+    ///
+    pub fn transmute_lens<L:View>(&mut self) -> Result<QueryLens<'_, L>> {
+        let plan = self.world.view_lens::<L>(self.plan)?;
+
+        Ok(QueryLens {
+            world: self.world,
+            plan,
+            marker: Default::default(),
+        })
+    }
+
+    ///
+    /// Splits the query into the first n matching entities and the rest.
+    /// The halves borrow the query mutably, so they can run concurrently,
+    /// e.g. in TaskScope tasks, without overlapping.
+    ///
+    pub fn split_at(&mut self, n: usize) -> (QueryPart<'_, Q>, QueryPart<'_, Q>) {
+        let count = self.count();
+        let n = n.min(count);
+
+        let mut iter = self.iter();
+        for _ in 0..n {
+            iter.next();
+        }
+        let mid = iter.position();
+
+        (
+            QueryPart::new(self.world, self.plan, (0, 0), n),
+            QueryPart::new(self.world, self.plan, mid, count - n),
+        )
+    }
+
+    ///
+    /// Splits the query into at most `chunks` disjoint parts of nearly
+    /// equal size, for fork-join work over the matching entities.
+    ///
+    pub fn par_split(&mut self, chunks: usize) -> Vec<QueryPart<'_, Q>> {
+        assert!(chunks > 0, "par_split chunks must be non-zero");
+
+        let count = self.count();
+        let size = count.div_ceil(chunks).max(1);
+
+        let mut parts = Vec::new();
+        let mut iter = self.iter();
+        let mut start = 0;
+
+        while start < count {
+            let len = size.min(count - start);
+
+            parts.push(QueryPart::new(self.world, self.plan, iter.position(), len));
+
+            for _ in 0..len {
+                iter.next();
+            }
+
+            start += len;
+        }
+
+        parts
+    }
+}
+
+pub struct QueryArrays<'a, Q:View, const N: usize> {
+    iter: ViewIterator<'a, Q>,
+    remainder: Vec<Q::Item<'a>>,
+}
+
+impl<'a, Q:View, const N: usize> QueryArrays<'a, Q, N>
+where
+    Q::Item<'a>: Copy
+{
+    const NON_ZERO: () = assert!(N > 0, "iter_arrays chunk size must be non-zero");
+
+    fn new(iter: ViewIterator<'a, Q>) -> Self {
+        let () = Self::NON_ZERO;
+
+        Self {
+            iter,
+            remainder: Vec::new(),
+        }
+    }
+
+    ///
+    /// Items left over after the last full chunk.
+    ///
+    pub fn remainder(&self) -> &[Q::Item<'a>] {
+        &self.remainder
+    }
 }
 
-impl<Q:View> Param for Query<'_, '_, Q>
+impl<'a, Q:View, const N: usize> Iterator for QueryArrays<'a, Q, N>
+where
+    Q::Item<'a>: Copy
 {
-    type Arg<'w, 's> = Query<'w, 's, Q>;
+    type Item = [Q::Item<'a>; N];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let first = self.iter.next()?;
+        let mut chunk = [first; N];
+
+        for (i, slot) in chunk.iter_mut().enumerate().skip(1) {
+            match self.iter.next() {
+                Some(item) => *slot = item,
+                None => {
+                    self.remainder.extend_from_slice(&chunk[..i]);
+                    return None;
+                }
+            }
+        }
+
+        Some(chunk)
+    }
+}
+
+///
+/// Query position kept across ticks in a Local, for processing a large
+/// entity set under a per-tick budget, e.g.
+/// `for item in cursor.iter(&query).take(budget)`. Each iteration
+/// resumes after the previous one, skipping despawned entities. An
+/// iteration that reaches the end restarts from the beginning next time.
+/// Q only tags the cursor, so a system can keep one per query.
+///
+pub struct PersistentCursor<Q> {
+    position: (usize, usize),
+    marker: PhantomData<fn() -> Q>,
+}
+
+impl<Q> PersistentCursor<Q> {
+    pub fn iter<'a, V:View, F:ViewFilter>(
+        &'a mut self,
+        query: &'a Query<'_, '_, V, F>
+    ) -> CursorIter<'a, V> {
+        let mut iter = query.iter();
+        iter.seek(self.position);
+
+        CursorIter {
+            iter,
+            position: &mut self.position,
+        }
+    }
+
+    ///
+    /// True if the next iteration starts from the beginning.
+    ///
+    pub fn is_start(&self) -> bool {
+        self.position == (0, 0)
+    }
+
+    pub fn reset(&mut self) {
+        self.position = (0, 0);
+    }
+}
+
+impl<Q> Default for PersistentCursor<Q> {
+    fn default() -> Self {
+        Self {
+            position: (0, 0),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<Q> HeapSize for PersistentCursor<Q> {}
+
+pub struct CursorIter<'a, Q:View> {
+    iter: ViewIterator<'a, Q>,
+    position: &'a mut (usize, usize),
+}
+
+impl<'a, Q:View> Iterator for CursorIter<'a, Q> {
+    type Item = Q::Item<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.iter.next() {
+            Some(item) => {
+                *self.position = self.iter.position();
+                Some(item)
+            }
+            None => {
+                *self.position = (0, 0);
+                None
+            }
+        }
+    }
+}
+
+///
+/// Disjoint range of a query's entities from Query::split_at or
+/// Query::par_split.
+///
+pub struct QueryPart<'a, Q:View> {
+    world: &'a UnsafeStore,
+    plan: &'a ViewPlan,
+    start: (usize, usize),
+    len: usize,
+    marker: PhantomData<Q>,
+}
+
+impl<'a, Q:View> QueryPart<'a, Q> {
+    fn new(
+        world: &'a UnsafeStore,
+        plan: &'a ViewPlan,
+        start: (usize, usize),
+        len: usize
+    ) -> Self {
+        Self {
+            world,
+            plan,
+            start,
+            len,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn iter(&mut self) -> Take<ViewIterator<'_, Q>> {
+        let mut iter = unsafe { self.world.as_mut().view_iter_from_plan(self.plan) };
+        iter.seek(self.start);
+
+        iter.take(self.len)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+// Parts of one query cover disjoint rows, and components are Send + Sync.
+// EntityRef views aren't Send, since they read any of the entity's columns.
+unsafe impl<Q:View + Send + Sync> Send for QueryPart<'_, Q> {}
+
+pub struct QueryLens<'w, L:View> {
+    world: &'w UnsafeStore,
+    plan: ViewPlan,
+    marker: PhantomData<L>,
+}
+
+impl<'w, L:View> QueryLens<'w, L> {
+    pub fn query(&mut self) -> Query<'_, '_, L> {
+        Query::new(self.world, &self.plan)
+    }
+}
+
+impl<Q:View, F:ViewFilter> Param for Query<'_, '_, Q, F>
+{
+    type Arg<'w, 's> = Query<'w, 's, Q, F>;
     type Local = ViewPlan;
 
     fn init(meta: &mut SystemMeta, world: &mut Store) -> Result<Self::Local> {
-        let plan = world.view_build::<Q>();
+        let plan = world.view_build_filtered::<Q, F>()?;
         
         for id in plan.components() {
             meta.insert_component(ComponentId::from(*id));
@@ -47,6 +332,10 @@ impl<Q:View> Param for Query<'_, '_, Q>
             meta.insert_component_mut(ComponentId::from(*id));
         }
 
+        if plan.is_exclusive() {
+            meta.set_exclusive();
+        }
+
         Ok(plan)
     }
 
@@ -62,9 +351,220 @@ impl<Q:View> Param for Query<'_, '_, Q>
 mod test {
     use std::{sync::{Arc, Mutex}, time::Duration, thread};
 
-    use crate::{core_app::{CoreApp, Core}, entity::Component, Commands, schedule::Executors, Schedules};
+    use crate::{core_app::{CoreApp, Core}, entity::Component, Commands, schedule::Executors, Schedules, Store, store::EntityRef};
+
+    use crate::{Local, TaskScope};
+
+    use super::{PersistentCursor, Query};
+
+    #[test]
+    fn iter_arrays() {
+        let mut app = CoreApp::new();
+
+        app.run_system(|s: &mut Store| { 
+            for i in 0..7 {
+                s.spawn(TestA(i));
+            }
+            Ok(())
+        }).unwrap();
+
+        let (chunks, rest) = app.eval(|q: Query<&TestA>| {
+            let mut arrays = q.iter_arrays::<3>();
+
+            let chunks: Vec<u32> = arrays.by_ref()
+                .map(|[a, b, c]| a.0 + b.0 + c.0)
+                .collect();
+
+            let rest: Vec<u32> = arrays.remainder().iter().map(|a| a.0).collect();
+
+            (chunks, rest)
+        }).unwrap();
 
-    use super::Query;
+        assert_eq!(chunks, vec![3, 12]);
+        assert_eq!(rest, vec![6]);
+    }
+
+    #[test]
+    fn split_at() {
+        let mut app = CoreApp::new();
+
+        app.run_system(|s: &mut Store| { 
+            for i in 0..7 {
+                s.spawn(TestA(i));
+            }
+            s.spawn((TestA(7), TestB(7)));
+            Ok(())
+        }).unwrap();
+
+        app.eval(|mut q: Query<&mut TestA>, tasks: TaskScope| {
+            let (mut head, mut tail) = q.split_at(3);
+            assert_eq!(head.len(), 3);
+            assert_eq!(tail.len(), 5);
+
+            tasks.scope(|scope| {
+                scope.spawn(move || head.iter().for_each(|a| a.0 += 100));
+                scope.spawn(move || tail.iter().for_each(|a| a.0 += 1000));
+            });
+        }).unwrap();
+
+        let mut values = app.eval(|q: Query<&TestA>| {
+            q.iter().map(|a| a.0).collect::<Vec<u32>>()
+        }).unwrap();
+        values.sort();
+
+        assert_eq!(values, vec![100, 101, 102, 1003, 1004, 1005, 1006, 1007]);
+
+        let lens = app.eval(|mut q: Query<&mut TestA>| {
+            let mut parts = q.par_split(3);
+
+            for part in &mut parts {
+                part.iter().for_each(|a| a.0 = 0);
+            }
+
+            parts.iter().map(|p| p.len()).collect::<Vec<usize>>()
+        }).unwrap();
+
+        assert_eq!(lens, vec![3, 3, 2]);
+        assert_eq!(app.eval(|q: Query<&TestA>| q.iter().filter(|a| a.0 == 0).count()).unwrap(), 8);
+    }
+
+    #[test]
+    fn explain() {
+        let mut app = CoreApp::new();
+
+        app.eval(|mut c: Commands| {
+            c.spawn(TestA(1));
+            c.spawn((TestA(2), TestB(2)));
+            c.spawn((TestA(3), TestB(3)));
+        }).unwrap();
+
+        let explain = app.eval(|q: Query<(&TestA, &mut TestB)>| q.explain()).unwrap();
+
+        assert_eq!(explain.columns.len(), 2);
+        assert!(explain.reads[0].ends_with("TestA"));
+        assert!(explain.writes[0].ends_with("TestB"));
+        assert_eq!(explain.tables.len(), 1);
+        assert_eq!(explain.rows(), 2);
+        assert!(explain.to_string().contains(": 2 rows"), "{}", explain);
+
+        let explain = app.eval(|q: Query<(&TestA, &TestC)>| q.explain()).unwrap();
+
+        assert_eq!(explain.rows(), 0);
+        assert!(explain.to_string().ends_with("no matching tables"), "{}", explain);
+    }
+
+    #[test]
+    fn persistent_cursor() {
+        let mut app = CoreApp::new();
+
+        let ids = app.eval(|mut c: Commands| {
+            (0..10).map(|i| c.spawn(TestA(i)).id()).collect::<Vec<_>>()
+        }).unwrap();
+
+        let values = Arc::new(Mutex::new(Vec::<String>::new()));
+        let ptr = values.clone();
+
+        app.system(Core, move |q: Query<&TestA>, mut cursor: Local<PersistentCursor<TestA>>| {
+            let items: Vec<String> = cursor.iter(&q)
+                .take(4)
+                .map(|a| a.0.to_string())
+                .collect();
+
+            push(&ptr, items.join(" "));
+        });
+
+        app.tick().unwrap();
+        assert_eq!(take(&values), "0 1 2 3");
+
+        app.eval(move |mut c: Commands| c.entity(ids[5]).despawn()).unwrap();
+
+        app.tick().unwrap();
+        assert_eq!(take(&values), "4 6 7 8");
+
+        app.tick().unwrap();
+        assert_eq!(take(&values), "9");
+
+        app.tick().unwrap();
+        assert_eq!(take(&values), "0 1 2 3");
+    }
+
+    #[test]
+    fn transmute_lens() {
+        let mut app = CoreApp::new();
+
+        app.run_system(|s: &mut Store| { 
+            s.spawn((TestA(1), TestB(10)));
+            s.spawn(TestA(2));
+            Ok(())
+        }).unwrap();
+
+        let values = Arc::new(Mutex::new(Vec::<String>::new()));
+        let ptr = values.clone();
+        app.system(Core, move |mut q: Query<(&mut TestA, &TestB)>| { 
+            for (a, b) in q.iter() {
+                a.0 += b.0;
+            }
+
+            let mut lens = q.transmute_lens::<&TestA>().unwrap();
+            for t in lens.query().iter() {
+                push(&ptr, format!("{:?}", t)); 
+            }
+
+            let mut lens = q.transmute_lens::<(&TestB, &mut TestA)>().unwrap();
+            for (b, a) in lens.query().iter() {
+                push(&ptr, format!("{:?}", (b, a))); 
+            }
+
+            assert!(q.transmute_lens::<&mut TestB>().is_err());
+            assert!(q.transmute_lens::<&TestC>().is_err());
+        });
+
+        app.tick().unwrap();
+        assert_eq!(take(&values), "TestA(11), (TestB(10), TestA(11))");
+    }
+
+    #[test]
+    fn query_entity_ref() {
+        let mut app = CoreApp::new();
+
+        app.run_system(|s: &mut Store| { 
+            s.spawn((TestA(1), TestB(10)));
+            s.spawn(TestB(2));
+            Ok(())
+        }).unwrap();
+
+        let values = Arc::new(Mutex::new(Vec::<String>::new()));
+        let ptr = values.clone();
+        app.system(Core, move |q: Query<(&TestB, EntityRef)>| { 
+            for (b, entity) in q.iter() {
+                push(&ptr, format!("{:?}/{:?}/{}", 
+                    b, entity.get::<TestA>(), entity.iter_components().count()
+                )); 
+            }
+        });
+
+        app.tick().unwrap();
+        assert_eq!(take(&values), "TestB(10)/Some(TestA(1))/2, TestB(2)/None/1");
+    }
+
+    #[test]
+    fn query_entity_ref_with_mut_rejected() {
+        let mut app = CoreApp::new();
+
+        app.run_system(|s: &mut Store| { 
+            s.spawn((TestA(1), TestB(10)));
+            Ok(())
+        }).unwrap();
+
+        app.system(Core, |q: Query<(&mut TestA, EntityRef)>| { 
+            for (a, entity) in q.iter() {
+                a.0 += entity.get::<TestA>().unwrap().0;
+            }
+        });
+
+        let err = app.tick().unwrap_err();
+        assert!(err.message().contains("EntityRef"), "{}", err.message());
+    }
 
     #[test]
     fn query_component() {
@@ -156,4 +656,14 @@ mod test {
     pub struct TestA(u32);
 
     impl Component for TestA {}
+
+    #[derive(Debug)]
+    pub struct TestB(u32);
+
+    impl Component for TestB {}
+
+    #[derive(Debug)]
+    pub struct TestC;
+
+    impl Component for TestC {}
 }
@@ -129,9 +129,10 @@ impl<T: Send+'static> Param for ResMut<'_, T> {
         world: &'w UnsafeStore,
         _state: &'s mut Self::Local,
     ) -> Result<ResMut<'w, T>> {
-        Ok(ResMut {
-            value: unsafe { world.as_mut().get_resource_mut().unwrap() }
-        })
+        match unsafe { world.as_mut().get_resource_mut() } {
+            Some(value) => Ok(ResMut { value }),
+            None => Err(format!("ResMut<{}> is not a resource", type_name::<T>()).into()),
+        }
     }
 }
 
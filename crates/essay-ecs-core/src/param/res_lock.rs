@@ -0,0 +1,191 @@
+use std::{
+    any::type_name,
+    ops::Deref,
+    sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+use crate::{
+    error::Result,
+    schedule::{SystemMeta, UnsafeStore},
+    Store
+};
+
+use super::Param;
+
+///
+/// Resource wrapped in an RwLock, for ResLock and ResArc params. The lock
+/// guards the value itself, so the planner treats both params as reads.
+///
+pub struct Shared<T>(Arc<RwLock<T>>);
+
+impl<T> Shared<T> {
+    pub fn new(value: T) -> Self {
+        Self(Arc::new(RwLock::new(value)))
+    }
+
+    pub fn read(&self) -> RwLockReadGuard<'_, T> {
+        self.0.read().unwrap()
+    }
+
+    pub fn write(&self) -> RwLockWriteGuard<'_, T> {
+        self.0.write().unwrap()
+    }
+
+    pub fn arc(&self) -> Arc<RwLock<T>> {
+        self.0.clone()
+    }
+}
+
+impl<T> Clone for Shared<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+///
+/// Borrowed lock on a Shared<T> resource.
+///
+pub struct ResLock<'w, T> {
+    shared: &'w Shared<T>,
+}
+
+impl<T> ResLock<'_, T> {
+    pub fn read(&self) -> RwLockReadGuard<'_, T> {
+        self.shared.read()
+    }
+
+    pub fn write(&self) -> RwLockWriteGuard<'_, T> {
+        self.shared.write()
+    }
+}
+
+impl<T: Send + Sync + 'static> Param for ResLock<'_, T> {
+    type Arg<'w, 's> = ResLock<'w, T>;
+    type Local = ();
+
+    fn arg<'w, 's>(
+        store: &'w UnsafeStore,
+        _state: &'s mut Self::Local,
+    ) -> Result<Self::Arg<'w, 's>> {
+        match store.get_resource::<Shared<T>>() {
+            Some(shared) => Ok(ResLock { shared }),
+            None => Err(format!("ResLock<{}> is not a resource", type_name::<T>()).into()),
+        }
+    }
+
+    fn init(meta: &mut SystemMeta, store: &mut Store) -> Result<Self::Local> {
+        init_shared::<T>(meta, store, "ResLock")
+    }
+}
+
+///
+/// Owned handle to a Shared<T> resource, which can outlive the system run,
+/// e.g. moved into a thread or future that holds a read guard while the
+/// schedule continues.
+///
+pub struct ResArc<T> {
+    value: Arc<RwLock<T>>,
+}
+
+impl<T> ResArc<T> {
+    pub fn into_inner(self) -> Arc<RwLock<T>> {
+        self.value
+    }
+}
+
+impl<T> Deref for ResArc<T> {
+    type Target = Arc<RwLock<T>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<T: Send + Sync + 'static> Param for ResArc<T> {
+    type Arg<'w, 's> = ResArc<T>;
+    type Local = ();
+
+    fn arg<'w, 's>(
+        store: &'w UnsafeStore,
+        _state: &'s mut Self::Local,
+    ) -> Result<Self::Arg<'w, 's>> {
+        match store.get_resource::<Shared<T>>() {
+            Some(shared) => Ok(ResArc { value: shared.arc() }),
+            None => Err(format!("ResArc<{}> is not a resource", type_name::<T>()).into()),
+        }
+    }
+
+    fn init(meta: &mut SystemMeta, store: &mut Store) -> Result<Self::Local> {
+        init_shared::<T>(meta, store, "ResArc")
+    }
+}
+
+fn init_shared<T: Send + Sync + 'static>(
+    meta: &mut SystemMeta,
+    store: &mut Store,
+    param: &str
+) -> Result<()> {
+    if store.contains_resource::<Shared<T>>() {
+        meta.insert_resource(store.get_resource_id::<Shared<T>>());
+
+        Ok(())
+    } else {
+        Err(format!("{}<{}> is an unknown resource", param, type_name::<T>()).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::{mpsc, Arc, Mutex}, thread};
+
+    use crate::core_app::{Core, CoreApp};
+
+    use super::{ResArc, ResLock, Shared};
+
+    #[test]
+    fn res_lock() {
+        let mut app = CoreApp::new();
+        app.insert_resource(Shared::new(10u32));
+
+        app.system(Core, |res: ResLock<u32>| {
+            *res.write() += 1;
+        });
+
+        app.tick().unwrap();
+        app.tick().unwrap();
+
+        assert_eq!(*app.resource::<Shared<u32>>().read(), 12);
+    }
+
+    #[test]
+    fn res_arc_outlives_run() {
+        let mut app = CoreApp::new();
+        app.insert_resource(Shared::new(vec![1u32, 2, 3]));
+
+        let handles = Arc::new(Mutex::new(Vec::new()));
+
+        // the thread reads only after the store's write below
+        let (sender, receiver) = mpsc::channel::<()>();
+        let receiver = Arc::new(Mutex::new(Some(receiver)));
+
+        let ptr = handles.clone();
+        app.system(Core, move |res: ResArc<Vec<u32>>| {
+            let value = res.into_inner();
+            let receiver = receiver.lock().unwrap().take().unwrap();
+
+            ptr.lock().unwrap().push(thread::spawn(move || {
+                receiver.recv().unwrap();
+
+                value.read().unwrap().iter().sum::<u32>()
+            }));
+        });
+
+        app.tick().unwrap();
+
+        app.resource::<Shared<Vec<u32>>>().write().push(4);
+        sender.send(()).unwrap();
+
+        let handle = handles.lock().unwrap().pop().unwrap();
+        assert_eq!(handle.join().unwrap(), 10);
+    }
+}
@@ -0,0 +1,213 @@
+use std::ops::{Deref, DerefMut};
+
+use crate::{
+    entity::EntityId,
+    error::Result,
+    schedule::{SystemMeta, UnsafeStore},
+    Store
+};
+
+use super::Param;
+
+///
+/// Global seed for Rng params, read when a system is initialized.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RngSeed(pub u64);
+
+///
+/// Deterministic random stream for a system, keyed by the RngSeed
+/// resource and the SystemId, so results don't depend on which systems
+/// run in parallel.
+///
+pub struct Rng<'s> {
+    state: &'s mut RngState,
+}
+
+pub struct RngState {
+    seed: u64,
+    run: u64,
+    stream: RngStream,
+}
+
+impl Rng<'_> {
+    ///
+    /// Sub-stream for an entity in the current run, independent of the
+    /// order entities are visited.
+    ///
+    pub fn for_entity(&self, id: EntityId) -> RngStream {
+        let key = ((id.index() as u64) << 32) | id.generation() as u64;
+
+        RngStream::new(self.state.seed ^ mix(self.state.run) ^ mix(mix(key)))
+    }
+}
+
+impl Deref for Rng<'_> {
+    type Target = RngStream;
+
+    fn deref(&self) -> &Self::Target {
+        &self.state.stream
+    }
+}
+
+impl DerefMut for Rng<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.state.stream
+    }
+}
+
+impl Param for Rng<'_> {
+    type Arg<'w, 's> = Rng<'s>;
+    type Local = RngState;
+
+    fn init(meta: &mut SystemMeta, store: &mut Store) -> Result<Self::Local> {
+        let seed = store.get_resource::<RngSeed>().map_or(0, |seed| seed.0);
+        let seed = mix(seed ^ mix(meta.id().index() as u64));
+
+        Ok(RngState {
+            seed,
+            run: 0,
+            stream: RngStream::new(seed),
+        })
+    }
+
+    fn arg<'w, 's>(
+        _store: &'w UnsafeStore,
+        state: &'s mut Self::Local,
+    ) -> Result<Self::Arg<'w, 's>> {
+        state.run += 1;
+
+        Ok(Rng { state })
+    }
+}
+
+///
+/// SplitMix64 generator, small and fast but not cryptographic.
+///
+#[derive(Clone, Debug)]
+pub struct RngStream {
+    state: u64,
+}
+
+impl RngStream {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+        mix(self.state)
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    ///
+    /// Uniform in [0, 1).
+    ///
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 * (1.0 / (1u64 << 24) as f32)
+    }
+
+    ///
+    /// Uniform in [0, 1).
+    ///
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+
+    ///
+    /// Uniform in [0, n).
+    ///
+    pub fn below(&mut self, n: u64) -> u64 {
+        assert!(n > 0);
+
+        ((self.next_u64() as u128 * n as u128) >> 64) as u64
+    }
+}
+
+fn mix(value: u64) -> u64 {
+    let mut z = value;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::{core_app::{Core, CoreApp}, entity::{Component, EntityId}, Query, Store};
+
+    use super::{Rng, RngSeed};
+
+    #[test]
+    fn deterministic_streams() {
+        let run = |seed: u64| {
+            let mut app = CoreApp::new();
+            app.insert_resource(RngSeed(seed));
+
+            let values = Arc::new(Mutex::new(Vec::<u64>::new()));
+
+            let ptr = values.clone();
+            app.system(Core, move |mut rng: Rng| {
+                ptr.lock().unwrap().push(rng.next_u64());
+            });
+
+            let ptr = values.clone();
+            app.system(Core, move |mut rng: Rng| {
+                ptr.lock().unwrap().push(rng.below(1000) + 1_000_000);
+            });
+
+            app.tick().unwrap();
+            app.tick().unwrap();
+
+            let mut values = values.lock().unwrap().clone();
+            values.sort();
+            values
+        };
+
+        assert_eq!(run(1), run(1));
+        assert_ne!(run(1), run(2));
+
+        let values = run(1);
+        assert_eq!(values.len(), 4);
+        assert!(values[0] != values[1]);
+    }
+
+    #[test]
+    fn entity_streams() {
+        let mut app = CoreApp::new();
+
+        app.run_system(|s: &mut Store| {
+            for i in 0..4 {
+                s.spawn(TestA(i));
+            }
+            Ok(())
+        }).unwrap();
+
+        let values = app.eval(|rng: Rng, q: Query<(EntityId, &TestA)>| {
+            let mut forward: Vec<u32> = q.iter()
+                .map(|(id, _)| rng.for_entity(id).next_u32())
+                .collect();
+
+            let mut reverse: Vec<u32> = q.iter().collect::<Vec<_>>().into_iter().rev()
+                .map(|(id, _)| rng.for_entity(id).next_u32())
+                .collect();
+            reverse.reverse();
+
+            assert_eq!(forward, reverse);
+
+            forward.dedup();
+            forward.len()
+        }).unwrap();
+
+        assert_eq!(values, 4);
+    }
+
+    struct TestA(#[allow(unused)] u32);
+
+    impl Component for TestA {}
+}
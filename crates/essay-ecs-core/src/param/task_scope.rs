@@ -0,0 +1,329 @@
+use std::{
+    marker::PhantomData,
+    mem,
+    num::NonZeroUsize,
+    panic::{self, AssertUnwindSafe},
+    sync::{atomic::{AtomicUsize, Ordering}, Arc, Mutex},
+    thread::{self, Thread},
+    time::Duration,
+};
+
+use crate::{
+    error::Result,
+    schedule::{JobQueue, SystemMeta, UnsafeStore},
+    Store
+};
+
+use super::Param;
+
+///
+/// Structured concurrency for a system. Tasks spawned in TaskScope::scope
+/// can borrow the system's params and are joined before scope returns,
+/// so a heavy system can split its work without Arc or unsafe. Tasks run
+/// on the multithreaded executor's thread pool. Without a pool, e.g. in
+/// Store::eval, the single executor or a main-thread system, each task
+/// runs when it's spawned.
+///
+pub struct TaskScope<'s> {
+    n_threads: &'s usize,
+}
+
+impl TaskScope<'_> {
+    ///
+    /// Available parallelism, a hint for splitting work into tasks.
+    ///
+    pub fn n_threads(&self) -> usize {
+        *self.n_threads
+    }
+
+    ///
+    /// Runs fun and waits for its tasks. Like std::thread::scope, a panic
+    /// in a task that isn't joined panics here after every task ends.
+    ///
+    pub fn scope<'env, F, R>(&self, fun: F) -> R
+    where
+        F: for<'scope> FnOnce(&'scope Scope<'scope, 'env>) -> R
+    {
+        let scope = Scope {
+            jobs: JobQueue::current(),
+            state: Arc::new(ScopeState {
+                n_pending: AtomicUsize::new(0),
+                n_panics: AtomicUsize::new(0),
+                owner: thread::current(),
+            }),
+            scope: PhantomData,
+            env: PhantomData,
+        };
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| fun(&scope)));
+
+        // tasks borrow from the scope, so every one must end first
+        while scope.state.n_pending.load(Ordering::Acquire) > 0 {
+            scope.help();
+        }
+
+        match result {
+            Ok(_) if scope.state.n_panics.load(Ordering::Acquire) > 0 => {
+                panic!("a scoped task panicked");
+            }
+            Ok(value) => value,
+            Err(err) => panic::resume_unwind(err),
+        }
+    }
+}
+
+impl Param for TaskScope<'_> {
+    type Arg<'w, 's> = TaskScope<'s>;
+    type Local = usize;
+
+    fn init(_meta: &mut SystemMeta, _store: &mut Store) -> Result<Self::Local> {
+        Ok(thread::available_parallelism().map_or(1, NonZeroUsize::get))
+    }
+
+    fn arg<'w, 's>(
+        _store: &'w UnsafeStore,
+        n_threads: &'s mut Self::Local,
+    ) -> Result<Self::Arg<'w, 's>> {
+        Ok(TaskScope { n_threads })
+    }
+}
+
+pub struct Scope<'scope, 'env: 'scope> {
+    jobs: Option<Arc<JobQueue>>,
+    state: Arc<ScopeState>,
+    scope: PhantomData<&'scope mut &'scope ()>,
+    env: PhantomData<&'env mut &'env ()>,
+}
+
+struct ScopeState {
+    n_pending: AtomicUsize,
+    // panics not yet taken by Task::join
+    n_panics: AtomicUsize,
+    owner: Thread,
+}
+
+type TaskResult<T> = Arc<Mutex<Option<thread::Result<T>>>>;
+
+impl<'scope> Scope<'scope, '_> {
+    pub fn spawn<F, T>(&'scope self, task: F) -> Task<'scope, T>
+    where
+        F: FnOnce() -> T + Send + 'scope,
+        T: Send + 'scope
+    {
+        let result: TaskResult<T> = Default::default();
+
+        self.state.n_pending.fetch_add(1, Ordering::AcqRel);
+
+        let job_result = Arc::clone(&result);
+        let state = Arc::clone(&self.state);
+
+        let job: Box<dyn FnOnce() + Send + 'scope> = Box::new(move || {
+            let value = panic::catch_unwind(AssertUnwindSafe(task));
+
+            if value.is_err() {
+                state.n_panics.fetch_add(1, Ordering::AcqRel);
+            }
+
+            *job_result.lock().unwrap() = Some(value);
+
+            // a dropped Task leaves the job holding the result, which can
+            // borrow from the scope, so it's dropped before the scope is
+            // released
+            mem::drop(job_result);
+
+            state.n_pending.fetch_sub(1, Ordering::AcqRel);
+            state.owner.unpark();
+        });
+
+        // SAFETY: TaskScope::scope waits for every job before returning,
+        // so the job's borrows outlive it
+        let job: Box<dyn FnOnce() + Send> = unsafe { mem::transmute(job) };
+
+        match &self.jobs {
+            Some(jobs) => jobs.push(job),
+            None => job(),
+        }
+
+        Task {
+            result,
+            state: Arc::clone(&self.state),
+            jobs: self.jobs.clone(),
+            marker: PhantomData,
+        }
+    }
+
+    fn help(&self) {
+        help(self.jobs.as_deref());
+    }
+}
+
+// runs another queued job while waiting, so a wait can't starve the pool
+fn help(jobs: Option<&JobQueue>) {
+    if ! jobs.is_some_and(|jobs| jobs.run_one()) {
+        // the finishing job unparks the scope's owner, and the timeout
+        // covers a join from another thread
+        thread::park_timeout(Duration::from_millis(1));
+    }
+}
+
+///
+/// Handle to a scoped task. Tasks that aren't joined explicitly are
+/// joined when the scope ends.
+///
+pub struct Task<'scope, T> {
+    result: TaskResult<T>,
+    state: Arc<ScopeState>,
+    jobs: Option<Arc<JobQueue>>,
+    marker: PhantomData<&'scope ()>,
+}
+
+impl<T> Task<'_, T> {
+    ///
+    /// Waits for the task's result. A panic in the task is resumed in
+    /// the system.
+    ///
+    pub fn join(self) -> T {
+        loop {
+            if let Some(value) = self.result.lock().unwrap().take() {
+                match value {
+                    Ok(value) => return value,
+                    Err(err) => {
+                        self.state.n_panics.fetch_sub(1, Ordering::AcqRel);
+                        panic::resume_unwind(err)
+                    }
+                }
+            }
+
+            help(self.jobs.as_deref());
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.result.lock().unwrap().is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashSet,
+        mem,
+        sync::{atomic::{AtomicUsize, Ordering}, Arc, Mutex},
+        thread,
+        time::Duration,
+    };
+
+    use crate::{core_app::{Core, CoreApp}, schedule::Executors, Res, ResMut, Schedule, Store};
+
+    use super::TaskScope;
+
+    #[test]
+    fn task_scope() {
+        let mut app = CoreApp::new();
+        app.insert_resource((1..=1000u64).collect::<Vec<u64>>());
+        app.insert_resource(0u64);
+
+        app.system(Core, |tasks: TaskScope, values: Res<Vec<u64>>, mut sum: ResMut<u64>| {
+            assert!(tasks.n_threads() > 0);
+
+            *sum = tasks.scope(|scope| {
+                let handles: Vec<_> = values.chunks(100)
+                    .map(|chunk| scope.spawn(move || chunk.iter().sum::<u64>()))
+                    .collect();
+
+                handles.into_iter().map(|task| task.join()).sum()
+            });
+        });
+
+        app.tick().unwrap();
+
+        assert_eq!(*app.resource::<u64>(), 500500);
+    }
+
+    #[test]
+    fn task_scope_pool_threads() {
+        let mut world = Store::new();
+        let mut schedule = Schedule::new();
+        schedule.set_executor(Executors::Multithreaded);
+
+        let threads = Arc::new(Mutex::new(HashSet::new()));
+        let ptr = threads.clone();
+
+        schedule.add_system(move |tasks: TaskScope| {
+            tasks.scope(|scope| {
+                for _ in 0..8 {
+                    let ptr = ptr.clone();
+                    scope.spawn(move || { ptr.lock().unwrap().insert(thread::current().id()); });
+                }
+            });
+        });
+
+        for _ in 0..10 {
+            schedule.tick(&mut world).unwrap();
+        }
+
+        // new threads per task would give 80 ids
+        let n_threads = thread::available_parallelism().unwrap().get();
+        assert!(threads.lock().unwrap().len() <= n_threads + 1);
+    }
+
+    #[test]
+    fn task_scope_drop_task() {
+        let mut world = Store::new();
+        let mut schedule = Schedule::new();
+        schedule.set_executor(Executors::Multithreaded);
+
+        schedule.add_system(|tasks: TaskScope| {
+            let drops = AtomicUsize::new(0);
+
+            tasks.scope(|scope| {
+                for _ in 0..8 {
+                    let drops = &drops;
+                    mem::drop(scope.spawn(move || DropCount(drops)));
+                }
+            });
+
+            // each result was dropped before the scope returned
+            assert_eq!(drops.load(Ordering::Acquire), 8);
+        });
+
+        schedule.tick(&mut world).unwrap();
+    }
+
+    struct DropCount<'a>(&'a AtomicUsize);
+
+    impl Drop for DropCount<'_> {
+        fn drop(&mut self) {
+            thread::sleep(Duration::from_millis(10));
+            self.0.fetch_add(1, Ordering::Release);
+        }
+    }
+
+    #[test]
+    fn task_scope_join_panic() {
+        let mut world = Store::new();
+
+        world.eval(|tasks: TaskScope| {
+            tasks.scope(|scope| {
+                let task = scope.spawn(|| -> u32 { panic!("task failed") });
+                let ok = scope.spawn(|| 2);
+
+                let caught = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| task.join()));
+                assert!(caught.is_err());
+                assert_eq!(ok.join(), 2);
+            });
+        }).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "a scoped task panicked")]
+    fn task_scope_unjoined_panic() {
+        let mut world = Store::new();
+
+        world.eval(|tasks: TaskScope| {
+            tasks.scope(|scope| {
+                scope.spawn(|| panic!("task failed"));
+            });
+        }).unwrap();
+    }
+}
@@ -1,3 +1,5 @@
+use std::ops::Deref;
+
 use crate::{
     error::Result,
     schedule::{SystemMeta, UnsafeStore}, 
@@ -23,3 +25,127 @@ impl Param for &Store {
         Ok(())
     }
 }
+
+///
+/// Read-only access to every component and resource, e.g. for debug or
+/// save systems. Unlike &Store it isn't exclusive: it runs after writers
+/// and in parallel with other readers. A system with ReadStore can't
+/// also write, since its reads would alias the writes.
+///
+pub struct ReadStore<'w>(&'w Store);
+
+impl Deref for ReadStore<'_> {
+    type Target = Store;
+
+    fn deref(&self) -> &Self::Target {
+        self.0
+    }
+}
+
+impl Param for ReadStore<'_> {
+    type Arg<'w, 's> = ReadStore<'w>;
+    type Local = ();
+
+    fn arg<'w, 's>(
+        world: &'w UnsafeStore,
+        _state: &'s mut Self::Local,
+    ) -> Result<Self::Arg<'w, 's>> {
+        Ok(ReadStore(world))
+    }
+
+    fn init(meta: &mut SystemMeta, _world: &mut Store) -> Result<Self::Local> {
+        meta.set_read_all();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{core_app::{Core, CoreApp}, entity::Component, schedule::{Executors, SystemMeta}, util::test::TestValues, Query, ResMut};
+
+    use super::ReadStore;
+
+    #[test]
+    fn read_store_after_writer() {
+        let mut app = CoreApp::new();
+        app.set_executor(Executors::Multithreaded);
+        app.insert_resource(0u32);
+
+        let mut values = TestValues::new();
+
+        let mut ptr = values.clone();
+        app.system(Core, move |store: ReadStore| {
+            ptr.push(&format!("read {}", store.resource::<u32>()));
+        });
+
+        app.system(Core, |mut value: ResMut<u32>| *value += 1);
+
+        app.tick().unwrap();
+        assert_eq!(values.take(), "read 1");
+
+        app.tick().unwrap();
+        assert_eq!(values.take(), "read 2");
+    }
+
+    #[test]
+    fn read_store_with_res_mut_rejected() {
+        let mut app = CoreApp::new();
+        app.insert_resource(0u32);
+
+        app.system(Core, |store: ReadStore, mut value: ResMut<u32>| {
+            *value += *store.resource::<u32>();
+        });
+
+        let err = app.tick().unwrap_err();
+        assert!(err.message().contains("reads the whole store"), "{}", err.message());
+    }
+
+    #[test]
+    fn read_store_with_mut_query_rejected() {
+        let mut app = CoreApp::new();
+
+        app.system(Core, |_store: ReadStore, q: Query<&mut TestA>| {
+            for a in q.iter() {
+                a.0 += 1;
+            }
+        });
+
+        let err = app.tick().unwrap_err();
+        assert!(err.message().contains("reads the whole store"), "{}", err.message());
+    }
+
+    #[test]
+    fn read_store_with_mut_each_item_rejected() {
+        let mut app = CoreApp::new();
+
+        app.system(Core, |a: &mut TestA, _store: ReadStore| {
+            a.0 += 1;
+        });
+
+        let err = app.tick().unwrap_err();
+        assert!(err.message().contains("reads the whole store"), "{}", err.message());
+    }
+
+    #[test]
+    fn read_store_conflicts_with_writers_only() {
+        let mut store = crate::Store::new();
+        store.insert_resource(0u32);
+        let id = store.get_resource_id::<u32>();
+
+        let mut read_all = SystemMeta::empty();
+        read_all.set_read_all();
+
+        let mut reader = SystemMeta::empty();
+        reader.insert_resource(id);
+        assert!(read_all.conflict(&reader).is_none());
+
+        let mut writer = SystemMeta::empty();
+        writer.insert_resource_mut(id);
+        assert!(read_all.conflict(&writer).is_some());
+    }
+
+    struct TestA(u32);
+
+    impl Component for TestA {}
+}
@@ -1,8 +1,17 @@
+use core::fmt;
 use std::{collections::HashMap, any::{TypeId, type_name}, ptr::NonNull, alloc::Layout, mem::{ManuallyDrop, self}};
 
+#[cfg(debug_assertions)]
+use std::{collections::BTreeSet, sync::Mutex};
+
+type DebugFn = unsafe fn(NonNull<u8>, &mut fmt::Formatter<'_>) -> fmt::Result;
+
 pub(crate) struct Resources {
     resource_map: HashMap<TypeId,ResourceId>,
     resources: Vec<Option<Resource>>,
+    debug_map: HashMap<TypeId, DebugFn>,
+    #[cfg(debug_assertions)]
+    borrows: Mutex<HashMap<ResourceId, Borrow>>,
 }
 
 impl Resources {
@@ -10,9 +19,33 @@ impl Resources {
         Self {
             resource_map: HashMap::new(),
             resources: Vec::new(),
+            debug_map: HashMap::new(),
+            #[cfg(debug_assertions)]
+            borrows: Mutex::new(HashMap::new()),
         }
     }
 
+    ///
+    /// Registers T's Debug impl, used by ResourceInfo for inspectors.
+    ///
+    pub fn register_debug<T: fmt::Debug + 'static>(&mut self) {
+        self.debug_map.insert(TypeId::of::<T>(), |data, f| unsafe {
+            fmt::Debug::fmt(data.cast::<T>().as_ref(), f)
+        });
+    }
+
+    ///
+    /// Every resource in insertion order, without knowing its type.
+    ///
+    pub fn iter(&self) -> impl Iterator<Item=ResourceInfo<'_>> {
+        self.resources.iter()
+            .filter_map(|resource| resource.as_ref())
+            .map(|resource| ResourceInfo {
+                resource,
+                debug: self.debug_map.get(&resource.type_id).copied(),
+            })
+    }
+
     pub fn insert<T: 'static>(&mut self, value: T) {
         let id = ResourceId::new(self.resources.len());
         let type_id = TypeId::of::<T>();
@@ -21,9 +54,8 @@ impl Resources {
 
         if id.index() == self.resources.len() {
             self.resources.push(Some(Resource::new(id, value)));
-        } else {
-            // TODO: drop
-            self.resources[id.index()] = Some(Resource::new(id, value));
+        } else if let Some(old) = self.resources[id.index()].replace(Resource::new(id, value)) {
+            unsafe { old.drop_value(); }
         }
     }
 
@@ -36,6 +68,10 @@ impl Resources {
         }
     }
 
+    pub(crate) fn find_resource_id(&self, type_id: TypeId) -> Option<ResourceId> {
+        self.resource_map.get(&type_id).copied()
+    }
+
     pub fn get<T: 'static>(&self) -> Option<&T> {
         let type_id = TypeId::of::<T>();
 
@@ -62,10 +98,93 @@ impl Resources {
         }
     }
 
+    ///
+    /// Mutable reference through a shared borrow. Callers must ensure
+    /// no other reference to the same resource is live.
+    ///
+    #[allow(clippy::mut_from_ref)]
+    pub(crate) unsafe fn get_unchecked_mut<T: 'static>(&self) -> Option<&mut T> {
+        let id = self.resource_map.get(&TypeId::of::<T>())?;
+
+        self.resources[id.index()].as_ref().map(|resource| resource.deref_mut())
+    }
+
     pub fn contains_resource<T: 'static>(&self) -> bool {
         let type_id = TypeId::of::<T>();
 
-        self.resource_map.contains_key(&type_id)
+        match self.resource_map.get(&type_id) {
+            Some(id) => self.resources[id.index()].is_some(),
+            None => false,
+        }
+    }
+
+    ///
+    /// Drops every resource whose type keep rejects.
+    ///
+    pub(crate) fn retain(&mut self, mut keep: impl FnMut(TypeId) -> bool) {
+        for (type_id, id) in &self.resource_map {
+            if ! keep(*type_id) {
+                if let Some(resource) = self.resources[id.index()].take() {
+                    unsafe { resource.drop_value(); }
+                }
+            }
+        }
+    }
+
+    ///
+    /// Records the running system as a holder of its resources until the
+    /// guard drops. Panics naming both systems if a resource is already
+    /// held with conflicting access, which means the plan let
+    /// conflicting systems overlap.
+    ///
+    #[cfg(debug_assertions)]
+    pub(crate) fn borrow<'a>(
+        &'a self,
+        system: &'a str,
+        reads: &'a BTreeSet<ResourceId>,
+        writes: &'a BTreeSet<ResourceId>,
+    ) -> ResourceBorrow<'a> {
+        let conflict = {
+            let mut borrows = self.borrows.lock().unwrap();
+
+            let conflict = writes.iter()
+                .filter_map(|id| borrows.get(id).and_then(|b| b.holder()).map(|h| (id, h)))
+                .chain(reads.iter()
+                    .filter(|id| ! writes.contains(id))
+                    .filter_map(|id| borrows.get(id).and_then(|b| b.writer()).map(|h| (id, h))))
+                .next()
+                .map(|(id, holder)| (*id, holder.to_string()));
+
+            if conflict.is_none() {
+                for id in writes {
+                    borrows.entry(*id).or_default().set_writer(system);
+                }
+
+                for id in reads.iter().filter(|id| ! writes.contains(id)) {
+                    borrows.entry(*id).or_default().add_reader(system);
+                }
+            }
+
+            conflict
+        };
+
+        if let Some((id, holder)) = conflict {
+            let name = self.resources.get(id.index())
+                .and_then(|r| r.as_ref())
+                .map_or("unknown", |r| r.name);
+
+            panic!(
+                "resource {} already borrowed: system {:?} holds it while system {:?} runs",
+                name, holder, system
+            );
+        }
+
+        ResourceBorrow {
+            resources: self,
+            system,
+            reads,
+            writes,
+        }
     }
 
     pub fn remove<T: 'static>(&mut self) -> Option<T> {
@@ -82,23 +201,107 @@ impl Resources {
     }
 }
 
+// names are kept in reused strings, so steady-state ticks don't allocate
+#[cfg(debug_assertions)]
+#[derive(Default)]
+struct Borrow {
+    readers: Vec<String>,
+    n_readers: usize,
+    writer: String,
+    is_written: bool,
+}
+
+#[cfg(debug_assertions)]
+impl Borrow {
+    fn holder(&self) -> Option<&str> {
+        self.writer().or(self.readers[..self.n_readers].first().map(|r| r.as_str()))
+    }
+
+    fn writer(&self) -> Option<&str> {
+        if self.is_written { Some(&self.writer) } else { None }
+    }
+
+    fn set_writer(&mut self, system: &str) {
+        self.writer.clear();
+        self.writer.push_str(system);
+        self.is_written = true;
+    }
+
+    fn add_reader(&mut self, system: &str) {
+        if self.n_readers < self.readers.len() {
+            let reader = &mut self.readers[self.n_readers];
+            reader.clear();
+            reader.push_str(system);
+        } else {
+            self.readers.push(system.to_string());
+        }
+
+        self.n_readers += 1;
+    }
+
+    fn remove_reader(&mut self, system: &str) {
+        if let Some(i) = self.readers[..self.n_readers].iter().position(|r| r == system) {
+            self.readers.swap(i, self.n_readers - 1);
+            self.n_readers -= 1;
+        }
+    }
+}
+
+///
+/// A system's hold on its resources, from Resources::borrow.
+///
+#[cfg(debug_assertions)]
+pub(crate) struct ResourceBorrow<'a> {
+    resources: &'a Resources,
+    system: &'a str,
+    reads: &'a BTreeSet<ResourceId>,
+    writes: &'a BTreeSet<ResourceId>,
+}
+
+#[cfg(debug_assertions)]
+impl Drop for ResourceBorrow<'_> {
+    fn drop(&mut self) {
+        let Ok(mut borrows) = self.resources.borrows.lock() else {
+            return;
+        };
+
+        for id in self.writes {
+            if let Some(borrow) = borrows.get_mut(id) {
+                borrow.is_written = false;
+            }
+        }
+
+        for id in self.reads.iter().filter(|id| ! self.writes.contains(id)) {
+            if let Some(borrow) = borrows.get_mut(id) {
+                borrow.remove_reader(self.system);
+            }
+        }
+    }
+}
+
 struct Resource {
-    _id: ResourceId,
-    _name: String,
+    id: ResourceId,
+    type_id: TypeId,
+    name: &'static str,
     //value: Ptr,
     data: NonNull<u8>,
+    layout: Layout,
+    drop: unsafe fn(NonNull<u8>),
 }
 
 impl Resource {
-    pub(crate) fn new<T>(id: ResourceId, value: T) -> Self {
+    pub(crate) fn new<T: 'static>(id: ResourceId, value: T) -> Self {
         let layout = Layout::new::<T>();
         let data = unsafe { std::alloc::alloc(layout) };
         let data = NonNull::new(data).unwrap();
 
         let mut resource = Self {
-            _id: id,
-            _name: type_name::<Self>().to_string(),
+            id,
+            type_id: TypeId::of::<T>(),
+            name: type_name::<T>(),
             data: data,
+            layout,
+            drop: |data| unsafe { data.cast::<T>().drop_in_place() },
 
             // marker: Default::default(),
         };
@@ -134,6 +337,14 @@ impl Resource {
     unsafe fn take<T>(self) -> T {
         self.data.as_ptr().cast::<T>().read()
     }
+
+    unsafe fn drop_value(self) {
+        (self.drop)(self.data);
+
+        if self.layout.size() > 0 {
+            std::alloc::dealloc(self.data.as_ptr(), self.layout);
+        }
+    }
 }
 /*
 impl<T> Drop for Resource<T> {
@@ -145,6 +356,44 @@ impl<T> Drop for Resource<T> {
 }
 */
 
+///
+/// Type-erased view of a resource, from Store::iter_resources.
+///
+pub struct ResourceInfo<'a> {
+    resource: &'a Resource,
+    debug: Option<DebugFn>,
+}
+
+impl ResourceInfo<'_> {
+    pub fn id(&self) -> ResourceId {
+        self.resource.id
+    }
+
+    pub fn type_id(&self) -> TypeId {
+        self.resource.type_id
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.resource.name
+    }
+
+    ///
+    /// The value's Debug output if its type was registered with
+    /// Store::register_resource_debug.
+    ///
+    pub fn debug(&self) -> Option<String> {
+        self.debug.map(|debug| format!("{:?}", DebugValue(self.resource.data, debug)))
+    }
+}
+
+struct DebugValue(NonNull<u8>, DebugFn);
+
+impl fmt::Debug for DebugValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        unsafe { (self.1)(self.0, f) }
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct ResourceId(usize);
 
@@ -162,6 +411,34 @@ impl ResourceId {
 mod tests {
     use super::Resources;
 
+    #[cfg(debug_assertions)]
+    #[test]
+    fn borrow_conflict_names_systems() {
+        use std::{collections::BTreeSet, panic};
+
+        let mut resources = Resources::new();
+        resources.insert(TestA(1));
+
+        let ids: BTreeSet<_> = [resources.get_resource_id::<TestA>()].into();
+        let none = BTreeSet::new();
+
+        {
+            let _a = resources.borrow("reader_a", &ids, &none);
+            let _b = resources.borrow("reader_b", &ids, &none);
+        }
+
+        let _writer = resources.borrow("writer", &none, &ids);
+
+        let err = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            resources.borrow("reader", &ids, &none);
+        })).unwrap_err();
+
+        let msg = err.downcast_ref::<String>().unwrap();
+        assert!(msg.contains("TestA"));
+        assert!(msg.contains("\"writer\""));
+        assert!(msg.contains("\"reader\""));
+    }
+
     #[test]
     fn set_get() {
         let mut resources = Resources::new();
@@ -207,6 +484,29 @@ mod tests {
         assert_eq!(resources.get::<TestA>(), None);
     }
 
+    #[test]
+    fn iter() {
+        let mut resources = Resources::new();
+
+        resources.insert(TestA(1));
+        resources.insert(TestB(2));
+        resources.register_debug::<TestA>();
+
+        let infos: Vec<(usize, String, Option<String>)> = resources.iter()
+            .map(|r| (r.id().index(), r.name().to_string(), r.debug()))
+            .collect();
+
+        assert_eq!(infos.len(), 2);
+        assert_eq!(infos[0].0, 0);
+        assert!(infos[0].1.ends_with("TestA"));
+        assert_eq!(infos[0].2, Some("TestA(1)".to_string()));
+        assert!(infos[1].1.ends_with("TestB"));
+        assert_eq!(infos[1].2, None);
+
+        resources.remove::<TestA>();
+        assert_eq!(resources.iter().count(), 1);
+    }
+
     #[derive(PartialEq, Debug)]
     struct TestA(u32);
 
@@ -1,8 +1,11 @@
+use std::{error, fmt, time::Duration};
+
 use crate::{error::Result, Schedule, Store};
 
 use super::{
     multithreaded::MultithreadedExecutor, 
     plan::Plan, 
+    unsafe_cell::UnsafeSendCell,
     UnsafeStore, 
 };
 
@@ -13,6 +16,49 @@ pub trait Executor: Send {
         schedule: Schedule, 
         store: Store
     ) -> Result<(Schedule, Store)>;
+
+    ///
+    /// Schedule and store of a failed run, if every system had stopped,
+    /// so the caller can keep using the store instead of closing it.
+    /// Later ticks call it again until it returns them.
+    ///
+    fn recover(&mut self) -> Option<(Schedule, Store)> {
+        None
+    }
+
+    ///
+    /// True if a panic stopped the executor, so the schedule replaces it.
+    ///
+    fn is_closed(&self) -> bool {
+        false
+    }
+
+    ///
+    /// Starts the schedule on a store that other schedules are running on
+    /// in the same tick, leaving commands to the caller. Returns the
+    /// schedule if the executor can't run it in the background, and the
+    /// caller runs it serially instead.
+    ///
+    /// # Safety
+    ///
+    /// The store must outlive join_shared, and the other schedules'
+    /// access must not conflict with this schedule's.
+    ///
+    unsafe fn start_shared(
+        &mut self, 
+        schedule: Schedule, 
+        _store: &UnsafeStore
+    ) -> Option<Schedule> {
+        Some(schedule)
+    }
+
+    ///
+    /// Waits for the schedule started by start_shared, returning it with
+    /// the run's result. No system is running on the store on return.
+    ///
+    fn join_shared(&mut self) -> Option<(Schedule, Result<()>)> {
+        None
+    }
 }
 
 pub trait ExecutorFactory: Send + 'static {
@@ -36,7 +82,7 @@ impl Default for Executors {
 impl ExecutorFactory for Executors {
     fn create(&self, plan: Plan) -> Box<dyn Executor> {
         match self {
-            Executors::Single => Box::new(SingleExecutor(plan)),
+            Executors::Single => Box::new(SingleExecutor(plan, UnsafeSendCell::new(None))),
             Executors::Multithreaded => {
                 Box::new(MultithreadedExecutor::new(plan))
             },
@@ -48,12 +94,101 @@ impl ExecutorFactory for Executors {
     }
 }
 
+///
+/// Resource with metrics from the latest multithreaded tick, to check
+/// whether a schedule actually runs in parallel.
+///
+#[derive(Debug, Clone, Default)]
+pub struct ExecutorDiagnostics {
+    pub(crate) tick_time: Duration,
+    pub(crate) parent_busy: Duration,
+    pub(crate) worker_busy: Vec<Duration>,
+    pub(crate) max_queue_depth: usize,
+    pub(crate) n_serial: usize,
+    pub(crate) n_parallel: usize,
+    pub(crate) n_markers: usize,
+}
+
+impl ExecutorDiagnostics {
+    pub fn tick_time(&self) -> Duration {
+        self.tick_time
+    }
+
+    ///
+    /// Time spent running systems on the executor's own thread.
+    ///
+    pub fn parent_busy(&self) -> Duration {
+        self.parent_busy
+    }
+
+    pub fn worker_busy(&self) -> &[Duration] {
+        &self.worker_busy
+    }
+
+    pub fn max_queue_depth(&self) -> usize {
+        self.max_queue_depth
+    }
+
+    ///
+    /// Phase markers resolved by the executor without a dispatch.
+    ///
+    pub fn n_markers(&self) -> usize {
+        self.n_markers
+    }
+
+    ///
+    /// Fraction of systems run serially on the executor thread.
+    ///
+    pub fn serial_ratio(&self) -> f32 {
+        let total = self.n_serial + self.n_parallel;
+
+        if total > 0 {
+            self.n_serial as f32 / total as f32
+        } else {
+            0.
+        }
+    }
+
+    ///
+    /// Fraction of available worker time spent running systems.
+    ///
+    pub fn utilization(&self) -> f32 {
+        let available = self.tick_time.as_secs_f32() * self.worker_busy.len() as f32;
+
+        if available > 0. {
+            let busy: Duration = self.worker_busy.iter().sum();
+
+            busy.as_secs_f32() / available
+        } else {
+            0.
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScheduleErr {
+    Timeout { system: String, elapsed: Duration },
+}
+
+impl fmt::Display for ScheduleErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScheduleErr::Timeout { system, elapsed } => {
+                write!(f, "system {} timed out after {:?}", system, elapsed)
+            }
+        }
+    }
+}
+
+impl error::Error for ScheduleErr {}
+
 impl Default for Box<dyn ExecutorFactory> {
     fn default() -> Self {
         Executors::default().box_clone()
     }
 }
-struct SingleExecutor(Plan);
+// the failed run is only accessed through &mut self
+struct SingleExecutor(Plan, UnsafeSendCell<Option<(Schedule, Store)>>);
 
 impl SingleExecutor {
     fn run_inner(
@@ -84,7 +219,11 @@ impl Executor for SingleExecutor {
     ) -> Result<(Schedule, Store)> {
         let mut world = UnsafeStore::new(world);
 
-        self.run_inner(&mut schedule, &mut world)?;
+        if let Err(err) = self.run_inner(&mut schedule, &mut world) {
+            unsafe { *self.1.as_mut() = Some((schedule, world.take())); }
+
+            return Err(err);
+        }
         /*
         for id in self.0.order() {
             let meta = schedule.meta(*id);
@@ -100,4 +239,8 @@ impl Executor for SingleExecutor {
 
         Ok((schedule, world.take()))
     }
+
+    fn recover(&mut self) -> Option<(Schedule, Store)> {
+        unsafe { self.1.as_mut().take() }
+    }
 }
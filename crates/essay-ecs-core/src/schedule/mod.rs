@@ -6,27 +6,35 @@ mod multithreaded;
 mod plan;
 mod thread_pool;
 mod phase;
-mod preorder;
 mod schedule;
+mod observer;
 
 use essay_ecs_core_macros::Phase;
 
-pub use planner::SystemMeta;
+pub use planner::{SystemMeta, Priority, CycleStrategy};
 
 pub use schedule::{
     Schedules, Schedule, ScheduleLabel, BoxedLabel,
 };
 
 pub use system::{
-    IntoSystemConfig,
+    IntoSystemConfig, SystemLabel,
 };
 
 pub use executor::{
-    Executors, Executor, ExecutorFactory,
+    Executors, Executor, ExecutorFactory, ScheduleErr, ExecutorDiagnostics,
 };
 
+pub use multithreaded::Watchdog;
+
+pub(crate) use thread_pool::JobQueue;
+
 pub use unsafe_cell::UnsafeStore;
 
+pub use plan::PlanExport;
+
+pub use observer::{SystemObserver, PlanHook, PlanView};
+
 pub use phase::{
-    DefaultPhase, Phase, IntoPhaseConfig, IntoPhaseConfigs,
+    DefaultPhase, Phase, PhaseId, IntoPhaseConfig, IntoPhaseConfigs,
 };
@@ -1,25 +1,64 @@
-use std::sync::Arc;
+use std::{
+    cell::{Cell, RefCell},
+    cmp::Reverse, 
+    sync::{atomic::{AtomicBool, AtomicUsize, Ordering}, Arc, Mutex}, 
+    thread,
+    time::{Duration, Instant}
+};
 
 use fixedbitset::FixedBitSet;
+use log::warn;
 
 use crate::{
-    error::Result,
+    error::{Error, Result},
     Schedule, Store,
     system::SystemId,
 };    
 
 use super::{
-    executor::{Executor, ExecutorFactory},
+    executor::{Executor, ExecutorFactory, ScheduleErr, ExecutorDiagnostics},
     thread_pool::{ThreadPool, TaskSender, ThreadPoolBuilder}, 
     plan::Plan, 
     unsafe_cell::UnsafeSendCell, UnsafeStore
 };
 
-type ArcWorld = Arc<UnsafeSendCell<Option<UnsafeStore>>>;
+type ArcWorld = Arc<UnsafeSendCell<Option<TickWorld>>>;
 type ArcSchedule = Arc<UnsafeSendCell<Option<Schedule>>>;
+type WorkerBusy = Arc<Mutex<Vec<Duration>>>;
+type ArcSettled = Arc<AtomicBool>;
+type ArcRunning = Arc<AtomicUsize>;
 
 pub struct MultithreadedExecutorFactory;
 
+// store of the running tick, owned by the executor or shared with other
+// schedules by Store::run_schedules_parallel. It's stored once per
+// executor, and boxing would allocate every tick.
+#[allow(clippy::large_enum_variant)]
+enum TickWorld {
+    Owned(UnsafeStore),
+    Shared(*mut UnsafeStore),
+}
+
+impl TickWorld {
+    unsafe fn get_ref(&self) -> &UnsafeStore {
+        match self {
+            TickWorld::Owned(world) => world,
+            TickWorld::Shared(world) => &**world,
+        }
+    }
+
+    unsafe fn get_mut(&mut self) -> &mut UnsafeStore {
+        match self {
+            TickWorld::Owned(world) => world,
+            TickWorld::Shared(world) => &mut **world,
+        }
+    }
+
+    fn is_shared(&self) -> bool {
+        matches!(self, TickWorld::Shared(_))
+    }
+}
+
 impl ExecutorFactory for MultithreadedExecutorFactory {
     fn create(&self, plan: Plan) -> Box<dyn Executor> {
         Box::new(MultithreadedExecutor::new(plan))
@@ -30,38 +69,164 @@ impl ExecutorFactory for MultithreadedExecutorFactory {
     }
 }
 
+///
+/// Multithreaded executor that reports child systems running longer than
+/// timeout, and optionally aborts the tick with ScheduleErr::Timeout.
+/// An aborted system keeps running on its thread. The next tick after
+/// it finishes recovers the store, and ticks before then fail.
+///
+#[derive(Clone)]
+pub struct Watchdog {
+    timeout: Duration,
+    is_abort: bool,
+    timeouts: Arc<Mutex<Vec<String>>>,
+}
+
+impl Watchdog {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            is_abort: false,
+            timeouts: Default::default(),
+        }
+    }
+
+    pub fn abort(mut self, is_abort: bool) -> Self {
+        self.is_abort = is_abort;
+
+        self
+    }
+
+    ///
+    /// Names of systems that have exceeded the timeout, in order.
+    ///
+    pub fn timeouts(&self) -> Vec<String> {
+        self.timeouts.lock().unwrap().clone()
+    }
+
+    fn check(&self, schedule: &Schedule, running: &mut [Running]) -> Result<()> {
+        for item in running.iter_mut().filter(|item| ! item.is_timeout) {
+            let elapsed = item.start.elapsed();
+
+            if elapsed >= self.timeout {
+                item.is_timeout = true;
+
+                let name = schedule.meta(item.id).name().to_string();
+                warn!("system {} has run for {:?}", name, elapsed);
+                self.timeouts.lock().unwrap().push(name.clone());
+
+                if self.is_abort {
+                    return Err(Error::other(ScheduleErr::Timeout { 
+                        system: name, 
+                        elapsed 
+                    }));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl ExecutorFactory for Watchdog {
+    fn create(&self, plan: Plan) -> Box<dyn Executor> {
+        Box::new(MultithreadedExecutor::new_with_watchdog(plan, Some(self.clone())))
+    }
+
+    fn box_clone(&self) -> Box<dyn ExecutorFactory> {
+        Box::new(self.clone())
+    }
+}
+
 pub struct MultithreadedExecutor {
     thread_pool: Option<ThreadPool>,
     
     schedule: ArcSchedule,
     world: ArcWorld,
+    // true when the last tick ended with no systems running
+    is_settled: ArcSettled,
+    // children running a system, including ones left by an aborted tick
+    n_running: ArcRunning,
 }
 
 struct ParentTask {
     plan: Plan,
+    watchdog: Option<Watchdog>,
 
     schedule: ArcSchedule,
     world: ArcWorld,
+    busy: WorkerBusy,
+    is_settled: ArcSettled,
+    n_running: ArcRunning,
+    // completions an aborted tick left unread
+    n_stale: Cell<usize>,
+
+    // kept across ticks, so a steady-state tick doesn't allocate
+    buffers: RefCell<TickBuffers>,
+}
+
+#[derive(Default)]
+struct TickBuffers {
+    n_incoming: Vec<usize>,
+    ready: FixedBitSet,
+    order: Vec<usize>,
+    pending: Vec<SystemId>,
+    markers: Vec<SystemId>,
+    started: Vec<usize>,
+    completed: Vec<SystemId>,
+    running: Vec<Running>,
+    children: Vec<SystemId>,
+    main_systems: Vec<SystemId>,
 }
 
 struct ChildTask {
     world: ArcWorld,
     schedule: ArcSchedule,
+
+    worker: usize,
+    busy: WorkerBusy,
+    n_running: ArcRunning,
+}
+
+struct Running {
+    id: SystemId,
+    start: Instant,
+    is_timeout: bool,
 }
 
 impl MultithreadedExecutor {
     pub fn new(plan: Plan) -> Self {
+        Self::new_with_watchdog(plan, None)
+    }
+
+    fn new_with_watchdog(plan: Plan, watchdog: Option<Watchdog>) -> Self {
         let arc_schedule: ArcSchedule = Arc::new(UnsafeSendCell::new(None));
         let arc_world: ArcWorld = Arc::new(UnsafeSendCell::new(None));
+        let busy: WorkerBusy = Default::default();
+        let is_settled: ArcSettled = Default::default();
+        let n_running: ArcRunning = Default::default();
+
+        // every system in the plan can be queued or completed at once,
+        // plus the parent's completion message
+        let capacity = plan.len() + 1;
 
         let parent_task = ParentTask {
             plan,
+            watchdog,
             schedule: arc_schedule.clone(),
             world: arc_world.clone(),
+            busy: busy.clone(),
+            is_settled: is_settled.clone(),
+            n_running: n_running.clone(),
+            n_stale: Default::default(),
+            buffers: Default::default(),
         };
 
+        let n_workers = AtomicUsize::new(0);
+
         let arc_schedule_child: ArcSchedule = Arc::clone(&arc_schedule);
         let arc_world_child: ArcWorld = Arc::clone(&arc_world);
+        let n_running_child: ArcRunning = Arc::clone(&n_running);
 
         let pool = ThreadPoolBuilder::new().parent(
             move |sender| {
@@ -70,15 +235,20 @@ impl MultithreadedExecutor {
             let child_task = ChildTask::new(
                 Arc::clone(&arc_schedule_child),
                 Arc::clone(&arc_world_child),
+                n_workers.fetch_add(1, Ordering::Relaxed),
+                Arc::clone(&busy),
+                Arc::clone(&n_running_child),
             );
 
             Box::new(move |s| { child_task.run(s).unwrap(); })
-        }).build();
+        }).capacity(capacity).build();
 
         Self {
             thread_pool: Some(pool),
             schedule: arc_schedule,
             world: arc_world,
+            is_settled,
+            n_running,
         }
     }
 
@@ -89,6 +259,17 @@ impl MultithreadedExecutor {
 
         Ok(())
     }
+
+    unsafe fn run_main(schedule: &ArcSchedule, world: &ArcWorld, id: SystemId) -> Result<()> {
+        match (schedule.get_ref(), world.as_mut()) {
+            // the plan orders exclusive systems after all others
+            (Some(schedule), Some(world)) if schedule.meta(id).is_exclusive() => {
+                schedule.run_system(id, world.get_mut())
+            }
+            (Some(schedule), Some(world)) => schedule.run_unsafe(id, world.get_ref()),
+            _ => Err(format!("unset world\n\tin {}:{}", file!(), line!()).into()),
+        }
+    }
 }
 
 impl Executor for MultithreadedExecutor {
@@ -100,25 +281,109 @@ impl Executor for MultithreadedExecutor {
         match &self.thread_pool {
             Some(thread_pool) => { 
                 unsafe {
-                    self.world.as_mut().replace(UnsafeStore::new(world));
+                    self.world.as_mut().replace(TickWorld::Owned(UnsafeStore::new(world)));
                     self.schedule.as_mut().replace(schedule);
                 }
 
-                thread_pool.start()?;
+                let schedule = &self.schedule;
+                let world = &self.world;
+
+                thread_pool.start_with(|id| unsafe {
+                    Self::run_main(schedule, world, id)
+                })?;
     
                 let world = unsafe { self.world.as_mut().take() };
                 let schedule = unsafe { self.schedule.as_mut().take() };
     
-                Ok((schedule.unwrap(), world.unwrap().take()))
+                match world {
+                    Some(TickWorld::Owned(world)) => Ok((schedule.unwrap(), world.take())),
+                    _ => Err(format!("unset world\n\tin {}:{}", file!(), line!()).into()),
+                }
             },
             None => { Err("thread pool is closed".into()) }
         }
     }
+
+    fn recover(&mut self) -> Option<(Schedule, Store)> {
+        // a panic or watchdog abort can leave systems running on the store,
+        // though a panic's unstarted systems never run once the pool stops
+        if ! self.is_settled.load(Ordering::Acquire)
+            && self.n_running.load(Ordering::Acquire) > 0
+            && ! self.thread_pool.as_ref().is_some_and(ThreadPool::is_stopped) {
+            return None;
+        }
+
+        let world = unsafe { self.world.as_mut().take() };
+        let schedule = unsafe { self.schedule.as_mut().take() };
+
+        match world? {
+            TickWorld::Owned(world) => Some((schedule?, world.take())),
+            TickWorld::Shared(_) => None,
+        }
+    }
+
+    fn is_closed(&self) -> bool {
+        ! self.thread_pool.as_ref().is_some_and(ThreadPool::is_alive)
+    }
+
+    unsafe fn start_shared(
+        &mut self, 
+        schedule: Schedule, 
+        store: &UnsafeStore
+    ) -> Option<Schedule> {
+        let Some(thread_pool) = &self.thread_pool else { return Some(schedule) };
+
+        // children only use the shared store through UnsafeStore's cell
+        let world = store as *const UnsafeStore as *mut UnsafeStore;
+        self.world.as_mut().replace(TickWorld::Shared(world));
+        self.schedule.as_mut().replace(schedule);
+
+        if thread_pool.begin().is_err() {
+            self.world.as_mut().take();
+
+            return self.schedule.as_mut().take();
+        }
+
+        None
+    }
+
+    fn join_shared(&mut self) -> Option<(Schedule, Result<()>)> {
+        let result = match &self.thread_pool {
+            Some(thread_pool) => {
+                let schedule = &self.schedule;
+                let world = &self.world;
+
+                let result = thread_pool.wait_with(|id| unsafe {
+                    Self::run_main(schedule, world, id)
+                });
+
+                // the caller owns the store, so systems an aborted tick left
+                // running must finish before it gets it back
+                while result.is_err()
+                    && ! self.is_settled.load(Ordering::Acquire)
+                    && self.n_running.load(Ordering::Acquire) > 0
+                    && ! thread_pool.is_stopped() {
+                    thread::sleep(Duration::from_millis(1));
+                }
+
+                result
+            }
+            None => Err("thread pool is closed".into()),
+        };
+
+        unsafe { self.world.as_mut().take(); }
+        let schedule = unsafe { self.schedule.as_mut().take() };
+
+        Some((schedule?, result))
+    }
 }
 
 impl Drop for MultithreadedExecutor {
     fn drop(&mut self) {
-        self.close().unwrap();
+        // a pool stopped by a panic reports it when closing
+        if let Err(err) = self.close() {
+            warn!("closing executor after a panic: {}", err.message());
+        }
     }
 }
 
@@ -126,7 +391,11 @@ impl ParentTask {
     fn run(&self, sender: &TaskSender) -> Result<()> {
         if let Some(schedule) = unsafe { self.schedule.as_mut() } {
             if let Some(world) = unsafe { self.world.as_mut() } {
-                return self.run_impl(sender, schedule, world)
+                self.is_settled.store(false, Ordering::Release);
+
+                let is_shared = world.is_shared();
+
+                return self.run_impl(sender, schedule, unsafe { world.get_mut() }, is_shared)
             }
         }
 
@@ -137,31 +406,81 @@ impl ParentTask {
         &self, 
         sender: &TaskSender,
         schedule: &mut Schedule,
-        world: &mut UnsafeStore
+        world: &mut UnsafeStore,
+        is_shared: bool,
     ) -> Result<()> {
         let n = self.plan.len();
         let mut n_active: usize = 0;
         let mut n_remaining = self.plan.len();
-        let mut n_incoming = self.plan.n_incoming().clone();
         let mut n_ready: usize = 0;
         let mut n_child: usize = 0;
 
-        let mut ready = FixedBitSet::with_capacity(n);
+        // completions of the aborted tick's systems, which have finished
+        // because the store was recovered
+        for _ in 0..self.n_stale.replace(0) {
+            sender.read();
+        }
+
+        let mut buffers = self.buffers.borrow_mut();
+        let TickBuffers {
+            n_incoming,
+            ready,
+            order,
+            pending,
+            markers,
+            started,
+            completed,
+            running,
+            children,
+            main_systems,
+        } = &mut *buffers;
+
+        n_incoming.clone_from(self.plan.n_incoming());
+
+        ready.clear();
+        ready.grow(n);
+
+        // buffers from a failed tick may hold stale entries
+        started.clear();
+        completed.clear();
+        running.clear();
+        children.clear();
+        main_systems.clear();
 
         for (i, n) in n_incoming.iter().enumerate() {
             if *n == 0 {
-                ready.set(i, true);
-                n_ready += 1;
+                let id = self.plan.system_id(i);
+
+                if schedule.meta(id).is_marker() {
+                    markers.push(id);
+                } else {
+                    ready.set(i, true);
+                    n_ready += 1;
+                }
             }
         }
 
-        let mut started = Vec::<usize>::new();
-        let mut completed = Vec::<SystemId>::new();
+        let tick_start = Instant::now();
+        let mut diagnostics = ExecutorDiagnostics::default();
+
+        for busy in self.busy.lock().unwrap().iter_mut() {
+            *busy = Duration::ZERO;
+        }
+
+        for id in markers.drain(..) {
+            let (n_new, n_markers) = self.update_ready(schedule, id, n_incoming, ready, pending);
+
+            n_ready += n_new;
+            n_remaining -= n_markers + 1;
+            diagnostics.n_markers += n_markers + 1;
+        }
 
         while n_remaining + n_active > 0 {
             assert!(n_ready + n_active > 0);
 
-            for order_id in ready.ones() {
+            self.ready_order(schedule, ready, order);
+
+            for order_id in order.drain(..) {
                 let id = self.plan.system_id(order_id);
 
                 started.push(order_id);
@@ -169,21 +488,35 @@ impl ParentTask {
 
                 let meta = schedule.meta(id);
 
-                if meta.is_marker() {
-                    completed.push(id);
+                if meta.is_main_thread() {
+                    // the parent runs on the pool's thread, not the caller's
+                    main_systems.push(id);
+
+                    n_child += 1;
                 } else if meta.is_exclusive() {
                     assert_eq!(n_active, 1);
 
-                    unsafe { schedule.run_system(id, world)?; }
+                    let start = Instant::now();
+                    unsafe { self.run_serial(schedule, id, world)?; }
+                    diagnostics.parent_busy += start.elapsed();
+                    diagnostics.n_serial += 1;
 
                     completed.push(id);
                 } else if n_ready == 1 && n_active == 1 {
                     // only one task in this ready-set
-                    unsafe { schedule.run_system(id, world)?; }
+                    let start = Instant::now();
+                    unsafe { self.run_serial(schedule, id, world)?; }
+                    diagnostics.parent_busy += start.elapsed();
+                    diagnostics.n_serial += 1;
 
                     completed.push(id);
                 } else {
-                    sender.send(id);
+                    self.n_running.fetch_add(1, Ordering::AcqRel);
+                    children.push(id);
+
+                    if self.watchdog.is_some() {
+                        running.push(Running { id, start: Instant::now(), is_timeout: false });
+                    }
 
                     n_child += 1;
                 }
@@ -194,54 +527,183 @@ impl ParentTask {
                 n_ready -= 1;
             }
 
-            if n_child > 0 {
-                sender.flush();
+            // the whole ready set goes to the children with one wakeup
+            sender.send_all(children);
+            diagnostics.n_parallel += children.len();
+            children.clear();
+
+            for id in main_systems.drain(..) {
+                sender.send_main(id);
+                diagnostics.n_serial += 1;
+            }
+
+            diagnostics.max_queue_depth = diagnostics.max_queue_depth.max(n_child);
 
-                n_child -= self.read_completed(sender, &mut completed);
+            if n_child > 0 {
+                n_child -= match self.read_completed(
+                    sender, 
+                    schedule, 
+                    running, 
+                    completed
+                ) {
+                    Ok(n_read) => n_read,
+                    Err(err) => {
+                        self.n_stale.set(n_child);
+                        return Err(err);
+                    }
+                };
             }
 
             for id in completed.drain(..) {
-                n_ready += self.update_ready(id, &mut n_incoming, &mut ready);
+                let (n_new, n_markers) = self.update_ready(schedule, id, n_incoming, ready, pending);
 
+                n_ready += n_new;
                 n_active -= 1;
-                n_remaining -= 1;
+                n_remaining -= n_markers + 1;
+                diagnostics.n_markers += n_markers;
             }
 
         }
 
+        diagnostics.tick_time = tick_start.elapsed();
+
+        // all children are complete, so the parent has exclusive access,
+        // unless other schedules share the store
+        if ! is_shared {
+            self.update_diagnostics(world, diagnostics);
+        }
+
+        self.is_settled.store(true, Ordering::Release);
+
         Ok(())
     }
 
+    fn update_diagnostics(&self, world: &mut UnsafeStore, mut diagnostics: ExecutorDiagnostics) {
+        match world.get_resource_mut::<ExecutorDiagnostics>() {
+            Some(prev) => {
+                // reuses the previous tick's worker_busy allocation
+                let mut worker_busy = std::mem::take(&mut prev.worker_busy);
+                worker_busy.clone_from(&self.busy.lock().unwrap());
+
+                *prev = ExecutorDiagnostics { worker_busy, ..diagnostics };
+            }
+            None => {
+                diagnostics.worker_busy = self.busy.lock().unwrap().clone();
+                world.insert_resource(diagnostics);
+            }
+        }
+    }
+
+    // runs a system when no children are running, so an error leaves the
+    // store recoverable
+    unsafe fn run_serial(
+        &self,
+        schedule: &Schedule,
+        id: SystemId,
+        world: &mut UnsafeStore
+    ) -> Result<()> {
+        let result = schedule.run_system(id, world);
+
+        if result.is_err() {
+            self.is_settled.store(true, Ordering::Release);
+        }
+
+        result
+    }
+
+    ///
+    /// Ready systems in descending priority, keeping plan order for ties.
+    ///
+    fn ready_order(&self, schedule: &Schedule, ready: &FixedBitSet, order: &mut Vec<usize>) {
+        order.clear();
+        order.extend(ready.ones());
+
+        order.sort_by_key(|order_id| {
+            Reverse(schedule.meta(self.plan.system_id(*order_id)).priority())
+        });
+    }
+
     fn read_completed(
         &self, 
         sender: &TaskSender, 
+        schedule: &Schedule,
+        running: &mut Vec<Running>,
         completed: &mut Vec<SystemId>
-    ) -> usize {
-        // TODO! multiread when available
-        let id = sender.read(); 
+    ) -> Result<usize> {
+        let id = match &self.watchdog {
+            Some(watchdog) => loop {
+                if let Some(id) = sender.read_timeout(watchdog.timeout) {
+                    break id;
+                }
+
+                watchdog.check(schedule, running)?;
+            },
+            None => sender.read(),
+        };
 
+        let start = completed.len();
         completed.push(id);
-        1
+
+        // drain other completions without blocking
+        while let Some(id) = sender.try_read() {
+            completed.push(id);
+        }
+
+        let n_read = completed.len() - start;
+
+        if ! running.is_empty() {
+            running.retain(|item| ! completed[start..].contains(&item.id));
+        }
+
+        Ok(n_read)
     }
 
+    ///
+    /// Marks the systems after id as ready. Markers do nothing, so a run
+    /// of markers is resolved here instead of costing a dispatch round each.
+    /// Returns the new ready count and the number of markers resolved.
+    ///
     fn update_ready(
         &self, 
+        schedule: &Schedule,
         id: SystemId, 
-        n_incoming: &mut Vec<usize>,
-        ready: &mut FixedBitSet
-    ) -> usize {
+        n_incoming: &mut [usize],
+        ready: &mut FixedBitSet,
+        pending: &mut Vec<SystemId>,
+    ) -> (usize, usize) {
         let mut n_ready = 0;
+        let mut n_markers = 0;
+
+        pending.clear();
+        pending.push(id);
 
-        for outgoing in self.plan.outgoing(id) {
-            n_incoming[*outgoing] -= 1;
+        while let Some(id) = pending.pop() {
+            for outgoing in self.plan.outgoing(id) {
+                n_incoming[*outgoing] -= 1;
 
-            if n_incoming[*outgoing] == 0 {
-                ready.set(*outgoing, true);
-                n_ready += 1;
+                if n_incoming[*outgoing] == 0 {
+                    let next = self.plan.system_id(*outgoing);
+
+                    if schedule.meta(next).is_marker() {
+                        pending.push(next);
+                        n_markers += 1;
+                    } else {
+                        ready.set(*outgoing, true);
+                        n_ready += 1;
+                    }
+                }
             }
         }
 
-        n_ready
+        (n_ready, n_markers)
+    }
+}
+
+struct RunningGuard<'a>(&'a ArcRunning);
+
+impl Drop for RunningGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
     }
 }
 
@@ -249,34 +711,300 @@ impl ChildTask {
     fn new(
         schedule: ArcSchedule,
         world: ArcWorld,
+        worker: usize,
+        busy: WorkerBusy,
+        n_running: ArcRunning,
     ) -> Self {
+        {
+            let mut busy = busy.lock().unwrap();
+
+            if busy.len() <= worker {
+                busy.resize(worker + 1, Duration::ZERO);
+            }
+        }
+
         Self {
             schedule,
-            world
+            world,
+            worker,
+            busy,
+            n_running,
         }
     }
 
     fn run(&self, id: SystemId) -> Result<()> {
+        // a panicking system still stops running on the store
+        let _running = RunningGuard(&self.n_running);
+
         if let Some(schedule) = unsafe { self.schedule.get_ref() } {
             if let Some(world) = unsafe { self.world.get_ref() } {
-                return unsafe { schedule.run_unsafe(id, world) };
+                let start = Instant::now();
+                let result = unsafe { schedule.run_unsafe(id, world.get_ref()) };
+                self.add_busy(start.elapsed());
+
+                return result;
             }
         }
 
         Err(format!("unset world\n\tin {}:{}", file!(), line!()).into())
     }
+
+    fn add_busy(&self, elapsed: Duration) {
+        self.busy.lock().unwrap()[self.worker] += elapsed;
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::{thread, time::Duration, sync::{Arc, Mutex}};
+    use std::{thread, time::Duration, sync::{atomic::{AtomicBool, AtomicUsize, Ordering}, Arc, Mutex}};
+
+    use fixedbitset::FixedBitSet;
 
     use crate::{Store, Schedule, 
         schedule::{Phase,IntoPhaseConfigs, 
-            Executor, ExecutorFactory}, IntoSystemConfig,
+            Executor, ExecutorFactory, ExecutorDiagnostics, Priority}, IntoSystemConfig, ResMut,
     };
 
-    use super::{MultithreadedExecutor, MultithreadedExecutorFactory};
+    use super::{
+        MultithreadedExecutor, MultithreadedExecutorFactory, Watchdog, ParentTask,
+        UnsafeSendCell,
+    };
+
+    #[test]
+    fn ready_order_priority() {
+        let mut schedule = Schedule::new();
+        let mut world = Store::new();
+
+        schedule.add_system(fast_system);
+        schedule.add_system(slow_system.priority(Priority::HIGH));
+        schedule.add_system(fast_system.priority(Priority::LOW));
+        schedule.add_system(slow_system);
+
+        schedule.init(&mut world).unwrap();
+
+        let plan = schedule.plan();
+        let n = plan.len();
+
+        let task = ParentTask {
+            plan,
+            watchdog: None,
+            schedule: Arc::new(UnsafeSendCell::new(None)),
+            world: Arc::new(UnsafeSendCell::new(None)),
+            busy: Default::default(),
+            is_settled: Default::default(),
+            n_running: Default::default(),
+            n_stale: Default::default(),
+            buffers: Default::default(),
+        };
+
+        let mut ready = FixedBitSet::with_capacity(n);
+        ready.set_range(.., true);
+
+        let mut order = Vec::new();
+        task.ready_order(&schedule, &ready, &mut order);
+
+        let priorities: Vec<u32> = order
+            .iter()
+            .map(|i| schedule.meta(task.plan.system_id(*i)).priority().value())
+            .collect();
+
+        let mut sorted = priorities.clone();
+        sorted.sort_by(|a, b| b.cmp(a));
+
+        assert_eq!(priorities, sorted);
+        assert_eq!(priorities[0], Priority::HIGH.value());
+        assert_eq!(priorities[n - 1], Priority::LOW.value());
+    }
+
+    #[test]
+    fn ready_set_larger_than_channels() {
+        let mut world = Store::new();
+        let mut schedule = Schedule::new();
+
+        let n_run = Arc::new(AtomicUsize::new(0));
+
+        // more completions than the default channel capacity in one ready set
+        for _ in 0..3000 {
+            let ptr = n_run.clone();
+            schedule.add_system(move || { ptr.fetch_add(1, Ordering::Relaxed); });
+        }
+
+        schedule.set_executor(MultithreadedExecutorFactory);
+
+        schedule.tick(&mut world).unwrap();
+        assert_eq!(n_run.load(Ordering::Relaxed), 3000);
+    }
+
+    #[test]
+    fn watchdog_timeout() {
+        let mut world = Store::new();
+
+        let watchdog = Watchdog::new(Duration::from_millis(50));
+        let mut schedule = new_schedule_slow();
+        schedule.set_executor(watchdog.clone());
+
+        schedule.tick(&mut world).unwrap();
+
+        assert!(watchdog.timeouts().iter().any(|name| name.contains("slow_system")));
+    }
+
+    #[test]
+    fn watchdog_abort() {
+        let mut world = Store::new();
+
+        let watchdog = Watchdog::new(Duration::from_millis(50)).abort(true);
+        let mut schedule = new_schedule_slow();
+        schedule.set_executor(watchdog.clone());
+
+        let err = schedule.tick(&mut world).unwrap_err();
+
+        assert!(err.message().contains("timed out"));
+        assert!(! watchdog.timeouts().is_empty());
+    }
+
+    #[test]
+    fn watchdog_abort_recover() {
+        let mut world = Store::new();
+        world.insert_resource(0u32);
+
+        let is_slow = Arc::new(AtomicBool::new(true));
+
+        let mut schedule = Schedule::new();
+        let ptr = is_slow.clone();
+        schedule.add_system(move || {
+            if ptr.swap(false, Ordering::Relaxed) {
+                slow_system();
+            }
+        });
+        schedule.add_system(|mut n: ResMut<u32>| *n += 1);
+        schedule.add_system(fast_system);
+        schedule.set_executor(Watchdog::new(Duration::from_millis(50)).abort(true));
+
+        assert!(schedule.tick(&mut world).is_err());
+
+        let err = schedule.tick(&mut world).unwrap_err();
+        assert!(err.message().contains("still running"));
+
+        thread::sleep(Duration::from_millis(600));
+
+        schedule.tick(&mut world).unwrap();
+        assert_eq!(*world.resource::<u32>(), 2);
+
+        schedule.tick(&mut world).unwrap();
+        assert_eq!(*world.resource::<u32>(), 3);
+    }
+
+    #[test]
+    fn panic_recover() {
+        let mut world = Store::new();
+        world.insert_resource(0u32);
+
+        let is_panic = Arc::new(AtomicBool::new(true));
+
+        let mut schedule = Schedule::new();
+        schedule.set_executor(MultithreadedExecutorFactory);
+
+        let ptr = is_panic.clone();
+        schedule.add_system(move || {
+            if ptr.swap(false, Ordering::Relaxed) {
+                panic!("system panic");
+            }
+        });
+        schedule.add_system(|mut n: ResMut<u32>| *n += 1);
+        schedule.add_system(fast_system);
+
+        assert!(schedule.tick(&mut world).is_err());
+
+        // other systems of the failed tick may still be finishing
+        let mut n_retry = 0;
+        while let Err(err) = schedule.tick(&mut world) {
+            assert!(err.message().contains("still running"), "{}", err.message());
+            assert!(n_retry < 1000);
+            n_retry += 1;
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        let n = *world.resource::<u32>();
+        schedule.tick(&mut world).unwrap();
+        assert_eq!(*world.resource::<u32>(), n + 1);
+    }
+
+    #[test]
+    fn main_thread_system() {
+        let mut world = Store::new();
+        let mut schedule = Schedule::new();
+        schedule.set_executor(MultithreadedExecutorFactory);
+
+        let threads = Arc::new(Mutex::new(Vec::new()));
+
+        let ptr = threads.clone();
+        schedule.add_system((move || {
+            ptr.lock().unwrap().push(("main", thread::current().id()));
+        }).main_thread());
+
+        let ptr = threads.clone();
+        schedule.add_system(move || {
+            ptr.lock().unwrap().push(("other", thread::current().id()));
+        });
+
+        schedule.add_system(fast_system);
+
+        schedule.tick(&mut world).unwrap();
+        schedule.tick(&mut world).unwrap();
+
+        let threads = threads.lock().unwrap();
+        assert_eq!(threads.len(), 4);
+
+        for (name, id) in threads.iter() {
+            assert_eq!(*name == "main", *id == thread::current().id());
+        }
+    }
+
+    #[test]
+    fn main_thread_exclusive_system() {
+        let mut world = Store::new();
+        world.insert_resource(0u32);
+
+        let mut schedule = Schedule::new();
+        schedule.set_executor(MultithreadedExecutorFactory);
+
+        let threads = Arc::new(Mutex::new(Vec::new()));
+
+        let ptr = threads.clone();
+        schedule.add_system((move |store: &mut Store| {
+            *store.resource_mut::<u32>() += 1;
+            ptr.lock().unwrap().push(thread::current().id());
+            Ok(())
+        }).main_thread());
+
+        schedule.add_system(fast_system);
+        schedule.add_system(fast_system);
+
+        schedule.tick(&mut world).unwrap();
+        schedule.tick(&mut world).unwrap();
+
+        assert_eq!(*world.resource::<u32>(), 2);
+
+        let threads = threads.lock().unwrap();
+        assert_eq!(threads.len(), 2);
+        assert!(threads.iter().all(|id| *id == thread::current().id()));
+    }
+
+    fn new_schedule_slow() -> Schedule {
+        let mut schedule = Schedule::new();
+
+        schedule.add_system(slow_system);
+        schedule.add_system(fast_system);
+
+        schedule
+    }
+
+    fn slow_system() {
+        thread::sleep(Duration::from_millis(500));
+    }
+
+    fn fast_system() {
+    }
 
     #[test]
     fn two_concurrent_no_phase() {
@@ -313,6 +1041,98 @@ mod tests {
         assert_eq!(take(&value), "[S, [S, S], S]");
     }
 
+    #[test]
+    fn many_small_systems() {
+        let mut schedule = Schedule::new();
+        let mut world = Store::new();
+
+        let count = Arc::new(Mutex::new(0));
+
+        for _ in 0..200 {
+            let ptr = count.clone();
+            schedule.add_system(move || {
+                *ptr.lock().unwrap() += 1;
+            });
+        }
+
+        schedule.init(&mut world).unwrap();
+
+        let mut exec = MultithreadedExecutor::new(schedule.plan());
+
+        (schedule, world) = exec.run(schedule, world).unwrap();
+        assert_eq!(*count.lock().unwrap(), 200);
+
+        exec.run(schedule, world).unwrap();
+        assert_eq!(*count.lock().unwrap(), 400);
+    }
+
+    #[test]
+    fn diagnostics() {
+        let mut schedule = Schedule::new();
+        let mut world = Store::new();
+
+        schedule.add_system(slow_system);
+        schedule.add_system(slow_system);
+
+        schedule.init(&mut world).unwrap();
+
+        let mut exec = MultithreadedExecutor::new(schedule.plan());
+
+        (_, world) = exec.run(schedule, world).unwrap();
+
+        let diag = world.resource::<ExecutorDiagnostics>();
+        assert_eq!(diag.serial_ratio(), 0.);
+        assert_eq!(diag.max_queue_depth(), 2);
+        assert!(diag.tick_time() >= Duration::from_millis(500));
+
+        let busy: Duration = diag.worker_busy().iter().sum();
+        assert!(busy >= Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn marker_chain() {
+        let mut schedule = Schedule::new();
+        schedule.add_phases((
+            TestPhase::A,
+            TestPhase::B,
+            TestPhase::C,
+        ).chain());
+
+        let mut world = Store::new();
+
+        let value = Arc::new(Mutex::new(Vec::<String>::new()));
+
+        let ptr = value.clone();
+        schedule.add_system((move || {
+            push(&ptr, format!("A"));
+        }).phase(TestPhase::A));
+
+        let ptr = value.clone();
+        schedule.add_system((move || {
+            push(&ptr, format!("C"));
+        }).phase(TestPhase::C));
+
+        schedule.init(&mut world).unwrap();
+
+        let plan = schedule.plan();
+        let n_markers = plan.order().iter()
+            .filter(|id| schedule.meta(**id).is_marker())
+            .count();
+        assert!(n_markers > 0);
+
+        let mut exec = MultithreadedExecutor::new(plan);
+
+        (schedule, world) = exec.run(schedule, world).unwrap();
+        assert_eq!(take(&value), "A, C");
+
+        let diag = world.resource::<ExecutorDiagnostics>();
+        assert_eq!(diag.n_markers(), n_markers);
+        assert_eq!(diag.n_serial + diag.n_parallel, 2);
+
+        exec.run(schedule, world).unwrap();
+        assert_eq!(take(&value), "A, C");
+    }
+
     #[test]
     fn two_concurrent_phase_b() {
         let mut schedule = Schedule::new();
@@ -0,0 +1,85 @@
+use std::time::Duration;
+
+use crate::{error::Result, system::SystemId};
+
+use super::{SystemMeta, plan::Plan, planner::Planner};
+
+///
+/// Observer called around every system run, used for profilers, tracing
+/// and watchdogs. With the multithreaded executor, observers are called
+/// from the worker threads.
+///
+pub trait SystemObserver: Send + Sync + 'static {
+    fn before_run(&self, _meta: &SystemMeta) {
+    }
+
+    fn after_run(&self, _meta: &SystemMeta, _elapsed: Duration) {
+    }
+}
+
+///
+/// Hook called each time a schedule is planned, for tooling like
+/// ambiguity checkers, visualizers and validators. An error fails the
+/// tick that replanned.
+///
+pub trait PlanHook: Send + Sync + 'static {
+    fn on_plan(&self, plan: &PlanView) -> Result<()>;
+}
+
+impl<F> PlanHook for F
+where
+    F: Fn(&PlanView) -> Result<()> + Send + Sync + 'static
+{
+    fn on_plan(&self, plan: &PlanView) -> Result<()> {
+        self(plan)
+    }
+}
+
+///
+/// Read-only view of a new plan and its system metas.
+///
+pub struct PlanView<'a> {
+    planner: &'a Planner,
+    plan: &'a Plan,
+}
+
+impl<'a> PlanView<'a> {
+    pub(crate) fn new(planner: &'a Planner, plan: &'a Plan) -> Self {
+        Self {
+            planner,
+            plan,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.plan.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.plan.len() == 0
+    }
+
+    ///
+    /// Systems in plan order.
+    ///
+    pub fn order(&self) -> &[SystemId] {
+        self.plan.order()
+    }
+
+    pub fn meta(&self, id: SystemId) -> &SystemMeta {
+        self.planner.meta(id)
+    }
+
+    pub fn metas(&self) -> impl Iterator<Item=&SystemMeta> {
+        self.plan.order().iter().map(|id| self.planner.meta(*id))
+    }
+
+    ///
+    /// Systems that wait for id to complete before running.
+    ///
+    pub fn after(&self, id: SystemId) -> Vec<SystemId> {
+        self.plan.outgoing(id).iter()
+            .map(|i| self.plan.system_id(*i))
+            .collect()
+    }
+}
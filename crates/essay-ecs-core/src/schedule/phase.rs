@@ -5,9 +5,9 @@ use std::{
     hash::{Hash, Hasher}, ops::{Index, IndexMut},
 };
 
-use crate::{system::SystemId, util::DynLabel};
+use crate::{system::SystemId, util::{DynLabel, LabelId, intern_label}};
 
-use super::preorder::{NodeId, Preorder};
+use crate::util::graph::{NodeId, Preorder};
 
 ///
 /// See SystemSet in bevy_ecs/schedule/schedule.rs
@@ -20,6 +20,10 @@ pub trait Phase: Send + DynLabel + fmt::Debug {
     }
 
     fn box_clone(&self) -> Box<dyn Phase>;
+
+    fn label_id(&self) -> LabelId {
+        intern_label(self.as_dyn_eq(), || self.box_clone())
+    }
 }
 
 impl Phase for DefaultPhase {
@@ -75,7 +79,7 @@ pub struct DefaultPhase;
 //
 
 pub(crate) struct PhasePreorder {
-    phase_map: HashMap<Box<dyn Phase>, PhaseId>,
+    phase_map: HashMap<LabelId, PhaseId>,
     phases: Vec<PhaseItem>,
     preorder: Preorder,
 }
@@ -88,7 +92,7 @@ impl PhasePreorder {
             preorder: Preorder::new(),
         };
 
-        preorder.add_node(Box::new(DefaultPhase));
+        preorder.add_node(&DefaultPhase);
 
         preorder
     }
@@ -96,11 +100,11 @@ impl PhasePreorder {
     pub fn add_phase(&mut self, config: PhaseConfig) -> PhaseId {
         let PhaseConfig { phase } = config;
 
-        self.add_node(phase)
+        self.add_node(phase.as_ref())
     }
 
     pub fn add_box_phase(&mut self, phase: &Box<dyn Phase>) -> PhaseId {
-        self.add_node(phase.box_clone())
+        self.add_node(phase.as_ref())
     }
 
     pub fn add_phases(&mut self, config: PhaseConfigs) {
@@ -128,12 +132,13 @@ impl PhasePreorder {
         }
     }
 
-    fn add_node(&mut self, phase: Box<dyn Phase>) -> PhaseId {
-        *self.phase_map.entry(phase.box_clone()).or_insert_with(|| {
+    fn add_node(&mut self, phase: &dyn Phase) -> PhaseId {
+        *self.phase_map.entry(phase.label_id()).or_insert_with(|| {
             let node_id = self.preorder.add_node(0);
             let id = PhaseId::from(node_id);
             self.phases.push(PhaseItem {
                 id,
+                name: phase.name(),
                 first_id: None,
                 last_id: None,
             });
@@ -141,6 +146,10 @@ impl PhasePreorder {
         })
     }
 
+    pub(crate) fn phases(&self) -> &[PhaseItem] {
+        &self.phases
+    }
+
     pub(crate) fn uninit_phases(&self) -> Vec<PhaseId> {
         self.phases
             .iter()
@@ -174,7 +183,6 @@ impl PhasePreorder {
     /// 
     pub(crate) fn incoming_systems(&self, phase_id: PhaseId) -> Vec<SystemId> {
         self.preorder.incoming(NodeId::from(phase_id))
-            .iter()
             .map(|n| { self.phases[n.0].last() })
             .collect::<Vec<SystemId>>()
     }
@@ -255,12 +263,21 @@ impl From<NodeId> for PhaseId {
 #[derive(Clone)]
 pub struct PhaseItem {
     id: PhaseId,
+    name: String,
 
     first_id: Option<SystemId>,
     last_id: Option<SystemId>,
 }
 
 impl PhaseItem {
+    pub(crate) fn id(&self) -> PhaseId {
+        self.id
+    }
+
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
     pub(crate) fn first(&self) -> SystemId {
         self.first_id.unwrap()
     }
@@ -1,6 +1,9 @@
-use crate::system::SystemId;
+use core::fmt;
+use std::str::FromStr;
 
-use super::preorder::{Preorder, NodeId};
+use crate::{error::{Error, Result}, system::SystemId};
+
+use crate::util::graph::{Preorder, NodeId};
 
 pub struct Plan {
     systems: Vec<PlanSystem>,
@@ -23,10 +26,9 @@ impl Plan {
             .collect();
 
         let systems : Vec<PlanSystem> = preorder.node_ids()
-            .iter()
             .map(|n| PlanSystem::new(
                 preorder, 
-                *n,
+                n,
                 &order
             )).collect();
 
@@ -60,18 +62,165 @@ impl Plan {
     pub(crate) fn outgoing(&self, id: SystemId) -> &Vec<usize> {
         &self.systems[id.index()].outgoing
     }
+
+    pub(crate) fn export(&self, hash: u64) -> PlanExport {
+        PlanExport {
+            hash,
+            order: self.order.iter().map(|id| id.index()).collect(),
+            outgoing: self.systems.iter().map(|s| s.outgoing.clone()).collect(),
+        }
+    }
+
+    ///
+    /// Rebuilds a plan from an export, checking that it's a valid plan for
+    /// n systems.
+    ///
+    pub(crate) fn from_export(export: &PlanExport, n: usize) -> Result<Self> {
+        if export.order.len() != n || export.outgoing.len() != n {
+            return Err(format!(
+                "plan has {} systems, schedule has {}", export.order.len(), n
+            ).into());
+        }
+
+        // position of each system in the order
+        let mut pos = vec![usize::MAX; n];
+        for (i, id) in export.order.iter().enumerate() {
+            if *id >= n || pos[*id] != usize::MAX {
+                return Err(format!("invalid system {} in plan order", id).into());
+            }
+            pos[*id] = i;
+        }
+
+        let mut n_incoming = vec![0; n];
+        for (id, outgoing) in export.outgoing.iter().enumerate() {
+            for target in outgoing {
+                // arrows must point forward for the executor to finish
+                if *target >= n || *target <= pos[id] {
+                    return Err(format!("invalid arrow from system {} in plan", id).into());
+                }
+
+                n_incoming[*target] += 1;
+            }
+        }
+
+        let systems = (0..n).map(|id| PlanSystem {
+            n_incoming: n_incoming[pos[id]],
+            outgoing: export.outgoing[id].clone(),
+        }).collect();
+
+        Ok(Self {
+            order: export.order.iter().map(|i| SystemId(*i)).collect(),
+            systems,
+            n_incoming,
+        })
+    }
+}
+
+///
+/// Serializable copy of a compiled plan, from Schedule::export_plan,
+/// tagged with a hash of the schedule's systems and their access. Text
+/// form via Display and FromStr.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlanExport {
+    hash: u64,
+    order: Vec<usize>,
+    outgoing: Vec<Vec<usize>>,
+}
+
+impl PlanExport {
+    ///
+    /// Hash of the system set the plan was compiled for.
+    ///
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+}
+
+impl fmt::Display for PlanExport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "plan {:016x}", self.hash)?;
+
+        write!(f, "order")?;
+        for i in &self.order {
+            write!(f, " {}", i)?;
+        }
+        writeln!(f)?;
+
+        for (id, outgoing) in self.outgoing.iter().enumerate() {
+            write!(f, "{} >", id)?;
+            for i in outgoing {
+                write!(f, " {}", i)?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for PlanExport {
+    type Err = Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        let mut lines = text.lines();
+
+        let hash = lines.next()
+            .and_then(|line| line.strip_prefix("plan "))
+            .and_then(|hash| u64::from_str_radix(hash.trim(), 16).ok())
+            .ok_or_else(|| Error::from("plan export missing plan header"))?;
+
+        let order = lines.next()
+            .and_then(|line| line.strip_prefix("order"))
+            .ok_or_else(|| Error::from("plan export missing order"))?;
+        let order = parse_indices(order)?;
+
+        let mut outgoing = Vec::new();
+
+        for line in lines.filter(|line| ! line.trim().is_empty()) {
+            let (id, targets) = line.split_once('>')
+                .ok_or_else(|| Error::from(format!("invalid plan line {:?}", line)))?;
+
+            if id.trim().parse::<usize>().ok() != Some(outgoing.len()) {
+                return Err(format!("invalid plan line {:?}", line).into());
+            }
+
+            outgoing.push(parse_indices(targets)?);
+        }
+
+        Ok(Self {
+            hash,
+            order,
+            outgoing,
+        })
+    }
+}
+
+fn parse_indices(text: &str) -> Result<Vec<usize>> {
+    text.split_whitespace()
+        .map(|i| i.parse::<usize>()
+            .map_err(|_| Error::from(format!("invalid plan index {:?}", i))))
+        .collect()
 }
 
 impl PlanSystem {
     fn new(
         preorder: &Preorder, 
         id: NodeId,
-        order: &Vec<NodeId>) -> Self {
+        order: &[NodeId]) -> Self {
         Self {
-            n_incoming: preorder.incoming(NodeId::from(id)).len(),
-            outgoing: preorder.outgoing(NodeId::from(id)).iter()
+            n_incoming: preorder.incoming(id).len(),
+            outgoing: preorder.outgoing(id)
                 .map(|n|
-                    order.iter().position(|n2| n == n2).unwrap()
+                    order.iter().position(|n2| n == *n2).unwrap()
                 ).collect(),
         }
     }
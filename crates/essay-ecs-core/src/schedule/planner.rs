@@ -1,9 +1,13 @@
 use core::fmt;
-use std::{collections::{HashMap, HashSet}, hash};
+use std::{collections::{BTreeSet, HashMap}, hash::{self, Hash, Hasher}};
 
-use crate::{resource::ResourceId, entity::ComponentId, system::SystemId};
+use fixedbitset::FixedBitSet;
 
-use super::{preorder::{Preorder, NodeId}, plan::Plan, phase::{PhaseId, PhasePreorder}, Phase};
+use crate::{error::Result, resource::ResourceId, entity::ComponentId, system::SystemId};
+
+use crate::util::graph::{Preorder, NodeId};
+
+use super::{plan::Plan, phase::{PhaseId, PhasePreorder}, Phase};
 
 
 pub struct Planner {
@@ -14,6 +18,10 @@ pub struct Planner {
     preorder: Preorder,
 
     order: Vec<SystemId>,
+
+    cycle_strategy: CycleStrategy,
+    // arrows removed by the cycle strategy in the last sort
+    broken: Vec<(NodeId, NodeId)>,
 }
 
 impl Planner {
@@ -24,6 +32,8 @@ impl Planner {
             // uninit_systems: Default::default(),
             preorder: Preorder::new(),
             order: Default::default(),
+            cycle_strategy: Default::default(),
+            broken: Default::default(),
         }
     }
     
@@ -49,17 +59,100 @@ impl Planner {
         id
     }
 
-    pub(crate) fn sort(&mut self) {
+    ///
+    /// Clears a removed system's access, keeping its phase so the
+    /// preorder is unchanged.
+    ///
+    pub(crate) fn remove(&mut self, id: SystemId) {
+        let phase_id = self.systems[id.index()].phase_id;
+
+        self.systems[id.index()] = SystemMeta::new(id, "removed".to_string(), phase_id);
+    }
+
+    ///
+    /// Orders before ahead of after, e.g. for chained systems.
+    ///
+    pub(crate) fn add_arrow(&mut self, before: SystemId, after: SystemId) {
+        self.preorder.add_arrow(NodeId::from(before), NodeId::from(after));
+    }
+
+    pub(crate) fn sort(&mut self) -> Result<()> {
         let mut preorder = self.create_preorder();
 
+        self.broken = self.break_cycles(&mut preorder)?;
+
         self.order = preorder.sort().iter()
             .map(|n| SystemId::from(*n))
             .collect();
+
+        Ok(())
     }
 
     pub(crate) fn plan(&mut self) -> Plan {
         // TODO: use order from sort instead of regenerating?
-        Plan::new(&mut self.create_preorder())
+        Plan::new(&mut self.plan_preorder())
+    }
+
+    ///
+    /// The preorder with phase arrows added and the arrows broken by the
+    /// cycle strategy removed.
+    ///
+    fn plan_preorder(&mut self) -> Preorder {
+        let mut preorder = self.create_preorder();
+
+        for (source, target) in &self.broken {
+            preorder.remove_arrow(*source, *target);
+        }
+
+        preorder
+    }
+
+    pub(crate) fn set_cycle_strategy(&mut self, strategy: CycleStrategy) {
+        self.cycle_strategy = strategy;
+    }
+
+    ///
+    /// Removes arrows to break cycles as the strategy allows. The default
+    /// heuristic is left to the preorder's sort.
+    ///
+    fn break_cycles(&self, preorder: &mut Preorder) -> Result<Vec<(NodeId, NodeId)>> {
+        if self.cycle_strategy == CycleStrategy::Heuristic {
+            return Ok(Vec::new());
+        }
+
+        let mut cycle = Vec::new();
+
+        let result = preorder.break_cycles(|arrows| {
+            let meta = |id: NodeId| &self.systems[id.index()];
+
+            let arrow = match self.cycle_strategy {
+                CycleStrategy::Heuristic | CycleStrategy::Fail => None,
+                CycleStrategy::LowestPriority => arrows.iter().copied().min_by_key(|(s, t)| {
+                    let (a, b) = (meta(*s).priority, meta(*t).priority);
+
+                    (a.min(b), a.max(b))
+                }),
+                CycleStrategy::Marked => arrows.iter().copied()
+                    .find(|(_, t)| meta(*t).is_cycle_break),
+            };
+
+            if arrow.is_none() {
+                cycle = arrows.to_vec();
+            }
+
+            arrow
+        });
+
+        result.map_err(|_| {
+            let names: Vec<String> = cycle.iter()
+                .map(|(s, t)| format!("{} -> {}",
+                    self.systems[s.index()].name(),
+                    self.systems[t.index()].name()
+                ))
+                .collect();
+
+            format!("cycle in schedule ({:?}): {}", self.cycle_strategy, names.join(", ")).into()
+        })
     }
 
     fn create_preorder(&mut self) -> Preorder {
@@ -118,6 +211,22 @@ impl Planner {
         &self.systems[id.index()]
     }
 
+    ///
+    /// First pair of systems from the two planners with conflicting
+    /// access, which prevents running them in parallel.
+    ///
+    pub(crate) fn conflict(&self, other: &Planner) -> Option<String> {
+        for a in self.systems.iter().filter(|meta| ! meta.is_marker()) {
+            for b in other.systems.iter().filter(|meta| ! meta.is_marker()) {
+                if let Some(access) = a.conflict(b) {
+                    return Some(format!("{} and {} both access {}", a.name(), b.name(), access));
+                }
+            }
+        }
+
+        None
+    }
+
     pub(crate) fn meta_mut(&mut self, id: SystemId) -> &mut SystemMeta {
         &mut self.systems[id.index()]
     }
@@ -126,9 +235,155 @@ impl Planner {
         self.phases.add_box_phase(phase)
     }
 
+    ///
+    /// Names of the declared phases with no systems.
+    ///
+    pub(crate) fn empty_phases(&self) -> Vec<String> {
+        self.phases.phases().iter()
+            .filter(|phase| phase.id() != PhaseId::zero())
+            .filter(|phase| ! self.systems.iter().any(|meta| {
+                meta.phase_id == phase.id() && ! meta.is_marker()
+            }))
+            .map(|phase| phase.name().to_string())
+            .collect()
+    }
+
     pub(crate) fn phases_mut(&mut self) -> &mut PhasePreorder {
         &mut self.phases
     }
+
+    ///
+    /// Hash of the systems, their access and the explicit arrows, which
+    /// determines the plan, for validating imported plans.
+    ///
+    pub(crate) fn system_set_hash(&self) -> u64 {
+        let mut hasher = StableHasher::new();
+
+        for meta in &self.systems {
+            meta.name.hash(&mut hasher);
+            meta.priority.hash(&mut hasher);
+            meta.is_cycle_break.hash(&mut hasher);
+            AccessGroup::from(meta).hash(&mut hasher);
+        }
+
+        for node in self.preorder.node_ids() {
+            for target in self.preorder.outgoing(node) {
+                (node.index(), target.index()).hash(&mut hasher);
+            }
+        }
+
+        self.phases.sort().hash(&mut hasher);
+        self.cycle_strategy.hash(&mut hasher);
+
+        hasher.finish()
+    }
+
+    ///
+    /// Checks that an imported plan keeps every arrow of the planned
+    /// preorder, including phase order, and orders every pair of systems
+    /// with conflicting access, since an edited export could drop an
+    /// arrow and let them run out of order or in parallel.
+    ///
+    pub(crate) fn check_plan(&mut self, plan: &Plan) -> Result<()> {
+        let n = plan.len();
+
+        let mut pos = vec![0; n];
+        for (i, id) in plan.order().iter().enumerate() {
+            pos[id.index()] = i;
+        }
+
+        // reachable positions from each position, filled in reverse order
+        let mut reach = vec![FixedBitSet::with_capacity(n); n];
+        for i in (0..n).rev() {
+            let mut set = FixedBitSet::with_capacity(n);
+
+            for target in plan.outgoing(plan.system_id(i)) {
+                set.insert(*target);
+                set.union_with(&reach[*target]);
+            }
+
+            reach[i] = set;
+        }
+
+        // sorting drops the arrows the heuristic breaks, as in Plan::new
+        let mut preorder = self.plan_preorder();
+        preorder.sort();
+
+        for source in preorder.node_ids() {
+            for target in preorder.outgoing(source) {
+                if ! reach[pos[source.index()]].contains(pos[target.index()]) {
+                    return Err(format!(
+                        "plan doesn't order {} before {}",
+                        self.systems[source.index()].name(),
+                        self.systems[target.index()].name()
+                    ).into());
+                }
+            }
+        }
+
+        let metas: Vec<&SystemMeta> = self.systems.iter()
+            .filter(|meta| ! meta.is_marker())
+            .collect();
+
+        for (i, a) in metas.iter().enumerate() {
+            for b in &metas[i + 1..] {
+                let (pa, pb) = (pos[a.id.index()], pos[b.id.index()]);
+                let (first, last) = if pa < pb { (pa, pb) } else { (pb, pa) };
+
+                if ! reach[first].contains(last) {
+                    if let Some(access) = a.conflict(b) {
+                        return Err(format!(
+                            "plan doesn't order {} and {}, which both access {}",
+                            a.name(), b.name(), access
+                        ).into());
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+///
+/// FNV-1a, so plan hashes don't change between builds or platforms
+/// like DefaultHasher may.
+///
+struct StableHasher(u64);
+
+impl StableHasher {
+    fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
 }
 
 impl Default for Planner {
@@ -138,6 +393,8 @@ impl Default for Planner {
             systems: Default::default(), 
             preorder: Default::default(),
             order: Default::default(),
+            cycle_strategy: Default::default(),
+            broken: Default::default(),
         }
     }
 }
@@ -152,12 +409,19 @@ pub struct SystemMeta {
 
     is_exclusive: bool,
     is_marker: bool,
+    is_main_thread: bool,
+    is_read_all: bool,
+    is_cycle_break: bool,
+
+    resources: BTreeSet<ResourceId>,
+    mut_resources: BTreeSet<ResourceId>,
 
-    resources: HashSet<ResourceId>,
-    mut_resources: HashSet<ResourceId>,
+    components: BTreeSet<ComponentId>,
+    mut_components: BTreeSet<ComponentId>,
 
-    components: HashSet<ComponentId>,
-    mut_components: HashSet<ComponentId>,
+    // calls to set_read_all and the mut inserts, to check one param set
+    n_read_all: usize,
+    n_writes: usize,
 }
 
 impl SystemMeta {
@@ -174,12 +438,18 @@ impl SystemMeta {
 
             is_marker: false,
             is_exclusive: false,
+            is_main_thread: false,
+            is_read_all: false,
+            is_cycle_break: false,
 
             resources: Default::default(),
             mut_resources: Default::default(),
 
             components: Default::default(),
             mut_components: Default::default(),
+
+            n_read_all: 0,
+            n_writes: 0,
         }
     }
 
@@ -192,12 +462,18 @@ impl SystemMeta {
 
             is_marker: false,
             is_exclusive: false,
+            is_main_thread: false,
+            is_read_all: false,
+            is_cycle_break: false,
 
             resources: Default::default(),
             mut_resources: Default::default(),
 
             components: Default::default(),
             mut_components: Default::default(),
+
+            n_read_all: 0,
+            n_writes: 0,
         }
     }
 
@@ -205,6 +481,17 @@ impl SystemMeta {
         self.id
     }
 
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    ///
+    /// Phase the system runs in, PhaseId::index 0 for the default phase.
+    ///
+    pub fn phase_id(&self) -> PhaseId {
+        self.phase_id
+    }
+
     pub fn set_exclusive(&mut self) {
         self.is_exclusive = true;
     }
@@ -213,11 +500,46 @@ impl SystemMeta {
         self.is_exclusive
     }
 
+    pub fn set_main_thread(&mut self) {
+        self.is_main_thread = true;
+    }
+
+    ///
+    /// True if the system must run on the thread that ticks the schedule.
+    ///
+    pub fn is_main_thread(&self) -> bool {
+        self.is_main_thread
+    }
+
+    pub fn set_cycle_break(&mut self) {
+        self.is_cycle_break = true;
+    }
+
+    ///
+    /// True if CycleStrategy::Marked may remove arrows into the system.
+    ///
+    pub fn is_cycle_break(&self) -> bool {
+        self.is_cycle_break
+    }
+
+    ///
+    /// Marks the system as reading every resource and component, e.g.
+    /// ReadStore. It runs after writers but in parallel with readers.
+    ///
+    pub fn set_read_all(&mut self) {
+        self.is_read_all = true;
+        self.n_read_all += 1;
+    }
+
+    pub fn is_read_all(&self) -> bool {
+        self.is_read_all
+    }
+
     pub(crate) fn set_marker(&mut self) {
         self.is_marker = true;
     }
 
-    pub(crate) fn is_marker(&self) -> bool {
+    pub fn is_marker(&self) -> bool {
         self.is_marker
     }
 
@@ -243,14 +565,78 @@ impl SystemMeta {
 
     pub fn insert_resource_mut(&mut self, id: ResourceId) {
         self.mut_resources.insert(id);
+        self.n_writes += 1;
+    }
+
+    ///
+    /// Resources the system reads. Writes are in mut_resources.
+    ///
+    pub fn resources(&self) -> &BTreeSet<ResourceId> {
+        &self.resources
+    }
+
+    pub fn mut_resources(&self) -> &BTreeSet<ResourceId> {
+        &self.mut_resources
     }
 
     pub fn insert_component(&mut self, id: ComponentId) {
         self.components.insert(id);
     }
 
+    ///
+    /// Components the system reads. Writes are in mut_components.
+    ///
+    pub fn components(&self) -> &BTreeSet<ComponentId> {
+        &self.components
+    }
+
+    pub fn mut_components(&self) -> &BTreeSet<ComponentId> {
+        &self.mut_components
+    }
+
     pub fn insert_component_mut(&mut self, id: ComponentId) {
         self.mut_components.insert(id);
+        self.n_writes += 1;
+    }
+
+    pub(crate) fn access_mark(&self) -> (usize, usize) {
+        (self.n_read_all, self.n_writes)
+    }
+
+    ///
+    /// Error if the params initialized since mark both read the whole
+    /// store and write, since the reads would alias the writes.
+    ///
+    pub(crate) fn check_read_all(&self, mark: (usize, usize)) -> Result<()> {
+        if mark.0 < self.n_read_all && mark.1 < self.n_writes {
+            Err(format!("system {} reads the whole store and can't also write", self.name).into())
+        } else {
+            Ok(())
+        }
+    }
+
+    pub(crate) fn conflict(&self, other: &SystemMeta) -> Option<String> {
+        if self.is_exclusive || other.is_exclusive {
+            return Some("the whole store".to_string());
+        }
+
+        for (a, b) in [(self, other), (other, self)] {
+            if a.is_read_all && (! b.mut_resources.is_empty() || ! b.mut_components.is_empty()) {
+                return Some("the whole store".to_string());
+            }
+
+            if let Some(id) = a.mut_resources.iter()
+                .find(|id| b.resources.contains(id) || b.mut_resources.contains(id)) {
+                return Some(format!("{:?}", id));
+            }
+
+            if let Some(id) = a.mut_components.iter()
+                .find(|id| b.components.contains(id) || b.mut_components.contains(id)) {
+                return Some(format!("{:?}", id));
+            }
+        }
+
+        None
     }
 }
 
@@ -269,6 +655,10 @@ impl fmt::Debug for SystemMeta {
 }
 
 pub struct PhasePlan {
+    // systems reachable by explicit arrows, e.g. chained systems, which
+    // override access order
+    explicit: Vec<FixedBitSet>,
+
     group_map: HashMap<AccessGroup, AccessGroupId>,
     groups: Vec<AccessGroup>,
 
@@ -284,6 +674,8 @@ impl PhasePlan {
         mut preorder: Preorder
     ) -> Preorder {
         let mut phase_plan = Self {
+            explicit: preorder.reachable(),
+
             group_map: Default::default(),
             groups: Default::default(),
 
@@ -355,7 +747,7 @@ impl PhasePlan {
     /// 
     fn internal_arrows(&mut self, preorder: &mut Preorder) {
         for group in &mut self.groups {
-            group.internal_arrows(preorder);
+            group.internal_arrows(preorder, &self.explicit);
         }
     }
 
@@ -406,22 +798,38 @@ impl PhasePlan {
                 }
             }
 
+            // write -> read for global readers
+            if group.is_read_all {
+                for mut_ids in self.resource_mut_map.values().chain(self.component_mut_map.values()) {
+                    let mut_ids = mut_ids.iter()
+                        .filter(|id| **id != group.id)
+                        .copied()
+                        .collect::<Vec<AccessGroupId>>();
+
+                    self.arrows_from_tail(preorder, &mut_ids, group);
+                }
+            }
+
             // world -> all
             if let Some(exclusive) = self.exclusive {
                 let exclusive_last = self.groups[exclusive.0].last.unwrap();
 
                 match group.first {
                     Some(first) => { 
-                        preorder.add_arrow(
-                            NodeId::from(exclusive_last),
-                            NodeId::from(first),
+                        add_access_arrow(
+                            preorder,
+                            &self.explicit,
+                            exclusive_last,
+                            first,
                         );
                     },
                     None => {
                         for id in &group.systems {
-                            preorder.add_arrow(
-                                NodeId::from(exclusive_last),
-                                NodeId::from(*id),
+                            add_access_arrow(
+                                preorder,
+                                &self.explicit,
+                                exclusive_last,
+                                *id,
                             );
                         }
                     }
@@ -445,7 +853,7 @@ impl PhasePlan {
             let last = self.groups[id.0].last.clone();
             
             if let Some(last) = last {
-                group.arrows_from_tail(preorder, last);
+                group.arrows_from_tail(preorder, &self.explicit, last);
                 return;
             }
         }
@@ -463,6 +871,7 @@ pub struct AccessGroup {
     
     is_exclusive: bool,
     is_marker: bool, 
+    is_read_all: bool,
 
     resources: Vec<ResourceId>,
     mut_resources: Vec<ResourceId>,
@@ -487,7 +896,7 @@ impl AccessGroup {
     /// Adds arrows internal to the group, specifically writers are
     /// are ordered
     /// 
-    fn internal_arrows(&mut self, preorder: &mut Preorder) {
+    fn internal_arrows(&mut self, preorder: &mut Preorder, explicit: &[FixedBitSet]) {
         if self.is_write() {
             let mut iter = self.systems.iter();
 
@@ -498,10 +907,7 @@ impl AccessGroup {
             self.first = Some(*prev_id);
     
             for next_id in iter {
-                preorder.add_arrow(
-                    NodeId::from(*prev_id),
-                    NodeId::from(*next_id)
-                );
+                add_access_arrow(preorder, explicit, *prev_id, *next_id);
     
                 prev_id = next_id;
             }
@@ -510,16 +916,28 @@ impl AccessGroup {
         }
     }
 
-    fn arrows_from_tail(&self, preorder: &mut Preorder, tail: SystemId) {
+    fn arrows_from_tail(&self, preorder: &mut Preorder, explicit: &[FixedBitSet], tail: SystemId) {
         for id in &self.systems {
-            preorder.add_arrow(
-                NodeId::from(tail),
-                NodeId::from(*id),
-            );
+            add_access_arrow(preorder, explicit, tail, *id);
         }
     }
 }
 
+///
+/// Adds an access arrow unless an explicit arrow orders the systems the
+/// other way, e.g. a chained reader before its writer.
+///
+fn add_access_arrow(
+    preorder: &mut Preorder,
+    explicit: &[FixedBitSet],
+    source: SystemId,
+    target: SystemId
+) {
+    if ! explicit[target.index()].contains(source.index()) {
+        preorder.add_arrow(NodeId::from(source), NodeId::from(target));
+    }
+}
+
 impl From<&SystemMeta> for AccessGroup {
     fn from(meta: &SystemMeta) -> Self {
         let mut group = AccessGroup {
@@ -528,11 +946,18 @@ impl From<&SystemMeta> for AccessGroup {
 
             is_exclusive: meta.is_exclusive, 
             is_marker: meta.is_marker,
+            is_read_all: meta.is_read_all,
 
-            resources: meta.resources.iter().map(|s| *s).collect(),
+            // a write covers a read of the same id, e.g. a condition's
+            // ResMut<A> with its system's Res<A>
+            resources: meta.resources.difference(&meta.mut_resources)
+                .copied()
+                .collect(),
             mut_resources: meta.mut_resources.iter().map(|s| *s).collect(),
 
-            components: meta.components.iter().map(|s| *s).collect(),
+            components: meta.components.difference(&meta.mut_components)
+                .copied()
+                .collect(),
             mut_components: meta.mut_components.iter().map(|s| *s).collect(),
 
             systems: Vec::new(),
@@ -556,6 +981,7 @@ impl PartialEq for AccessGroup {
         self.phase_id == other.phase_id
         && self.is_exclusive == other.is_exclusive
         && self.is_marker == other.is_marker
+        && self.is_read_all == other.is_read_all
         && self.resources == other.resources
         && self.mut_resources == other.mut_resources
         && self.components == other.components
@@ -571,6 +997,7 @@ impl hash::Hash for AccessGroup {
 
         self.is_exclusive.hash(state);
         self.is_marker.hash(state);
+        self.is_read_all.hash(state);
 
         self.resources.hash(state);
         self.mut_resources.hash(state);
@@ -586,6 +1013,7 @@ impl fmt::Debug for AccessGroup {
         .field("phase", &self.phase_id)
         .field("is_exclusive", &self.is_exclusive)
         .field("is_flush", &self.is_marker)
+        .field("is_read_all", &self.is_read_all)
         .field("resources", &self.resources)
         .field("mut_resources", &self.mut_resources)
         .field("components", &self.components)
@@ -606,9 +1034,39 @@ impl AccessGroupId {
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialOrd, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialOrd, Ord, PartialEq, Eq, Hash)]
 pub struct Priority(u32);
 
+///
+/// How a schedule resolves ordering cycles, e.g. chains that contradict
+/// each other. Silent breaks can reorder a pipeline in subtle ways, so
+/// schedules can fail instead or only break arrows they allow.
+///
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum CycleStrategy {
+    ///
+    /// Breaks the cycle at a heuristically chosen system.
+    ///
+    #[default]
+    Heuristic,
+
+    ///
+    /// Fails the tick with the cycle.
+    ///
+    Fail,
+
+    ///
+    /// Removes the arrow between the lowest priority systems.
+    ///
+    LowestPriority,
+
+    ///
+    /// Removes an arrow into a system marked with allow_cycle_break,
+    /// failing if the cycle has none.
+    ///
+    Marked,
+}
+
 impl Priority {
     pub const HIGH : Priority = Priority(2000);
     pub const DEFAULT : Priority = Priority(1000);
@@ -646,7 +1104,8 @@ mod test {
     use crate::{
         core_app::{CoreApp, Core}, 
         entity::Component, 
-        Res, ResMut, Commands, Store, schedule::Executors, util::test::TestValues
+        Res, ResMut, Commands, Store, schedule::Executors, util::test::TestValues,
+        IntoSystemConfig,
     };
 
     #[test]
@@ -868,6 +1327,42 @@ mod test {
         assert_eq!(values.take(), "[A, A], [B, B], [C, [C, C], C]");
     }
 
+    ///
+    /// A run_if condition's ResMut<A> orders its system like the system's
+    /// own params
+    /// 
+    #[test]
+    fn condition_resmut_sequential() {
+        let mut app = CoreApp::new();
+
+        app.set_executor(Executors::Multithreaded);
+        app.insert_resource("test".to_string());
+
+        let mut values = TestValues::new();
+
+        let mut ptr = values.clone();
+        app.system(Core, move |_res: Res<String>| {
+            ptr.push(format!("[A"));
+            thread::sleep(Duration::from_millis(100));
+            ptr.push(format!("A]"));
+        });
+        
+        let mut ptr = values.clone();
+        app.system(Core, (move |_res: Res<String>| {
+            ptr.push(format!("[B"));
+            thread::sleep(Duration::from_millis(100));
+            ptr.push(format!("B]"));
+        }).run_if(|_res: ResMut<String>| true));
+
+        app.tick().unwrap();
+
+        let values = values.take();
+        assert!(
+            values == "[A, A], [B, B]" || values == "[B, B], [A, A]",
+            "{}", values
+        );
+    }
+
     ///
     /// Component read &TestA can execute in parallel
     /// 
@@ -876,7 +1371,7 @@ mod test {
         let mut app = CoreApp::new();
 
         app.set_executor(Executors::Multithreaded);
-        app.run_system(|mut c: Commands| c.spawn(TestA(100))).unwrap();
+        app.run_system(|mut c: Commands| { c.spawn(TestA(100)); }).unwrap();
 
         let mut values = TestValues::new();
 
@@ -908,7 +1403,7 @@ mod test {
         let mut app = CoreApp::new();
 
         app.set_executor(Executors::Multithreaded);
-        app.run_system(|mut c: Commands| c.spawn(TestA(100))).unwrap();
+        app.run_system(|mut c: Commands| { c.spawn(TestA(100)); }).unwrap();
 
         let mut values = TestValues::new();
 
@@ -940,8 +1435,8 @@ mod test {
         let mut app = CoreApp::new();
 
         app.set_executor(Executors::Multithreaded);
-        app.run_system(|mut c: Commands| c.spawn(TestA(100))).unwrap();
-        app.run_system(|mut c: Commands| c.spawn(TestB(200))).unwrap();
+        app.run_system(|mut c: Commands| { c.spawn(TestA(100)); }).unwrap();
+        app.run_system(|mut c: Commands| { c.spawn(TestB(200)); }).unwrap();
 
         let mut values = TestValues::new();
 
@@ -974,7 +1469,7 @@ mod test {
         let mut app = CoreApp::new();
 
         app.set_executor(Executors::Multithreaded);
-        app.run_system(|mut c: Commands| c.spawn(TestA(100))).unwrap();
+        app.run_system(|mut c: Commands| { c.spawn(TestA(100)); }).unwrap();
 
         let mut values = TestValues::new();
 
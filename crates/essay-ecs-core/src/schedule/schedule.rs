@@ -1,21 +1,24 @@
 use core::fmt;
 
-use std::{hash::{Hash, Hasher}, collections::HashMap};
+use std::{hash::{Hash, Hasher}, collections::HashMap, mem, sync::Arc, time::Instant};
+
+use log::{info, warn};
 
 use crate::{
     error::Result,
+    param::BufferKind,
     system::{SystemId, System}, 
     store::Store, 
-    util::DynLabel, IntoSystemConfig};
+    util::{DynLabel, LabelId, intern_label, graph::NodeId}, IntoSystemConfig};
 
 use super::{
     phase::{IntoPhaseConfig, IntoPhaseConfigs, PhaseId}, 
-    preorder::NodeId, 
     SystemMeta, 
-    plan::Plan, 
+    plan::{Plan, PlanExport}, 
     unsafe_cell::UnsafeSyncCell, 
-    planner::Planner, 
-    UnsafeStore, executor::{Executor, ExecutorFactory}, system::SystemConfig
+    planner::{CycleStrategy, Planner}, 
+    UnsafeStore, executor::{Executor, ExecutorFactory}, system::{SystemConfig, SystemLabel},
+    observer::{SystemObserver, PlanHook, PlanView},
 };
 
 ///
@@ -27,8 +30,13 @@ pub type BoxedCondition<Out=bool> = UnsafeSyncCell<Box<dyn System<Out=Out>>>;
 pub type BoxedLabel = Box<dyn ScheduleLabel>;
 
 pub struct Schedules {
-    schedule_map: HashMap<Box<dyn ScheduleLabel>, Schedule>,
+    // labels are kept after their schedule is removed, so the remove and
+    // re-insert around each run doesn't allocate
+    labels: HashMap<LabelId, BoxedLabel>,
+    schedule_map: HashMap<LabelId, Schedule>,
     default_executor: Box<dyn ExecutorFactory>,
+    observers: Vec<Arc<dyn SystemObserver>>,
+    plan_hooks: Vec<Arc<dyn PlanHook>>,
 }
 
 impl Schedules {
@@ -36,14 +44,14 @@ impl Schedules {
         &self, 
         label: impl AsRef<dyn ScheduleLabel>
     ) -> Option<&Schedule> {
-        self.schedule_map.get(label.as_ref())
+        self.schedule_map.get(&label.as_ref().label_id())
     }
 
     pub fn get_mut(
         &mut self, 
         label: impl AsRef<dyn ScheduleLabel>
     ) -> Option<&mut Schedule> {
-        self.schedule_map.get_mut(label.as_ref())
+        self.schedule_map.get_mut(&label.as_ref().label_id())
     }
 
     pub fn insert(
@@ -51,28 +59,67 @@ impl Schedules {
         label: impl AsRef<dyn ScheduleLabel>, 
         schedule: Schedule
     ) -> Option<Schedule> {
-        self.schedule_map.insert(label.as_ref().box_clone(), schedule)
+        let label = label.as_ref();
+        let id = label.label_id();
+
+        self.labels.entry(id).or_insert_with(|| label.box_clone());
+
+        self.insert_id(id, schedule)
+    }
+
+    pub(crate) fn insert_id(
+        &mut self, 
+        id: LabelId, 
+        mut schedule: Schedule
+    ) -> Option<Schedule> {
+        // schedules are removed and re-inserted around each run
+        let observers = &mut schedule.inner_mut().observers;
+
+        for observer in &self.observers {
+            if ! observers.iter().any(|o| Arc::ptr_eq(o, observer)) {
+                observers.push(observer.clone());
+            }
+        }
+
+        let plan_hooks = &mut schedule.inner_mut().plan_hooks;
+
+        for hook in &self.plan_hooks {
+            if ! plan_hooks.iter().any(|h| Arc::ptr_eq(h, hook)) {
+                plan_hooks.push(hook.clone());
+            }
+        }
+
+        self.schedule_map.insert(id, schedule)
+    }
+
+    pub fn labels(&self) -> Vec<BoxedLabel> {
+        self.schedule_map.keys().map(|id| self.labels[id].box_clone()).collect()
     }
 
     pub fn contains(
         &mut self, 
         label: impl AsRef<dyn ScheduleLabel>, 
     ) -> bool {
-        self.schedule_map.contains_key(label.as_ref())
+        self.schedule_map.contains_key(&label.as_ref().label_id())
     }
 
     pub fn remove(
         &mut self, 
         label: &dyn ScheduleLabel
     ) -> Option<Schedule> {
-        self.schedule_map.remove(label)
+        self.schedule_map.remove(&label.label_id())
     }
 
     pub fn remove_entry(
         &mut self, 
         label: &dyn ScheduleLabel
     ) -> Option<(BoxedLabel, Schedule)> {
-        self.schedule_map.remove_entry(label)
+        self.schedule_map.remove(&label.label_id())
+            .map(|schedule| (label.box_clone(), schedule))
+    }
+
+    pub(crate) fn remove_id(&mut self, id: LabelId) -> Option<Schedule> {
+        self.schedule_map.remove(&id)
     }
 
     pub fn add_system<M>(
@@ -80,7 +127,7 @@ impl Schedules {
         label: impl AsRef<dyn ScheduleLabel>, 
         config: impl IntoSystemConfig<M>,
     ) {
-        self.schedule_map.get_mut(label.as_ref())
+        self.schedule_map.get_mut(&label.as_ref().label_id())
             .unwrap_or_else(|| panic!("add_system with an unknown schedule {:?}", label.as_ref()))
             .add_system::<M>(config);
     }
@@ -90,25 +137,75 @@ impl Schedules {
         label: impl AsRef<dyn ScheduleLabel>, 
         config: impl IntoPhaseConfigs,
     ) {
-        self.schedule_map.get_mut(label.as_ref())
+        self.schedule_map.get_mut(&label.as_ref().label_id())
             .unwrap_or_else(|| panic!("add_system with an unknown schedule {:?}", label.as_ref()))
             .add_phases(config);
     }
 
     pub fn set_executor(&mut self, executor: impl ExecutorFactory + 'static) {
-        self.default_executor = Box::new(executor);
+        self.set_executor_box(Box::new(executor));
+    }
+
+    pub fn set_executor_box(&mut self, executor: Box<dyn ExecutorFactory>) {
+        self.default_executor = executor;
 
         for schedule in self.schedule_map.values_mut() {
             schedule.set_executor_factory(self.default_executor.box_clone());
         }
     }
 
+    ///
+    /// Adds an observer to every current and future schedule.
+    ///
+    pub fn add_observer(&mut self, observer: impl SystemObserver) {
+        let observer: Arc<dyn SystemObserver> = Arc::new(observer);
+
+        for schedule in self.schedule_map.values_mut() {
+            schedule.inner_mut().observers.push(observer.clone());
+        }
+
+        self.observers.push(observer);
+    }
+
+    ///
+    /// Adds a plan hook to every current and future schedule.
+    ///
+    pub fn add_plan_hook(&mut self, hook: impl PlanHook) {
+        let hook: Arc<dyn PlanHook> = Arc::new(hook);
+
+        for schedule in self.schedule_map.values_mut() {
+            schedule.inner_mut().add_plan_hook(hook.clone());
+        }
+
+        self.plan_hooks.push(hook);
+    }
+
+    ///
+    /// Removes labeled systems from every schedule. A schedule that is
+    /// currently running isn't in Schedules, so it's unaffected.
+    ///
+    pub fn remove_systems(&mut self, label: impl SystemLabel) -> usize {
+        self.schedule_map.values_mut()
+            .map(|schedule| schedule.inner_mut().remove_systems(&label))
+            .sum()
+    }
+
+    ///
+    /// Approximate bytes held by each system of every schedule, except
+    /// schedules that are currently running.
+    ///
+    pub fn system_memory(&self) -> Vec<(String, usize)> {
+        self.schedule_map.values()
+            .flat_map(|schedule| schedule.system_memory())
+            .collect()
+    }
+
     pub fn tick(
         &mut self, 
         label: impl AsRef<dyn ScheduleLabel>, 
         world: &mut Store
     ) -> Result<()> {
-        let schedule = self.schedule_map.get_mut(label.as_ref()).unwrap();
+        let schedule = self.schedule_map.get_mut(&label.as_ref().label_id()).unwrap();
         
         schedule.tick(world)
     }
@@ -117,8 +214,11 @@ impl Schedules {
 impl Default for Schedules {
     fn default() -> Self {
         Self { 
+            labels: HashMap::new(),
             schedule_map: HashMap::new(),
             default_executor: Default::default(),
+            observers: Vec::new(),
+            plan_hooks: Vec::new(),
          }
     }
 }
@@ -137,9 +237,17 @@ impl Schedule {
         &mut self, 
         config: impl IntoSystemConfig<M>
     ) {
-        for system_cfg in config.into_config().systems {
-            self.inner_mut().add_system(system_cfg);
+        let configs = config.into_config();
+
+        let ids: Vec<SystemId> = configs.systems.into_iter()
+            .map(|system_cfg| self.inner_mut().add_system(system_cfg))
+            .collect();
+
+        for (before, after) in configs.arrows {
+            self.inner_mut().planner.add_arrow(ids[before], ids[after]);
         }
+
+        self.inner_mut().config_errors.extend(configs.errors);
     }
 
     pub fn add_phase(&mut self, into_config: impl IntoPhaseConfig) { // -> PhaseItem {
@@ -183,7 +291,11 @@ impl Schedule {
     }
     */
 
-    pub fn tick(&mut self, world: &mut Store) -> Result<()> {
+    ///
+    /// Initializes new systems, replans and creates the executor if the
+    /// schedule changed. Called by tick, or early to warm up a schedule.
+    ///
+    pub fn prepare(&mut self, world: &mut Store) -> Result<()> {
         let mut is_init = false;
         while self.inner_mut().is_stale {
             self.inner_mut().is_stale = false;
@@ -194,11 +306,39 @@ impl Schedule {
         if is_init {
             self.executor = None; // force drop/close
             let plan = self.plan();
+            if let Err(err) = self.inner().run_plan_hooks(&plan) {
+                // replan on the next tick instead of running a rejected plan
+                self.inner_mut().is_stale = true;
+                return Err(err);
+            }
+
+            self.inner_mut().order = plan.order().clone();
             self.executor = Some(
                 self.inner_mut().executor_factory.create(plan)
             );
         }
 
+        Ok(())
+    }
+
+    ///
+    /// Initializes new systems like prepare, but reports every problem
+    /// instead of failing on the first: params that can't initialize,
+    /// e.g. resources never inserted, conditions on marker systems and
+    /// phases without systems.
+    ///
+    pub fn validate(&mut self, world: &mut Store) -> Vec<String> {
+        self.inner_mut().validate(world)
+    }
+
+    pub fn tick(&mut self, world: &mut Store) -> Result<()> {
+        // a watchdog abort keeps the store until its systems finish
+        if self.inner.is_none() && ! self.recover(world) {
+            return Err("systems from an aborted tick are still running".into());
+        }
+
+        self.prepare(world)?;
+
         let exec_schedule = self.take();
         let exec_world = world.take();
 
@@ -207,7 +347,14 @@ impl Schedule {
             None => { return Err(format!("missing executor\n\tin {}", module_path!()).into()); }
         };
         
-        let (exec_schedule, exec_world)= executor.run(exec_schedule, exec_world)?;
+        let (exec_schedule, exec_world) = match executor.run(exec_schedule, exec_world) {
+            Ok(value) => value,
+            Err(err) => {
+                self.recover(world);
+
+                return Err(err);
+            }
+        };
 
         self.replace(exec_schedule);
         world.replace(exec_world);
@@ -234,6 +381,33 @@ impl Schedule {
         //self.inner().planner.plan(phase_order)
     }
 
+    fn recover(&mut self, world: &mut Store) -> bool {
+        let recovered = match &mut self.executor {
+            Some(executor) => executor.recover(),
+            None => None,
+        };
+
+        match recovered {
+            Some((exec_schedule, exec_world)) => {
+                self.replace(exec_schedule);
+                world.replace(exec_world);
+
+                // applies, or stages in a transaction, completed systems' commands
+                self.flush(world);
+
+                // a panic stops the executor, so the schedule replans with
+                // a new one
+                if self.executor.as_ref().is_some_and(|executor| executor.is_closed()) {
+                    self.executor = None;
+                    self.inner_mut().is_stale = true;
+                }
+
+                true
+            }
+            None => false,
+        }
+    }
+
     pub(crate) fn flush(&mut self, world: &mut Store) {
         self.inner_mut().flush(world);
     }
@@ -243,13 +417,110 @@ impl Schedule {
         id: SystemId, 
         world: &mut UnsafeStore
     ) -> Result<()> {
-        self.inner().systems[id.index()].as_mut().run(world)
+        let inner = self.inner();
+
+        if inner.is_enabled(id, world) {
+            world.check_transaction(inner.planner.meta(id))?;
+
+            inner.observe(id, || inner.systems[id.index()].access().run(world))
+        } else {
+            Ok(())
+        }
     }
 
     pub(crate) unsafe fn run_unsafe(&self, id: SystemId, world: &UnsafeStore) -> Result<()> {
         self.inner().run_unsafe(id, world)
     }
 
+    ///
+    /// Runs the prepared systems serially in plan order, leaving command
+    /// flushes to the caller.
+    ///
+    pub(crate) unsafe fn run_deferred(&self, world: &UnsafeStore) -> Result<()> {
+        let inner = self.inner();
+
+        for id in &inner.order {
+            if ! inner.planner.meta(*id).is_marker() {
+                inner.run_unsafe(*id, world)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// Conflicting access between the two prepared schedules' systems, or
+    /// None if they can run in parallel.
+    ///
+    pub(crate) fn conflict(&self, other: &Schedule) -> Option<String> {
+        self.inner().planner.conflict(&other.inner().planner)
+    }
+
+    ///
+    /// Runs the prepared schedules at the same time, each on its own
+    /// executor's threads. A schedule whose executor can't run in the
+    /// background, e.g. the single executor, runs on the calling thread.
+    ///
+    pub(crate) unsafe fn run_parallel(
+        schedules: &mut [&mut Schedule], 
+        world: &UnsafeStore
+    ) -> Result<()> {
+        let mut started = Vec::new();
+
+        for (i, schedule) in schedules.iter_mut().enumerate() {
+            let exec_schedule = schedule.take();
+
+            let unstarted = match &mut schedule.executor {
+                Some(executor) => executor.start_shared(exec_schedule, world),
+                None => Some(exec_schedule),
+            };
+
+            match unstarted {
+                Some(exec_schedule) => schedule.replace(exec_schedule),
+                None => started.push(i),
+            }
+        }
+
+        let mut result = Ok(());
+
+        for (i, schedule) in schedules.iter().enumerate() {
+            if ! started.contains(&i) {
+                result = result.and(schedule.run_deferred(world));
+            }
+        }
+
+        for i in started {
+            let schedule = &mut *schedules[i];
+
+            match schedule.executor.as_mut().and_then(|executor| executor.join_shared()) {
+                Some((exec_schedule, value)) => {
+                    schedule.replace(exec_schedule);
+                    result = result.and(value);
+                }
+                None => {
+                    result = result.and(Err("executor lost its shared schedule".into()));
+                }
+            }
+
+            // a panic stops the executor, so the schedule replans with a new one
+            if schedule.executor.as_ref().is_some_and(|executor| executor.is_closed()) {
+                schedule.executor = None;
+
+                if let Some(inner) = &mut schedule.inner {
+                    inner.is_stale = true;
+                }
+            }
+        }
+
+        result
+    }
+
+    pub(crate) fn has_main_thread(&self) -> bool {
+        let inner = self.inner();
+
+        inner.order.iter().any(|id| inner.planner.meta(*id).is_main_thread())
+    }
+
     fn inner(&self) -> &ScheduleInner {
         match &self.inner {
             Some(inner) => inner,
@@ -283,6 +554,96 @@ impl Schedule {
         self.inner_mut().set_executor_factory(Box::new(executor));
     }
 
+    ///
+    /// Adds an observer called before and after each system run.
+    ///
+    pub fn add_observer(&mut self, observer: impl SystemObserver) {
+        self.inner_mut().observers.push(Arc::new(observer));
+    }
+
+    ///
+    /// Adds a hook called with the new plan whenever the schedule is
+    /// replanned.
+    ///
+    pub fn add_plan_hook(&mut self, hook: impl PlanHook) {
+        self.inner_mut().add_plan_hook(Arc::new(hook));
+    }
+
+    ///
+    /// Flushes the systems' buffers in stages by kind, e.g. Commands
+    /// before index updates, instead of one pass in system order.
+    /// Buffers of unlisted kinds flush last.
+    ///
+    pub fn set_flush_order(&mut self, order: impl IntoIterator<Item=BufferKind>) {
+        self.inner_mut().flush_order = order.into_iter().collect();
+    }
+
+    ///
+    /// Sets how ordering cycles are broken. Takes effect when the
+    /// schedule is next prepared.
+    ///
+    pub fn set_cycle_strategy(&mut self, strategy: CycleStrategy) {
+        let inner = self.inner_mut();
+
+        inner.planner.set_cycle_strategy(strategy);
+        inner.is_stale = true;
+    }
+
+    ///
+    /// Prepares the schedule and exports its compiled plan, so startup
+    /// can skip planning for large fixed schedules with import_plan.
+    ///
+    pub fn export_plan(&mut self, world: &mut Store) -> Result<PlanExport> {
+        self.prepare(world)?;
+
+        let inner = self.inner_mut();
+        let hash = inner.planner.system_set_hash();
+
+        Ok(inner.planner.plan().export(hash))
+    }
+
+    ///
+    /// Uses an exported plan instead of planning when the schedule is
+    /// next prepared. If the systems or their access no longer match the
+    /// export, or the export leaves conflicting systems unordered, the
+    /// schedule is planned as usual.
+    ///
+    pub fn import_plan(&mut self, plan: PlanExport) {
+        let inner = self.inner_mut();
+
+        inner.imported_plan = Some(plan);
+        inner.is_stale = true;
+    }
+
+    ///
+    /// True if the current plan came from import_plan.
+    ///
+    pub fn is_plan_imported(&self) -> bool {
+        self.inner().is_plan_imported
+    }
+
+    ///
+    /// Removes the systems added with the label and returns the number
+    /// removed. The schedule is replanned on its next tick.
+    ///
+    pub fn remove_systems(&mut self, label: impl SystemLabel) -> usize {
+        self.inner_mut().remove_systems(&label)
+    }
+
+    ///
+    /// Approximate bytes held by each system's local state and pending
+    /// commands, keyed by system name.
+    ///
+    pub fn system_memory(&self) -> Vec<(String, usize)> {
+        let inner = self.inner();
+
+        inner.systems.iter().enumerate()
+            .map(|(i, system)| (inner.planner.meta(SystemId(i)), system.get_ref().memory_size()))
+            .filter(|(_, size)| *size > 0)
+            .map(|(meta, size)| (meta.name().to_string(), size))
+            .collect()
+    }
+
     fn set_executor_factory(&mut self, factory: Box<dyn ExecutorFactory>) {
         self.inner_mut().set_executor_factory(factory);
     }
@@ -297,10 +658,21 @@ impl Default for Schedule {
                 systems: Default::default(),
                 uninit_systems: Default::default(),
                 conditions: Default::default(),
+                labels: Default::default(),
+                config_errors: Default::default(),
 
                 planner: Planner::new(),
+                order: Vec::new(),
 
                 executor_factory: Default::default(),
+
+                observers: Vec::new(),
+                plan_hooks: Vec::new(),
+
+                flush_order: Vec::new(),
+
+                imported_plan: None,
+                is_plan_imported: false,
     
                 is_stale: true,
             }),
@@ -314,21 +686,37 @@ struct ScheduleInner {
 
     systems: Vec<BoxedSystem>,
     conditions: Vec<Vec<BoxedCondition>>,
+    labels: Vec<Vec<Box<dyn SystemLabel>>>,
     uninit_systems: Vec<SystemId>,
+    // invalid system configs, e.g. run_if on a group
+    config_errors: Vec<String>,
 
     planner: Planner,
+    order: Vec<SystemId>,
 
     executor_factory: Box<dyn ExecutorFactory>,
 
+    observers: Vec<Arc<dyn SystemObserver>>,
+    plan_hooks: Vec<Arc<dyn PlanHook>>,
+
+    flush_order: Vec<BufferKind>,
+
+    imported_plan: Option<PlanExport>,
+    is_plan_imported: bool,
+
     is_stale: bool,
 }
 
 impl ScheduleInner {
-    fn add_system(&mut self, config: SystemConfig) {
+    fn add_system(&mut self, config: SystemConfig) -> SystemId {
         let SystemConfig {
             system,
             phases,
             mut conditions,
+            priority,
+            labels,
+            is_main_thread,
+            is_cycle_break,
         } = config;
 
         let phase_ids = phases
@@ -357,13 +745,56 @@ impl ScheduleInner {
 
         self.is_stale = true;
 
-        self.add_system2(
+        let id = self.add_system2(
             UnsafeSyncCell::new(system), 
             phase_id, 
             conditions.drain(..)
             .map(|s| UnsafeSyncCell::new(s))
             .collect(),
         );
+
+        if let Some(priority) = priority {
+            self.planner.meta_mut(id).set_priority(priority);
+        }
+
+        if is_main_thread {
+            self.planner.meta_mut(id).set_main_thread();
+        }
+
+        if is_cycle_break {
+            self.planner.meta_mut(id).set_cycle_break();
+        }
+
+        self.labels[id.index()] = labels;
+
+        id
+    }
+
+    ///
+    /// Replaces labeled systems with no-op systems, keeping the SystemIds
+    /// stable, and marks the schedule for replanning.
+    ///
+    fn remove_systems(&mut self, label: &dyn SystemLabel) -> usize {
+        let mut count = 0;
+
+        for i in 0..self.systems.len() {
+            if self.labels[i].iter().any(|l| l.as_ref() == label) {
+                let id = SystemId(i);
+
+                self.systems[i] = UnsafeSyncCell::new(Box::new(RemovedSystem));
+                self.conditions[i].clear();
+                self.labels[i].clear();
+                self.planner.remove(id);
+
+                count += 1;
+            }
+        }
+
+        if count > 0 {
+            self.is_stale = true;
+        }
+
+        count
     }
 
     fn add_system2(
@@ -377,6 +808,7 @@ impl ScheduleInner {
 
         self.systems.push(system);
         self.conditions.push(conditions);
+        self.labels.push(Vec::new());
         self.uninit_systems.push(id);
 
         self.planner.add(id, type_name, phase_id);
@@ -409,6 +841,10 @@ impl ScheduleInner {
     }
 
     pub(crate) fn init(&mut self, world: &mut Store) -> Result<()> {
+        if let Some(err) = self.config_errors.first() {
+            return Err(err.clone().into());
+        }
+
         self.init_phases();
 
         for id in self.uninit_systems.drain(..) {
@@ -417,16 +853,57 @@ impl ScheduleInner {
             
             system.get_mut().init(&mut meta, world)?;
 
+            // conditions run in their system's task, so their accesses
+            // are planned as part of the system
             for cond in &mut self.conditions[id.index()] {
                 cond.get_mut().init(&mut meta, world)?;
             }
         }
 
-        self.planner.sort();
+        self.planner.sort()?;
 
         Ok(())
     }
 
+    fn validate(&mut self, world: &mut Store) -> Vec<String> {
+        self.init_phases();
+
+        let mut problems = self.config_errors.clone();
+        let mut uninit = Vec::new();
+
+        for id in mem::take(&mut self.uninit_systems) {
+            let meta = self.planner.meta_mut(id);
+
+            let result = self.systems[id.index()].get_mut().init(meta, world)
+                .and_then(|_| self.conditions[id.index()].iter_mut()
+                    .try_for_each(|cond| cond.get_mut().init(meta, world))
+                );
+
+            if let Err(err) = result {
+                problems.push(err.message().to_string());
+                uninit.push(id);
+            }
+        }
+
+        // failed systems are initialized again by prepare
+        self.uninit_systems = uninit;
+        self.is_stale = true;
+
+        for (i, conditions) in self.conditions.iter().enumerate() {
+            let meta = self.planner.meta(SystemId(i));
+
+            if ! conditions.is_empty() && meta.is_marker() {
+                problems.push(format!("conditions on marker system {}", meta.name()));
+            }
+        }
+
+        for phase in self.planner.empty_phases() {
+            problems.push(format!("phase {} has no systems", phase));
+        }
+
+        problems
+    }
+
     fn init_phases(&mut self) {
         let uninit = self.planner.phases_mut().uninit_phases();
 
@@ -449,12 +926,66 @@ impl ScheduleInner {
     }
 
     pub(crate) fn plan(&mut self) -> Plan {
+        self.is_plan_imported = false;
+
+        if let Some(export) = &self.imported_plan {
+            if export.hash() != self.planner.system_set_hash() {
+                info!("imported plan doesn't match the schedule's systems, replanning");
+                self.imported_plan = None;
+            } else {
+                let plan = Plan::from_export(export, self.systems.len())
+                    .and_then(|plan| self.planner.check_plan(&plan).map(|_| plan));
+
+                match plan {
+                    Ok(plan) => {
+                        self.is_plan_imported = true;
+                        return plan;
+                    }
+                    Err(err) => {
+                        warn!("invalid imported plan, replanning: {}", err.message());
+                        self.imported_plan = None;
+                    }
+                }
+            }
+        }
+
         self.planner.plan()
     }
 
+    fn add_plan_hook(&mut self, hook: Arc<dyn PlanHook>) {
+        self.plan_hooks.push(hook);
+        // an existing plan is reported on the next tick
+        self.is_stale = true;
+    }
+
+    fn run_plan_hooks(&self, plan: &Plan) -> Result<()> {
+        let view = PlanView::new(&self.planner, plan);
+
+        for hook in &self.plan_hooks {
+            hook.on_plan(&view)?;
+        }
+
+        Ok(())
+    }
+
     pub(crate) fn flush(&mut self, world: &mut Store) {
+        if self.flush_order.is_empty() {
+            for system in &mut self.systems {
+                system.get_mut().flush(world);
+            }
+
+            return;
+        }
+
+        for kind in &self.flush_order {
+            for system in &mut self.systems {
+                system.get_mut().flush_filtered(world, &|k| k == Some(*kind));
+            }
+        }
+
+        let order = &self.flush_order;
         for system in &mut self.systems {
-            system.get_mut().flush(world);
+            system.get_mut().flush_filtered(world, &|k| k.is_none_or(|k| ! order.contains(&k)));
         }
     }
 
@@ -471,15 +1002,53 @@ impl ScheduleInner {
     */
 
     unsafe fn run_unsafe(&self, id: SystemId, world: &UnsafeStore) -> Result<()> {
-        if self.conditions[id.index()].iter()
-            .fold(true, |v, cond| {
-            cond.as_mut().run_unsafe(world).unwrap() && v
-        }) {
-            self.systems[id.index()].as_mut().run_unsafe(world)
+        if self.is_enabled(id, world) {
+            let meta = self.planner.meta(id);
+
+            world.check_transaction(meta)?;
+
+            // exclusive systems own the whole store, so nothing can overlap
+            #[cfg(debug_assertions)]
+            let _borrow = (! meta.is_exclusive()).then(|| world.borrow_resources(meta));
+
+            self.observe(id, || self.systems[id.index()].access().run_unsafe(world))
         } else {
             Ok(())
         }
     }
+
+    unsafe fn is_enabled(&self, id: SystemId, world: &UnsafeStore) -> bool {
+        let mut is_enabled = true;
+
+        // every condition runs, even after one fails
+        for cond in &self.conditions[id.index()] {
+            is_enabled &= cond.access().run_unsafe(world).unwrap();
+        }
+
+        is_enabled
+    }
+
+    fn observe(&self, id: SystemId, run: impl FnOnce() -> Result<()>) -> Result<()> {
+        if self.observers.is_empty() {
+            return run();
+        }
+
+        let meta = self.planner.meta(id);
+
+        for observer in &self.observers {
+            observer.before_run(meta);
+        }
+
+        let start = Instant::now();
+        let result = run();
+        let elapsed = start.elapsed();
+
+        for observer in &self.observers {
+            observer.after_run(meta, elapsed);
+        }
+
+        result
+    }
 }
 
 struct PhaseSystem(PhaseId);
@@ -503,8 +1072,29 @@ impl System for PhaseSystem {
     }
 }
 
+struct RemovedSystem;
+
+impl System for RemovedSystem {
+    type Out = ();
+
+    fn init(&mut self, _meta: &mut SystemMeta, _world: &mut Store) -> Result<()> {
+        Ok(())
+    }
+
+    unsafe fn run_unsafe(&mut self, _world: &UnsafeStore) -> Result<Self::Out> {
+        Ok(())
+    }
+
+    fn flush(&mut self, _world: &mut Store) {
+    }
+}
+
 pub trait ScheduleLabel : Send + DynLabel + fmt::Debug {
     fn box_clone(&self) -> BoxedLabel;
+
+    fn label_id(&self) -> LabelId {
+        intern_label(self.as_dyn_eq(), || self.box_clone())
+    }
 }
 
 impl PartialEq for dyn ScheduleLabel {
@@ -541,7 +1131,9 @@ impl From<SystemId> for NodeId {
 
 #[cfg(test)]
 mod tests {
-    use crate::{store::Store, schedule::Phase, util::test::TestValues};
+    use std::{mem, sync::{Arc, Mutex}, thread, time::Duration};
+
+    use crate::{store::Store, schedule::{CycleStrategy, Phase, Priority, SystemMeta, SystemObserver, PlanView, PlanExport}, util::test::TestValues};
 
     use super::{Schedule, ScheduleLabel};
     use crate::*;
@@ -643,6 +1235,113 @@ mod tests {
         assert_eq!(values.take(), "b, c");
     }
 
+    #[test]
+    fn chain_systems() {
+        let mut values = TestValues::new();
+        let mut world = Store::new();
+
+        let mut schedule = Schedule::new();
+
+        let mut ptr_a = values.clone();
+        let mut ptr_b = values.clone();
+        let mut ptr_c = values.clone();
+        schedule.add_system((
+            move || {
+                thread::sleep(Duration::from_millis(20));
+                ptr_a.push("a");
+            },
+            move || {
+                thread::sleep(Duration::from_millis(10));
+                ptr_b.push("b");
+            },
+            move || ptr_c.push("c"),
+        ).chain());
+
+        schedule.tick(&mut world).unwrap();
+        assert_eq!(values.take(), "a, b, c");
+
+        // nested chains keep their order, unordered with each other
+        let mut schedule = Schedule::new();
+
+        let mut ptr_c = values.clone();
+        let mut ptr_b = values.clone();
+        let mut ptr_a = values.clone();
+        schedule.add_system((
+            (move || ptr_c.push("c"), move || ptr_b.push("b")).chain(),
+            move || ptr_a.push("a"),
+        ));
+
+        schedule.tick(&mut world).unwrap();
+        let value = values.take();
+        assert!(value.find("c").unwrap() < value.find("b").unwrap(), "{}", value);
+    }
+
+    #[test]
+    fn chain_overrides_access_order() {
+        let mut values = TestValues::new();
+        let mut world = Store::new();
+        world.insert_resource(0u32);
+        world.insert_resource(0u64);
+
+        let mut schedule = Schedule::new();
+
+        // access order is write -> read, and the u64 writer gives the
+        // reader more incoming arrows
+        let mut ptr = values.clone();
+        schedule.add_system((
+            move |value: Res<u32>, _b: Res<u64>| ptr.push(&format!("read {}", *value)),
+            |mut value: ResMut<u32>| *value += 1,
+        ).chain());
+        schedule.add_system(|mut value: ResMut<u64>| *value += 1);
+
+        schedule.tick(&mut world).unwrap();
+        assert_eq!(values.take(), "read 0");
+
+        schedule.tick(&mut world).unwrap();
+        assert_eq!(values.take(), "read 1");
+    }
+
+    #[test]
+    fn cycle_strategy() {
+        let mut values = TestValues::new();
+        let mut world = Store::new();
+
+        // the chain orders b before a, against the phase order
+        let build = |values: &TestValues, strategy: CycleStrategy| {
+            let mut schedule = new_schedule_a_b_c();
+            schedule.set_cycle_strategy(strategy);
+
+            let mut ptr_b = values.clone();
+            let mut ptr_a = values.clone();
+            schedule.add_system((
+                (move || ptr_b.push("b")).phase(TestPhase::B),
+                (move || ptr_a.push("a")).phase(TestPhase::A)
+                    .priority(Priority::LOW)
+                    .allow_cycle_break(),
+            ).chain());
+
+            schedule
+        };
+
+        let mut schedule = build(&values, CycleStrategy::Heuristic);
+        schedule.tick(&mut world).unwrap();
+        // the heuristic breaks the phase arrow, keeping the chain
+        assert_eq!(values.take(), "b, a");
+
+        let mut schedule = build(&values, CycleStrategy::Fail);
+        let err = schedule.tick(&mut world).unwrap_err();
+        assert!(err.message().contains("cycle in schedule"), "{}", err.message());
+        assert_eq!(values.take(), "");
+
+        let mut schedule = build(&values, CycleStrategy::Marked);
+        schedule.tick(&mut world).unwrap();
+        assert_eq!(values.take(), "a, b");
+
+        let mut schedule = build(&values, CycleStrategy::LowestPriority);
+        schedule.tick(&mut world).unwrap();
+        assert_eq!(values.take(), "a, b");
+    }
+
     fn new_schedule_a_b_c() -> Schedule {
         let mut schedule = Schedule::new();
         schedule.add_phases((
@@ -655,6 +1354,271 @@ mod tests {
         schedule
     }
 
+    #[test]
+    fn observer() {
+        let values = TestValues::new();
+
+        let mut world = Store::new();
+        let mut schedule = Schedule::new();
+        schedule.set_executor(schedule::Executors::Single);
+        schedule.add_observer(TestObserver(Mutex::new(values.clone())));
+
+        let mut ptr = values.clone();
+        schedule.add_system(move || { 
+            ptr.push("a"); 
+        });
+
+        schedule.tick(&mut world).unwrap();
+        assert_eq!(values.clone().take(), "before, a, after");
+
+        let mut schedules = Schedules::default();
+        schedules.insert(core_app::Core, Schedule::new());
+        schedules.add_observer(TestObserver(Mutex::new(values.clone())));
+
+        let mut ptr = values.clone();
+        schedules.add_system(core_app::Core, move || { 
+            ptr.push("b"); 
+        });
+
+        schedules.tick(core_app::Core, &mut world).unwrap();
+        assert_eq!(values.clone().take(), "before, b, after");
+
+        // re-inserting a schedule doesn't add the observers again
+        let schedule = schedules.remove(&core_app::Core).unwrap();
+        schedules.insert(core_app::Core, schedule);
+
+        schedules.tick(core_app::Core, &mut world).unwrap();
+        schedules.tick(core_app::Core, &mut world).unwrap();
+        assert_eq!(values.clone().take(), "before, b, after, before, b, after");
+    }
+
+    #[test]
+    fn plan_hook() {
+        let mut world = Store::new();
+        let mut schedule = Schedule::new();
+
+        let counts = Arc::new(Mutex::new(Vec::<usize>::new()));
+
+        let ptr = counts.clone();
+        schedule.add_plan_hook(move |plan: &PlanView| {
+            let n = plan.metas().filter(|meta| ! meta.is_marker()).count();
+            ptr.lock().unwrap().push(n);
+            Ok(())
+        });
+
+        schedule.add_system(|| {});
+        schedule.tick(&mut world).unwrap();
+        schedule.tick(&mut world).unwrap();
+        assert_eq!(*counts.lock().unwrap(), vec![1]);
+
+        schedule.add_system(|| {});
+        schedule.tick(&mut world).unwrap();
+        assert_eq!(*counts.lock().unwrap(), vec![1, 2]);
+
+        schedule.add_plan_hook(|_: &PlanView| Err("rejected plan".into()));
+        let err = schedule.tick(&mut world).unwrap_err();
+        assert!(err.message().contains("rejected plan"));
+    }
+
+    #[test]
+    fn plan_view_meta_access() {
+        let mut world = Store::new();
+        world.insert_resource(0u32);
+        world.insert_resource(0u64);
+
+        let id_u32 = world.get_resource_id::<u32>();
+        let id_u64 = world.get_resource_id::<u64>();
+
+        let mut schedule = Schedule::new();
+        schedule.add_system((|_: Res<u32>, _: ResMut<u64>| {}).phase(TestPhase::A));
+
+        let access = Arc::new(Mutex::new(Vec::new()));
+
+        let ptr = access.clone();
+        schedule.add_plan_hook(move |plan: &PlanView| {
+            for meta in plan.metas().filter(|meta| ! meta.is_marker()) {
+                ptr.lock().unwrap().push((
+                    meta.resources().iter().copied().collect::<Vec<_>>(),
+                    meta.mut_resources().iter().copied().collect::<Vec<_>>(),
+                    meta.phase_id().index() != 0,
+                    meta.is_exclusive(),
+                ));
+            }
+            Ok(())
+        });
+
+        schedule.tick(&mut world).unwrap();
+
+        assert_eq!(*access.lock().unwrap(), vec![(vec![id_u32], vec![id_u64], true, false)]);
+    }
+
+    #[test]
+    fn export_import_plan() {
+        let mut world = Store::new();
+        world.insert_resource(0u32);
+
+        let mut values = TestValues::new();
+
+        let build = |values: &TestValues| {
+            let mut schedule = Schedule::new();
+
+            let mut ptr = values.clone();
+            schedule.add_system(move |_: Res<u32>| ptr.push("read"));
+            let mut ptr = values.clone();
+            schedule.add_system(move |_: ResMut<u32>| ptr.push("write"));
+
+            schedule
+        };
+
+        let text = build(&values).export_plan(&mut world).unwrap().to_string();
+        let export: PlanExport = text.parse().unwrap();
+        assert_eq!(export.to_string(), text);
+
+        let mut schedule = build(&values);
+        schedule.import_plan(export.clone());
+        schedule.tick(&mut world).unwrap();
+        assert!(schedule.is_plan_imported());
+        assert_eq!(values.take(), "write, read");
+
+        // a changed system set falls back to planning
+        let mut schedule = build(&values);
+        schedule.add_system(|| {});
+        schedule.import_plan(export);
+        schedule.tick(&mut world).unwrap();
+        assert!(! schedule.is_plan_imported());
+        assert_eq!(values.take(), "write, read");
+    }
+
+    #[test]
+    fn import_plan_missing_conflict_arrow() {
+        let mut world = Store::new();
+        world.insert_resource(0u32);
+
+        let mut values = TestValues::new();
+
+        let build = |values: &TestValues| {
+            let mut schedule = Schedule::new();
+
+            let mut ptr = values.clone();
+            schedule.add_system(move |_: Res<u32>| ptr.push("read"));
+            let mut ptr = values.clone();
+            schedule.add_system(move |_: ResMut<u32>| ptr.push("write"));
+
+            schedule
+        };
+
+        let text = build(&values).export_plan(&mut world).unwrap().to_string();
+
+        // keep the hash but drop the arrow between the reader and writer
+        let edited: String = text.lines()
+            .map(|line| match line.split_once('>') {
+                Some((id, _)) => format!("{}>\n", id),
+                None => format!("{}\n", line),
+            })
+            .collect();
+        assert_ne!(edited, text);
+        let export: PlanExport = edited.parse().unwrap();
+
+        let mut schedule = build(&values);
+        schedule.import_plan(export);
+        schedule.tick(&mut world).unwrap();
+        assert!(! schedule.is_plan_imported());
+        assert_eq!(values.take(), "write, read");
+    }
+
+    #[test]
+    fn import_plan_missing_phase_arrow() {
+        let mut world = Store::new();
+
+        let mut values = TestValues::new();
+
+        let build = |values: &TestValues| {
+            let mut schedule = new_schedule_a_b_c();
+
+            let mut ptr = values.clone();
+            schedule.add_system((move || ptr.push("c")).phase(TestPhase::C));
+            let mut ptr = values.clone();
+            schedule.add_system((move || ptr.push("a")).phase(TestPhase::A));
+
+            schedule
+        };
+
+        let text = build(&values).export_plan(&mut world).unwrap().to_string();
+
+        // the systems don't conflict, so only the phase order is lost
+        let edited: String = text.lines()
+            .map(|line| match line.split_once('>') {
+                Some((id, _)) => format!("{}>\n", id),
+                None => format!("{}\n", line),
+            })
+            .collect();
+        let export: PlanExport = edited.parse().unwrap();
+
+        let mut schedule = build(&values);
+        schedule.import_plan(export);
+        schedule.tick(&mut world).unwrap();
+        assert!(! schedule.is_plan_imported());
+        assert_eq!(values.take(), "a, c");
+    }
+
+    #[test]
+    fn system_memory() {
+        let mut world = Store::new();
+        let mut schedule = Schedule::new();
+
+        schedule.add_system(|_local: Local<u64>| {});
+        schedule.add_system(|mut c: Commands, mut n: Local<usize>| {
+            *n += 1;
+
+            for _ in 0..*n * 100 {
+                c.add(|_: &mut Store| {});
+            }
+        });
+
+        schedule.tick(&mut world).unwrap();
+        let mut before: Vec<usize> = schedule.system_memory().iter().map(|m| m.1).collect();
+        before.sort();
+
+        schedule.tick(&mut world).unwrap();
+        let mut after: Vec<usize> = schedule.system_memory().iter().map(|m| m.1).collect();
+        after.sort();
+
+        assert_eq!(before.len(), 2);
+        assert_eq!(before[0], 8);
+        assert_eq!(after[0], 8);
+        assert!(before[1] > 100 * 8);
+        assert!(after[1] > before[1]);
+    }
+
+    #[test]
+    fn system_memory_exclusive() {
+        let mut world = Store::new();
+        let mut schedule = Schedule::new();
+
+        schedule.add_system(|_store: &mut Store, mut list: Local<Vec<u64>>| -> error::Result<()> {
+            list.reserve_exact(64);
+            Ok(())
+        });
+
+        schedule.tick(&mut world).unwrap();
+        let memory = schedule.system_memory();
+
+        assert_eq!(memory.len(), 1);
+        assert!(memory[0].1 >= mem::size_of::<Vec<u64>>() + 64 * 8);
+    }
+
+    struct TestObserver(Mutex<TestValues>);
+
+    impl SystemObserver for TestObserver {
+        fn before_run(&self, _meta: &SystemMeta) {
+            self.0.lock().unwrap().push("before");
+        }
+
+        fn after_run(&self, _meta: &SystemMeta, _elapsed: Duration) {
+            self.0.lock().unwrap().push("after");
+        }
+    }
+
     #[derive(PartialEq, Hash, Eq, Clone, Debug)]
     enum TestSchedule {
         A,
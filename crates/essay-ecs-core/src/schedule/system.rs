@@ -1,16 +1,68 @@
-use crate::{system::System, IntoSystem};
+use core::fmt;
+use std::hash::{Hash, Hasher};
 
-use super::Phase;
+use crate::{system::System, util::DynLabel, IntoSystem};
+
+use super::{Phase, Priority};
+
+///
+/// Label for a group of systems, e.g. a feature, so the group can be
+/// removed together with Schedule::remove_systems.
+///
+pub trait SystemLabel: Send + DynLabel + fmt::Debug {
+    fn box_clone(&self) -> Box<dyn SystemLabel>;
+}
+
+impl PartialEq for dyn SystemLabel {
+    fn eq(&self, other: &Self) -> bool {
+        self.dyn_eq(other.as_dyn_eq())
+    }
+}
+
+impl Eq for dyn SystemLabel {}
+
+impl Hash for dyn SystemLabel {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.dyn_hash(state);
+    }
+}
 
 pub struct SystemConfigs {
     pub(crate) systems: Vec::<SystemConfig>,
+
+    // (before, after) indices into systems
+    pub(crate) arrows: Vec<(usize, usize)>,
+
+    // reported when the schedule initializes
+    pub(crate) errors: Vec<String>,
 }
 
 impl SystemConfigs {
     fn new(system: Box<dyn System<Out=()>>) -> Self {
         Self {
-            systems: vec![SystemConfig::new(system)]
+            systems: vec![SystemConfig::new(system)],
+            arrows: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    ///
+    /// Runs the systems in order, each after the previous one completes.
+    ///
+    pub fn chained(mut self) -> SystemConfigs {
+        for i in 1..self.systems.len() {
+            self.arrows.push((i - 1, i));
         }
+
+        self
+    }
+
+    fn append(&mut self, mut configs: SystemConfigs) {
+        let offset = self.systems.len();
+
+        self.systems.append(&mut configs.systems);
+        self.arrows.extend(configs.arrows.iter().map(|(a, b)| (a + offset, b + offset)));
+        self.errors.append(&mut configs.errors);
     }
 
     fn phase(mut self, phase: impl Phase) -> SystemConfigs {
@@ -23,15 +75,53 @@ impl SystemConfigs {
         self
     }
 
-    fn run_if<N>(self, _condition: impl IntoSystem<bool, N>) -> SystemConfigs {
-        /*
-        config.conditions.push(Box::new(IntoSystem::into_system(condition)));
-        config
-        */
+    fn priority(mut self, priority: Priority) -> SystemConfigs {
+        for system in &mut self.systems {
+            system.priority = Some(priority);
+        }
+
+        self
+    }
+
+    fn label(mut self, label: impl SystemLabel) -> SystemConfigs {
+        for system in &mut self.systems {
+            system.labels.push(label.box_clone());
+        }
+
+        self
+    }
+
+    fn main_thread(mut self) -> SystemConfigs {
+        for system in &mut self.systems {
+            system.is_main_thread = true;
+        }
+
+        self
+    }
+
+    fn allow_cycle_break(mut self) -> SystemConfigs {
+        for system in &mut self.systems {
+            system.is_cycle_break = true;
+        }
+
+        self
+    }
 
-        todo!();
+    fn run_if<N>(mut self, condition: impl IntoSystem<bool, N>) -> SystemConfigs {
+        // conditions aren't shareable, so groups can't take one
+        if self.systems.len() != 1 {
+            let names: Vec<&str> = self.systems.iter()
+                .map(|config| config.system.type_name())
+                .collect();
 
-        //self
+            self.errors.push(format!("run_if needs a single system, not {:?}", names));
+
+            return self;
+        }
+
+        self.systems[0].conditions.push(Box::new(IntoSystem::into_system(condition)));
+
+        self
     }
 }
 
@@ -41,6 +131,14 @@ pub(crate) struct SystemConfig {
     pub(crate) phases: Vec<Box<dyn Phase>>,
 
     pub(crate) conditions: Vec<Box<dyn System<Out = bool>>>,
+
+    pub(crate) priority: Option<Priority>,
+
+    pub(crate) labels: Vec<Box<dyn SystemLabel>>,
+
+    pub(crate) is_main_thread: bool,
+
+    pub(crate) is_cycle_break: bool,
 }
 
 impl SystemConfig {
@@ -49,6 +147,10 @@ impl SystemConfig {
             system,
             phases: Vec::new(),
             conditions: Vec::new(),
+            priority: None,
+            labels: Vec::new(),
+            is_main_thread: false,
+            is_cycle_break: false,
         }
     }
 }
@@ -60,9 +162,45 @@ pub trait IntoSystemConfig<M> : Sized {
         self.into_config().phase(phase)
     }
 
+    ///
+    /// Dispatch order among systems that are ready at the same time.
+    ///
+    fn priority(self, priority: Priority) -> SystemConfigs {
+        self.into_config().priority(priority)
+    }
+
+    fn label(self, label: impl SystemLabel) -> SystemConfigs {
+        self.into_config().label(label)
+    }
+
     fn run_if<N>(self, condition: impl IntoSystem<bool, N>) -> SystemConfigs {
         self.into_config().run_if(condition)
     }
+
+    ///
+    /// Runs the system on the thread that ticks the schedule, for APIs
+    /// like GUI toolkits or OpenGL that require the main thread. Other
+    /// systems still run in parallel.
+    ///
+    fn main_thread(self) -> SystemConfigs {
+        self.into_config().main_thread()
+    }
+
+    ///
+    /// Lets CycleStrategy::Marked break ordering cycles by removing
+    /// arrows into the system.
+    ///
+    fn allow_cycle_break(self) -> SystemConfigs {
+        self.into_config().allow_cycle_break()
+    }
+
+    ///
+    /// Orders a tuple of systems, e.g. (a, b, c).chain() runs a, then b,
+    /// then c.
+    ///
+    fn chain(self) -> SystemConfigs {
+        self.into_config().chained()
+    }
 }
 
 //struct IsSelf;
@@ -87,3 +225,35 @@ where
         SystemConfigs::new(Box::new(IntoSystem::into_system(self)))
     }
 }
+
+pub struct SystemConfigTupleMarker;
+
+macro_rules! impl_system_config_tuple {
+    ($(($name:ident, $marker:ident)),*) => {
+        #[allow(non_snake_case)]
+        impl<$($name: IntoSystemConfig<$marker>, $marker,)*> 
+            IntoSystemConfig<(SystemConfigTupleMarker, $($marker,)*)> for ($($name,)*)
+        {
+            fn into_config(self) -> SystemConfigs {
+                let ($($name,)*) = self;
+                let mut configs = SystemConfigs {
+                    systems: Vec::new(),
+                    arrows: Vec::new(),
+                    errors: Vec::new(),
+                };
+                $(
+                    configs.append($name.into_config());
+                )*
+                configs
+            }
+        }
+    }
+}
+
+impl_system_config_tuple!((S1, M1), (S2, M2));
+impl_system_config_tuple!((S1, M1), (S2, M2), (S3, M3));
+impl_system_config_tuple!((S1, M1), (S2, M2), (S3, M3), (S4, M4));
+impl_system_config_tuple!((S1, M1), (S2, M2), (S3, M3), (S4, M4), (S5, M5));
+impl_system_config_tuple!((S1, M1), (S2, M2), (S3, M3), (S4, M4), (S5, M5), (S6, M6));
+impl_system_config_tuple!((S1, M1), (S2, M2), (S3, M3), (S4, M4), (S5, M5), (S6, M6), (S7, M7));
+impl_system_config_tuple!((S1, M1), (S2, M2), (S3, M3), (S4, M4), (S5, M5), (S6, M6), (S7, M7), (S8, M8));
@@ -1,10 +1,16 @@
 use core::{fmt, panic};
 use std::{
-    thread::{self, JoinHandle}, 
-    sync::{mpsc::{self, Receiver, Sender}, Arc}, 
+    cell::RefCell,
+    thread::{self, JoinHandle, Thread}, 
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError, SyncSender, TryRecvError},
+        Arc, OnceLock,
+    }, 
+    time::Duration,
 };
 
-use concurrent_queue::{ConcurrentQueue, PopError};
+use concurrent_queue::{ConcurrentQueue, PopError, PushError};
 use log::info;
 
 use crate::{
@@ -12,6 +18,12 @@ use crate::{
     system::SystemId
 };
 
+// Channels and the task queue are preallocated so steady-state ticks
+// don't allocate. Executors size them to hold every system in their plan,
+// because a child blocked on a full completion channel can't take the
+// tasks the parent is waiting to queue.
+const CHANNEL_CAPACITY: usize = 1024;
+
 //
 // ThreadPoolBuilder
 //
@@ -20,6 +32,7 @@ pub struct ThreadPoolBuilder {
     parent_task: Option<Box<dyn Fn(&TaskSender) -> Result<()> + Send>>,
     child_task_builder: Option<Box<dyn Fn() -> Box<dyn Fn(SystemId) + Send>>>,
     n_threads: Option<usize>,
+    capacity: usize,
 }
 
 impl ThreadPoolBuilder {
@@ -28,6 +41,7 @@ impl ThreadPoolBuilder {
             parent_task: None,
             child_task_builder: None,
             n_threads: None,
+            capacity: CHANNEL_CAPACITY,
         }
     }
 
@@ -57,14 +71,26 @@ impl ThreadPoolBuilder {
         self
     }
 
+    ///
+    /// Systems the task queue and channels hold without blocking, which
+    /// must cover every system that can be in flight at once.
+    ///
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+
+        self
+    }
+
     pub fn build(self) -> ThreadPool {
         assert!(! self.parent_task.is_none());
         assert!(! self.child_task_builder.is_none());
 
-        let (executive_sender, main_reader) = mpsc::channel();
-        let (main_sender, executive_reader) = mpsc::channel();
+        let capacity = self.capacity;
+
+        let (executive_sender, main_reader) = mpsc::sync_channel(capacity);
+        let (main_sender, executive_reader) = mpsc::sync_channel(capacity);
 
-        let (task_sender, task_reader) = mpsc::channel();
+        let (task_sender, task_reader) = mpsc::sync_channel(capacity);
 
         let n_threads = match self.n_threads {
             Some(n_threads) => n_threads,
@@ -72,8 +98,9 @@ impl ThreadPoolBuilder {
         };
 
         let mut registry = Registry {
-            queue: ConcurrentQueue::unbounded(),
+            queue: ConcurrentQueue::bounded(capacity),
             tasks: Vec::new(),
+            n_alive: AtomicUsize::new(n_threads),
         };
 
         for _ in 0..n_threads {
@@ -81,6 +108,7 @@ impl ThreadPoolBuilder {
         }
 
         let registry = Arc::new(registry);
+        let jobs = Arc::new(JobQueue::new());
 
         let mut handles = Vec::<JoinHandle<()>>::new();
 
@@ -90,6 +118,7 @@ impl ThreadPoolBuilder {
             let mut task_thread = ChildThread::new(
                 builder(),
                 Arc::clone(&registry), 
+                Arc::clone(&jobs),
                 task_sender.clone(),
             );
 
@@ -100,13 +129,18 @@ impl ThreadPoolBuilder {
             handles.push(handle);
         }
 
+        let _ = jobs.threads.set(handles.iter().map(|h| h.thread().clone()).collect());
+
+        let main_task_sender = task_sender.clone();
+
         let mut executive = ParentThread {
             task: self.parent_task.unwrap(),
 
             main_reader,
             main_sender,
 
-            registry,
+            registry: Arc::clone(&registry),
+            jobs,
 
             task_receiver: task_reader,
             handles,
@@ -120,9 +154,11 @@ impl ThreadPoolBuilder {
             //threads: Vec::new(),
 
             executive: Some(handle),
+            registry,
 
             executive_sender,
             executive_reader,
+            main_task_sender,
         }
     }
 }
@@ -130,15 +166,35 @@ impl ThreadPoolBuilder {
 pub struct ThreadPool {
     //threads: Vec<Thread>,
     executive: Option<JoinHandle<()>>,
+    registry: Arc<Registry>,
 
-    executive_sender: Sender<MainMessage>,
+    executive_sender: SyncSender<MainMessage>,
     executive_reader: Receiver<MainMessage>,
+    main_task_sender: SyncSender<Result<SystemId>>,
 }
 
 struct ChildThread {
     task: Box<dyn Fn(SystemId) + Send>,
     registry: Arc<Registry>,
-    sender: Sender<Result<SystemId>>,
+    jobs: Arc<JobQueue>,
+    sender: SyncSender<Result<SystemId>>,
+}
+
+pub(crate) type Job = Box<dyn FnOnce() + Send>;
+
+///
+/// Closures run by the pool's children when no system is queued, e.g.
+/// TaskScope tasks. A thread waiting on a job runs queued jobs itself,
+/// so jobs finish even when every child is busy.
+///
+pub(crate) struct JobQueue {
+    jobs: ConcurrentQueue<Job>,
+    threads: OnceLock<Vec<Thread>>,
+}
+
+thread_local! {
+    // the queue of the pool running on this thread
+    static JOBS: RefCell<Option<Arc<JobQueue>>> = const { RefCell::new(None) };
 }
 
 pub struct TaskSender<'a> {
@@ -152,6 +208,7 @@ enum MainMessage {
     Exit,
     Error(Error),
     Panic(Error),
+    RunMain(SystemId),
 }
 
 enum TaskMessage {
@@ -162,6 +219,8 @@ enum TaskMessage {
 struct Registry {
     queue: ConcurrentQueue<TaskMessage>,
     tasks: Vec<TaskInfo>,
+    // children that haven't exited
+    n_alive: AtomicUsize,
 }
 
 impl Registry {
@@ -187,10 +246,34 @@ impl TaskInfo {
 //
 
 impl ThreadPool {
-    pub fn start(&self) -> Result<()> {
-        if let Err(err) = self.executive_sender.send(MainMessage::Start) {
-            return Err(err.to_string().into());
+    ///
+    /// Starts the parent task, running the systems it sends with
+    /// TaskSender::send_main on the calling thread. The first main task
+    /// error is returned when the parent completes.
+    ///
+    pub fn start_with(&self, main_task: impl Fn(SystemId) -> Result<()>) -> Result<()> {
+        self.begin()?;
+
+        self.wait_with(main_task)
+    }
+
+    ///
+    /// Starts the parent task without waiting for it, so the caller can
+    /// start other pools. wait_with completes the run.
+    ///
+    pub fn begin(&self) -> Result<()> {
+        match self.executive_sender.send(MainMessage::Start) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(err.to_string().into()),
         }
+    }
+
+    ///
+    /// Waits for the parent task started by begin, running its main
+    /// thread systems on the calling thread.
+    ///
+    pub fn wait_with(&self, main_task: impl Fn(SystemId) -> Result<()>) -> Result<()> {
+        let mut main_error: Option<Error> = None;
         
         loop {
             match self.executive_reader.recv() {
@@ -198,8 +281,20 @@ impl ThreadPool {
                     return Err(format!("unexpected exit\n\tin {}:{}", file!(), line!()).into());
                     // panic!("unexpected exit");
                 }
+                Ok(MainMessage::RunMain(id)) => {
+                    if let Err(err) = main_task(id) {
+                        main_error.get_or_insert(err);
+                    }
+
+                    if let Err(err) = self.main_task_sender.send(Ok(id)) {
+                        return Err(format!("{:?}\n\tat {}:{}", err, file!(), line!()).into());
+                    }
+                }
                 Ok(MainMessage::Complete) => {
-                    return Ok(());
+                    return match main_error {
+                        Some(err) => Err(err),
+                        None => Ok(()),
+                    };
                 }
                 Ok(MainMessage::Panic(error)) => {
                     return Err(error);
@@ -217,6 +312,21 @@ impl ThreadPool {
         }
     }
 
+    ///
+    /// False once the parent thread has exited, e.g. after a panic.
+    ///
+    pub fn is_alive(&self) -> bool {
+        self.executive.as_ref().is_some_and(|handle| ! handle.is_finished())
+    }
+
+    ///
+    /// True once the parent and every child have exited, so no task is
+    /// running or will run.
+    ///
+    pub fn is_stopped(&self) -> bool {
+        ! self.is_alive() && self.registry.n_alive.load(Ordering::Acquire) == 0
+    }
+
     pub fn close(&mut self) -> Result<()> {
         match self.executive.take() {
             Some(handle) => {
@@ -257,16 +367,67 @@ pub struct ParentThread {
     task: Box<dyn Fn(&TaskSender) -> Result<()> + Send>,
 
     main_reader: Receiver<MainMessage>,
-    main_sender: Sender<MainMessage>,
+    main_sender: SyncSender<MainMessage>,
 
     registry: Arc<Registry>,
+    jobs: Arc<JobQueue>,
 
     task_receiver: Receiver<Result<SystemId>>,
     handles: Vec<JoinHandle<()>>,
 }
 
+impl JobQueue {
+    fn new() -> Self {
+        Self {
+            jobs: ConcurrentQueue::unbounded(),
+            threads: OnceLock::new(),
+        }
+    }
+
+    ///
+    /// Queue of the pool running the current thread's system, if any.
+    ///
+    pub(crate) fn current() -> Option<Arc<JobQueue>> {
+        JOBS.with(|jobs| jobs.borrow().clone())
+    }
+
+    fn set_current(jobs: &Arc<JobQueue>) {
+        JOBS.with(|current| *current.borrow_mut() = Some(Arc::clone(jobs)));
+    }
+
+    pub(crate) fn push(&self, job: Job) {
+        if let Err(err) = self.jobs.push(job) {
+            // the pool has closed, so the caller runs the job
+            err.into_inner()();
+            return;
+        }
+
+        self.unpark_all();
+    }
+
+    fn unpark_all(&self) {
+        if let Some(threads) = self.threads.get() {
+            for thread in threads {
+                thread.unpark();
+            }
+        }
+    }
+
+    ///
+    /// Runs one queued job, returning false if the queue is empty.
+    ///
+    pub(crate) fn run_one(&self) -> bool {
+        match self.jobs.pop() {
+            Ok(job) => { job(); true }
+            Err(_) => false,
+        }
+    }
+}
+
 impl ParentThread {
     pub fn run(&mut self) -> Result<()> {
+        JobQueue::set_current(&self.jobs);
+
         let mut guard = ParentGuard::new(self);
 
         let sender = TaskSender { thread: &self };
@@ -352,16 +513,20 @@ impl ChildThread {
     pub fn new(
         task: Box<dyn Fn(SystemId) + Send>,
         registry: Arc<Registry>, 
-        sender: Sender<Result<SystemId>>,
+        jobs: Arc<JobQueue>,
+        sender: SyncSender<Result<SystemId>>,
     ) -> Self {
         Self {
             task,
             registry,
+            jobs,
             sender,
         }
     }
 
     pub fn run(&mut self) {
+        JobQueue::set_current(&self.jobs);
+
         let mut guard = ChildGuard::new(self);
 
         let queue = &self.registry.queue;
@@ -370,17 +535,28 @@ impl ChildThread {
             let msg = match queue.pop() {
                 Ok(msg) => msg,
                 Err(PopError::Empty) => {
-                    thread::park();
+                    // systems take priority over jobs
+                    if ! self.jobs.run_one() {
+                        thread::park();
+                    }
                     continue;
                 }
-                Err(err) => panic!("unknown queue error {:?}", err)
+                Err(PopError::Closed) => {
+                    // queued tasks have run, so the child exits
+                    guard.close();
+                    return;
+                }
             };
 
             match msg {
                 TaskMessage::Start(id) => {
                     (self.task)(id);
 
-                    self.sender.send(Ok(id)).unwrap();
+                    // the parent is gone if the pool closed while the system ran
+                    if self.sender.send(Ok(id)).is_err() {
+                        guard.close();
+                        return;
+                    }
                 },
                 TaskMessage::_Exit => {
                     guard.close();
@@ -412,35 +588,78 @@ impl<'a> ChildGuard<'a> {
 impl Drop for ChildGuard<'_> {
     fn drop(&mut self) {
         if ! self.is_close {
-            self.child.sender.send(Err("ChildPanic".into())).unwrap();
+            let _ = self.child.sender.send(Err("ChildPanic".into()));
             self.child.registry.close();
+
+            // parked children run the queued tasks and exit
+            self.child.jobs.unpark_all();
         }
+
+        self.child.registry.n_alive.fetch_sub(1, Ordering::AcqRel);
     }
 }
 
 impl<'a> TaskSender<'a> {
-    pub fn send(&self, system_id: SystemId)
-    {
-        self.thread.registry.queue.push(TaskMessage::Start(system_id)).unwrap();
-    }
+    ///
+    /// Queues a ready set of systems and wakes the children once for the
+    /// whole batch, instead of once per system.
+    ///
+    pub fn send_all(&self, system_ids: &[SystemId]) {
+        if system_ids.is_empty() {
+            return;
+        }
+
+        for id in system_ids {
+            let mut msg = TaskMessage::Start(*id);
+
+            // a full queue waits for the children to take tasks
+            loop {
+                match self.thread.registry.queue.push(msg) {
+                    Ok(_) => break,
+                    Err(PushError::Full(full)) => {
+                        msg = full;
+                        self.thread.unpark();
+                        thread::yield_now();
+                    }
+                    Err(err) => panic!("task queue error {:?}", err),
+                }
+            }
+        }
 
-    pub fn flush(&self) {
         self.thread.unpark();
     }
 
+    ///
+    /// Runs the system on the thread that called ThreadPool::start_with.
+    /// Its completion is read like a child's.
+    ///
+    pub fn send_main(&self, system_id: SystemId) {
+        self.thread.main_sender.send(MainMessage::RunMain(system_id)).unwrap();
+    }
+
     pub fn read(&self) -> SystemId {
         self.thread.task_receiver.recv().unwrap().unwrap()
     }
 
-    pub fn _try_read(&self) -> Option<SystemId> {
+    pub fn read_timeout(&self, timeout: Duration) -> Option<SystemId> {
+        match self.thread.task_receiver.recv_timeout(timeout) {
+            Ok(id) => Some(id.unwrap()),
+            Err(RecvTimeoutError::Timeout) => None,
+            Err(err) => panic!("task receive error {:?}", err),
+        }
+    }
+
+    pub fn try_read(&self) -> Option<SystemId> {
         match self.thread.task_receiver.try_recv() {
             Ok(id) => Some(id.unwrap()),
+            Err(TryRecvError::Empty) => None,
             Err(msg) => { panic!("msg {:?}", msg); }
         }
     }
 
     fn close(&self) {
         self.thread.registry.close();
+        self.thread.unpark();
     }
 }
 
@@ -478,9 +697,7 @@ mod tests {
             move |sender| {
             ptr.lock().unwrap().push(format!("[P"));
 
-            sender.send(SystemId(0));
-            sender.send(SystemId(1));
-            sender.flush();
+            sender.send_all(&[SystemId(0), SystemId(1)]);
 
             sender.read();
             sender.read();
@@ -499,7 +716,7 @@ mod tests {
         })._n_threads(2)
         .build();
 
-        pool.start().unwrap();
+        pool.start_with(|_| Ok(())).unwrap();
 
         let list: Vec<String> = values.lock().unwrap().drain(..).collect();
         assert_eq!(list.join(", "), "[P, [C, [C, C], C], P]");
@@ -507,6 +724,46 @@ mod tests {
         pool.close().unwrap();
     }
 
+    #[test]
+    fn try_read_completions() {
+        let values = Arc::new(Mutex::new(Vec::<String>::new()));
+
+        let ptr = values.clone();
+
+        let mut pool = ThreadPoolBuilder::new().parent(
+            move |sender| {
+            assert_eq!(sender.try_read(), None);
+
+            sender.send_all(&[SystemId(0), SystemId(1), SystemId(2)]);
+
+            let mut ids = vec![sender.read()];
+
+            while ids.len() < 3 {
+                match sender.try_read() {
+                    Some(id) => ids.push(id),
+                    None => thread::yield_now(),
+                }
+            }
+
+            assert_eq!(sender.try_read(), None);
+
+            ids.sort_by_key(|id| id.index());
+            ptr.lock().unwrap().push(format!("{:?}", ids));
+
+            Ok(())
+        }).child(move || {
+            Box::new(move |_s| {})
+        })._n_threads(2)
+        .build();
+
+        pool.start_with(|_| Ok(())).unwrap();
+
+        let list: Vec<String> = values.lock().unwrap().drain(..).collect();
+        assert_eq!(list.join(", "), format!("{:?}", [SystemId(0), SystemId(1), SystemId(2)]));
+
+        pool.close().unwrap();
+    }
+
     #[test]
     fn two_tasks_one_thread() {
         let values = Arc::new(Mutex::new(Vec::<String>::new()));
@@ -518,9 +775,7 @@ mod tests {
         move |sender| {
             ptr.lock().unwrap().push(format!("[P"));
 
-            sender.send(SystemId(0));
-            sender.send(SystemId(1));
-            sender.flush();
+            sender.send_all(&[SystemId(0), SystemId(1)]);
 
             sender.read();
             sender.read();
@@ -538,7 +793,7 @@ mod tests {
             })
         })._n_threads(1).build();
 
-        pool.start().unwrap();
+        pool.start_with(|_| Ok(())).unwrap();
 
         let list: Vec<String> = values.lock().unwrap().drain(..).collect();
         assert_eq!(list.join(", "), "[P, [C, C], [C, C], P]");
@@ -568,7 +823,7 @@ mod tests {
             })
         }).build();
 
-        pool.start().unwrap();
+        pool.start_with(|_| Ok(())).unwrap();
 
         let list: Vec<String> = values.lock().unwrap().drain(..).collect();
         assert_eq!(list.join(", "), "[P, [C, C], [C, C], P]");
@@ -588,9 +843,7 @@ mod tests {
         move |sender| {
             ptr.lock().unwrap().push(format!("[P"));
 
-            sender.send(SystemId(0));
-            sender.send(SystemId(1));
-            sender.flush();
+            sender.send_all(&[SystemId(0), SystemId(1)]);
 
             sender.read();
             sender.read();
@@ -608,7 +861,7 @@ mod tests {
             })
         }).build();
 
-        pool.start().unwrap();
+        pool.start_with(|_| Ok(())).unwrap();
 
         let list: Vec<String> = values.lock().unwrap().drain(..).collect();
         assert_eq!(list.join(", "), "[P, [C, C], [C, C], P]");
@@ -1,8 +1,18 @@
 use std::{cell::UnsafeCell, ops::{Deref, DerefMut}};
 
+#[cfg(debug_assertions)]
+use std::sync::atomic::{AtomicBool, Ordering};
+
 use crate::Store;
 
+///
+/// Cell for systems shared with executor threads. Shared access goes
+/// through an AccessGuard token, and debug builds panic if two tokens
+/// for the same cell are live at once.
+///
 pub struct UnsafeSyncCell<T: ?Sized> {
+    #[cfg(debug_assertions)]
+    borrowed: AtomicBool,
     value: UnsafeCell<T>,
 }
 
@@ -12,6 +22,8 @@ unsafe impl<T: ?Sized + Send> Send for UnsafeSyncCell<T> {}
 impl<T> UnsafeSyncCell<T> {
     pub const fn new(value: T) -> Self {
         Self {
+            #[cfg(debug_assertions)]
+            borrowed: AtomicBool::new(false),
             value: UnsafeCell::new(value),
         }
     }
@@ -21,11 +33,28 @@ impl<T> UnsafeSyncCell<T> {
     }
 
     pub(crate) fn get_ref(&self) -> &T {
+        #[cfg(debug_assertions)]
+        assert!(! self.borrowed.load(Ordering::Acquire), "UnsafeSyncCell read while borrowed");
+
         unsafe { self.value.get().as_ref().unwrap() }
     }
 
-    pub(crate) unsafe fn as_mut(&self) -> &mut T {
-        &mut *self.value.get()
+    ///
+    /// Exclusive access token for the value.
+    ///
+    /// # Safety
+    ///
+    /// The caller's plan must ensure no other token for this cell is live.
+    /// Debug builds check this and panic on overlapping access.
+    ///
+    pub(crate) unsafe fn access(&self) -> AccessGuard<'_, T> {
+        #[cfg(debug_assertions)]
+        assert!(
+            self.borrowed.compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed).is_ok(),
+            "UnsafeSyncCell accessed concurrently"
+        );
+
+        AccessGuard { cell: self }
     }
 
     pub(crate) fn _get(&self) -> *mut T {
@@ -33,6 +62,31 @@ impl<T> UnsafeSyncCell<T> {
     }
 }
 
+pub(crate) struct AccessGuard<'a, T> {
+    cell: &'a UnsafeSyncCell<T>,
+}
+
+impl<T> Deref for AccessGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.cell.value.get() }
+    }
+}
+
+impl<T> DerefMut for AccessGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { &mut *self.cell.value.get() }
+    }
+}
+
+#[cfg(debug_assertions)]
+impl<T> Drop for AccessGuard<'_, T> {
+    fn drop(&mut self) {
+        self.cell.borrowed.store(false, Ordering::Release);
+    }
+}
+
 pub struct UnsafeSendCell<T>(UnsafeCell<T>);
 
 impl<T> UnsafeSendCell<T> {
@@ -93,3 +147,28 @@ impl DerefMut for UnsafeStore {
         self.get_mut()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::UnsafeSyncCell;
+
+    #[test]
+    fn access_sequential() {
+        let cell = UnsafeSyncCell::new(1);
+
+        unsafe { *cell.access() += 1; }
+        unsafe { *cell.access() += 1; }
+
+        assert_eq!(*cell.get_ref(), 3);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "UnsafeSyncCell accessed concurrently")]
+    fn access_overlap() {
+        let cell = UnsafeSyncCell::new(1);
+
+        let _a = unsafe { cell.access() };
+        let _b = unsafe { cell.access() };
+    }
+}
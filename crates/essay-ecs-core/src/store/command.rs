@@ -1,18 +1,28 @@
-use std::collections::VecDeque;
+use std::{any::type_name, collections::VecDeque, marker::PhantomData, mem};
 
-use crate::entity::{Component, EntityId};
+use crate::entity::{Bundle, EntityAllocator, EntityId};
 
 use crate::store::Store;
 
-use super::entity_command::{Spawn, EntityCommands, SpawnEmpty};
+use super::command_trace::{CommandKind, CommandRecord, CommandTrace};
+use super::entity_command::{Spawn, EntityCommands, SpawnEmpty, SpawnBatch};
 
 pub trait Command: Send + 'static {
     fn flush(self: Box<Self>, world: &mut Store);
+
+    ///
+    /// Records the command before it's applied, when command tracing
+    /// is enabled.
+    ///
+    fn trace(&self, trace: &mut CommandTrace) {
+        trace.push(CommandRecord::new(CommandKind::Other, None, Some(type_name::<Self>())));
+    }
 }
 
 pub struct Commands<'w, 's> {
-    world: &'w mut Store,
+    alloc: EntityAllocator,
     queue: &'s mut CommandQueue,
+    marker: PhantomData<&'w mut Store>,
 }
 
 type BoxCommand = Box<dyn Command>;
@@ -31,10 +41,18 @@ impl<'w, 's> Commands<'w, 's> {
     pub(crate) fn new(
         world: &'w mut Store,
         queue: &'s mut CommandQueue
+    ) -> Self {
+        Self::from_alloc(world.entity_allocator(), queue)
+    }
+
+    pub(crate) fn from_alloc(
+        alloc: EntityAllocator,
+        queue: &'s mut CommandQueue
     ) -> Self {
         Self {
-            world,
+            alloc,
             queue,
+            marker: PhantomData,
         }
     }
 }
@@ -48,7 +66,7 @@ impl<'w, 's> Commands<'w, 's> {
     }
 
     pub fn spawn_empty<'a>(&'a mut self) -> EntityCommands<'a, 'w, 's> {
-        let id = self.world.alloc_entity_id();
+        let id = self.alloc.alloc();
 
         self.add(SpawnEmpty::new(id));
 
@@ -56,13 +74,63 @@ impl<'w, 's> Commands<'w, 's> {
     }
 
     ///
-    /// Spawn an entity
+    /// Spawn an entity. The id is reserved immediately, and the entity
+    /// is created when the commands are flushed.
     ///
-    pub fn spawn<T:Component+'static>(&mut self, value: T) {
-        let id = self.world.alloc_entity_id();
+    pub fn spawn<'a, T:Bundle + Send>(&'a mut self, value: T) -> EntityCommands<'a, 'w, 's> {
+        let id = self.alloc.alloc();
 
         self.add(Spawn::new(id, value));
+
+        EntityCommands::new(self, id)
     }
+
+    ///
+    /// Spawn entities with the same bundle type as a single command.
+    /// Ids are reserved immediately and returned in order.
+    ///
+    pub fn spawn_batch<T, I>(&mut self, values: I) -> Vec<EntityId>
+    where
+        T: Bundle + Send,
+        I: IntoIterator<Item=T>
+    {
+        let values: Vec<(EntityId, T)> = values.into_iter()
+            .map(|value| (self.alloc.alloc(), value))
+            .collect();
+
+        let ids = values.iter().map(|(id, _)| *id).collect();
+
+        self.add(SpawnBatch::new(values));
+
+        ids
+    }
+}
+
+///
+/// Spawns a list of bundles in one expression, returning their ids.
+/// An optional `shared:` value is cloned into every entity.
+///
+/// ```ignore
+/// let ids = spawn!(commands, shared: Team(1);
+///     (Pos(0.), Ship),
+///     (Pos(1.), Ship, Leader),
+/// );
+/// ```
+///
+#[macro_export]
+macro_rules! spawn {
+    ($commands:expr, shared: $shared:expr; $($bundle:expr),* $(,)?) => {{
+        let shared = $shared;
+        let commands = &mut $commands;
+
+        vec![$(commands.spawn(($bundle, ::std::clone::Clone::clone(&shared))).id()),*]
+    }};
+
+    ($commands:expr; $($bundle:expr),* $(,)?) => {{
+        let commands = &mut $commands;
+
+        vec![$(commands.spawn($bundle).id()),*]
+    }};
 }
 
 //
@@ -74,10 +142,48 @@ impl CommandQueue {
         self.queue.push_back(Box::new(command))
     }
 
+    pub(crate) fn add_box(&mut self, command: BoxCommand) {
+        self.queue.push_back(command)
+    }
+
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
     pub(crate) fn flush(&mut self, world: &mut Store) {
-        for command in self.queue.drain(..) {
-            command.flush(world);
+        if let Some(staged) = world.staged_commands() {
+            staged.queue.append(&mut self.queue);
+            return;
         }
+
+        world.add_commands_applied(self.queue.len());
+
+        if world.contains_resource::<CommandTrace>() {
+            for command in self.queue.drain(..) {
+                // a command may remove the trace resource
+                if let Some(trace) = world.get_resource_mut::<CommandTrace>() {
+                    command.trace(trace);
+                }
+                command.flush(world);
+            }
+        } else {
+            for command in self.queue.drain(..) {
+                command.flush(world);
+            }
+        }
+    }
+
+    ///
+    /// Bytes held by pending commands and the queue's retained capacity.
+    ///
+    pub fn memory_size(&self) -> usize {
+        mem::size_of::<Self>()
+            + self.queue.capacity() * mem::size_of::<BoxCommand>()
+            + self.queue.iter().map(|cmd| mem::size_of_val(cmd.as_ref())).sum::<usize>()
     }
 }
 
@@ -152,6 +258,10 @@ impl<T:Send+Sync+'static> Command for InsertResource<T> {
     fn flush(self: Box<Self>, world: &mut Store) {
         world.insert_resource(self.value);
     }
+
+    fn trace(&self, trace: &mut CommandTrace) {
+        trace.push(CommandRecord::new(CommandKind::InsertResource, None, Some(type_name::<T>())));
+    }
 }
 
 impl Commands<'_, '_> {
@@ -196,14 +306,33 @@ mod tests {
     fn spawn() {
         let mut app = CoreApp::new();
 
-        app.run_system(|mut c: Commands| c.spawn(TestA(100))).unwrap();
+        app.run_system(|mut c: Commands| { c.spawn(TestA(100)); }).unwrap();
 
         let values: Vec<TestA> = app.query::<&TestA>()
             .map(|t| t.clone())
             .collect();
         assert_eq!(values, vec![TestA(100)]);
 
-        app.run_system(|mut c: Commands| c.spawn(TestA(200))).unwrap();
+        app.run_system(|mut c: Commands| { c.spawn(TestA(200)); }).unwrap();
+
+        let values: Vec<TestA> = app.query::<&TestA>()
+            .map(|t| t.clone())
+            .collect();
+        assert_eq!(values, vec![TestA(100), TestA(200)]);
+    }
+
+    #[test]
+    fn exclusive_store_and_commands() {
+        let mut app = CoreApp::new();
+
+        let id = app.eval(|store: &mut Store, mut c: Commands| {
+            store.spawn(TestA(100));
+
+            Ok(c.spawn(TestA(200)).id())
+        }).unwrap();
+
+        assert_eq!(app.eval(move |store: &mut Store| Ok(store.get::<TestA>(id).cloned())).unwrap(),
+            Some(TestA(200)));
 
         let values: Vec<TestA> = app.query::<&TestA>()
             .map(|t| t.clone())
@@ -0,0 +1,123 @@
+use std::sync::mpsc::{self, Receiver, Sender};
+
+use crate::entity::{Bundle, Component, EntityAllocator, EntityId};
+
+use super::{
+    command::{Command, CommandQueue},
+    entity_command::{EntityDespawn, EntityInsert, Spawn},
+    Store,
+};
+
+type BoxCommand = Box<dyn Command>;
+
+///
+/// Commands handle for threads outside the schedule, e.g. network or
+/// file loaders. Commands are queued until the store applies them with
+/// Store::apply_sent_commands, in the First schedule for apps.
+///
+#[derive(Clone)]
+pub struct CommandsSender {
+    alloc: EntityAllocator,
+    sender: Sender<BoxCommand>,
+}
+
+impl CommandsSender {
+    ///
+    /// Queues a command. Returns false if the store has been dropped.
+    ///
+    pub fn add(&self, command: impl Command) -> bool {
+        self.sender.send(Box::new(command)).is_ok()
+    }
+
+    ///
+    /// Spawns an entity when the commands are applied. The id is reserved
+    /// immediately.
+    ///
+    pub fn spawn<T: Bundle + Send>(&self, value: T) -> EntityId {
+        let id = self.alloc.alloc();
+
+        self.add(Spawn::new(id, value));
+
+        id
+    }
+
+    pub fn insert<T: Component>(&self, id: EntityId, value: T) {
+        self.add(EntityInsert::new(id, value));
+    }
+
+    pub fn despawn(&self, id: EntityId) {
+        self.add(EntityDespawn::new(id));
+    }
+
+    pub fn insert_resource<T: Send + Sync + 'static>(&self, value: T) {
+        self.add(move |store: &mut Store| store.insert_resource(value));
+    }
+}
+
+///
+/// Receiving side of the CommandsSender handles, a store resource.
+///
+pub(crate) struct SentCommands {
+    sender: Sender<BoxCommand>,
+    receiver: Receiver<BoxCommand>,
+}
+
+impl SentCommands {
+    pub(crate) fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+
+        Self {
+            sender,
+            receiver,
+        }
+    }
+
+    pub(crate) fn sender(&self, alloc: EntityAllocator) -> CommandsSender {
+        CommandsSender {
+            alloc,
+            sender: self.sender.clone(),
+        }
+    }
+
+    pub(crate) fn drain(&self) -> CommandQueue {
+        let mut queue = CommandQueue::default();
+
+        while let Ok(command) = self.receiver.try_recv() {
+            queue.add_box(command);
+        }
+
+        queue
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use crate::{entity::Component, store::Store};
+
+    #[test]
+    fn sender_from_thread() {
+        let mut store = Store::new();
+
+        let sender = store.commands_sender();
+
+        let id = thread::spawn(move || {
+            sender.insert_resource(TestA(3));
+            sender.spawn(TestA(1))
+        }).join().unwrap();
+
+        assert!(store.get::<TestA>(id).is_none());
+
+        assert_eq!(store.apply_sent_commands(), 2);
+        assert_eq!(store.get::<TestA>(id), Some(&TestA(1)));
+        assert_eq!(store.resource::<TestA>(), &TestA(3));
+
+        assert_eq!(store.apply_sent_commands(), 0);
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct TestA(u32);
+
+    impl Component for TestA {}
+}
@@ -0,0 +1,178 @@
+use std::any::type_name;
+
+use crate::entity::EntityId;
+
+///
+/// Commands applied since the last tick, recorded while command tracing
+/// is enabled with Store::trace_commands, so tests can assert on a
+/// tick's side effects.
+///
+#[derive(Default)]
+pub struct CommandTrace {
+    records: Vec<CommandRecord>,
+}
+
+impl CommandTrace {
+    pub fn push(&mut self, record: CommandRecord) {
+        self.records.push(record);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item=&CommandRecord> {
+        self.records.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    ///
+    /// Number of kind records for type T. Spawns record each component of
+    /// the bundle, so a spawn of (A, B) counts for both A and B.
+    ///
+    pub fn count<T: ?Sized + 'static>(&self, kind: CommandKind) -> usize {
+        let name = type_name::<T>();
+
+        self.records.iter()
+            .filter(|record| record.kind == kind && record.type_name == Some(name))
+            .count()
+    }
+
+    pub fn clear(&mut self) {
+        self.records.clear();
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommandKind {
+    Spawn,
+    Insert,
+    Despawn,
+    InsertResource,
+    Other,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CommandRecord {
+    kind: CommandKind,
+    entity: Option<EntityId>,
+    type_name: Option<&'static str>,
+}
+
+impl CommandRecord {
+    pub fn new(
+        kind: CommandKind, 
+        entity: Option<EntityId>, 
+        type_name: Option<&'static str>
+    ) -> Self {
+        Self {
+            kind,
+            entity,
+            type_name,
+        }
+    }
+
+    pub fn kind(&self) -> CommandKind {
+        self.kind
+    }
+
+    pub fn entity(&self) -> Option<EntityId> {
+        self.entity
+    }
+
+    ///
+    /// Component or resource type name, or the command's own type
+    /// for CommandKind::Other.
+    ///
+    pub fn type_name(&self) -> Option<&'static str> {
+        self.type_name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{core_app::CoreApp, entity::Component, store::Store, Commands};
+
+    use super::{CommandKind, CommandTrace};
+
+    #[test]
+    fn trace_commands() {
+        let mut app = CoreApp::new();
+        app.trace_commands();
+
+        let id = app.eval(|mut c: Commands| {
+            for i in 0..3 {
+                c.spawn(TestA(i));
+            }
+
+            c.spawn_batch((0..2).map(TestB));
+            c.insert_resource(TestB(7));
+
+            c.spawn(TestB(1)).insert(TestA(1)).id()
+        }).unwrap();
+
+        let trace = app.resource::<CommandTrace>();
+        assert_eq!(trace.count::<TestA>(CommandKind::Spawn), 3);
+        assert_eq!(trace.count::<TestB>(CommandKind::Spawn), 3);
+        assert_eq!(trace.count::<TestB>(CommandKind::InsertResource), 1);
+        assert_eq!(trace.count::<TestA>(CommandKind::Insert), 1);
+        assert_eq!(trace.iter().last().unwrap().entity(), Some(id));
+
+        assert_eq!(app.resource::<TestB>().0, 7);
+        let mut values : Vec<usize> = app.query::<&TestA>().map(|a| a.0).collect();
+        values.sort();
+        assert_eq!(values, vec![0, 1, 1, 2]);
+
+        app.eval(move |mut c: Commands| c.entity(id).despawn()).unwrap();
+
+        let trace = app.resource::<CommandTrace>();
+        assert_eq!(trace.iter().filter(|r| r.kind() == CommandKind::Despawn).count(), 1);
+
+        app.eval(|mut c: Commands| { c.spawn((TestA(5), TestB(5))); }).unwrap();
+
+        let trace = app.resource::<CommandTrace>();
+        assert_eq!(trace.count::<TestA>(CommandKind::Spawn), 4);
+        assert_eq!(trace.count::<TestB>(CommandKind::Spawn), 4);
+
+        // each tick starts a new trace
+        app.tick().unwrap();
+        assert!(app.resource::<CommandTrace>().is_empty());
+    }
+
+    #[test]
+    fn trace_removed_by_command() {
+        let mut store = Store::new();
+        store.trace_commands(true);
+
+        store.eval(|mut c: Commands| {
+            c.add(|store: &mut Store| store.trace_commands(false));
+            c.spawn(TestA(1));
+        }).unwrap();
+
+        assert!(store.get_resource::<CommandTrace>().is_none());
+        assert_eq!(store.commands_applied(), 2);
+    }
+
+    #[test]
+    fn trace_disabled() {
+        let mut store = Store::new();
+        store.trace_commands(true);
+        store.trace_commands(false);
+
+        store.eval(|mut c: Commands| { c.spawn(TestA(1)); }).unwrap();
+
+        assert!(store.get_resource::<CommandTrace>().is_none());
+        assert_eq!(store.commands_applied(), 1);
+    }
+
+    struct TestA(usize);
+
+    impl Component for TestA {}
+
+    struct TestB(usize);
+
+    impl Component for TestB {}
+}
@@ -0,0 +1,104 @@
+use crate::{entity::{Component, EntityId}, Query};
+
+use super::{Command, Commands, Store};
+
+///
+/// Marks an entity despawned by Commands::despawn_delayed, visible to
+/// cleanup systems, e.g. death animations, until Despawning::update
+/// removes the entity after its grace period.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Despawning {
+    ticks: u32,
+}
+
+impl Despawning {
+    ///
+    /// Ticks left before the entity is despawned, including this one.
+    ///
+    pub fn ticks_left(&self) -> u32 {
+        self.ticks
+    }
+
+    ///
+    /// Counts down the grace periods, once per tick, and despawns the
+    /// entities whose period has ended. Apps run it in Last.
+    ///
+    pub fn update(mut commands: Commands, query: Query<(EntityId, &mut Despawning)>) {
+        for (id, despawning) in query.iter() {
+            despawning.ticks = despawning.ticks.saturating_sub(1);
+
+            if despawning.ticks == 0 {
+                commands.entity(id).despawn();
+            }
+        }
+    }
+}
+
+impl Component for Despawning {}
+
+struct DespawnDelayed {
+    id: EntityId,
+    ticks: u32,
+}
+
+impl Command for DespawnDelayed {
+    fn flush(self: Box<Self>, store: &mut Store) {
+        if ! store.contains_entity(self.id) {
+            return;
+        }
+
+        if self.ticks == 0 {
+            store.despawn(self.id);
+        } else if store.get::<Despawning>(self.id).is_none() {
+            // an earlier delayed despawn keeps its countdown
+            store.insert(self.id, Despawning { ticks: self.ticks });
+        }
+    }
+}
+
+impl Commands<'_, '_> {
+    ///
+    /// Tags the entity with Despawning for `ticks` ticks before it's
+    /// despawned, giving cleanup systems a grace period. Zero ticks
+    /// despawns when commands are applied.
+    ///
+    pub fn despawn_delayed(&mut self, id: EntityId, ticks: u32) {
+        self.add(DespawnDelayed { id, ticks });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{core_app::{Core, CoreApp}, entity::Component, Commands, Query};
+
+    use super::Despawning;
+
+    #[test]
+    fn despawn_delayed() {
+        let mut app = CoreApp::new();
+        app.system(Core, Despawning::update);
+
+        let id = app.eval(|mut c: Commands| c.spawn(TestA(1)).id()).unwrap();
+
+        app.run_system(move |mut c: Commands| c.despawn_delayed(id, 2)).unwrap();
+
+        let ticks = |app: &mut CoreApp| {
+            app.eval(|q: Query<&Despawning>| q.iter().map(|d| d.ticks_left()).next()).unwrap()
+        };
+
+        assert_eq!(ticks(&mut app), Some(2));
+
+        app.tick().unwrap();
+        assert_eq!(ticks(&mut app), Some(1));
+        assert_eq!(app.eval(|q: Query<&TestA>| q.count()).unwrap(), 1);
+
+        app.tick().unwrap();
+        assert_eq!(app.eval(|q: Query<&TestA>| q.count()).unwrap(), 0);
+    }
+
+    #[allow(unused)]
+    struct TestA(u32);
+
+    impl Component for TestA {}
+}
@@ -1,6 +1,8 @@
-use crate::{entity::{Component, EntityId}, Store, Commands};
+use std::any::type_name;
 
-use super::Command;
+use crate::{entity::{Bundle, Component, EntityId}, Store, Commands};
+
+use super::{command_trace::{CommandKind, CommandRecord, CommandTrace}, Command};
 
 pub struct EntityCommands<'a, 'w, 's> {
     commands: &'a mut Commands<'w, 's>,
@@ -15,6 +17,10 @@ impl<'a, 'w, 's> EntityCommands<'a, 'w, 's> {
         }
     }
 
+    pub fn id(&self) -> EntityId {
+        self.id
+    }
+
     pub fn insert<T:Component + 'static>(&mut self, value: T) -> &mut Self {
         self.commands.add(EntityInsert::new(self.id, value));
 
@@ -45,17 +51,21 @@ impl Command for SpawnEmpty {
     fn flush(self: Box<Self>, world: &mut Store) {
         world.spawn_empty_id(self.id);
     }
+
+    fn trace(&self, trace: &mut CommandTrace) {
+        trace.push(CommandRecord::new(CommandKind::Spawn, Some(self.id), None));
+    }
 }
 
 ///
 /// world.spawn()
 /// 
-pub(crate) struct Spawn<T:Component + 'static> {
+pub(crate) struct Spawn<T:Bundle + Send> {
     id: EntityId,
     value: T,
 }
 
-impl<T:Component + 'static> Spawn<T> {
+impl<T:Bundle + Send> Spawn<T> {
     pub(crate) fn new(id: EntityId, value: T) -> Self {
         Self {
             id,
@@ -64,10 +74,41 @@ impl<T:Component + 'static> Spawn<T> {
     }
 }
 
-impl<T:Component + 'static> Command for Spawn<T> {
+impl<T:Bundle + Send> Command for Spawn<T> {
     fn flush(self: Box<Self>, world: &mut Store) {
         world.spawn_id(self.id, self.value);
     }
+
+    fn trace(&self, trace: &mut CommandTrace) {
+        trace_spawn::<T>(trace, self.id);
+    }
+}
+
+///
+/// world.spawn() for a batch sharing one insert plan
+/// 
+pub(crate) struct SpawnBatch<T:Bundle + Send> {
+    values: Vec<(EntityId, T)>,
+}
+
+impl<T:Bundle + Send> SpawnBatch<T> {
+    pub(crate) fn new(values: Vec<(EntityId, T)>) -> Self {
+        Self {
+            values,
+        }
+    }
+}
+
+impl<T:Bundle + Send> Command for SpawnBatch<T> {
+    fn flush(self: Box<Self>, world: &mut Store) {
+        world.spawn_batch_id(self.values);
+    }
+
+    fn trace(&self, trace: &mut CommandTrace) {
+        for (id, _) in &self.values {
+            trace_spawn::<T>(trace, *id);
+        }
+    }
 }
 
 ///
@@ -89,7 +130,14 @@ impl<T:Component + 'static> EntityInsert<T> {
 
 impl<T:Component + 'static> Command for EntityInsert<T> {
     fn flush(self: Box<Self>, world: &mut Store) {
-        world.insert(self.id, self.value);
+        // a stale id's slot may belong to a new entity
+        if world.contains_entity(self.id) {
+            world.insert(self.id, self.value);
+        }
+    }
+
+    fn trace(&self, trace: &mut CommandTrace) {
+        trace.push(CommandRecord::new(CommandKind::Insert, Some(self.id), Some(type_name::<T>())));
     }
 }
 
@@ -110,7 +158,26 @@ impl EntityDespawn {
 
 impl Command for EntityDespawn {
     fn flush(self: Box<Self>, world: &mut Store) {
-        world.despawn(self.id);
+        if world.contains_entity(self.id) {
+            world.despawn(self.id);
+        }
+    }
+
+    fn trace(&self, trace: &mut CommandTrace) {
+        trace.push(CommandRecord::new(CommandKind::Despawn, Some(self.id), None));
+    }
+}
+
+///
+/// One spawn record per component, so a tuple spawn counts for each of
+/// its component types.
+///
+fn trace_spawn<T:Bundle>(trace: &mut CommandTrace, id: EntityId) {
+    let mut names = Vec::new();
+    T::type_names(&mut names);
+
+    for name in names {
+        trace.push(CommandRecord::new(CommandKind::Spawn, Some(id), Some(name)));
     }
 }
 
@@ -122,14 +189,14 @@ mod tests {
     fn spawn() {
         let mut app = CoreApp::new();
 
-        app.run_system(|mut c: Commands| c.spawn(TestA(100))).unwrap();
+        app.run_system(|mut c: Commands| { c.spawn(TestA(100)); }).unwrap();
 
         let values: Vec<TestA> = app.query::<&TestA>()
             .map(|t| t.clone())
             .collect();
         assert_eq!(values, vec![TestA(100)]);
 
-        app.run_system(|mut c: Commands| c.spawn(TestA(200))).unwrap();
+        app.run_system(|mut c: Commands| { c.spawn(TestA(200)); }).unwrap();
 
         let values: Vec<TestA> = app.query::<&TestA>()
             .map(|t| t.clone())
@@ -137,6 +204,68 @@ mod tests {
         assert_eq!(values, vec![TestA(100), TestA(200)]);
     }
 
+    #[test]
+    fn spawn_id_insert() {
+        let mut app = CoreApp::new();
+
+        let id = app.eval(|mut c: Commands| {
+            c.spawn(TestA(100)).insert(TestB(101)).id()
+        }).unwrap();
+
+        let values: Vec<(EntityId, TestA, TestB)> = app.query::<(EntityId, &TestA, &TestB)>()
+            .map(|(id, a, b)| (id, a.clone(), b.clone()))
+            .collect();
+        assert_eq!(values, vec![(id, TestA(100), TestB(101))]);
+
+        app.run_system(|mut c: Commands| { c.spawn((TestA(200), TestB(201))); }).unwrap();
+
+        let values: Vec<TestB> = app.query::<&TestB>()
+            .map(|t| t.clone())
+            .collect();
+        assert_eq!(values, vec![TestB(101), TestB(201)]);
+    }
+
+    #[test]
+    fn spawn_batch() {
+        let mut app = CoreApp::new();
+
+        let ids = app.eval(|mut c: Commands| {
+            c.spawn_batch((1..4).map(TestA))
+        }).unwrap();
+
+        let values: Vec<(EntityId, TestA)> = app.query::<(EntityId, &TestA)>()
+            .map(|(id, a)| (id, a.clone()))
+            .collect();
+        assert_eq!(values, vec![
+            (ids[0], TestA(1)), (ids[1], TestA(2)), (ids[2], TestA(3))
+        ]);
+    }
+
+    #[test]
+    fn spawn_macro() {
+        let mut app = CoreApp::new();
+
+        let ids = app.eval(|mut c: Commands| {
+            crate::spawn!(c, shared: TestB(7);
+                TestA(1),
+                TestA(2),
+            )
+        }).unwrap();
+
+        let values: Vec<(EntityId, TestA, TestB)> = app.query::<(EntityId, &TestA, &TestB)>()
+            .map(|(id, a, b)| (id, a.clone(), b.clone()))
+            .collect();
+        assert_eq!(values, vec![
+            (ids[0], TestA(1), TestB(7)), (ids[1], TestA(2), TestB(7))
+        ]);
+
+        let ids = app.eval(|mut c: Commands| {
+            crate::spawn!(c; TestB(8))
+        }).unwrap();
+
+        assert_eq!(app.query::<(EntityId, &TestB)>().filter(|(id, _)| *id == ids[0]).count(), 1);
+    }
+
     #[test]
     fn spawn_empty_insert() {
         let mut app = CoreApp::new();
@@ -164,7 +293,7 @@ mod tests {
     fn despawn() {
         let mut app = CoreApp::new();
 
-        app.run_system(|mut c: Commands| c.spawn(TestA(100))).unwrap();
+        app.run_system(|mut c: Commands| { c.spawn(TestA(100)); }).unwrap();
 
         let values: Vec<TestA> = app.query::<&TestA>()
             .map(|t| t.clone())
@@ -1,9 +1,9 @@
-use crate::{Store, entity::{EntityId, Component}};
+use crate::{Store, entity::{Bundle, EntityId, Component, EntityStore}};
 
 pub struct EntityRef<'a> {
     id: EntityId,
     
-    world: &'a Store,
+    world: &'a EntityStore,
 }
 
 pub struct EntityMut<'a> {
@@ -13,16 +13,31 @@ pub struct EntityMut<'a> {
 }
 
 impl<'a> EntityRef<'a> {
-    pub(crate) fn new(id: EntityId, world: &'a Store) -> Self {
+    pub(crate) fn new(id: EntityId, world: &'a EntityStore) -> Self {
         Self {
             id,
             world,
         }
     }
 
-    pub fn get<T:Component>(&self) -> Option<&T> {
+    pub fn id(&self) -> EntityId {
+        self.id
+    }
+
+    pub fn get<T:Component>(&self) -> Option<&'a T> {
         self.world.get::<T>(self.id)
     }
+
+    pub fn contains<T:Component>(&self) -> bool {
+        self.get::<T>().is_some()
+    }
+
+    ///
+    /// Type names of the entity's components, for debugging and inspection.
+    ///
+    pub fn iter_components(&self) -> impl Iterator<Item=&'a str> {
+        self.world.component_names(self.id)
+    }
 }
 
 impl<'a> EntityMut<'a> {
@@ -33,7 +48,48 @@ impl<'a> EntityMut<'a> {
         }
     }
 
-    pub fn despawn(&mut self) {
+    pub fn id(&self) -> EntityId {
+        self.id
+    }
+
+    pub fn get<T:Component>(&self) -> Option<&T> {
+        self.world.get::<T>(self.id)
+    }
+
+    pub fn get_mut<T:Component>(&mut self) -> Option<&mut T> {
+        self.world.get_mut::<T>(self.id)
+    }
+
+    pub fn contains<T:Component>(&self) -> bool {
+        self.get::<T>().is_some()
+    }
+
+    pub fn insert<T:Component>(&mut self, value: T) -> &mut Self {
+        self.world.insert(self.id, value);
+
+        self
+    }
+
+    ///
+    /// Adds the bundle's components, replacing any the entity already has.
+    ///
+    pub fn insert_bundle<T:Bundle>(&mut self, value: T) -> &mut Self {
+        self.world.insert_bundle(self.id, value);
+
+        self
+    }
+
+    ///
+    /// Removes the bundle's components the entity has. Components not in
+    /// the entity are ignored.
+    ///
+    pub fn remove_bundle<T:Bundle>(&mut self) -> &mut Self {
+        self.world.remove_bundle::<T>(self.id);
+
+        self
+    }
+
+    pub fn despawn(self) {
         self.world.despawn(self.id);
     }
 }
\ No newline at end of file
@@ -0,0 +1,124 @@
+use std::vec;
+
+use crate::entity::{Component, EntityId};
+
+use super::{Command, Commands, Store};
+
+///
+/// Per-entity message queue, filled by Commands::send_to when commands are
+/// applied and emptied by the entity's owner with drain.
+///
+pub struct Mailbox<T> {
+    messages: Vec<T>,
+}
+
+impl<T> Mailbox<T> {
+    pub fn new() -> Self {
+        Self { messages: Vec::new() }
+    }
+
+    pub fn push(&mut self, message: T) {
+        self.messages.push(message);
+    }
+
+    pub fn drain(&mut self) -> vec::Drain<'_, T> {
+        self.messages.drain(..)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item=&T> {
+        self.messages.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+}
+
+impl<T> Default for Mailbox<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Send + Sync + 'static> Component for Mailbox<T> {}
+
+struct SendTo<T> {
+    id: EntityId,
+    message: T,
+}
+
+impl<T: Send + Sync + 'static> Command for SendTo<T> {
+    fn flush(self: Box<Self>, store: &mut Store) {
+        if ! store.contains_entity(self.id) {
+            return;
+        }
+
+        if let Some(mailbox) = store.get_mut::<Mailbox<T>>(self.id) {
+            mailbox.push(self.message);
+        } else {
+            let mut mailbox = Mailbox::new();
+            mailbox.push(self.message);
+
+            store.insert(self.id, mailbox);
+        }
+    }
+}
+
+impl Commands<'_, '_> {
+    ///
+    /// Delivers the message to the entity's Mailbox<T> when commands are
+    /// applied, adding the mailbox if needed. Messages to despawned
+    /// entities are dropped.
+    ///
+    pub fn send_to<T: Send + Sync + 'static>(&mut self, id: EntityId, message: T) {
+        self.add(SendTo { id, message });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{core_app::CoreApp, entity::{Component, EntityId}, Commands, Query};
+
+    use super::Mailbox;
+
+    #[test]
+    fn send_to() {
+        let mut app = CoreApp::new();
+
+        let (a, b, c) = app.eval(|mut c: Commands| {
+            let a = c.spawn(TestA(1)).id();
+            let b = c.spawn((TestA(2), Mailbox::<u32>::new())).id();
+            let c = c.spawn(TestA(3)).id();
+
+            (a, b, c)
+        }).unwrap();
+
+        app.run_system(move |mut cmd: Commands, q: Query<(EntityId, &TestA)>| {
+            for (id, value) in q.iter() {
+                if id != c {
+                    cmd.send_to(a, value.0);
+                    cmd.send_to(b, value.0 * 10);
+                }
+            }
+            cmd.entity(c).despawn();
+            cmd.send_to(c, 100u32);
+        }).unwrap();
+
+        let mut values = app.eval(|q: Query<(EntityId, &mut Mailbox<u32>)>| {
+            q.iter()
+                .map(|(id, mailbox)| (id, mailbox.drain().collect::<Vec<u32>>()))
+                .collect::<Vec<_>>()
+        }).unwrap();
+        values.sort_by_key(|(id, _)| id.index());
+
+        assert_eq!(values, vec![(a, vec![1, 2]), (b, vec![10, 20])]);
+    }
+
+    struct TestA(u32);
+
+    impl Component for TestA {}
+}
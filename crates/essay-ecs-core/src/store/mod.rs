@@ -3,15 +3,41 @@ mod resource_command;
 mod entity_command;
 mod store;
 mod command;
+mod command_trace;
+mod snapshot;
+mod mailbox;
+mod transaction;
+mod weak_ref;
+mod command_sender;
+mod despawning;
+mod stable_id;
 
 pub use store::{
-    Store, FromStore
+    Store, FromStore, ResourcesMut,
 };
 
 pub use command::{
     Commands, Command, CommandQueue,
 };
 
+pub use command_trace::{
+    CommandTrace, CommandRecord, CommandKind,
+};
+
+pub use command_sender::CommandsSender;
+
+pub use mailbox::Mailbox;
+
+pub use despawning::Despawning;
+
+pub use stable_id::StableId;
+
+pub use snapshot::{
+    ComponentSnapshot, SnapshotComponents,
+};
+
 pub use entity_ref::{
     EntityRef, EntityMut,
-};
\ No newline at end of file
+};
+
+pub use weak_ref::WeakRef;
\ No newline at end of file
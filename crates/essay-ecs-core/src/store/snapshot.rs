@@ -0,0 +1,226 @@
+use std::{any::TypeId, collections::HashSet, marker::PhantomData};
+
+use crate::entity::{Component, ComponentId, EntityId};
+
+use super::{stable_id::StableId, Store};
+
+///
+/// Copy of only the selected component types with their entity ids,
+/// for cheap rollback. See Store::snapshot_components. Entities with a
+/// StableId are restored by stable id, even if they were respawned.
+///
+pub struct ComponentSnapshot<S: SnapshotComponents> {
+    data: S::Data,
+    marker: PhantomData<S>,
+}
+
+impl<S: SnapshotComponents> ComponentSnapshot<S> {
+    pub(crate) fn new(store: &mut Store) -> Self {
+        Self {
+            data: S::snapshot(store),
+            marker: PhantomData,
+        }
+    }
+
+    pub(crate) fn restore(&self, store: &mut Store) -> usize {
+        S::restore(store, &self.data)
+    }
+}
+
+///
+/// Component types that can be snapshotted, a Clone component or a tuple
+/// of them.
+///
+pub trait SnapshotComponents: 'static {
+    type Data: Send + 'static;
+
+    fn snapshot(store: &mut Store) -> Self::Data;
+
+    fn restore(store: &mut Store, data: &Self::Data) -> usize;
+
+    fn component_ids(store: &mut Store, ids: &mut Vec<ComponentId>);
+}
+
+impl<T: Component + Clone> SnapshotComponents for T {
+    type Data = Vec<(EntityId, Option<StableId>, T)>;
+
+    fn snapshot(store: &mut Store) -> Self::Data {
+        let values: Vec<(EntityId, T)> = store.query::<(EntityId, &T)>()
+            .map(|(id, value)| (id, value.clone()))
+            .collect();
+
+        values.into_iter()
+            .map(|(id, value)| (id, store.stable_id(id), value))
+            .collect()
+    }
+
+    fn restore(store: &mut Store, data: &Self::Data) -> usize {
+        let mut count = 0;
+        let mut restored = HashSet::with_capacity(data.len());
+
+        // StableId is read-only, so it's restored by insert, which updates
+        // the stable id map, and its entities are found by entity id
+        let is_stable_id = TypeId::of::<T>() == TypeId::of::<StableId>();
+
+        for (id, stable_id, value) in data {
+            let id = match stable_id {
+                Some(stable_id) if ! is_stable_id => match store.stable_entity(*stable_id) {
+                    Some(id) => id,
+                    None => continue,
+                },
+                _ => *id,
+            };
+
+            if ! store.contains_entity(id) {
+                continue;
+            }
+
+            let item = if is_stable_id { None } else { store.get_mut::<T>(id) };
+
+            match item {
+                Some(item) => *item = value.clone(),
+                None => { store.insert(id, value.clone()); }
+            }
+
+            restored.insert(id);
+            count += 1;
+        }
+
+        // entities that gained the component after the snapshot lose it
+        let added: Vec<EntityId> = store.query::<(EntityId, &T)>()
+            .map(|(id, _)| id)
+            .filter(|id| ! restored.contains(id))
+            .collect();
+
+        for id in added {
+            store.remove_bundle::<T>(id);
+        }
+
+        count
+    }
+
+    fn component_ids(store: &mut Store, ids: &mut Vec<ComponentId>) {
+        ids.push(store.add_component_id::<T>());
+    }
+}
+
+macro_rules! impl_snapshot_tuple {
+    ($($part:ident),*) => {
+        #[allow(non_snake_case)]
+        impl<$($part: SnapshotComponents),*> SnapshotComponents for ($($part,)*) {
+            type Data = ($($part::Data,)*);
+
+            fn snapshot(store: &mut Store) -> Self::Data {
+                ($($part::snapshot(store),)*)
+            }
+
+            fn restore(store: &mut Store, data: &Self::Data) -> usize {
+                let ($($part,)*) = data;
+
+                0 $(+ $part::restore(store, $part))*
+            }
+
+            fn component_ids(store: &mut Store, ids: &mut Vec<ComponentId>) {
+                $($part::component_ids(store, ids);)*
+            }
+        }
+    }
+}
+
+impl_snapshot_tuple!(P1, P2);
+impl_snapshot_tuple!(P1, P2, P3);
+impl_snapshot_tuple!(P1, P2, P3, P4);
+impl_snapshot_tuple!(P1, P2, P3, P4, P5);
+impl_snapshot_tuple!(P1, P2, P3, P4, P5, P6);
+impl_snapshot_tuple!(P1, P2, P3, P4, P5, P6, P7);
+impl_snapshot_tuple!(P1, P2, P3, P4, P5, P6, P7, P8);
+
+#[cfg(test)]
+mod tests {
+    use crate::{entity::Component, store::{Store, StableId}};
+
+    #[test]
+    fn snapshot_restore() {
+        let mut store = Store::new();
+
+        let id_a = store.spawn((TestA(1), TestB(10), TestC(100)));
+        let id_b = store.spawn(TestA(2));
+        let id_c = store.spawn(TestB(30));
+
+        let snapshot = store.snapshot_components::<(TestA, TestB)>();
+
+        *store.get_mut::<TestA>(id_a).unwrap() = TestA(5);
+        *store.get_mut::<TestB>(id_a).unwrap() = TestB(50);
+        *store.get_mut::<TestC>(id_a).unwrap() = TestC(500);
+        *store.get_mut::<TestA>(id_b).unwrap() = TestA(6);
+        store.despawn(id_c);
+
+        assert_eq!(store.restore_components(&snapshot), 3);
+
+        assert_eq!(store.get::<TestA>(id_a), Some(&TestA(1)));
+        assert_eq!(store.get::<TestB>(id_a), Some(&TestB(10)));
+        assert_eq!(store.get::<TestC>(id_a), Some(&TestC(500)));
+        assert_eq!(store.get::<TestA>(id_b), Some(&TestA(2)));
+        assert!(! store.contains_entity(id_c));
+    }
+
+    #[test]
+    fn restore_removes_added() {
+        let mut store = Store::new();
+
+        let id_a = store.spawn(TestA(1));
+        let id_b = store.spawn(TestB(2));
+
+        let snapshot = store.snapshot_components::<TestA>();
+
+        store.entity_mut(id_b).insert(TestA(20));
+        let id_c = store.spawn((TestA(3), TestB(3)));
+
+        assert_eq!(store.restore_components(&snapshot), 1);
+
+        assert_eq!(store.get::<TestA>(id_a), Some(&TestA(1)));
+        assert_eq!(store.get::<TestA>(id_b), None);
+        assert_eq!(store.get::<TestB>(id_b), Some(&TestB(2)));
+        assert_eq!(store.get::<TestA>(id_c), None);
+        assert_eq!(store.get::<TestB>(id_c), Some(&TestB(3)));
+        assert_eq!(store.count::<&TestA>(), 1);
+    }
+
+    #[test]
+    fn restore_stable_id() {
+        let mut store = Store::new();
+
+        let s1 = StableId::from_u128(1);
+        let s2 = StableId::from_u128(2);
+
+        let id_a = store.spawn((TestA(1), s1));
+        let id_b = store.spawn(TestA(2));
+
+        let snapshot = store.snapshot_components::<StableId>();
+
+        store.entity_mut(id_a).insert(s2);
+        store.entity_mut(id_b).insert(StableId::from_u128(3));
+
+        assert_eq!(store.restore_components(&snapshot), 1);
+
+        assert_eq!(store.get::<StableId>(id_a), Some(&s1));
+        assert_eq!(store.stable_entity(s1), Some(id_a));
+        assert_eq!(store.stable_entity(s2), None);
+        assert_eq!(store.stable_id(id_b), None);
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct TestA(u32);
+
+    impl Component for TestA {}
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct TestB(u32);
+
+    impl Component for TestB {}
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct TestC(u32);
+
+    impl Component for TestC {}
+}
@@ -0,0 +1,220 @@
+use std::{
+    collections::{hash_map::RandomState, HashMap},
+    fmt,
+    hash::{BuildHasher, Hasher},
+    sync::atomic::{AtomicU64, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::entity::{Component, EntityId};
+
+///
+/// Persistent 128-bit entity id, for references that must survive
+/// save/load or cross networked peers where EntityIds aren't stable.
+/// The store maps stable ids to entities, see Store::stable_entity.
+///
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct StableId(u128);
+
+impl StableId {
+    pub fn from_u128(value: u128) -> Self {
+        Self(value)
+    }
+
+    ///
+    /// New random id in the UUID v4 layout.
+    ///
+    pub fn random() -> Self {
+        static SEQ: AtomicU64 = AtomicU64::new(0);
+
+        let seq = SEQ.fetch_add(1, Ordering::Relaxed);
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |t| t.as_nanos() as u64);
+
+        let hash = |salt: u64| {
+            let mut hasher = RandomState::new().build_hasher();
+            hasher.write_u64(seq);
+            hasher.write_u64(nanos);
+            hasher.write_u64(salt);
+            hasher.finish()
+        };
+
+        let value = ((hash(0) as u128) << 64) | hash(1) as u128;
+
+        // version 4, variant 1
+        let value = (value & !(0xf << 76)) | (0x4 << 76);
+        let value = (value & !(0x3 << 62)) | (0x2 << 62);
+
+        Self(value)
+    }
+
+    pub fn as_u128(&self) -> u128 {
+        self.0
+    }
+}
+
+impl Component for StableId {}
+
+impl fmt::Debug for StableId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "StableId({})", self)
+    }
+}
+
+impl fmt::Display for StableId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let v = self.0;
+
+        write!(f, "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+            (v >> 96) as u32,
+            (v >> 80) as u16,
+            (v >> 64) as u16,
+            (v >> 48) as u16,
+            v & 0xffff_ffff_ffff,
+        )
+    }
+}
+
+///
+/// Bidirectional StableId map, kept current by the store as StableId
+/// components are spawned, inserted, removed and despawned.
+///
+#[derive(Default)]
+pub(crate) struct StableIds {
+    entities: HashMap<StableId, EntityId>,
+    stable_ids: HashMap<EntityId, StableId>,
+}
+
+impl StableIds {
+    pub(crate) fn entity(&self, stable_id: StableId) -> Option<EntityId> {
+        self.entities.get(&stable_id).copied()
+    }
+
+    pub(crate) fn stable_id(&self, id: EntityId) -> Option<StableId> {
+        self.stable_ids.get(&id).copied()
+    }
+
+    pub(crate) fn update(&mut self, id: EntityId, stable_id: Option<StableId>) {
+        if self.stable_ids.get(&id).copied() == stable_id {
+            return;
+        }
+
+        self.remove(id);
+
+        if let Some(stable_id) = stable_id {
+            // the newest entity claims a duplicate stable id
+            if let Some(prev) = self.entities.insert(stable_id, id) {
+                self.stable_ids.remove(&prev);
+            }
+
+            self.stable_ids.insert(id, stable_id);
+        }
+    }
+
+    pub(crate) fn remove(&mut self, id: EntityId) {
+        if let Some(stable_id) = self.stable_ids.remove(&id) {
+            self.entities.remove(&stable_id);
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.entities.clear();
+        self.stable_ids.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic;
+
+    use crate::{entity::Component, store::Store, Query};
+
+    use super::StableId;
+
+    #[test]
+    fn stable_id_read_only() {
+        let mut store = Store::new();
+
+        let s1 = StableId::from_u128(1);
+        let s2 = StableId::from_u128(2);
+
+        let id = store.spawn((TestA(1), s1));
+
+        assert!(store.eval(|_q: Query<&mut StableId>| {}).is_err());
+        assert!(store.eval(|_q: Query<&StableId>| {}).is_ok());
+
+        let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            if let Some(stable_id) = store.get_mut::<StableId>(id) {
+                *stable_id = s2;
+            }
+        }));
+        assert!(result.is_err());
+
+        // a new StableId replaces the old one in the map
+        store.entity_mut(id).insert(s2);
+        assert_eq!(store.stable_entity(s1), None);
+        assert_eq!(store.stable_entity(s2), Some(id));
+    }
+
+    #[test]
+    fn stable_id_map() {
+        let mut store = Store::new();
+
+        let s1 = StableId::from_u128(1);
+        let s2 = StableId::from_u128(2);
+
+        let id_a = store.spawn((TestA(1), s1));
+        let id_b = store.spawn(TestA(2));
+
+        assert_eq!(store.stable_entity(s1), Some(id_a));
+        assert_eq!(store.stable_id(id_a), Some(s1));
+        assert_eq!(store.stable_id(id_b), None);
+
+        store.entity_mut(id_b).insert(s2);
+        assert_eq!(store.stable_entity(s2), Some(id_b));
+
+        store.entity_mut(id_b).remove_bundle::<StableId>();
+        assert_eq!(store.stable_entity(s2), None);
+
+        store.despawn(id_a);
+        assert_eq!(store.stable_entity(s1), None);
+
+        // a reloaded entity gets a new EntityId under the same stable id
+        let id_c = store.spawn((TestA(1), s1));
+        assert_ne!(id_c, id_a);
+        assert_eq!(store.stable_entity(s1), Some(id_c));
+    }
+
+    #[test]
+    fn snapshot_by_stable_id() {
+        let mut store = Store::new();
+
+        let s1 = StableId::random();
+        let id = store.spawn((TestA(1), s1));
+
+        let snapshot = store.snapshot_components::<TestA>();
+
+        store.despawn(id);
+        let id_b = store.spawn((TestA(7), s1));
+
+        assert_eq!(store.restore_components(&snapshot), 1);
+        assert_eq!(store.get::<TestA>(id_b), Some(&TestA(1)));
+    }
+
+    #[test]
+    fn random_uuid_format() {
+        let id = StableId::random();
+
+        assert_ne!(id, StableId::random());
+
+        let text = id.to_string();
+        assert_eq!(text.len(), 36);
+        assert_eq!(&text[14..15], "4");
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct TestA(u32);
+
+    impl Component for TestA {}
+}
@@ -1,14 +1,20 @@
+use core::fmt;
+use std::{any::{type_name, TypeId}, borrow::Cow};
+
 use crate::{
-    entity::{Bundle, Component, EntityId, EntityStore, View, ViewIterator, ViewPlan}, 
+    entity::{Bundle, Component, ComponentId, EntityAllocator, EntityId, EntityStore, TableId, View, ViewExplain, ViewFilter, ViewId, ViewIterator, ViewPlan, WorldId}, 
     error::Result,
-    resource::{ResourceId, Resources}, 
+    resource::{ResourceId, ResourceInfo, Resources}, 
     schedule::{ScheduleLabel, Schedules, SystemMeta, UnsafeStore}, 
-    system::System,
+    system::{CachedSystem, System},
     IntoSystem, 
     Schedule,
 };
 
-use super::{entity_ref::EntityMut, EntityRef};
+use super::{command::CommandQueue, command_trace::CommandTrace, command_sender::{CommandsSender, SentCommands}, entity_ref::EntityMut, snapshot::{ComponentSnapshot, SnapshotComponents}, stable_id::{StableId, StableIds}, EntityRef};
+
+#[cfg(debug_assertions)]
+use crate::resource::ResourceBorrow;
 
 pub struct Store(Option<StoreInner>);
 
@@ -22,6 +28,9 @@ impl Store {
                 entities: EntityStore::new(),
                 resources: Resources::new(),
                 resources_non_send: Resources::new(),
+                stable_ids: StableIds::default(),
+                commands_applied: 0,
+                staged: None,
             }))
     }
 
@@ -33,10 +42,74 @@ impl Store {
         self.0.as_mut().unwrap()
     }
 
-    fn is_active(&self) -> bool {
+    pub(crate) fn is_active(&self) -> bool {
         self.0.is_some()
     }
 
+    ///
+    /// Total number of queued commands applied to this store.
+    ///
+    pub fn commands_applied(&self) -> usize {
+        self.deref().commands_applied
+    }
+
+    ///
+    /// Records applied commands in the CommandTrace resource, which apps
+    /// clear at the start of each tick. A debug aid for tests.
+    ///
+    pub fn trace_commands(&mut self, is_trace: bool) {
+        if ! is_trace {
+            self.remove_resource::<CommandTrace>();
+        } else if ! self.contains_resource::<CommandTrace>() {
+            self.insert_resource(CommandTrace::default());
+        }
+    }
+
+    pub(crate) fn clear_command_trace(&mut self) {
+        if let Some(trace) = self.get_resource_mut::<CommandTrace>() {
+            trace.clear();
+        }
+    }
+
+    ///
+    /// Handle for queueing commands from other threads. Apply them with
+    /// apply_sent_commands.
+    ///
+    pub fn commands_sender(&mut self) -> CommandsSender {
+        if ! self.contains_resource::<SentCommands>() {
+            self.insert_resource(SentCommands::new());
+        }
+
+        let alloc = self.entity_allocator();
+
+        self.resource::<SentCommands>().sender(alloc)
+    }
+
+    ///
+    /// Applies the commands queued by CommandsSender handles, returning
+    /// the number applied.
+    ///
+    pub fn apply_sent_commands(&mut self) -> usize {
+        let mut queue = match self.get_resource::<SentCommands>() {
+            Some(sent) => sent.drain(),
+            None => return 0,
+        };
+
+        let n = queue.len();
+
+        queue.flush(self);
+
+        n
+    }
+
+    ///
+    /// Unique id for this store. In debug builds, EntityIds from another
+    /// store panic when used here.
+    ///
+    pub fn id(&self) -> WorldId {
+        self.deref().entities.world_id()
+    }
+
     //
     // Entities
     //
@@ -51,7 +124,7 @@ impl Store {
 
     pub fn get_entity(&self, id: EntityId) -> Option<EntityRef> {
         match self.deref().entities.get_entity(id) {
-            Some(id) => Some(EntityRef::new(id, self)),
+            Some(id) => Some(EntityRef::new(id, &self.deref().entities)),
             None => None,
         }
     }
@@ -63,18 +136,45 @@ impl Store {
         }
     }
 
+    pub fn contains_entity(&self, id: EntityId) -> bool {
+        self.deref().entities.contains_entity(id)
+    }
+
     pub fn get<T:'static>(&self, id: EntityId) -> Option<&T> {
         self.deref().entities.get::<T>(id)
     }
 
+    ///
+    /// Mutable T component of the entity. Panics for StableId, which is
+    /// changed by inserting a new StableId.
+    ///
     pub fn get_mut<T:'static>(&mut self, id: EntityId) -> Option<&mut T> {
+        check_mutable::<T>();
+
         self.deref_mut().entities.get_mut::<T>(id)
     }
 
+    ///
+    /// Mutable T components of distinct entities, e.g. to move a value
+    /// between two entities. Panics if an id is repeated.
+    ///
+    pub fn get_many_mut<T:'static, const N: usize>(
+        &mut self, 
+        ids: [EntityId; N]
+    ) -> Option<[&mut T; N]> {
+        check_mutable::<T>();
+
+        self.deref_mut().entities.get_many_mut::<T, N>(ids)
+    }
+
     pub(crate) fn alloc_entity_id(&mut self) -> EntityId {
         self.deref_mut().entities.alloc_entity_id()
     }
 
+    pub(crate) fn entity_allocator(&self) -> EntityAllocator {
+        self.deref().entities.allocator()
+    }
+
     pub fn spawn<T:Bundle>(&mut self, value: T) -> EntityId {
         let id = self.alloc_entity_id();
 
@@ -82,7 +182,61 @@ impl Store {
     }
 
     pub(crate) fn spawn_id<T:Bundle>(&mut self, id: EntityId, value: T) -> EntityId {
-        self.deref_mut().entities.spawn_id::<T>(id, value)
+        let id = self.deref_mut().entities.spawn_id::<T>(id, value);
+
+        self.update_stable_id(id);
+
+        id
+    }
+
+    ///
+    /// Reserves storage for `capacity` entities of bundle T so rapidly
+    /// respawned entities recycle rows instead of growing the tables.
+    ///
+    pub fn pool<T:Bundle>(&mut self, capacity: usize) {
+        self.deref_mut().entities.pool::<T>(capacity)
+    }
+
+    ///
+    /// Calls listener with each table's id and components, for existing
+    /// tables immediately and for new tables as entities create them.
+    /// Extensions such as spatial indexes track tables incrementally
+    /// instead of rescanning.
+    ///
+    pub fn on_new_table(
+        &mut self,
+        listener: impl FnMut(TableId, &[ComponentId]) + Send + Sync + 'static
+    ) {
+        self.deref_mut().entities.on_new_table(listener)
+    }
+
+    ///
+    /// Calls listener when a table starts matching a view, including
+    /// existing view tables immediately.
+    ///
+    pub fn on_new_view_table(
+        &mut self,
+        listener: impl FnMut(ViewId, TableId) + Send + Sync + 'static
+    ) {
+        self.deref_mut().entities.on_new_view_table(listener)
+    }
+
+    pub fn component_id<T:'static>(&self) -> Option<ComponentId> {
+        self.deref().entities.component_id::<T>()
+    }
+
+    pub(crate) fn add_component_id<T: Component>(&mut self) -> ComponentId {
+        ComponentId::from(self.deref_mut().entities.add_column_aligned::<T>(T::ALIGN))
+    }
+
+    pub(crate) fn spawn_batch_id<T:Bundle>(&mut self, values: Vec<(EntityId, T)>) {
+        let ids: Vec<EntityId> = values.iter().map(|(id, _)| *id).collect();
+
+        self.deref_mut().entities.spawn_batch_id::<T>(values);
+
+        for id in ids {
+            self.update_stable_id(id);
+        }
     }
 
     pub(crate) fn spawn_empty_id(&mut self, id: EntityId) -> EntityId {
@@ -94,17 +248,106 @@ impl Store {
         id: EntityId, 
         value: T
     ) -> EntityId {
-        self.deref_mut().entities.extend(id, value)
+        let id = self.deref_mut().entities.extend(id, value);
+
+        self.update_stable_id(id);
+
+        id
+    }
+
+    pub(crate) fn insert_bundle<T:Bundle>(&mut self, id: EntityId, value: T) -> EntityId {
+        let id = self.deref_mut().entities.extend(id, value);
+
+        self.update_stable_id(id);
+
+        id
+    }
+
+    pub(crate) fn remove_bundle<T:Bundle>(&mut self, id: EntityId) -> bool {
+        let is_removed = self.deref_mut().entities.remove_bundle::<T>(id);
+
+        self.update_stable_id(id);
+
+        is_removed
     }
 
     pub(crate) fn despawn(&mut self, id: EntityId) {
+        self.deref_mut().stable_ids.remove(id);
         self.deref_mut().entities.despawn(id)
     }
 
+    ///
+    /// Despawns every entity, keeping the tables, columns and view plans.
+    ///
+    pub fn despawn_all(&mut self) {
+        self.deref_mut().stable_ids.clear();
+        self.deref_mut().entities.despawn_all()
+    }
+
+    ///
+    /// Entity currently holding the StableId component, e.g. to resolve
+    /// references after a load.
+    ///
+    pub fn stable_entity(&self, stable_id: StableId) -> Option<EntityId> {
+        self.deref().stable_ids.entity(stable_id)
+    }
+
+    pub fn stable_id(&self, id: EntityId) -> Option<StableId> {
+        self.deref().stable_ids.stable_id(id)
+    }
+
+    fn update_stable_id(&mut self, id: EntityId) {
+        if self.component_id::<StableId>().is_none() {
+            return;
+        }
+
+        let stable_id = self.get::<StableId>(id).copied();
+
+        self.deref_mut().stable_ids.update(id, stable_id);
+    }
+
     pub fn view<V: View>(&mut self) -> ViewIterator<'_,V> {
         self.deref_mut().entities.iter_view::<V>()
     }
 
+    ///
+    /// Copies every T component into a vector in query order.
+    ///
+    pub fn export_column<T:Component + Copy>(&mut self) -> Vec<T> {
+        self.deref_mut().entities.export_column::<T>()
+    }
+
+    ///
+    /// Overwrites every T component in query order, the inverse of
+    /// export_column.
+    ///
+    pub fn import_column<T:Component>(
+        &mut self, 
+        values: impl IntoIterator<Item=T>
+    ) -> Result<usize> {
+        self.deref_mut().entities.import_column::<T>(values)
+    }
+
+    ///
+    /// Copies only the selected component types, e.g. `(Pos, Vel)`, with
+    /// their entity ids, for rollback without a full-world snapshot.
+    ///
+    pub fn snapshot_components<S:SnapshotComponents>(&mut self) -> ComponentSnapshot<S> {
+        ComponentSnapshot::new(self)
+    }
+
+    ///
+    /// Writes the snapshot's components back to entities that are still
+    /// alive, and removes them from entities that gained them after the
+    /// snapshot, returning the number of components restored.
+    ///
+    pub fn restore_components<S:SnapshotComponents>(
+        &mut self, 
+        snapshot: &ComponentSnapshot<S>
+    ) -> usize {
+        snapshot.restore(self)
+    }
+
     //
     // Resources
     //
@@ -126,6 +369,15 @@ impl Store {
         self.get_resource_mut::<T>().unwrap()
     }
 
+    ///
+    /// Mutable references to several distinct resources at once, e.g.
+    /// `store.resources_mut::<(A, B)>()`. Panics if a type is repeated
+    /// or a resource is missing.
+    ///
+    pub fn resources_mut<R: ResourcesMut>(&mut self) -> R::Item<'_> {
+        R::get_mut(self)
+    }
+
     pub fn contains_resource<T: 'static>(&self) -> bool {
         self.deref().resources.contains_resource::<T>()
     }
@@ -148,10 +400,44 @@ impl Store {
         self.deref_mut().resources.remove::<T>()
     }
 
+    ///
+    /// Drops the Send resources whose TypeId keep rejects.
+    ///
+    pub fn retain_resources(&mut self, keep: impl FnMut(TypeId) -> bool) {
+        self.deref_mut().resources.retain(keep)
+    }
+
+    ///
+    /// Every Send resource, type-erased, e.g. for inspectors and saves.
+    ///
+    pub fn iter_resources(&self) -> impl Iterator<Item=ResourceInfo<'_>> {
+        self.deref().resources.iter()
+    }
+
+    ///
+    /// Registers T's Debug impl for ResourceInfo::debug.
+    ///
+    pub fn register_resource_debug<T: fmt::Debug + Send + 'static>(&mut self) {
+        self.deref_mut().resources.register_debug::<T>()
+    }
+
+    ///
+    /// Marks the system's resources as held while it runs, for conflict
+    /// messages that name both systems.
+    ///
+    #[cfg(debug_assertions)]
+    pub(crate) fn borrow_resources<'a>(&'a self, meta: &'a SystemMeta) -> ResourceBorrow<'a> {
+        self.deref().resources.borrow(meta.name(), meta.resources(), meta.mut_resources())
+    }
+
     pub fn get_resource_id<T: 'static>(&mut self) -> ResourceId {
         self.deref_mut().resources.get_resource_id::<T>()
     }
 
+    pub(crate) fn find_resource_id(&self, type_id: TypeId) -> Option<ResourceId> {
+        self.deref().resources.find_resource_id(type_id)
+    }
+
     pub fn init_resource_non_send<T: FromStore + 'static>(&mut self) {
         if ! self.deref().resources_non_send.get::<T>().is_none() {
             return;
@@ -174,12 +460,87 @@ impl Store {
         self.deref_mut().entities.iter_view()
     }
 
-    pub(crate) fn view_build<Q:View>(&mut self) -> ViewPlan {
+    ///
+    /// The view's item for one entity, e.g. `store.query_one::<(&A, &mut B)>(id)`,
+    /// None if the entity is dead or doesn't match the view.
+    ///
+    pub fn query_one<Q:View>(&mut self, id: EntityId) -> Option<Q::Item<'_>> {
+        self.deref_mut().entities.view_one::<Q>(id)
+    }
+
+    pub(crate) fn view_build<Q:View>(&mut self) -> Result<ViewPlan> {
         self.deref_mut().entities.view_plan::<Q>()
     }
 
-    pub(crate) unsafe fn view_iter_from_plan<Q: View>(&mut self, plan: &ViewPlan) -> ViewIterator<Q> {
-        self.deref_mut().entities.iter_view_with_plan::<Q>(plan.clone())
+    pub(crate) fn view_build_filtered<Q:View, F:ViewFilter>(&mut self) -> Result<ViewPlan> {
+        self.deref_mut().entities.view_plan_filtered::<Q, F>()
+    }
+
+    pub(crate) fn view_lens<L:View>(&self, base: &ViewPlan) -> Result<ViewPlan> {
+        self.deref().entities.lens_plan::<L>(base)
+    }
+
+    ///
+    /// Number of live entities.
+    ///
+    pub fn entity_count(&self) -> usize {
+        self.deref().entities.entity_count()
+    }
+
+    ///
+    /// Number of entities matching the view, from table lengths.
+    ///
+    pub fn count<Q:View>(&mut self) -> usize {
+        let plan = match self.view_build::<Q>() {
+            Ok(plan) => plan,
+            Err(err) => panic!("{}", err),
+        };
+
+        self.count_from_plan(&plan)
+    }
+
+    pub(crate) fn count_from_plan(&self, plan: &ViewPlan) -> usize {
+        self.deref().entities.count_view(plan)
+    }
+
+    pub(crate) fn explain_from_plan(&self, plan: &ViewPlan) -> ViewExplain {
+        ViewExplain::new(&self.deref().entities, plan)
+    }
+
+    pub(crate) unsafe fn view_iter_from_plan<'a, Q: View>(
+        &'a mut self,
+        plan: &'a ViewPlan
+    ) -> ViewIterator<'a, Q> {
+        // borrows the plan, so iterating a query doesn't allocate
+        self.deref_mut().entities.iter_view_with_plan::<Q>(Cow::Borrowed(plan))
+    }
+
+    pub(crate) unsafe fn view_get_from_plan<Q: View>(
+        &mut self,
+        plan: &ViewPlan,
+        id: EntityId
+    ) -> Option<Q::Item<'_>> {
+        self.deref_mut().entities.view_get_with_plan::<Q>(plan, id)
+    }
+
+    pub(crate) fn view_contains(&self, plan: &ViewPlan, id: EntityId) -> bool {
+        self.deref().entities.view_contains(plan, id)
+    }
+
+    pub(crate) fn view_entities(&self, plan: &ViewPlan) -> Vec<EntityId> {
+        self.deref().entities.view_entities(plan)
+    }
+
+    pub(crate) fn enable_entity_changes(&mut self) {
+        self.deref_mut().entities.enable_change_log();
+    }
+
+    pub(crate) fn entity_change_seq(&self) -> usize {
+        self.deref().entities.change_seq()
+    }
+
+    pub(crate) fn entity_changes_since(&self, seq: usize) -> Option<&[EntityId]> {
+        self.deref().entities.changes_since(seq)
     }
 
     //
@@ -218,11 +579,114 @@ impl Store {
     }
 
     pub fn try_run_schedule(&mut self, label: impl AsRef<dyn ScheduleLabel>) -> Result<()> {
+        let label = label.as_ref();
+
         self.try_eval_schedule(label, |world, schedule| {
+            prepare_schedule(label, world, schedule)?;
+
             schedule.tick(world)
         })
     }
 
+    ///
+    /// Runs schedules concurrently in one tick, e.g. audio processing beside
+    /// the simulation. Each schedule runs on its own executor's threads,
+    /// except single executor schedules, which run on the calling thread.
+    /// Commands are applied after all complete, and ExecutorDiagnostics
+    /// isn't updated for the grouped schedules.
+    ///
+    /// Fails if the planners find access shared by the schedules, where
+    /// one writes. An exclusive system accesses the whole store, so a
+    /// schedule with one can't be grouped and must run on its own.
+    ///
+    pub fn run_schedules_parallel(&mut self, labels: &[&dyn ScheduleLabel]) -> Result<()> {
+        if ! self.is_active() {
+            return Err("store is closed, possibly from a previous fatal error".into())
+        }
+
+        let mut schedules = Vec::new();
+
+        for label in labels {
+            let Some(schedule) = self.get_resource_mut::<Schedules>()
+                .and_then(|s| s.remove_id(label.label_id()))
+            else {
+                self.restore_schedules(schedules);
+                return Err(format!("{:?} is an unknown ScheduleLabel", label).into());
+            };
+
+            schedules.push((*label, schedule));
+        }
+
+        let result = self.run_parallel(&mut schedules);
+
+        self.restore_schedules(schedules);
+
+        result
+    }
+
+    fn run_parallel(&mut self, schedules: &mut [(&dyn ScheduleLabel, Schedule)]) -> Result<()> {
+        for (label, schedule) in schedules.iter_mut() {
+            prepare_schedule(*label, self, schedule)?;
+        }
+
+        for (i, (label_a, a)) in schedules.iter().enumerate() {
+            if i > 0 && a.has_main_thread() {
+                return Err(format!(
+                    "schedule {:?} has main-thread systems, so it must be first to run in parallel",
+                    label_a
+                ).into());
+            }
+
+            for (label_b, b) in &schedules[i + 1..] {
+                if let Some(conflict) = a.conflict(b) {
+                    return Err(format!("schedules {:?} and {:?} can't run in parallel: {}",
+                        label_a, label_b, conflict
+                    ).into());
+                }
+            }
+        }
+
+        let world = UnsafeStore::new(self.take());
+
+        let mut refs: Vec<&mut Schedule> = schedules.iter_mut()
+            .map(|(_, schedule)| schedule)
+            .collect();
+        let result = unsafe { Schedule::run_parallel(&mut refs, &world) };
+
+        self.replace(world.take());
+
+        for (_, schedule) in schedules.iter_mut() {
+            schedule.flush(self);
+        }
+
+        result
+    }
+
+    fn restore_schedules(&mut self, schedules: Vec<(&dyn ScheduleLabel, Schedule)>) {
+        for (label, schedule) in schedules {
+            self.resource_mut::<Schedules>().insert_id(label.label_id(), schedule);
+        }
+    }
+
+    ///
+    /// Initializes and plans every schedule, so the first tick doesn't
+    /// pay the setup cost.
+    ///
+    pub fn finish_schedules(&mut self) -> Result<()> {
+        let labels = match self.get_resource::<Schedules>() {
+            Some(schedules) => schedules.labels(),
+            None => return Ok(()),
+        };
+
+        for label in labels {
+            self.try_eval_schedule(&label, |world, schedule| {
+                prepare_schedule(label.as_ref(), world, schedule)
+            })?;
+        }
+
+        Ok(())
+    }
+
     pub fn try_eval_schedule<R>(
         &mut self, 
         label: impl AsRef<dyn ScheduleLabel>,
@@ -233,36 +697,103 @@ impl Store {
         }
 
         let label = label.as_ref();
+        let id = label.label_id();
 
-        let Some((label, mut schedule))
+        let Some(mut schedule)
             = self.get_resource_mut::<Schedules>()
-                .and_then(|s| s.remove_entry(label))
+                .and_then(|s| s.remove_id(id))
         else {
             return Err(format!("{:?} is an unknown ScheduleLabel", label).into());
         };
 
-        let value = fun(self, &mut schedule)?;
+        let value = fun(self, &mut schedule);
 
-        self.resource_mut::<Schedules>().insert(label, schedule);
+        // a failed tick closes the store if its executor can't recover it
+        if self.is_active() {
+            self.resource_mut::<Schedules>().insert_id(id, schedule);
+        }
 
-        Ok(value)
+        value
     }   
 
+    ///
+    /// Initializes a system for repeated eval_cached calls.
+    ///
+    pub fn cached_system<O: 'static, M>(
+        &mut self,
+        into_system: impl IntoSystem<O, M>
+    ) -> Result<CachedSystem<O>> {
+        let mut store = UnsafeStore::new(self.take());
+        let system = CachedSystem::new(into_system, &mut store);
+        self.replace(store.take());
+
+        system
+    }
+
+    ///
+    /// Evaluates a cached system, reusing its state from previous calls.
+    ///
+    pub fn eval_cached<O: 'static>(&mut self, system: &mut CachedSystem<O>) -> Result<O> {
+        system.check_world(self);
+
+        let mut store = UnsafeStore::new(self.take());
+        let value = system.run(&mut store);
+        self.replace(store.take());
+
+        value
+    }
+
     pub fn eval<O, M>(&mut self, into_system: impl IntoSystem<O, M>) -> Result<O> {
         let mut system = IntoSystem::into_system(into_system);
         
         let mut meta = SystemMeta::empty();
     
         let mut store = UnsafeStore::new(self.take());
-        system.init(&mut meta, &mut store)?;
-        let value = system.run(&mut store);
-        system.flush(&mut store);
+
+        let init = system.init(&mut meta, &mut store)
+            .and_then(|_| store.check_transaction(&meta));
+
+        let value = match init {
+            Ok(_) => {
+                let value = system.run(&mut store);
+                system.flush(&mut store);
+                value
+            }
+            Err(err) => Err(err),
+        };
 
         self.replace(store.take());
 
         value
     }
 
+    pub(crate) fn add_commands_applied(&mut self, n: usize) {
+        self.deref_mut().commands_applied += n;
+    }
+
+    ///
+    /// Queue holding commands until the current transaction commits.
+    ///
+    pub(crate) fn staged_commands(&mut self) -> &mut Option<CommandQueue> {
+        &mut self.deref_mut().staged
+    }
+
+    pub(crate) fn is_transaction(&self) -> bool {
+        self.deref().staged.is_some()
+    }
+
+    ///
+    /// Records entity ids reserved until end_reserved, which frees the
+    /// unspawned ones if is_free.
+    ///
+    pub(crate) fn log_reserved(&mut self) {
+        self.deref_mut().entities.log_reserved();
+    }
+
+    pub(crate) fn end_reserved(&mut self, is_free: bool) {
+        self.deref_mut().entities.end_reserved(is_free);
+    }
+
     pub(crate) fn take(&mut self) -> Self {
         let inner = self.0.take();
 
@@ -278,6 +809,78 @@ pub(crate) struct StoreInner {
     pub(crate) entities: EntityStore,
     pub(crate) resources: Resources,
     pub(crate) resources_non_send: Resources,
+    pub(crate) stable_ids: StableIds,
+    pub(crate) commands_applied: usize,
+    pub(crate) staged: Option<CommandQueue>,
+}
+
+///
+/// Tuple of distinct resource types borrowed mutably by Store::resources_mut.
+///
+pub trait ResourcesMut {
+    type Item<'a>;
+
+    fn get_mut(store: &mut Store) -> Self::Item<'_>;
+}
+
+macro_rules! impl_resources_mut_tuple {
+    ($($t:ident),*) => {
+        #[allow(non_snake_case)]
+        impl<$($t: Send + 'static,)*> ResourcesMut for ($($t,)*) {
+            type Item<'a> = ($(&'a mut $t,)*);
+
+            fn get_mut(store: &mut Store) -> Self::Item<'_> {
+                let ids = [$((TypeId::of::<$t>(), type_name::<$t>()),)*];
+
+                for (i, (id, name)) in ids.iter().enumerate() {
+                    assert!(
+                        ! ids[..i].iter().any(|(prev, _)| prev == id),
+                        "resources_mut with duplicate resource {:?}", name
+                    );
+                }
+
+                let resources = &store.deref_mut().resources;
+
+                // types are distinct, so the references are disjoint
+                unsafe {
+                    ($(
+                        match resources.get_unchecked_mut::<$t>() {
+                            Some($t) => $t,
+                            None => panic!("unassigned resource {:?}", type_name::<$t>()),
+                        },
+                    )*)
+                }
+            }
+        }
+    }
+}
+
+impl_resources_mut_tuple!(R1);
+impl_resources_mut_tuple!(R1, R2);
+impl_resources_mut_tuple!(R1, R2, R3);
+impl_resources_mut_tuple!(R1, R2, R3, R4);
+impl_resources_mut_tuple!(R1, R2, R3, R4, R5);
+impl_resources_mut_tuple!(R1, R2, R3, R4, R5, R6);
+impl_resources_mut_tuple!(R1, R2, R3, R4, R5, R6, R7);
+impl_resources_mut_tuple!(R1, R2, R3, R4, R5, R6, R7, R8);
+
+// init errors, e.g. missing resources, name the schedule after the system
+fn prepare_schedule(
+    label: &dyn ScheduleLabel,
+    world: &mut Store,
+    schedule: &mut Schedule
+) -> Result<()> {
+    schedule.prepare(world)
+        .map_err(|err| err.rethrow(&format!("\n\tin schedule {:?}", label)))
+}
+
+// StableId is keyed in the stable id map, so in-place changes would
+// leave the map stale
+fn check_mutable<T:'static>() {
+    assert!(
+        TypeId::of::<T>() != TypeId::of::<StableId>(),
+        "StableId is read-only, insert a new StableId to change it"
+    );
 }
 
 impl<T:Default> FromStore for T {
@@ -288,7 +891,9 @@ impl<T:Default> FromStore for T {
 
 #[cfg(test)]
 mod tests {
-    use crate::entity::Component;
+    use std::sync::{Arc, Mutex};
+
+    use crate::{entity::Component, Local, Query};
 
     use super::Store;
 
@@ -397,7 +1002,280 @@ mod tests {
             "TestA(1002), TestA(2003)");
     }
 
-    #[derive(Clone, Debug, PartialEq)]
+    #[test]
+    fn export_import_column() {
+        let mut world = Store::new();
+
+        assert_eq!(world.export_column::<TestA>(), vec![]);
+
+        world.spawn(TestA(1));
+        world.spawn(TestB(10));
+        world.spawn(TestA(2));
+
+        assert_eq!(world.export_column::<TestA>(), vec![TestA(1), TestA(2)]);
+
+        let values: Vec<TestA> = world.export_column::<TestA>()
+            .iter()
+            .map(|v| TestA(v.0 * 10))
+            .collect();
+
+        assert_eq!(world.import_column(values).unwrap(), 2);
+        assert_eq!(world.export_column::<TestA>(), vec![TestA(10), TestA(20)]);
+
+        assert!(world.import_column(vec![TestA(3)]).is_err());
+        assert!(world.import_column(vec![TestA(3), TestA(4), TestA(5)]).is_err());
+        assert_eq!(world.export_column::<TestA>(), vec![TestA(10), TestA(20)]);
+    }
+
+    #[test]
+    fn world_id() {
+        let mut world_a = Store::new();
+        let world_b = Store::new();
+
+        assert_ne!(world_a.id(), world_b.id());
+
+        let id = world_a.spawn(TestA(1));
+
+        if cfg!(debug_assertions) {
+            assert_eq!(id.world(), Some(world_a.id()));
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic]
+    fn world_id_cross_store() {
+        let mut world_a = Store::new();
+        let world_b = Store::new();
+
+        let id = world_a.spawn(TestA(1));
+
+        world_b.get::<TestA>(id);
+    }
+
+    #[test]
+    fn entity_ref() {
+        let mut world = Store::new();
+
+        let id = world.spawn((TestA(1), TestB(2)));
+
+        let entity = world.entity(id);
+        assert_eq!(entity.id(), id);
+        assert_eq!(entity.get::<TestA>(), Some(&TestA(1)));
+        assert!(entity.contains::<TestB>());
+
+        let names: Vec<&str> = entity.iter_components().collect();
+        assert_eq!(names.len(), 2);
+        assert!(names.iter().any(|name| name.ends_with("TestA")));
+        assert!(names.iter().any(|name| name.ends_with("TestB")));
+
+        let id = world.spawn(TestA(3));
+        let entity = world.entity(id);
+        assert!(! entity.contains::<TestB>());
+        assert_eq!(entity.iter_components().count(), 1);
+    }
+
+    #[test]
+    fn entity_count() {
+        let mut world = Store::new();
+        assert_eq!(world.entity_count(), 0);
+
+        let id = world.spawn(TestA(1));
+        world.spawn((TestA(2), TestB(3)));
+        world.spawn(TestB(4));
+
+        assert_eq!(world.entity_count(), 3);
+        assert_eq!(world.count::<&TestA>(), 2);
+        assert_eq!(world.count::<(&TestA, &TestB)>(), 1);
+
+        world.despawn(id);
+        assert_eq!(world.entity_count(), 2);
+        assert_eq!(world.count::<&TestA>(), 1);
+    }
+
+    #[test]
+    fn get_many_mut() {
+        let mut world = Store::new();
+        let id_a = world.spawn(TestA(1));
+        let id_b = world.spawn(TestA(10));
+        let id_c = world.spawn(TestB(2));
+
+        let [a, b] = world.get_many_mut::<TestA, 2>([id_a, id_b]).unwrap();
+        b.0 += a.0;
+        a.0 = 0;
+
+        assert_eq!(world.get::<TestA>(id_a), Some(&TestA(0)));
+        assert_eq!(world.get::<TestA>(id_b), Some(&TestA(11)));
+
+        assert!(world.get_many_mut::<TestA, 2>([id_a, id_c]).is_none());
+    }
+
+    #[test]
+    fn query_one() {
+        let mut world = Store::new();
+        let id_a = world.spawn(TestA(1));
+        let id_ab = world.spawn((TestA(2), TestB(3)));
+
+        assert_eq!(world.query_one::<&TestA>(id_a), Some(&TestA(1)));
+        assert!(world.query_one::<(&TestA, &TestB)>(id_a).is_none());
+
+        let (a, b) = world.query_one::<(&mut TestA, &TestB)>(id_ab).unwrap();
+        a.0 += b.0 as u32;
+        assert_eq!(world.get::<TestA>(id_ab), Some(&TestA(5)));
+
+        world.despawn(id_a);
+        assert!(world.query_one::<&TestA>(id_a).is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn get_many_mut_duplicate() {
+        let mut world = Store::new();
+        let id_a = world.spawn(TestA(1));
+
+        world.get_many_mut::<TestA, 2>([id_a, id_a]);
+    }
+
+    #[test]
+    fn resources_mut() {
+        let mut world = Store::new();
+        world.insert_resource(TestA(1));
+        world.insert_resource(TestB(2));
+
+        let (a, b) = world.resources_mut::<(TestA, TestB)>();
+        a.0 += b.0 as u32;
+        b.0 += 10;
+
+        assert_eq!(world.resource::<TestA>(), &TestA(3));
+        assert_eq!(world.resource::<TestB>(), &TestB(12));
+    }
+
+    #[test]
+    #[should_panic]
+    fn resources_mut_duplicate() {
+        let mut world = Store::new();
+        world.insert_resource(TestA(1));
+
+        world.resources_mut::<(TestA, TestA)>();
+    }
+
+    #[test]
+    fn entity_mut() {
+        let mut world = Store::new();
+
+        let id = world.spawn(TestA(1));
+
+        let mut entity = world.entity_mut(id);
+        entity.insert_bundle((TestB(2), TestC(3)));
+        entity.get_mut::<TestA>().unwrap().0 += 10;
+
+        assert_eq!(entity.get::<TestA>(), Some(&TestA(11)));
+        assert_eq!(entity.get::<TestB>(), Some(&TestB(2)));
+        assert!(entity.contains::<TestC>());
+
+        entity.remove_bundle::<(TestA, TestC)>();
+
+        assert_eq!(entity.get::<TestA>(), None);
+        assert_eq!(entity.get::<TestB>(), Some(&TestB(2)));
+        assert!(! entity.contains::<TestC>());
+
+        assert_eq!(world.query::<&TestA>().count(), 0);
+        assert_eq!(world.query::<&TestB>().count(), 1);
+
+        world.entity_mut(id).despawn();
+
+        assert!(! world.contains_entity(id));
+    }
+
+    #[test]
+    fn insert_replaces_existing() {
+        let n_drop = Arc::new(Mutex::new(0));
+
+        let mut world = Store::new();
+        let id = world.spawn((TestA(1), TestDrop(1, n_drop.clone())));
+
+        world.entity_mut(id).insert(TestDrop(2, n_drop.clone()));
+
+        assert_eq!(*n_drop.lock().unwrap(), 1);
+        assert_eq!(world.get::<TestDrop>(id).map(|d| d.0), Some(2));
+        assert_eq!(world.get::<TestA>(id), Some(&TestA(1)));
+        assert_eq!(world.query::<&TestDrop>().count(), 1);
+
+        world.entity_mut(id).insert_bundle((TestB(3), TestDrop(3, n_drop.clone())));
+
+        assert_eq!(*n_drop.lock().unwrap(), 2);
+        assert_eq!(world.get::<TestDrop>(id).map(|d| d.0), Some(3));
+        assert_eq!(world.get::<TestB>(id), Some(&TestB(3)));
+
+        world.despawn(id);
+        assert_eq!(*n_drop.lock().unwrap(), 3);
+    }
+
+    #[test]
+    fn eval_cached() {
+        let mut world = Store::new();
+        world.spawn(TestA(1));
+        world.spawn(TestA(2));
+
+        let mut system = world.cached_system(|mut n: Local<u32>, q: Query<&TestA>| {
+            *n += 1;
+            *n + q.iter().map(|a| a.0).sum::<u32>()
+        }).unwrap();
+
+        assert_eq!(world.eval_cached(&mut system).unwrap(), 4);
+        assert_eq!(world.eval_cached(&mut system).unwrap(), 5);
+
+        world.spawn(TestA(10));
+
+        assert_eq!(world.eval_cached(&mut system).unwrap(), 16);
+    }
+
+    #[test]
+    #[should_panic(expected = "CachedSystem belongs to")]
+    fn eval_cached_cross_store() {
+        let mut world_a = Store::new();
+        let mut world_b = Store::new();
+
+        let mut system = world_a.cached_system(|q: Query<&TestA>| q.iter().count()).unwrap();
+
+        world_b.eval_cached(&mut system).unwrap();
+    }
+
+    #[test]
+    fn on_new_table() {
+        let mut world = Store::new();
+        world.spawn(TestA(1));
+
+        let tables = Arc::new(Mutex::new(Vec::new()));
+        let ptr = tables.clone();
+        world.on_new_table(move |id, components| {
+            ptr.lock().unwrap().push((id.index(), components.len()));
+        });
+
+        let view_tables = Arc::new(Mutex::new(0));
+        let ptr = view_tables.clone();
+        world.on_new_view_table(move |_view, _table| {
+            *ptr.lock().unwrap() += 1;
+        });
+
+        // the empty table and TestA's are reported on registration
+        assert_eq!(tables.lock().unwrap().len(), 2);
+
+        world.spawn(TestA(2));
+        assert_eq!(tables.lock().unwrap().len(), 2);
+
+        world.spawn((TestA(3), TestB(3)));
+        assert_eq!(tables.lock().unwrap().last(), Some(&(2, 2)));
+
+        let n_view_tables = *view_tables.lock().unwrap();
+        world.eval(|q: Query<&TestB>| q.iter().count()).unwrap();
+        assert_eq!(*view_tables.lock().unwrap(), n_view_tables + 1);
+
+        assert!(world.component_id::<TestA>().is_some());
+        assert!(world.component_id::<TestC>().is_none());
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
     struct TestA(u32);
 
     impl Component for TestA {}
@@ -406,4 +1284,19 @@ mod tests {
     struct TestB(u16);
 
     impl Component for TestB {}
+
+    #[derive(Debug, PartialEq)]
+    struct TestC(u32);
+
+    impl Component for TestC {}
+
+    struct TestDrop(u32, Arc<Mutex<usize>>);
+
+    impl Component for TestDrop {}
+
+    impl Drop for TestDrop {
+        fn drop(&mut self) {
+            *self.1.lock().unwrap() += 1;
+        }
+    }
 }
\ No newline at end of file
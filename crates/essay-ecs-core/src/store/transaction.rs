@@ -0,0 +1,291 @@
+use std::{any::TypeId, mem};
+
+use crate::{entity::ComponentId, error::Result, schedule::SystemMeta};
+
+use super::{CommandQueue, SnapshotComponents, Store};
+
+type Restore = Box<dyn FnOnce(&mut Store)>;
+
+#[derive(Default)]
+struct Tracked {
+    // snapshots taken at the start of each transaction
+    snapshots: Vec<fn(&mut Store) -> Restore>,
+
+    // types systems may write inside a transaction
+    components: Vec<ComponentId>,
+    resources: Vec<TypeId>,
+}
+
+impl Store {
+    ///
+    /// Runs fun as a transaction, e.g. a tick. Commands are staged and
+    /// applied only if fun succeeds, so later systems don't see them
+    /// until the commit. On failure the staged commands are dropped with
+    /// the entity ids they reserved, and tracked component and resource
+    /// values are restored, leaving the store usable.
+    ///
+    /// Systems that write an untracked component or resource fail inside
+    /// a transaction, because a failure couldn't restore their writes.
+    /// Exclusive systems and direct &mut Store calls, which drive the
+    /// schedules, aren't checked.
+    ///
+    pub fn transaction<R>(&mut self, fun: impl FnOnce(&mut Store) -> Result<R>) -> Result<R> {
+        if self.staged_commands().is_some() {
+            return fun(self);
+        }
+
+        let snapshots: Vec<fn(&mut Store) -> Restore> = match self.get_resource::<Tracked>() {
+            Some(tracked) => tracked.snapshots.clone(),
+            None => Vec::new(),
+        };
+
+        let restores: Vec<Restore> = snapshots.iter().map(|snapshot| snapshot(self)).collect();
+
+        *self.staged_commands() = Some(CommandQueue::default());
+        self.log_reserved();
+
+        let result = fun(self);
+
+        // a fatal error can close the store, leaving nothing to restore
+        if ! self.is_active() {
+            return result;
+        }
+
+        let mut staged = self.staged_commands().take().unwrap_or_default();
+
+        match result {
+            Ok(value) => {
+                self.end_reserved(false);
+                staged.flush(self);
+
+                Ok(value)
+            }
+            Err(err) => {
+                mem::drop(staged);
+                self.end_reserved(true);
+
+                for restore in restores {
+                    restore(self);
+                }
+
+                Err(err)
+            }
+        }
+    }
+
+    ///
+    /// Restores the values of the S components when a transaction fails.
+    ///
+    pub fn track_components<S: SnapshotComponents>(&mut self) {
+        let mut ids = Vec::new();
+        S::component_ids(self, &mut ids);
+
+        self.track(|store| {
+            let snapshot = store.snapshot_components::<S>();
+
+            Box::new(move |store| { store.restore_components(&snapshot); })
+        });
+
+        self.resource_mut::<Tracked>().components.extend(ids);
+    }
+
+    ///
+    /// Restores the resource when a transaction fails, removing it if it
+    /// was inserted during the transaction.
+    ///
+    pub fn track_resource<T: Clone + Send + 'static>(&mut self) {
+        self.track(|store| {
+            let value = store.get_resource::<T>().cloned();
+
+            Box::new(move |store| {
+                match value {
+                    Some(value) => store.insert_resource(value),
+                    None => { store.remove_resource::<T>(); }
+                }
+            })
+        });
+
+        self.resource_mut::<Tracked>().resources.push(TypeId::of::<T>());
+    }
+
+    ///
+    /// Lets systems write the resource inside a transaction without
+    /// restoring it when the transaction fails, e.g. per-tick buffers.
+    ///
+    pub fn untracked_resource<T: Send + 'static>(&mut self) {
+        self.init_resource::<Tracked>();
+
+        self.resource_mut::<Tracked>().resources.push(TypeId::of::<T>());
+    }
+
+    ///
+    /// Fails if a transaction is running and the system writes a
+    /// component or resource that a failed transaction wouldn't restore.
+    ///
+    pub(crate) fn check_transaction(&self, meta: &SystemMeta) -> Result<()> {
+        if ! self.is_transaction() || meta.is_exclusive() {
+            return Ok(());
+        }
+
+        let tracked = self.get_resource::<Tracked>();
+
+        if let Some(id) = meta.mut_components().iter()
+            .find(|id| ! tracked.is_some_and(|t| t.components.contains(id))) {
+            return Err(format!(
+                "{} writes untracked component {:?} inside a transaction, see Store::track_components",
+                meta.name(), id
+            ).into());
+        }
+
+        let is_tracked = |id| tracked.is_some_and(|t| {
+            t.resources.iter().any(|type_id| self.find_resource_id(*type_id) == Some(id))
+        });
+
+        if let Some(id) = meta.mut_resources().iter().find(|id| ! is_tracked(**id)) {
+            return Err(format!(
+                "{} writes untracked resource {:?} inside a transaction, see Store::track_resource",
+                meta.name(), id
+            ).into());
+        }
+
+        Ok(())
+    }
+
+    fn track(&mut self, snapshot: fn(&mut Store) -> Restore) {
+        self.init_resource::<Tracked>();
+
+        self.resource_mut::<Tracked>().snapshots.push(snapshot);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{entity::Component, Commands, Query, Res, ResMut, Store};
+
+    #[test]
+    fn transaction_commit() {
+        let mut store = Store::new();
+
+        let id = store.spawn(TestA(1));
+
+        store.transaction(|store| {
+            store.eval(|mut c: Commands| { c.spawn(TestA(2)); })?;
+
+            // staged until commit
+            assert_eq!(store.count::<&TestA>(), 1);
+
+            Ok(())
+        }).unwrap();
+
+        assert_eq!(store.count::<&TestA>(), 2);
+        assert_eq!(store.get::<TestA>(id), Some(&TestA(1)));
+    }
+
+    #[test]
+    fn transaction_rollback() {
+        let mut store = Store::new();
+        store.track_components::<TestA>();
+        store.track_resource::<u32>();
+
+        let id = store.spawn(TestA(1));
+        store.insert_resource(10u32);
+
+        let err = store.transaction(move |store| {
+            store.eval(move |mut c: Commands, q: Query<&mut TestA>| {
+                for a in q.iter() {
+                    a.0 += 100;
+                }
+
+                c.entity(id).despawn();
+                c.spawn(TestA(2));
+            })?;
+
+            *store.resource_mut::<u32>() = 20;
+
+            store.eval(|| -> crate::error::Result<()> { Err("failed".into()) })?
+        }).unwrap_err();
+
+        assert_eq!(err.message(), "failed");
+
+        assert!(store.contains_entity(id));
+        assert_eq!(store.get::<TestA>(id), Some(&TestA(1)));
+        assert_eq!(store.count::<&TestA>(), 1);
+        assert_eq!(store.resource::<u32>(), &10);
+    }
+
+    #[test]
+    fn transaction_rollback_frees_reserved_ids() {
+        let mut store = Store::new();
+
+        store.spawn(TestA(1));
+
+        let mut reserved = Vec::new();
+
+        for _ in 0..2 {
+            let err = store.transaction(|store| {
+                let id = store.eval(|mut c: Commands| c.spawn(TestA(2)).id())?;
+
+                store.eval(|| -> crate::error::Result<()> { Err("failed".into()) })??;
+
+                Ok(id)
+            }).unwrap_err();
+
+            assert_eq!(err.message(), "failed");
+
+            let id = store.spawn(TestA(3));
+            reserved.push(id);
+            store.despawn(id);
+        }
+
+        // the dropped spawn's slot is reused instead of leaking
+        assert_eq!(reserved[0].index(), 1);
+        assert_eq!(reserved[1].index(), 1);
+        assert_eq!(store.count::<&TestA>(), 1);
+    }
+
+    #[test]
+    fn transaction_untracked_write() {
+        let mut store = Store::new();
+        store.track_components::<TestA>();
+
+        store.spawn((TestA(1), TestB(1)));
+        store.insert_resource(10u32);
+
+        let err = store.transaction(|store| {
+            store.eval(|q: Query<&mut TestB>| { for b in q.iter() { b.0 += 1; } })
+        }).unwrap_err();
+
+        assert!(err.message().contains("writes untracked component"), "{}", err.message());
+        assert_eq!(store.query::<&TestB>().next(), Some(&TestB(1)));
+
+        let err = store.transaction(|store| {
+            store.eval(|mut n: ResMut<u32>| *n += 1)
+        }).unwrap_err();
+
+        assert!(err.message().contains("writes untracked resource"), "{}", err.message());
+        assert_eq!(store.resource::<u32>(), &10);
+
+        // reads and tracked writes are allowed
+        store.transaction(|store| {
+            store.eval(|q: Query<(&mut TestA, &TestB)>, n: Res<u32>| {
+                for (a, b) in q.iter() { a.0 += b.0 + *n; }
+            })
+        }).unwrap();
+
+        assert_eq!(store.query::<&TestA>().next(), Some(&TestA(12)));
+
+        // outside a transaction nothing is checked
+        store.eval(|mut n: ResMut<u32>| *n += 1).unwrap();
+        assert_eq!(store.resource::<u32>(), &11);
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct TestA(u32);
+
+    impl Component for TestA {}
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct TestB(u32);
+
+    impl Component for TestB {}
+}
@@ -0,0 +1,99 @@
+use crate::entity::EntityId;
+
+use super::{EntityRef, Store};
+
+///
+/// Entity reference for long-lived holders like resources. The id's
+/// generation changes when the entity is despawned, so a reference to a
+/// reused slot resolves to None instead of the new entity. Commands from
+/// `Commands::entity(weak.id())` are dropped the same way.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct WeakRef(EntityId);
+
+impl WeakRef {
+    pub fn new(id: EntityId) -> Self {
+        Self(id)
+    }
+
+    pub fn id(&self) -> EntityId {
+        self.0
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.0.generation()
+    }
+
+    pub fn is_alive(&self, store: &Store) -> bool {
+        store.contains_entity(self.0)
+    }
+
+    pub fn resolve<'a>(&self, store: &'a Store) -> Option<EntityRef<'a>> {
+        store.get_entity(self.0)
+    }
+}
+
+impl From<EntityId> for WeakRef {
+    fn from(id: EntityId) -> Self {
+        Self(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{core_app::CoreApp, entity::Component, Commands, Store};
+
+    use super::WeakRef;
+
+    #[test]
+    fn weak_ref_resolve() {
+        let mut store = Store::new();
+
+        let id = store.spawn(TestA(1));
+        let weak = WeakRef::from(id);
+
+        assert!(weak.is_alive(&store));
+        assert_eq!(weak.resolve(&store).unwrap().get::<TestA>(), Some(&TestA(1)));
+
+        store.eval(move |mut c: Commands| c.entity(id).despawn()).unwrap();
+        let id_b = store.spawn(TestA(2));
+
+        // slot reused with a new generation
+        assert_eq!(id_b.generation(), weak.generation() + 1);
+        assert!(! weak.is_alive(&store));
+        assert!(weak.resolve(&store).is_none());
+        assert_eq!(store.get::<TestA>(weak.id()), None);
+    }
+
+    #[test]
+    fn weak_ref_commands() {
+        let mut app = CoreApp::new();
+
+        let weak = app.eval(|mut c: Commands| WeakRef::from(c.spawn(TestA(1)).id())).unwrap();
+
+        app.eval(move |mut c: Commands| {
+            c.entity(weak.id()).despawn();
+            c.spawn(TestA(2));
+        }).unwrap();
+
+        // entity commands check the generation, so stale commands are
+        // dropped, leaving the reused slot alone
+        app.eval(move |mut c: Commands| {
+            c.entity(weak.id()).insert(TestB(3));
+            c.entity(weak.id()).despawn();
+        }).unwrap();
+
+        let values: Vec<u32> = app.query::<&TestA>().map(|a| a.0).collect();
+        assert_eq!(values, vec![2]);
+        assert_eq!(app.query::<&TestB>().count(), 0);
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct TestA(u32);
+
+    impl Component for TestA {}
+
+    struct TestB(#[allow(unused)] u32);
+
+    impl Component for TestB {}
+}
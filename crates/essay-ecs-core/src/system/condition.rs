@@ -1,9 +1,22 @@
+use crate::{entity::Component, Query};
+
+///
+/// Run condition that skips the system when no entity has a T, used as
+/// `.run_if(any_with_component::<T>)`.
+///
+pub fn any_with_component<T: Component>(query: Query<&T>) -> bool {
+    ! query.is_empty()
+}
+
 #[cfg(test)]
 mod test {
     use std::sync::{Arc, Mutex};
 
     use crate::core_app::{CoreApp, Core};
-    use crate::IntoSystemConfig;
+    use crate::entity::Component;
+    use crate::{IntoSystemConfig, Store};
+
+    use super::any_with_component;
 
     #[test]
     fn run_if() {
@@ -28,6 +41,43 @@ mod test {
         assert_eq!(take(&values), "system-true");
     }
 
+    #[test]
+    fn any_with_component_condition() {
+        let mut app = CoreApp::new();
+
+        let values = Arc::new(Mutex::new(Vec::<String>::new()));
+        
+        let ptr = values.clone();
+        app.system(Core, (move || { push(&ptr, "system" ); })
+            .run_if(any_with_component::<TestA>)
+        );
+
+        app.tick().unwrap();
+        assert_eq!(take(&values), "");
+
+        app.run_system(|store: &mut Store| { store.spawn(TestA(1)); Ok(()) }).unwrap();
+
+        app.tick().unwrap();
+        assert_eq!(take(&values), "system");
+    }
+
+    #[test]
+    fn run_if_group_error() {
+        let mut app = CoreApp::new();
+
+        app.system(Core, (run_empty, run_empty).run_if(run_true));
+
+        let err = app.tick().unwrap_err();
+        assert!(err.message().contains("run_if needs a single system"));
+    }
+
+    fn run_empty() {
+    }
+
+    struct TestA(#[allow(unused)] u32);
+
+    impl Component for TestA {}
+
     fn push(ptr: &Arc<Mutex<Vec<String>>>, value: &str) {
         ptr.lock().unwrap().push(value.to_string());
     }
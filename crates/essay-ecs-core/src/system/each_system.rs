@@ -1,4 +1,4 @@
-use std::marker::PhantomData;
+use std::{any::type_name, marker::PhantomData};
 
 use crate::{
     entity::{View, ComponentId}, 
@@ -8,7 +8,7 @@ use crate::{
     system::{System, IntoSystem},
 };
 
-use crate::param::{Param, Arg};
+use crate::param::{Param, Arg, BufferKind};
 
 // IsEach prevents collisions
 pub struct IsEach;
@@ -61,7 +61,10 @@ where
     type Out = ();
     
     fn init(&mut self, meta: &mut SystemMeta, world: &mut Store) -> Result<()> {
-        let plan = world.view_build::<F::Item<'_>>();
+        // the item's writes count against a ReadStore param
+        let mark = meta.access_mark();
+
+        let plan = world.view_build::<F::Item<'_>>()?;
 
         for id in plan.components() {
             meta.insert_component(ComponentId::from(*id));
@@ -70,9 +73,18 @@ where
         for id in plan.mut_components() {
             meta.insert_component_mut(ComponentId::from(*id));
         }
+
+        if plan.is_exclusive() {
+            meta.set_exclusive();
+        }
         
         //F::Item::init(meta);
-        self.state = Some(F::Params::init(meta, world)?);
+        match F::Params::init(meta, world).and_then(|state| {
+            meta.check_read_all(mark).map(|_| state)
+        }) {
+            Ok(state) => self.state = Some(state),
+            Err(err) => return Err(err.rethrow(&format!("\n\tin {}", type_name::<F>()))),
+        }
 
         Ok(())
     }
@@ -93,6 +105,14 @@ where
     fn flush(&mut self, world: &mut Store) {
         F::Params::flush(world, self.state.as_mut().unwrap());
     }
+
+    fn flush_filtered(&mut self, world: &mut Store, filter: &dyn Fn(Option<BufferKind>) -> bool) {
+        F::Params::flush_filtered(world, self.state.as_mut().unwrap(), filter);
+    }
+
+    fn memory_size(&self) -> usize {
+        self.state.as_ref().map_or(0, F::Params::memory_size)
+    }
 }
 
 impl<F:'static, M:'static> IntoSystem<(), fn(M,IsEach)> for F
@@ -150,7 +170,7 @@ mod tests {
     fn test_each() {
         let mut app = CoreApp::new();
 
-        app.run_system(|mut cmd: Commands| cmd.spawn(TestA(1))).unwrap();
+        app.run_system(|mut cmd: Commands| { cmd.spawn(TestA(1)); }).unwrap();
 
         // let values = TestValues::new();
 
@@ -7,7 +7,7 @@ use crate::{
     system::{IntoSystem, System},
 };
 
-use crate::param::{Param, Arg};
+use crate::param::{Param, Arg, BufferKind};
 
 // IsFun prevents collision
 pub struct IsFun;
@@ -46,7 +46,11 @@ where
     type Out = F::Out;
 
     fn init(&mut self, meta: &mut SystemMeta, store: &mut Store) -> Result<()> {
-        match F::Param::init(meta, store) {
+        let mark = meta.access_mark();
+
+        match F::Param::init(meta, store).and_then(|local| {
+            meta.check_read_all(mark).map(|_| local)
+        }) {
             Ok(local) => {
                 self.state = Some(local);
                 Ok(())
@@ -71,6 +75,14 @@ where
     fn flush(&mut self, store: &mut Store) {
         F::Param::flush(store, self.state.as_mut().unwrap());
     }
+
+    fn flush_filtered(&mut self, store: &mut Store, filter: &dyn Fn(Option<BufferKind>) -> bool) {
+        F::Param::flush_filtered(store, self.state.as_mut().unwrap(), filter);
+    }
+
+    fn memory_size(&self) -> usize {
+        self.state.as_ref().map_or(0, F::Param::memory_size)
+    }
 }    
 
 // struct IsFun;
@@ -134,6 +146,7 @@ mod tests {
     use std::marker::PhantomData;
 
     use crate::{
+        check_system,
         core_app::{Core, CoreApp}, 
         error::Result,
         schedule::{SystemMeta, UnsafeStore}, 
@@ -142,6 +155,9 @@ mod tests {
 
     use super::Param;
 
+    check_system!(test_null);
+    check_system!(test_arg3(TestArg<u8>, TestArg<u16>, TestArg<u32>));
+
     #[test]
     fn arg_tuples() -> Result<()> {
         let mut app = CoreApp::new();
@@ -1,9 +1,11 @@
-use std::marker::PhantomData;
+use std::{any::type_name, marker::PhantomData, mem};
 
 use crate::{
+    entity::EntityAllocator,
     error::Result,
+    param::{BufferKind, HeapSize},
     schedule::{SystemMeta, UnsafeStore},
-    store::Store, 
+    store::{CommandQueue, Commands, Store}, 
     system::{IntoSystem, System}, Local,
 };
 
@@ -23,6 +25,39 @@ pub trait ParamExcl {
     fn arg<'s>(
         state: &'s mut Self::State, 
     ) -> Self::Arg<'s>;
+
+    #[allow(unused_variables)]
+    fn flush(world: &mut Store, state: &mut Self::State) {
+    }
+
+    ///
+    /// Kind of deferred buffer flushed by the param, None if it has none.
+    ///
+    fn buffer_kind() -> Option<BufferKind> {
+        None
+    }
+
+    ///
+    /// Flushes the buffers whose kind the filter accepts, for ordered
+    /// flush stages.
+    ///
+    fn flush_filtered(
+        world: &mut Store,
+        state: &mut Self::State,
+        filter: &dyn Fn(Option<BufferKind>) -> bool
+    ) {
+        if filter(Self::buffer_kind()) {
+            Self::flush(world, state);
+        }
+    }
+
+    ///
+    /// Memory held by the param's state, for system diagnostics.
+    ///
+    #[allow(unused_variables)]
+    fn memory_size(state: &Self::State) -> usize {
+        0
+    }
 }
 
 pub type ArgExcl<'s, P> = <P as ParamExcl>::Arg<'s>;
@@ -62,7 +97,11 @@ where
 
     fn init(&mut self, meta: &mut SystemMeta, world: &mut Store) -> Result<()> {
         meta.set_exclusive();
-        self.state = Some(F::Params::init(world, meta)?);
+
+        match F::Params::init(world, meta) {
+            Ok(state) => self.state = Some(state),
+            Err(err) => return Err(err.rethrow(&format!("\n\tin {}", type_name::<F>()))),
+        }
 
         Ok(())
     }
@@ -72,7 +111,12 @@ where
             self.state.as_mut().unwrap(),
         );
 
-        self.fun.run(world, arg)
+        let result = self.fun.run(world, arg);
+
+        // exclusive systems apply their commands as soon as they return
+        F::Params::flush(world, self.state.as_mut().unwrap());
+
+        result
     }
 
     unsafe fn run_unsafe(&mut self, _world: &UnsafeStore) -> Result<Self::Out> {
@@ -80,7 +124,20 @@ where
         Err("can't run exclusive system in unsafe mode".into())
     }
 
-    fn flush(&mut self, _world: &mut Store) {
+    fn flush(&mut self, world: &mut Store) {
+        if let Some(state) = self.state.as_mut() {
+            F::Params::flush(world, state);
+        }
+    }
+
+    fn flush_filtered(&mut self, world: &mut Store, filter: &dyn Fn(Option<BufferKind>) -> bool) {
+        if let Some(state) = self.state.as_mut() {
+            F::Params::flush_filtered(world, state, filter);
+        }
+    }
+
+    fn memory_size(&self) -> usize {
+        self.state.as_ref().map_or(0, F::Params::memory_size)
     }
 }    
 
@@ -135,7 +192,7 @@ impl_excl_function!(P1, P2, P3, P4, P5, P6, P7);
 //
 // Local param
 //
-impl<'a, T: Default + Send + Sync + 'static> ParamExcl for Local<'a, T> {
+impl<'a, T: Default + HeapSize + Send + Sync + 'static> ParamExcl for Local<'a, T> {
     type State = T;
     type Arg<'s> = Local<'s, T>;
 
@@ -148,6 +205,42 @@ impl<'a, T: Default + Send + Sync + 'static> ParamExcl for Local<'a, T> {
     ) -> Self::Arg<'s> {
         Local(state)
     }
+
+    fn memory_size(state: &Self::State) -> usize {
+        mem::size_of::<T>() + state.heap_size()
+    }
+}
+
+//
+// Commands param, applied when the exclusive system returns
+//
+impl<'w, 's> ParamExcl for Commands<'w, 's> {
+    type State = (EntityAllocator, CommandQueue);
+    type Arg<'a> = Commands<'a, 'a>;
+
+    fn init(world: &mut Store, _meta: &mut SystemMeta) -> Result<Self::State> {
+        Ok((world.entity_allocator(), CommandQueue::default()))
+    }
+
+    fn arg<'a>(
+        state: &'a mut Self::State, 
+    ) -> Self::Arg<'a> {
+        let (alloc, queue) = state;
+
+        Commands::from_alloc(alloc.clone(), queue)
+    }
+
+    fn flush(world: &mut Store, state: &mut Self::State) {
+        state.1.flush(world);
+    }
+
+    fn buffer_kind() -> Option<BufferKind> {
+        Some(BufferKind::commands())
+    }
+
+    fn memory_size(state: &Self::State) -> usize {
+        state.1.memory_size()
+    }
 }
 
 //
@@ -178,6 +271,28 @@ macro_rules! impl_param_excl_tuple {
 
                 ($($param::arg($param),)*)
             }
+
+            fn flush(world: &mut Store, state: &mut Self::State) {
+                let ($($param,)*) = state;
+
+                $($param::flush(world, $param);)*
+            }
+
+            fn flush_filtered(
+                world: &mut Store,
+                state: &mut Self::State,
+                filter: &dyn Fn(Option<BufferKind>) -> bool
+            ) {
+                let ($($param,)*) = state;
+
+                $($param::flush_filtered(world, $param, filter);)*
+            }
+
+            fn memory_size(state: &Self::State) -> usize {
+                let ($($param,)*) = state;
+
+                0 $(+ $param::memory_size($param))*
+            }
         }
     }
 }
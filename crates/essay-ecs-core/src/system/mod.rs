@@ -5,5 +5,7 @@ mod fun_system_excl;
 mod each_system;
 
 pub use system::{
-    SystemId, System, IntoSystem,
+    SystemId, System, IntoSystem, CachedSystem, assert_param, assert_system,
 };
+
+pub use condition::any_with_component;
@@ -1,7 +1,9 @@
 use std::any::type_name;
 
 use crate::{
+    entity::WorldId,
     error::Result,
+    param::{BufferKind, Param},
     store::Store, 
     schedule::{SystemMeta, UnsafeStore}
 };
@@ -25,20 +27,124 @@ pub trait System: Send + Sync + 'static {
     }
 
     fn flush(&mut self, world: &mut Store);
+
+    ///
+    /// Flushes the buffers whose kind the filter accepts. Systems without
+    /// typed buffers flush everything as kind None.
+    ///
+    fn flush_filtered(&mut self, world: &mut Store, filter: &dyn Fn(Option<BufferKind>) -> bool) {
+        if filter(None) {
+            self.flush(world);
+        }
+    }
+
+    ///
+    /// Approximate bytes held by the system's local state and pending
+    /// commands.
+    ///
+    fn memory_size(&self) -> usize {
+        0
+    }
 }
 
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is not a system",
+    label = "not a system",
+    note = "every parameter of a system function must implement Param",
+    note = "exclusive systems take only &mut Store and return Result",
+    note = "check_system!(my_system(A, B)) with the parameter types reports the failing one",
+)]
 pub trait IntoSystem<Out, M>: Sized {
     type System: System<Out = Out>;
 
     fn into_system(this: Self) -> Self::System;
 }
 
+///
+/// Compile-time check that a function is a system, e.g. next to its
+/// definition, so errors point at the function rather than the
+/// `app.system` call. Listing the parameter types also checks each one,
+/// naming the parameter that isn't a Param.
+///
+/// ```ignore
+/// fn movement(mut q: Query<(&mut Pos, &Vel)>, time: Res<Time>) { ... }
+///
+/// check_system!(movement(Query<(&mut Pos, &Vel)>, Res<Time>));
+/// ```
+///
+#[macro_export]
+macro_rules! check_system {
+    ($system:ident($($param:ty),* $(,)?)) => {
+        const _: () = {
+            #[allow(unused)]
+            fn check() {
+                $($crate::system::assert_param::<$param>();)*
+                $crate::system::assert_system(&$system);
+            }
+        };
+    };
+
+    ($system:expr) => {
+        const _: () = {
+            #[allow(unused)]
+            fn check() {
+                $crate::system::assert_system(&$system);
+            }
+        };
+    };
+}
+
+#[doc(hidden)]
+pub fn assert_system<Out, M>(_system: &impl IntoSystem<Out, M>) {
+}
+
+#[doc(hidden)]
+pub fn assert_param<P: Param>() {
+}
+
 impl SystemId {
     pub fn index(&self) -> usize {
         self.0
     }
 }
 
+///
+/// System initialized once and evaluated repeatedly with
+/// Store::eval_cached, keeping its Local state and view plans. The
+/// plans belong to the store that initialized it, so evaluating it in
+/// another store panics.
+///
+pub struct CachedSystem<O> {
+    system: Box<dyn System<Out = O>>,
+    world: WorldId,
+}
+
+impl<O: 'static> CachedSystem<O> {
+    pub(crate) fn new<M>(
+        into_system: impl IntoSystem<O, M>,
+        store: &mut UnsafeStore
+    ) -> Result<Self> {
+        let mut system = IntoSystem::into_system(into_system);
+
+        system.init(&mut SystemMeta::empty(), store)?;
+
+        Ok(Self { system: Box::new(system), world: store.id() })
+    }
+
+    #[inline]
+    pub(crate) fn check_world(&self, store: &Store) {
+        assert!(store.id() == self.world,
+            "CachedSystem belongs to {:?} but was used in {:?}", self.world, store.id());
+    }
+
+    pub(crate) fn run(&mut self, store: &mut UnsafeStore) -> Result<O> {
+        let value = self.system.run(store);
+        self.system.flush(store);
+
+        value
+    }
+}
+
 impl<S, Out> IntoSystem<Out, ()> for S
 where
     S: System<Out = Out>
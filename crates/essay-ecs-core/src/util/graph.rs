@@ -1,12 +1,19 @@
 use core::fmt;
-use std::{collections::HashSet, cmp::Ordering};
+use std::{collections::BTreeSet, cmp::Ordering};
 
 use fixedbitset::FixedBitSet;
 use log::info;
 
+use crate::error::{Error, Result};
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct NodeId(pub(crate) usize);
 
+///
+/// Weighted DAG with a stable topological sort. Nodes become ready once
+/// their incoming nodes are sorted, and ready nodes are ordered by
+/// descending weight, then by NodeId.
+///
 #[derive(Clone)]
 pub struct Preorder {
     nodes: Vec<Node>,
@@ -18,8 +25,9 @@ struct Node {
 
     weight: u64, // greedy value
 
-    incoming: HashSet<NodeId>,
-    outgoing: HashSet<NodeId>,
+    // ordered sets so cycle-breaking and arrow order are stable across runs
+    incoming: BTreeSet<NodeId>,
+    outgoing: BTreeSet<NodeId>,
 }
 
 impl Preorder {
@@ -27,12 +35,30 @@ impl Preorder {
         Self::default()
     }
 
-    pub(crate) fn incoming(&self, id: NodeId) -> &HashSet<NodeId> {
-        &self.nodes[id.index()].incoming
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn weight(&self, id: NodeId) -> u64 {
+        self.nodes[id.index()].weight
+    }
+
+    ///
+    /// Sources of arrows into the node, in NodeId order.
+    ///
+    pub fn incoming(&self, id: NodeId) -> impl ExactSizeIterator<Item=NodeId> + '_ {
+        self.nodes[id.index()].incoming.iter().copied()
     }
 
-    pub(crate) fn outgoing(&self, id: NodeId) -> &HashSet<NodeId> {
-        &self.nodes[id.index()].outgoing
+    ///
+    /// Targets of arrows from the node, in NodeId order.
+    ///
+    pub fn outgoing(&self, id: NodeId) -> impl ExactSizeIterator<Item=NodeId> + '_ {
+        self.nodes[id.index()].outgoing.iter().copied()
     }
 
     pub fn add_node(&mut self, weight: u64) -> NodeId {
@@ -43,8 +69,8 @@ impl Preorder {
         id
     }
 
-    pub(crate) fn node_ids(&self) -> Vec<NodeId> {
-        self.nodes.iter().map(|n| n.id()).collect()
+    pub fn node_ids(&self) -> impl ExactSizeIterator<Item=NodeId> + '_ {
+        self.nodes.iter().map(|n| n.id())
     }
 
     pub fn add_arrow(&mut self, source_id: NodeId, target_id: NodeId) {
@@ -56,6 +82,10 @@ impl Preorder {
         //println!("Arrow[{:?}] out:{:?}", source_id, self.nodes[source_id.0].outgoing);
     }
 
+    ///
+    /// Sorts the nodes, breaking any cycles by removing arrows. Broken
+    /// arrows stay removed from the graph.
+    ///
     pub fn sort(&mut self) -> Vec<NodeId> {
         let mut results = Vec::<NodeId>::new();
 
@@ -65,34 +95,130 @@ impl Preorder {
         let mut completed = FixedBitSet::with_capacity(self.nodes.len());
 
         while results.len() < self.nodes.len() {
-            let start_len = results.len();
-            //println!("  Loop: {}", start_len);
+            if ! self.sort_ready(&pending, &mut completed, &mut results) {
+                self.break_cycle(&pending);
+            }
+
+            pending.difference_with(&completed);
+        }
+
+        assert!(results.len() == self.nodes.len());
+        results
+    }
 
-            completed.clear();
+    ///
+    /// Sorts the nodes, returning an error naming the unsorted nodes if
+    /// the graph has a cycle.
+    ///
+    pub fn try_sort(&self) -> Result<Vec<NodeId>> {
+        let mut results = Vec::<NodeId>::new();
 
-            for index in pending.ones() {
-                let node = &self.nodes[index];
+        let mut pending = FixedBitSet::with_capacity(self.nodes.len());
+        pending.insert_range(..);
 
-                if ! node.is_incoming_pending(&pending) {
-                    completed.insert(index);
-                    results.push(node.id());
-                    //println!("   Item: {:?}", node.id());
+        let mut completed = FixedBitSet::with_capacity(self.nodes.len());
+
+        while results.len() < self.nodes.len() {
+            if ! self.sort_ready(&pending, &mut completed, &mut results) {
+                let cycle: Vec<NodeId> = pending.ones()
+                    .map(NodeId)
+                    .filter(|n| self.is_cyclic(*n, &pending))
+                    .collect();
+
+                return Err(Error::new(&format!("cycle in graph: {:?}", cycle)));
+            }
+
+            pending.difference_with(&completed);
+        }
+
+        Ok(results)
+    }
+
+    ///
+    /// Sorts the nodes, breaking each cycle by removing the arrow pick
+    /// chooses from the cycle's arrows, and returns the removed arrows.
+    /// Fails with the cycle if pick returns None.
+    ///
+    pub fn break_cycles(
+        &mut self,
+        mut pick: impl FnMut(&[(NodeId, NodeId)]) -> Option<(NodeId, NodeId)>
+    ) -> Result<Vec<(NodeId, NodeId)>> {
+        let mut results = Vec::<NodeId>::new();
+        let mut broken = Vec::new();
+
+        let mut pending = FixedBitSet::with_capacity(self.nodes.len());
+        pending.insert_range(..);
+
+        let mut completed = FixedBitSet::with_capacity(self.nodes.len());
+
+        while results.len() < self.nodes.len() {
+            if ! self.sort_ready(&pending, &mut completed, &mut results) {
+                let arrows = self.cycle_arrows(&pending);
+
+                match pick(&arrows) {
+                    Some(arrow) if arrows.contains(&arrow) => {
+                        self.remove_arrow(arrow.0, arrow.1);
+                        broken.push(arrow);
+                    }
+                    _ => {
+                        return Err(Error::new(&format!("cycle in graph: {:?}", arrows)));
+                    }
                 }
             }
 
-            if results.len() == start_len {
-                self.break_cycle(&pending);
+            pending.difference_with(&completed);
+        }
+
+        Ok(broken)
+    }
+
+    pub fn remove_arrow(&mut self, source_id: NodeId, target_id: NodeId) {
+        self.nodes[source_id.0].remove_outgoing(target_id);
+        self.nodes[target_id.0].remove_incoming(source_id);
+    }
+
+    ///
+    /// Arrows between pending nodes that lie on a cycle.
+    ///
+    fn cycle_arrows(&self, pending: &FixedBitSet) -> Vec<(NodeId, NodeId)> {
+        let mut arrows = Vec::new();
+
+        for index in pending.ones() {
+            let id = NodeId(index);
+
+            for target in &self.nodes[index].outgoing {
+                if pending.contains(target.0) && self.is_path_to(*target, id) {
+                    arrows.push((id, *target));
+                }
             }
+        }
+
+        arrows
+    }
 
-            let new_results = &mut results.as_mut_slice()[start_len..];
-            
-            new_results.sort_by_key(|n| u64::MAX - self.nodes[n.0].weight);
+    fn sort_ready(
+        &self,
+        pending: &FixedBitSet,
+        completed: &mut FixedBitSet,
+        results: &mut Vec<NodeId>
+    ) -> bool {
+        let start_len = results.len();
 
-            pending.difference_with(&completed);
+        completed.clear();
+
+        for index in pending.ones() {
+            let node = &self.nodes[index];
+
+            if ! node.is_incoming_pending(pending) {
+                completed.insert(index);
+                results.push(node.id());
+            }
         }
 
-        assert!(results.len() == self.nodes.len());
-        results
+        // stable, so equal weights keep NodeId order
+        results[start_len..].sort_by_key(|n| u64::MAX - self.nodes[n.0].weight);
+
+        results.len() > start_len
     }
 
     fn break_cycle(&mut self, pending: &FixedBitSet) {
@@ -102,7 +228,7 @@ impl Preorder {
             .collect();
         
         cycle_ids.sort_by(|&a, &b| {
-            self.compare_nodes(a, b, pending)
+            self.compare_nodes(a, b)
         });
 
         let node_id = cycle_ids[0];
@@ -128,17 +254,12 @@ impl Preorder {
         return false;
     }
 
-    fn compare_nodes(
-        &self, 
-        id_a: NodeId, 
-        id_b: NodeId, 
-        pending: &FixedBitSet
-    ) -> Ordering {
+    fn compare_nodes(&self, id_a: NodeId, id_b: NodeId) -> Ordering {
         let node_a = &self.nodes[id_a.index()];
         let node_b = &self.nodes[id_b.index()];
 
-        let is_path_a_to_b = self.is_path_to(id_a, id_b, pending);
-        let is_path_b_to_a = self.is_path_to(id_b, id_a, pending);
+        let is_path_a_to_b = self.is_path_to(id_a, id_b);
+        let is_path_b_to_a = self.is_path_to(id_b, id_a);
 
         if is_path_a_to_b && ! is_path_b_to_a {
             return Ordering::Less;
@@ -165,7 +286,7 @@ impl Preorder {
     }
 
     fn is_cyclic(&self, id: NodeId, pending: &FixedBitSet) -> bool {
-        let mut visited = HashSet::<NodeId>::new();
+        let mut visited = BTreeSet::<NodeId>::new();
         visited.insert(id);
 
         self.is_cyclic_rec(id, id, pending, &mut visited)
@@ -176,7 +297,7 @@ impl Preorder {
         top_id: NodeId, 
         id: NodeId, 
         pending: &FixedBitSet,
-        visited: &mut HashSet<NodeId>,
+        visited: &mut BTreeSet<NodeId>,
     ) -> bool {
         let node = &self.nodes[id.index()];
         
@@ -195,40 +316,57 @@ impl Preorder {
         false
     }
 
-    fn is_path_to(
-        &self, 
-        id_a: NodeId, 
-        id_b: NodeId,
-        pending: &FixedBitSet
-    ) -> bool {
-        self.is_path_to_rec(id_a, id_b, id_a, pending, &mut HashSet::new())
+    ///
+    /// True if a path of arrows leads from id_a to id_b.
+    ///
+    pub fn has_path(&self, id_a: NodeId, id_b: NodeId) -> bool {
+        self.is_path_to(id_a, id_b)
+    }
+
+    ///
+    /// Nodes reachable from each node, including itself, so repeated path
+    /// checks don't each search the graph.
+    ///
+    pub fn reachable(&self) -> Vec<FixedBitSet> {
+        let n = self.nodes.len();
+
+        let mut reach = vec![FixedBitSet::with_capacity(n); n];
+        let mut stack = Vec::new();
+
+        for node in &self.nodes {
+            let set = &mut reach[node.id.index()];
+            set.insert(node.id.index());
+
+            stack.extend(node.outgoing.iter().copied());
+
+            while let Some(id) = stack.pop() {
+                if ! set.put(id.index()) {
+                    stack.extend(self.nodes[id.index()].outgoing.iter().copied());
+                }
+            }
+        }
+
+        reach
+    }
+
+    fn is_path_to(&self, id_a: NodeId, id_b: NodeId) -> bool {
+        self.is_path_to_rec(id_b, id_a, &mut BTreeSet::new())
     }
 
     fn is_path_to_rec(
         &self, 
-        id_a: NodeId, 
         id_b: NodeId,
         id: NodeId,
-        pending: &FixedBitSet,
-        visited: &mut HashSet<NodeId>,
+        visited: &mut BTreeSet<NodeId>,
     ) -> bool {
         if id == id_b {
-            return true;
-        } else if visited.contains(&id) {
-            return false;
+            true
+        } else if ! visited.insert(id) {
+            false
         } else {
-            visited.insert(id);
-
-            let node = &self.nodes[id.index()];
-            for id_out in &node.outgoing {
-                if self.is_path_to_rec(id_a, id_b, *id_out, pending, visited) {
-                    return true;
-                }
-            }
-
-            return false;
+            self.nodes[id.index()].outgoing.iter()
+                .any(|id_out| self.is_path_to_rec(id_b, *id_out, visited))
         }
-
     }
 }
 
@@ -471,6 +609,51 @@ mod tests {
 
     }
 
+    #[test]
+    fn deterministic_cycles() {
+        let arrows = [
+            (5, 4), (4, 3), (3, 5),
+            (2, 1), (1, 0), (0, 2),
+            (3, 0), (1, 5),
+        ];
+
+        let order = as_vec(graph(6, &arrows).sort());
+
+        for _ in 0..20 {
+            assert_eq!(as_vec(graph(6, &arrows).sort()), order);
+        }
+
+        let g = graph(6, &arrows);
+        let outgoing: Vec<usize> = g.outgoing(NodeId(3)).map(|n| n.0).collect();
+        assert_eq!(outgoing, vec![0, 5]);
+    }
+
+    #[test]
+    fn try_sort() {
+        let g = graph_w(&[0, 1, 0, 0], &[(0, 2), (3, 2)]);
+        assert_eq!(as_vec(g.try_sort().unwrap()), [1, 0, 3, 2]);
+
+        let g = graph(4, &[(0, 1), (1, 2), (2, 1), (2, 3)]);
+        let err = g.try_sort().unwrap_err();
+        assert_eq!(err.message(), "cycle in graph: [NodeId(1), NodeId(2)]");
+
+        let incoming: Vec<usize> = g.incoming(NodeId(1)).map(|n| n.0).collect();
+        assert_eq!(incoming, vec![0, 2]);
+        assert_eq!(g.node_ids().len(), 4);
+    }
+
+    #[test]
+    fn reachable() {
+        let g = graph(5, &[(0, 1), (1, 2), (2, 1), (3, 2)]);
+        let reach = g.reachable();
+
+        for a in 0..5 {
+            for b in 0..5 {
+                assert_eq!(reach[a].contains(b), g.has_path(NodeId(a), NodeId(b)), "{} -> {}", a, b);
+            }
+        }
+    }
+
     fn graph(n: usize, arrows: &[(usize, usize)]) -> Preorder {
         let mut graph = Preorder::new();
 
@@ -1,7 +1,13 @@
 ///
 /// See Bevy label.rs for original idea
 /// 
-use std::{hash::{Hash, Hasher}, any::Any};
+use std::{
+    any::Any,
+    cell::RefCell,
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+    sync::{Mutex, OnceLock},
+};
 
 pub trait DynLabel : 'static {
     fn as_any(&self) -> &dyn Any;
@@ -35,3 +41,126 @@ where
     }
 }
 
+
+///
+/// Interned label value, cheap to copy, hash and compare. Equal labels
+/// have equal ids for the life of the process.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct LabelId(u64);
+
+impl LabelId {
+    pub fn value(&self) -> u64 {
+        self.0
+    }
+}
+
+///
+/// Returns the id for the label, registering it with a clone on first use.
+/// Each thread caches the ids it has seen, so only a thread's first lookup
+/// of a label locks the registry.
+///
+pub fn intern_label(
+    label: &dyn DynLabel,
+    clone: impl Fn() -> Box<dyn DynLabel + Send>
+) -> LabelId {
+    let mut hasher = DefaultHasher::new();
+    label.dyn_hash(&mut hasher);
+    let hash = hasher.finish();
+
+    let cached = LOCAL_LABELS.with(|local| {
+        local.borrow().get(&hash)
+            .and_then(|bucket| find_label(bucket, label))
+    });
+
+    if let Some(id) = cached {
+        return id;
+    }
+
+    let id = intern_shared(label, hash, &clone);
+
+    let item = clone();
+    LOCAL_LABELS.with(|local| local.borrow_mut().entry(hash).or_default().push((item, id)));
+
+    id
+}
+
+fn find_label(bucket: &LabelBucket, label: &dyn DynLabel) -> Option<LabelId> {
+    bucket.iter()
+        .find(|(item, _)| item.dyn_eq(label))
+        .map(|(_, id)| *id)
+}
+
+fn intern_shared(
+    label: &dyn DynLabel,
+    hash: u64,
+    clone: &impl Fn() -> Box<dyn DynLabel + Send>
+) -> LabelId {
+    let mut registry = LABELS.get_or_init(Default::default).lock().unwrap();
+
+    let LabelRegistry { buckets, next } = &mut *registry;
+
+    let bucket = buckets.entry(hash).or_default();
+
+    if let Some(id) = find_label(bucket, label) {
+        return id;
+    }
+
+    let id = LabelId(*next);
+    *next += 1;
+
+    bucket.push((clone(), id));
+
+    id
+}
+
+static LABELS: OnceLock<Mutex<LabelRegistry>> = OnceLock::new();
+
+thread_local! {
+    static LOCAL_LABELS: RefCell<HashMap<u64, LabelBucket>> = Default::default();
+}
+
+type LabelBucket = Vec<(Box<dyn DynLabel + Send>, LabelId)>;
+
+#[derive(Default)]
+struct LabelRegistry {
+    buckets: HashMap<u64, LabelBucket>,
+    next: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::{intern_label, DynLabel, LabelId};
+
+    #[test]
+    fn intern() {
+        let a = id(TestLabel(1));
+        assert_eq!(a, id(TestLabel(1)));
+        assert_ne!(a, id(TestLabel(2)));
+        assert_ne!(a, id(OtherLabel(1)));
+        assert_eq!(id(OtherLabel(1)), id(OtherLabel(1)));
+    }
+
+    #[test]
+    fn intern_threads() {
+        let a = id(TestLabel(10));
+
+        let b = thread::spawn(|| (id(TestLabel(10)), id(TestLabel(11))))
+            .join().unwrap();
+
+        assert_eq!(a, b.0);
+        assert_eq!(id(TestLabel(11)), b.1);
+    }
+
+    fn id<L: DynLabel + Copy + Send>(label: L) -> LabelId {
+        intern_label(&label, || Box::new(label))
+    }
+
+    #[derive(Clone, Copy, PartialEq, Eq, Hash)]
+    struct TestLabel(u32);
+
+    #[derive(Clone, Copy, PartialEq, Eq, Hash)]
+    struct OtherLabel(u32);
+}
@@ -1,5 +1,6 @@
 mod label;
+pub mod graph;
 
 pub mod test;
 
-pub use label::DynLabel;
+pub use label::{DynLabel, LabelId, intern_label};
@@ -17,4 +17,4 @@ impl TestValues {
 
         vec.join(", ")
     }
-}
\ No newline at end of file
+}
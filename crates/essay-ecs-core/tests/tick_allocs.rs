@@ -0,0 +1,88 @@
+//
+// Steady-state ticks must not allocate. The counter is process-wide, so
+// allocations on the executor's worker threads count too. The test has
+// its own binary without the libtest harness, whose threads would
+// otherwise allocate while it counts.
+//
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use essay_ecs_core::{
+    entity::Component,
+    schedule::Executors,
+    Commands, Core, Query, Res, ResMut, Schedule, Schedules, Store,
+};
+
+struct CountingAlloc;
+
+static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        COUNT.fetch_add(1, Ordering::Relaxed);
+
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        COUNT.fetch_add(1, Ordering::Relaxed);
+
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
+
+struct TestComp(u32);
+impl Component for TestComp {}
+
+fn main() {
+    tick_without_allocs();
+
+    println!("tick_without_allocs ... ok");
+}
+
+fn tick_without_allocs() {
+    for executor in [Executors::Single, Executors::Multithreaded] {
+        let mut world = Store::new();
+        world.insert_resource(0u32);
+        world.insert_resource(0u64);
+        world.spawn(TestComp(1));
+
+        let mut schedule = Schedule::new();
+        schedule.set_executor(executor);
+
+        let mut schedules = Schedules::default();
+        schedules.insert(Core, schedule);
+
+        schedules.add_system(Core, |mut n: ResMut<u32>| *n += 1);
+        schedules.add_system(Core, |mut n: ResMut<u64>| *n += 1);
+        schedules.add_system(Core, |n: Res<u32>, q: Query<&TestComp>| {
+            assert!(q.iter().count() as u32 <= *n);
+        });
+        schedules.add_system(Core, |_n: Res<u64>, _c: Commands| {});
+
+        for _ in 0..100 {
+            schedules.tick(Core, &mut world).unwrap();
+        }
+
+        let start = COUNT.load(Ordering::Relaxed);
+
+        for _ in 0..100 {
+            schedules.tick(Core, &mut world).unwrap();
+        }
+
+        assert_eq!(COUNT.load(Ordering::Relaxed) - start, 0);
+
+        let values: Vec<u32> = world.query::<&TestComp>().map(|c| c.0).collect();
+        assert_eq!(values, vec![1]);
+    }
+}